@@ -0,0 +1,95 @@
+//! Steps a world made of many disjoint stacks of boxes.
+//!
+//! Each stack is an island of its own, so this measures the resolution of independent islands.
+//! Run it with and without the `parallel` feature to compare the sequential and parallel
+//! solvers:
+//!
+//! ```sh
+//! cargo bench --bench disjoint_stacks3
+//! cargo bench --bench disjoint_stacks3 --features parallel
+//! ```
+
+extern crate nalgebra as na;
+extern crate ncollide3d;
+extern crate nphysics3d;
+
+use std::time::Instant;
+
+use na::{Isometry3, Vector3};
+use ncollide3d::shape::{Cuboid, ShapeHandle};
+use nphysics3d::object::{BodyHandle, Material};
+use nphysics3d::volumetric::Volumetric;
+use nphysics3d::world::World;
+
+const COLLIDER_MARGIN: f32 = 0.01;
+const NUM_STACKS: usize = 16;
+const STACK_HEIGHT: usize = 10;
+const NUM_STEPS: usize = 200;
+
+fn main() {
+    let mut world = World::new();
+    world.set_gravity(Vector3::new(0.0, -9.81, 0.0));
+
+    let ground_size = 50.0;
+    let ground_shape =
+        ShapeHandle::new(Cuboid::new(Vector3::repeat(ground_size - COLLIDER_MARGIN)));
+    let ground_pos = Isometry3::new(Vector3::y() * -ground_size, na::zero());
+
+    world.add_collider(
+        COLLIDER_MARGIN,
+        ground_shape,
+        BodyHandle::ground(),
+        ground_pos,
+        Material::default(),
+    );
+
+    let rad = 0.5;
+    let geom = ShapeHandle::new(Cuboid::new(Vector3::repeat(rad - COLLIDER_MARGIN)));
+    let inertia = geom.inertia(1.0);
+    let center_of_mass = geom.center_of_mass();
+
+    // The stacks are far enough from each other to never touch, even if they fall.
+    let spacing = rad * 2.0 * STACK_HEIGHT as f32 * 2.0;
+
+    for i in 0..NUM_STACKS {
+        for j in 0..STACK_HEIGHT {
+            let x = i as f32 * spacing;
+            let y = rad + j as f32 * rad * 2.0;
+            let pos = Isometry3::new(Vector3::new(x, y, 0.0), na::zero());
+            let handle = world.add_rigid_body(pos, inertia, center_of_mass);
+            world
+                .rigid_body_mut(handle)
+                .unwrap()
+                .activation_status_mut()
+                .set_deactivation_threshold(None);
+
+            world.add_collider(
+                COLLIDER_MARGIN,
+                geom.clone(),
+                handle,
+                Isometry3::identity(),
+                Material::default(),
+            );
+        }
+    }
+
+    // Let the contacts settle before timing.
+    for _ in 0..10 {
+        world.step();
+    }
+
+    let start = Instant::now();
+    for _ in 0..NUM_STEPS {
+        world.step();
+    }
+    let elapsed = start.elapsed();
+    let elapsed = elapsed.as_secs() as f64 * 1.0e3 + elapsed.subsec_nanos() as f64 * 1.0e-6;
+
+    println!(
+        "{} stacks of {} boxes ({} islands): {:.3} ms per step",
+        NUM_STACKS,
+        STACK_HEIGHT,
+        world.last_step_stats().nislands,
+        elapsed / NUM_STEPS as f64
+    );
+}
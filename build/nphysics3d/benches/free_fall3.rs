@@ -0,0 +1,69 @@
+//! Steps a world made of many free-falling balls.
+//!
+//! None of the balls touch each other or the ground, so this measures the integration of
+//! rigid bodies that do not go through the constraint solver.
+//!
+//! ```sh
+//! cargo bench --bench free_fall3
+//! ```
+
+extern crate nalgebra as na;
+extern crate ncollide3d;
+extern crate nphysics3d;
+
+use std::time::Instant;
+
+use na::{Isometry3, Vector3};
+use ncollide3d::shape::{Ball, ShapeHandle};
+use nphysics3d::object::Material;
+use nphysics3d::volumetric::Volumetric;
+use nphysics3d::world::World;
+
+const COLLIDER_MARGIN: f32 = 0.01;
+const NUM_BALLS_PER_SIDE: usize = 20;
+const NUM_STEPS: usize = 200;
+
+fn main() {
+    let mut world = World::new();
+    world.set_gravity(Vector3::new(0.0, -9.81, 0.0));
+
+    let rad = 0.1;
+    let geom = ShapeHandle::new(Ball::new(rad - COLLIDER_MARGIN));
+    let inertia = geom.inertia(1.0);
+    let center_of_mass = geom.center_of_mass();
+    let shift = rad * 4.0;
+
+    for i in 0..NUM_BALLS_PER_SIDE {
+        for j in 0..NUM_BALLS_PER_SIDE {
+            for k in 0..NUM_BALLS_PER_SIDE {
+                let pos = Isometry3::new(
+                    Vector3::new(i as f32, j as f32, k as f32) * shift,
+                    na::zero(),
+                );
+                let handle = world.add_rigid_body(pos, inertia, center_of_mass);
+
+                world.add_collider(
+                    COLLIDER_MARGIN,
+                    geom.clone(),
+                    handle,
+                    Isometry3::identity(),
+                    Material::default(),
+                );
+            }
+        }
+    }
+
+    let start = Instant::now();
+    for _ in 0..NUM_STEPS {
+        world.step();
+    }
+    let elapsed = start.elapsed();
+    let elapsed = elapsed.as_secs() as f64 * 1.0e3 + elapsed.subsec_nanos() as f64 * 1.0e-6;
+
+    println!(
+        "{} free-falling balls ({} integrated without the solver): {:.3} ms per step",
+        NUM_BALLS_PER_SIDE * NUM_BALLS_PER_SIDE * NUM_BALLS_PER_SIDE,
+        world.last_step_stats().nfree_bodies,
+        elapsed / NUM_STEPS as f64
+    );
+}
@@ -0,0 +1,110 @@
+extern crate nalgebra as na;
+extern crate ncollide2d;
+extern crate nphysics2d;
+extern crate nphysics_testbed2d;
+
+use na::{Isometry2, Point2, Vector2};
+use ncollide2d::shape::{Ball, Cuboid, ShapeHandle};
+use nphysics2d::joint::{FixedJoint, RevoluteConstraint, RevoluteJoint};
+use nphysics2d::object::{BodyHandle, Material};
+use nphysics2d::volumetric::Volumetric;
+use nphysics2d::world::World;
+use nphysics_testbed2d::Testbed;
+use std::f32::consts::PI;
+
+const COLLIDER_MARGIN: f32 = 0.01;
+
+fn main() {
+    /*
+     * World
+     */
+    let mut world = World::new();
+    world.set_gravity(Vector2::new(0.0, -9.81));
+
+    /*
+     * Setup the four-bar linkage.
+     *
+     * The base, the crank, the coupler, and the rocker are links of the same
+     * multibody. The loop is closed by a revolute constraint between the rocker
+     * and the base.
+     */
+    let width = 0.05;
+    let base_len = 3.0;
+    let crank_len = 1.0;
+
+    let base_geom = ShapeHandle::new(Cuboid::new(Vector2::new(base_len / 2.0, width)));
+    let crank_geom = ShapeHandle::new(Cuboid::new(Vector2::new(crank_len / 2.0, width)));
+    let marker = ShapeHandle::new(Ball::new(0.1));
+
+    // The base is fixed to the ground.
+    let base_joint = FixedJoint::new(Isometry2::new(Vector2::x() * base_len / 2.0, na::zero()));
+    let base = world.add_multibody_link(
+        BodyHandle::ground(),
+        base_joint,
+        na::zero(),
+        na::zero(),
+        base_geom.inertia(1.0),
+        base_geom.center_of_mass(),
+    );
+
+    // The crank hangs from the left end of the base.
+    let crank = world.add_multibody_link(
+        base,
+        RevoluteJoint::new(-PI / 2.0),
+        Vector2::x() * -base_len / 2.0,
+        Vector2::x() * -crank_len / 2.0,
+        crank_geom.inertia(1.0),
+        crank_geom.center_of_mass(),
+    );
+
+    // The coupler is parallel to the base.
+    let coupler = world.add_multibody_link(
+        crank,
+        RevoluteJoint::new(PI / 2.0),
+        Vector2::x() * crank_len / 2.0,
+        Vector2::x() * -base_len / 2.0,
+        base_geom.inertia(1.0),
+        base_geom.center_of_mass(),
+    );
+
+    // The rocker goes from the coupler back to the right end of the base.
+    let rocker = world.add_multibody_link(
+        coupler,
+        RevoluteJoint::new(PI / 2.0),
+        Vector2::x() * base_len / 2.0,
+        Vector2::x() * -crank_len / 2.0,
+        crank_geom.inertia(1.0),
+        crank_geom.center_of_mass(),
+    );
+
+    // Close the loop.
+    let closure = RevoluteConstraint::new(
+        base,
+        rocker,
+        Point2::new(base_len / 2.0, 0.0),
+        Point2::new(crank_len / 2.0, 0.0),
+    );
+    world.add_constraint(closure);
+
+    for link in &[base, crank, coupler, rocker] {
+        world.add_collider(
+            COLLIDER_MARGIN,
+            marker.clone(),
+            *link,
+            Isometry2::identity(),
+            Material::default(),
+        );
+    }
+
+    // Give an initial swing to the crank.
+    world
+        .multibody_link_mut(crank)
+        .unwrap()
+        .joint_velocity_mut()[0] = 2.0;
+
+    /*
+     * Set up the testbed.
+     */
+    let testbed = Testbed::new(world);
+    testbed.run();
+}
@@ -0,0 +1,201 @@
+extern crate nalgebra as na;
+extern crate ncollide3d;
+extern crate nphysics3d;
+extern crate nphysics_testbed3d;
+
+use na::{DVector, Isometry3, Point3, Real, Unit, Vector3};
+use ncollide3d::shape::{Cuboid, ShapeHandle};
+use nphysics3d::joint::JointConstraint;
+use nphysics3d::object::{BodyHandle, BodySet, Material};
+use nphysics3d::solver::helper;
+use nphysics3d::solver::{
+    ConstraintSet, GenericNonlinearConstraint, IntegrationParameters, NonlinearConstraintGenerator,
+};
+use nphysics3d::volumetric::Volumetric;
+use nphysics3d::world::World;
+use nphysics_testbed3d::Testbed;
+use std::ops::Range;
+
+const COLLIDER_MARGIN: f32 = 0.01;
+
+/// A joint constraint implemented only with the public API of nphysics.
+///
+/// It keeps a point attached to the second body part on a plane attached to the first one.
+struct PointOnPlaneConstraint<N: Real> {
+    b1: BodyHandle,
+    b2: BodyHandle,
+    anchor1: Point3<N>,
+    normal1: Unit<Vector3<N>>,
+    anchor2: Point3<N>,
+    impulse: N,
+    bilateral_ground_rng: Range<usize>,
+    bilateral_rng: Range<usize>,
+}
+
+impl<N: Real> PointOnPlaneConstraint<N> {
+    fn new(
+        b1: BodyHandle,
+        b2: BodyHandle,
+        anchor1: Point3<N>,
+        normal1: Unit<Vector3<N>>,
+        anchor2: Point3<N>,
+    ) -> Self {
+        PointOnPlaneConstraint {
+            b1,
+            b2,
+            anchor1,
+            normal1,
+            anchor2,
+            impulse: N::zero(),
+            bilateral_ground_rng: 0..0,
+            bilateral_rng: 0..0,
+        }
+    }
+
+    fn world_frames(&self, bodies: &BodySet<N>) -> (Point3<N>, Point3<N>, Unit<Vector3<N>>) {
+        let pos1 = bodies.body_part(self.b1).position();
+        let pos2 = bodies.body_part(self.b2).position();
+        (pos1 * self.anchor1, pos2 * self.anchor2, pos1 * self.normal1)
+    }
+}
+
+impl<N: Real> JointConstraint<N> for PointOnPlaneConstraint<N> {
+    fn num_velocity_constraints(&self) -> usize {
+        1
+    }
+
+    fn anchors(&self) -> (BodyHandle, BodyHandle) {
+        (self.b1, self.b2)
+    }
+
+    fn velocity_constraints(
+        &mut self,
+        _: &IntegrationParameters<N>,
+        bodies: &BodySet<N>,
+        ext_vels: &DVector<N>,
+        ground_j_id: &mut usize,
+        j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        let (anchor1, anchor2, normal1) = self.world_frames(bodies);
+        let body1 = bodies.body_part(self.b1);
+        let body2 = bodies.body_part(self.b2);
+
+        let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
+        let first_bilateral = constraints.velocity.bilateral.len();
+
+        helper::restrict_relative_linear_velocity_to_plane(
+            &body1,
+            &body2,
+            body1.parent_companion_id(),
+            body2.parent_companion_id(),
+            &anchor1,
+            &anchor2,
+            &normal1,
+            ext_vels,
+            self.impulse,
+            0,
+            ground_j_id,
+            j_id,
+            jacobians,
+            constraints,
+        );
+
+        self.bilateral_ground_rng =
+            first_bilateral_ground..constraints.velocity.bilateral_ground.len();
+        self.bilateral_rng = first_bilateral..constraints.velocity.bilateral.len();
+    }
+
+    fn cache_impulses(&mut self, constraints: &ConstraintSet<N>) {
+        for c in &constraints.velocity.bilateral_ground[self.bilateral_ground_rng.clone()] {
+            self.impulse = c.impulse;
+        }
+
+        for c in &constraints.velocity.bilateral[self.bilateral_rng.clone()] {
+            self.impulse = c.impulse;
+        }
+    }
+}
+
+impl<N: Real> NonlinearConstraintGenerator<N> for PointOnPlaneConstraint<N> {
+    fn num_position_constraints(&self, bodies: &BodySet<N>) -> usize {
+        if self.is_active(bodies) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn position_constraint(
+        &self,
+        params: &IntegrationParameters<N>,
+        _: usize,
+        bodies: &mut BodySet<N>,
+        jacobians: &mut [N],
+    ) -> Option<GenericNonlinearConstraint<N>> {
+        let (anchor1, anchor2, normal1) = self.world_frames(bodies);
+        let body1 = bodies.body_part(self.b1);
+        let body2 = bodies.body_part(self.b2);
+
+        helper::cancel_relative_translation_wrt_axis(
+            params, &body1, &body2, &anchor1, &anchor2, &normal1, jacobians,
+        )
+    }
+}
+
+fn main() {
+    /*
+     * World
+     */
+    let mut world = World::new();
+    world.set_gravity(Vector3::new(0.0, -9.81, 0.0));
+
+    /*
+     * Ground
+     */
+    let ground_size = 50.0;
+    let ground_shape =
+        ShapeHandle::new(Cuboid::new(Vector3::repeat(ground_size - COLLIDER_MARGIN)));
+    let ground_pos = Isometry3::new(Vector3::y() * -ground_size, na::zero());
+
+    world.add_collider(
+        COLLIDER_MARGIN,
+        ground_shape,
+        BodyHandle::ground(),
+        ground_pos,
+        Material::default(),
+    );
+
+    /*
+     * A box whose corner slides on a tilted plane attached to the ground.
+     */
+    let rad = 0.5;
+    let cuboid = ShapeHandle::new(Cuboid::new(Vector3::repeat(rad - COLLIDER_MARGIN)));
+    let pos = Isometry3::new(Vector3::y() * 5.0, na::zero());
+    let rb = world.add_rigid_body(pos, cuboid.inertia(1.0), cuboid.center_of_mass());
+
+    world.add_collider(
+        COLLIDER_MARGIN,
+        cuboid,
+        rb,
+        Isometry3::identity(),
+        Material::default(),
+    );
+
+    let constraint = PointOnPlaneConstraint::new(
+        BodyHandle::ground(),
+        rb,
+        Point3::new(0.0, 5.0, 0.0),
+        Unit::new_normalize(Vector3::new(1.0, 0.0, 1.0)),
+        Point3::new(rad, rad, rad),
+    );
+    world.add_constraint(constraint);
+
+    /*
+     * Set up the testbed.
+     */
+    let mut testbed = Testbed::new(world);
+    testbed.look_at(Point3::new(-10.0, 5.0, -10.0), Point3::new(0.0, 3.0, 0.0));
+    testbed.run();
+}
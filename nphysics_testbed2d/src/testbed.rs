@@ -377,7 +377,7 @@ impl State for Testbed {
                             .constraint_mut(joint)
                             .downcast_mut::<MouseConstraint<f32>>()
                             .unwrap();
-                        joint.set_anchor_1(attach2);
+                        joint.set_target(attach2);
                     }
 
                     event.inhibited = modifiers.contains(Modifiers::Control)
@@ -433,7 +433,7 @@ impl State for Testbed {
                                 .constraint_mut(joint)
                                 .downcast_mut::<MouseConstraint<f32>>()
                                 .unwrap();
-                            joint.set_anchor_1(pos + dir * inter)
+                            joint.set_target(pos + dir * inter)
                         }
                     }
 
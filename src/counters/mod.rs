@@ -5,11 +5,13 @@ use std::fmt::{Display, Formatter, Result};
 pub use self::collision_detection_counters::CollisionDetectionCounters;
 pub use self::solver_counters::SolverCounters;
 pub use self::stages_counters::StagesCounters;
+pub use self::step_stats::StepStats;
 pub use self::timer::Timer;
 
 mod collision_detection_counters;
 mod solver_counters;
 mod stages_counters;
+mod step_stats;
 mod timer;
 
 /// Aggregation of all the performances counters tracked by nphysics.
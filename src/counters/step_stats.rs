@@ -0,0 +1,28 @@
+/// Statistics about the last step of a physics world.
+///
+/// Unlike the other counters, those statistics are always collected since they are cheap to
+/// populate.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct StepStats {
+    /// Number of islands of active bodies, i.e., of groups of active bodies connected by contacts
+    /// or joint constraints.
+    ///
+    /// An active body not connected to any other active body is an island on its own.
+    pub nislands: usize,
+    /// Number of active bodies.
+    pub nbodies: usize,
+    /// Number of active rigid bodies not involved in any contact or joint constraint.
+    ///
+    /// Those bodies are integrated without the constraint solver.
+    pub nfree_bodies: usize,
+    /// Number of velocity constraints generated by contacts.
+    pub ncontact_constraints: usize,
+    /// Number of velocity constraints generated by joint constraints and multibody joints.
+    pub njoint_constraints: usize,
+    /// Number of velocity iterations run by the constraint solver.
+    pub nvelocity_iterations: usize,
+    /// Number of position iterations run by the constraint solver.
+    pub nposition_iterations: usize,
+    /// Time spent for the whole step, in seconds, if the performance counters are enabled.
+    pub step_time: Option<f64>,
+}
@@ -31,7 +31,7 @@ impl<N: Real> ActivationManager<N> {
         );
 
         ActivationManager {
-            mix_factor: mix_factor,
+            mix_factor,
             ufind: Vec::new(),
             can_deactivate: Vec::new(),
             to_activate: Vec::new(),
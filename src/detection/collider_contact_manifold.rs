@@ -1,7 +1,9 @@
-use na::Real;
+use na::{Real, Unit};
 use ncollide::query::{ContactManifold, TrackedContact};
+use ncollide::utils::GenerationalId;
 
-use math::Isometry;
+use detection::ContactConstraintData;
+use math::{Isometry, Vector};
 use object::{BodyHandle, Collider};
 
 /// A contact manifold between two bodies.
@@ -27,6 +29,15 @@ pub struct ColliderContactManifold<'a, N: Real> {
     pub pos_wrt_body2: Isometry<N>,
     /// The contact manifold.
     pub manifold: &'a ContactManifold<N>,
+    /// The contacts modified by the contact modification handler of the world.
+    ///
+    /// This is empty if the world has no such handler.
+    pub modified_contacts: Vec<ContactConstraintData<N>>,
+    /// The impulses of the points of the world's `ContactManifoldCache` matching the contacts
+    /// of this manifold.
+    ///
+    /// They are used to warm-start the contacts the contact model has no impulse for yet.
+    pub cached_impulses: Vec<(GenerationalId, N)>,
 }
 
 impl<'a, N: Real> ColliderContactManifold<'a, N> {
@@ -60,6 +71,8 @@ impl<'a, N: Real> ColliderContactManifold<'a, N> {
             pos_wrt_body1,
             pos_wrt_body2,
             manifold,
+            modified_contacts: Vec::new(),
+            cached_impulses: Vec::new(),
         }
     }
 
@@ -78,6 +91,31 @@ impl<'a, N: Real> ColliderContactManifold<'a, N> {
         self.manifold.deepest_contact()
     }
 
+    /// The modified data of the given contact, if it was modified.
+    pub fn contact_data(&self, contact: &TrackedContact<N>) -> Option<&ContactConstraintData<N>> {
+        self.modified_contacts.iter().find(|data| data.id() == contact.id)
+    }
+
+    /// The normal of the given contact, taking its modified data into account.
+    pub fn contact_normal(&self, contact: &TrackedContact<N>) -> Unit<Vector<N>> {
+        self.contact_data(contact)
+            .and_then(|data| data.normal)
+            .unwrap_or(contact.contact.normal)
+    }
+
+    /// The impulse of the cached contact point matching the given contact, if any.
+    pub fn cached_impulse(&self, contact: &TrackedContact<N>) -> Option<N> {
+        self.cached_impulses
+            .iter()
+            .find(|entry| entry.0 == contact.id)
+            .map(|entry| entry.1)
+    }
+
+    /// Whether the given contact should generate constraints.
+    pub fn is_contact_enabled(&self, contact: &TrackedContact<N>) -> bool {
+        self.contact_data(contact).map_or(true, |data| data.enabled)
+    }
+
     /// The handle of the first body part involved in the contact.
     pub fn body1(&self) -> BodyHandle {
         self.collider1.data().body()
@@ -0,0 +1,21 @@
+use object::BodyHandle;
+
+/// An event emitted when two body parts start or stop touching.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ContactEvent {
+    /// The two body parts started touching.
+    Started(BodyHandle, BodyHandle),
+    /// The two body parts stopped touching.
+    Stopped(BodyHandle, BodyHandle),
+}
+
+/// Trait implemented by user-defined handlers of contact events.
+///
+/// The handler is called by the world at each timestep, right after the narrow phase,
+/// for each contact event generated during this timestep.
+pub trait ContactEventHandler: Send + Sync {
+    /// Called when the body parts `b1` and `b2` start touching.
+    fn handle_contact_started(&mut self, b1: BodyHandle, b2: BodyHandle);
+    /// Called when the body parts `b1` and `b2` stop touching.
+    fn handle_contact_stopped(&mut self, b1: BodyHandle, b2: BodyHandle);
+}
@@ -0,0 +1,261 @@
+use na::{self, Real};
+use std::collections::BTreeMap;
+
+use math::Point;
+use object::BodyHandle;
+
+/// The maximum number of contact points kept for a pair of body parts.
+pub const MAX_CACHED_CONTACTS: usize = 4;
+
+/// A contact point kept from one timestep to the next by a `ContactManifoldCache`.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct CachedContact<N: Real> {
+    /// The contact point on the first body part, expressed in its local-space.
+    pub local1: Point<N>,
+    /// The contact point on the second body part, expressed in its local-space.
+    pub local2: Point<N>,
+    /// The penetration depth of the contact when it was last seen.
+    pub depth: N,
+    /// The normal impulse accumulated for this contact by the solver.
+    pub impulse: N,
+}
+
+impl<N: Real> CachedContact<N> {
+    /// Whether `self` and `other` are closer than `max_drift` on both body parts.
+    pub fn matches(&self, other: &CachedContact<N>, max_drift: N) -> bool {
+        let drift_sq = max_drift * max_drift;
+
+        na::distance_squared(&self.local1, &other.local1) <= drift_sq
+            && na::distance_squared(&self.local2, &other.local2) <= drift_sq
+    }
+}
+
+/// A cache of the contact points between pairs of body parts, kept from one timestep to the next.
+///
+/// Contacts created by the narrow phase are matched against the points cached for the same
+/// pair of body parts at the previous timestep. A cached point is kept as long as both of its
+/// local positions stay closer than `max_drift` to a new contact. It is then used to warm-start
+/// the solver with the impulse it accumulated. Points that slid further are dropped and replaced
+/// by the new contacts. At most `MAX_CACHED_CONTACTS` points are kept per pair.
+pub struct ContactManifoldCache<N: Real> {
+    // NOTE: ordered so the iteration order does not depend on the hashing of the handles.
+    manifolds: BTreeMap<(BodyHandle, BodyHandle), Vec<CachedContact<N>>>,
+    max_drift: N,
+}
+
+impl<N: Real> ContactManifoldCache<N> {
+    /// Creates an empty cache dropping the points that drift further than `max_drift`.
+    pub fn new(max_drift: N) -> Self {
+        ContactManifoldCache {
+            manifolds: BTreeMap::new(),
+            max_drift,
+        }
+    }
+
+    /// The distance a contact point may slide on a body part before being dropped from the cache.
+    pub fn max_drift(&self) -> N {
+        self.max_drift
+    }
+
+    /// Sets the distance a contact point may slide on a body part before being dropped from the
+    /// cache.
+    pub fn set_max_drift(&mut self, max_drift: N) {
+        assert!(
+            max_drift >= N::zero(),
+            "The maximum drift of cached contacts must be positive or zero."
+        );
+        self.max_drift = max_drift
+    }
+
+    /// The points cached for the pair of body parts `(b1, b2)`.
+    ///
+    /// The order of the body parts is the one of the contact manifolds they were cached from,
+    /// so both `(b1, b2)` and `(b2, b1)` may have to be tested.
+    pub fn contacts(&self, b1: BodyHandle, b2: BodyHandle) -> Option<&[CachedContact<N>]> {
+        self.manifolds.get(&(b1, b2)).map(|contacts| &contacts[..])
+    }
+
+    /// An iterator through all the pairs of body parts with cached points, and their points.
+    ///
+    /// The pairs are yielded in increasing order of their body part handles.
+    pub fn pairs<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = (BodyHandle, BodyHandle, &'a [CachedContact<N>])> + 'a {
+        self.manifolds
+            .iter()
+            .map(|(pair, contacts)| (pair.0, pair.1, &contacts[..]))
+    }
+
+    /// The cached point matching `contact` for the pair of body parts `(b1, b2)`, if any.
+    pub fn find(
+        &self,
+        b1: BodyHandle,
+        b2: BodyHandle,
+        contact: &CachedContact<N>,
+    ) -> Option<&CachedContact<N>> {
+        let max_drift = self.max_drift;
+        self.manifolds
+            .get(&(b1, b2))?
+            .iter()
+            .find(|cached| cached.matches(contact, max_drift))
+    }
+
+    /// Replaces the points cached for the pair of body parts `(b1, b2)` by `contacts`.
+    ///
+    /// The new contacts matching a cached point keep its local positions. Cached points without
+    /// any match are dropped. If there are more than `MAX_CACHED_CONTACTS` points, the deepest one
+    /// is kept together with the points spreading the manifold the most.
+    pub fn update(&mut self, b1: BodyHandle, b2: BodyHandle, contacts: &[CachedContact<N>]) {
+        let max_drift = self.max_drift;
+        let cached = self.manifolds.entry((b1, b2)).or_insert_with(Vec::new);
+        let mut points = Vec::with_capacity(contacts.len());
+
+        for contact in contacts {
+            let mut point = *contact;
+
+            if let Some(old) = cached.iter().find(|old| old.matches(contact, max_drift)) {
+                point.local1 = old.local1;
+                point.local2 = old.local2;
+            }
+
+            points.push(point);
+        }
+
+        reduce(&mut points);
+        *cached = points;
+    }
+
+    /// Removes the points cached for the pair of body parts `(b1, b2)`.
+    pub fn remove(&mut self, b1: BodyHandle, b2: BodyHandle) {
+        let _ = self.manifolds.remove(&(b1, b2));
+    }
+
+    /// Only keeps the pairs of body parts for which `f` returns `true`.
+    pub fn retain<F: FnMut(BodyHandle, BodyHandle) -> bool>(&mut self, mut f: F) {
+        let stale: Vec<_> = self
+            .manifolds
+            .keys()
+            .filter(|pair| !f(pair.0, pair.1))
+            .cloned()
+            .collect();
+
+        for pair in stale {
+            let _ = self.manifolds.remove(&pair);
+        }
+    }
+
+    /// Removes all the cached points.
+    pub fn clear(&mut self) {
+        self.manifolds.clear()
+    }
+
+    /// The number of pairs of body parts with cached points.
+    pub fn len(&self) -> usize {
+        self.manifolds.len()
+    }
+
+    /// Whether no pair of body parts has cached points.
+    pub fn is_empty(&self) -> bool {
+        self.manifolds.is_empty()
+    }
+}
+
+// Reduces `points` to at most `MAX_CACHED_CONTACTS` points: the deepest one, and then greedily
+// the points farthest from the ones already selected.
+fn reduce<N: Real>(points: &mut Vec<CachedContact<N>>) {
+    if points.len() <= MAX_CACHED_CONTACTS {
+        return;
+    }
+
+    let deepest = (1..points.len()).fold(0, |best, i| {
+        if points[i].depth > points[best].depth {
+            i
+        } else {
+            best
+        }
+    });
+    points.swap(0, deepest);
+
+    for selected in 1..MAX_CACHED_CONTACTS {
+        let mut farthest = selected;
+        let mut max_dist = -N::one();
+
+        for (i, candidate) in points.iter().enumerate().skip(selected) {
+            let dist = points[..selected]
+                .iter()
+                .map(|p| na::distance_squared(&p.local1, &candidate.local1))
+                .fold(N::max_value(), |a, b| na::inf(&a, &b));
+
+            if dist > max_dist {
+                max_dist = dist;
+                farthest = i;
+            }
+        }
+
+        points.swap(selected, farthest);
+    }
+
+    points.truncate(MAX_CACHED_CONTACTS);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CachedContact, ContactManifoldCache, MAX_CACHED_CONTACTS};
+    use math::{Point, Vector};
+    use object::BodyHandle;
+
+    fn point(x: f32, impulse: f32) -> CachedContact<f32> {
+        CachedContact {
+            local1: Point::origin() + Vector::x() * x,
+            local2: Point::origin(),
+            depth: 0.0,
+            impulse,
+        }
+    }
+
+    #[test]
+    fn contact_manifold_cache_drops_the_points_that_slid_too_far() {
+        let b1 = BodyHandle::ground();
+        let b2 = BodyHandle::new(0, 0);
+
+        let mut cache = ContactManifoldCache::new(0.1);
+        cache.update(b1, b2, &[point(0.0, 1.0)]);
+
+        // A point that barely moved keeps its cached position.
+        cache.update(b1, b2, &[point(0.05, 2.0)]);
+        let kept = cache.contacts(b1, b2).unwrap()[0];
+        assert_eq!(kept.local1, Point::origin());
+        assert_relative_eq!(kept.impulse, 2.0);
+        assert!(cache.find(b1, b2, &point(0.08, 0.0)).is_some());
+
+        // A point that slid too far is replaced.
+        cache.update(b1, b2, &[point(0.5, 3.0)]);
+        let replaced = cache.contacts(b1, b2).unwrap()[0];
+        assert_eq!(replaced.local1, Point::origin() + Vector::x() * 0.5);
+        assert!(cache.find(b1, b2, &point(0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn contact_manifold_cache_keeps_the_deepest_and_most_spread_points() {
+        let b1 = BodyHandle::ground();
+        let b2 = BodyHandle::new(0, 0);
+        let mut cache = ContactManifoldCache::new(0.1);
+
+        let mut many: Vec<_> = (0..8).map(|i| point(i as f32, 0.0)).collect();
+        many[5].depth = 1.0;
+        cache.update(b1, b2, &many);
+
+        {
+            // At most four points are kept, including the deepest one and both ends.
+            let points = cache.contacts(b1, b2).unwrap();
+            assert_eq!(points.len(), MAX_CACHED_CONTACTS);
+            assert!(points.iter().any(|p| relative_eq!(p.depth, 1.0)));
+            assert!(points.iter().any(|p| relative_eq!(p.local1.x, 0.0)));
+            assert!(points.iter().any(|p| relative_eq!(p.local1.x, 7.0)));
+        }
+
+        cache.retain(|_, _| false);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+use na::{Real, Unit};
+use ncollide::query::{Contact, TrackedContact};
+use ncollide::utils::GenerationalId;
+
+use math::Vector;
+use object::BodyHandle;
+
+/// The data of a single contact that can be modified before its constraints are generated.
+#[derive(Clone, Debug)]
+pub struct ContactConstraintData<N: Real> {
+    id: GenerationalId,
+    contact: Contact<N>,
+    /// The friction coefficient used instead of the one combined from the collider materials.
+    pub friction: Option<N>,
+    /// The restitution coefficient used instead of the one combined from the collider materials.
+    pub restitution: Option<N>,
+    /// The contact normal used instead of the one computed by the narrow phase.
+    ///
+    /// It must point toward the second body part, like the normal of `self.contact()`.
+    pub normal: Option<Unit<Vector<N>>>,
+    /// Whether this contact generates constraints. The contact is ignored by the solver if `false`.
+    pub enabled: bool,
+}
+
+impl<N: Real> ContactConstraintData<N> {
+    /// Initializes the contact data of `contact`, without any override.
+    pub fn new(contact: &TrackedContact<N>) -> Self {
+        ContactConstraintData {
+            id: contact.id,
+            contact: contact.contact.clone(),
+            friction: None,
+            restitution: None,
+            normal: None,
+            enabled: true,
+        }
+    }
+
+    /// The identifier of the contact this data applies to.
+    pub fn id(&self) -> GenerationalId {
+        self.id
+    }
+
+    /// The contact geometry, in world-space.
+    pub fn contact(&self) -> &Contact<N> {
+        &self.contact
+    }
+}
+
+/// Trait implemented by user-defined handlers modifying contacts before they are solved.
+///
+/// The handler is called by the world at each timestep, after the narrow phase, for each contact
+/// involving at least one active dynamic body part.
+pub trait ContactModificationHandler<N: Real>: Send + Sync {
+    /// Called for each contact between the body parts `b1` and `b2`.
+    fn modify_contact(
+        &mut self,
+        b1: BodyHandle,
+        b2: BodyHandle,
+        contact: &mut ContactConstraintData<N>,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::{ContactConstraintData, ContactModificationHandler};
+    use math::{Isometry, Vector};
+    use object::{BodyHandle, Material};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn contact_modification_handler_overrides_and_removes_contacts() {
+        struct IceAndHoles {
+            on_ice: BodyHandle,
+            over_hole: BodyHandle,
+        }
+
+        impl ContactModificationHandler<f32> for IceAndHoles {
+            fn modify_contact(
+                &mut self,
+                b1: BodyHandle,
+                b2: BodyHandle,
+                contact: &mut ContactConstraintData<f32>,
+            ) {
+                if b1 == self.on_ice || b2 == self.on_ice {
+                    contact.friction = Some(0.0);
+                } else if b1 == self.over_hole || b2 == self.over_hole {
+                    contact.enabled = false;
+                }
+            }
+        }
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = world.add_collider(
+            0.01,
+            floor,
+            BodyHandle::ground(),
+            Isometry::new(Vector::y() * -10.0, na::zero()),
+            Material::new(0.0, 1.0),
+        );
+
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let add_cube = |world: &mut World<f32>, x: f32| {
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::x() * x + Vector::y() * 0.5, na::zero()),
+                cube.inertia(1.0),
+                cube.center_of_mass(),
+            );
+            let _ = world.add_collider(
+                0.01,
+                cube.clone(),
+                body,
+                Isometry::identity(),
+                Material::new(0.0, 1.0),
+            );
+            world
+                .rigid_body_mut(body)
+                .unwrap()
+                .set_linear_velocity(Vector::x() * 3.0);
+            body
+        };
+
+        let on_ice = add_cube(&mut world, -6.0);
+        let over_hole = add_cube(&mut world, 0.0);
+        let rough = add_cube(&mut world, 6.0);
+        world.set_contact_modification_handler(IceAndHoles { on_ice, over_hole });
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let on_ice = world.rigid_body(on_ice).unwrap();
+        let over_hole = world.rigid_body(over_hole).unwrap();
+        let rough = world.rigid_body(rough).unwrap();
+        assert!(on_ice.velocity().linear.x > 2.9, "On ice: {}", on_ice.velocity().linear);
+        assert!(rough.velocity().linear.x.abs() < 1.0e-2, "Rough: {}", rough.velocity().linear);
+        assert!(over_hole.position().translation.vector.y < -1.0, "The box did not fall.");
+    }
+}
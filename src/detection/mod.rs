@@ -2,6 +2,14 @@
 
 pub use self::activation_manager::ActivationManager;
 pub use self::collider_contact_manifold::ColliderContactManifold;
+pub use self::contact_manifold_cache::{CachedContact, ContactManifoldCache, MAX_CACHED_CONTACTS};
+pub use self::contact_event::{ContactEvent, ContactEventHandler};
+pub use self::contact_modification::{ContactConstraintData, ContactModificationHandler};
+pub use self::narrow_phase_algorithm::{gjk_epa_contact, NarrowPhaseAlgorithm};
 
 mod collider_contact_manifold;
+mod contact_manifold_cache;
+mod contact_event;
+mod contact_modification;
+mod narrow_phase_algorithm;
 mod activation_manager;
@@ -0,0 +1,491 @@
+use alga::linear::FiniteDimInnerSpace;
+#[cfg(feature = "dim3")]
+use na;
+use na::{DMatrix, DVector, Real, Unit};
+use ncollide::query::Contact;
+use ncollide::shape::SupportMap;
+
+use math::{Isometry, Point, Vector, DIM};
+
+/// The algorithm used by the world to compute the contact normals of pairs of convex shapes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NarrowPhaseAlgorithm<N: Real> {
+    /// The contact manifold generators of ncollide, selected from the types of both shapes.
+    Default,
+    /// The GJK algorithm, followed by the EPA algorithm for penetrating shapes.
+    ///
+    /// The contact points of each pair of shapes having a support map are still generated by
+    /// ncollide, but their normal is replaced by the one computed by `gjk_epa_contact` with those
+    /// termination criteria. Fewer iterations and a larger tolerance are cheaper, but the normals
+    /// of very thin shapes may then be inaccurate.
+    GjkEpa {
+        /// The maximum number of iterations of both GJK and EPA.
+        max_iterations: usize,
+        /// The distance below which an iteration is considered not to improve the result anymore.
+        tolerance: N,
+    },
+}
+
+impl<N: Real> Default for NarrowPhaseAlgorithm<N> {
+    fn default() -> Self {
+        NarrowPhaseAlgorithm::Default
+    }
+}
+
+// A point of the Minkowski difference of two shapes, with the support points it comes from.
+#[derive(Copy, Clone, Debug)]
+struct SupportPoint<N: Real> {
+    w: Vector<N>,
+    a: Point<N>,
+    b: Point<N>,
+}
+
+/// Computes the contact between the convex shapes `g1` and `g2` with GJK and EPA.
+///
+/// Returns `None` if the shapes are separated by more than `prediction`. The normal of the
+/// contact points toward `g2`. Its depth is negative if the shapes do not penetrate. Both
+/// algorithms stop after `max_iterations` iterations, or as soon as an iteration improves the
+/// distance (or the penetration depth) by less than `tolerance`.
+pub fn gjk_epa_contact<N: Real>(
+    m1: &Isometry<N>,
+    g1: &SupportMap<N>,
+    m2: &Isometry<N>,
+    g2: &SupportMap<N>,
+    prediction: N,
+    max_iterations: usize,
+    tolerance: N,
+) -> Option<Contact<N>> {
+    let support = |dir: &Vector<N>| {
+        let a = g1.support_point(m1, dir);
+        let b = g2.support_point(m2, &-*dir);
+
+        SupportPoint { w: a - b, a, b }
+    };
+
+    let mut dir = m2.translation.vector - m1.translation.vector;
+    if dir.norm_squared() <= N::default_epsilon() {
+        dir = Vector::x();
+    }
+
+    let mut simplex = vec![support(&dir)];
+
+    for _ in 0..max_iterations {
+        let (v, reduced) = closest_point_on_simplex(&simplex);
+        simplex = reduced;
+
+        if simplex.len() == DIM + 1 || v.norm_squared() <= N::default_epsilon() {
+            return epa(simplex, &support, max_iterations, tolerance);
+        }
+
+        let (a, b) = witness_points(&simplex, &v);
+        let dist = v.norm();
+        let w = support(&-v);
+        let lower_bound = w.w.dot(&v) / dist;
+
+        if lower_bound > prediction {
+            return None;
+        }
+
+        if dist - lower_bound <= tolerance {
+            return separated_contact(a, b, v, dist, prediction);
+        }
+
+        simplex.push(w);
+    }
+
+    let (v, reduced) = closest_point_on_simplex(&simplex);
+    let (a, b) = witness_points(&reduced, &v);
+    separated_contact(a, b, v, v.norm(), prediction)
+}
+
+fn separated_contact<N: Real>(
+    a: Point<N>,
+    b: Point<N>,
+    v: Vector<N>,
+    dist: N,
+    prediction: N,
+) -> Option<Contact<N>> {
+    if dist > prediction {
+        return None;
+    }
+
+    // The closest point `v = a - b` of the Minkowski difference points from `b` toward `a`.
+    let normal = Unit::try_new(-v, N::default_epsilon())?;
+    Some(Contact::new(a, b, normal, -dist))
+}
+
+// The support points of both shapes corresponding to the point `v` of `simplex`.
+fn witness_points<N: Real>(simplex: &[SupportPoint<N>], v: &Vector<N>) -> (Point<N>, Point<N>) {
+    let points: Vec<_> = simplex.iter().map(|p| p.w).collect();
+    let bcoords = affine_coordinates(&points).unwrap_or_else(|| {
+        // Degenerate simplex: fall back to its vertex closest to `v`.
+        let mut res = vec![N::zero(); simplex.len()];
+        let best = (1..points.len()).fold(0, |best, i| {
+            if (points[i] - v).norm_squared() < (points[best] - v).norm_squared() {
+                i
+            } else {
+                best
+            }
+        });
+        res[best] = N::one();
+        res
+    });
+
+    let mut a = Vector::zeros();
+    let mut b = Vector::zeros();
+
+    for (p, lambda) in simplex.iter().zip(bcoords.iter()) {
+        a += p.a.coords * *lambda;
+        b += p.b.coords * *lambda;
+    }
+
+    (Point::from(a), Point::from(b))
+}
+
+// The affine coordinates of the point of the affine hull of `points` closest to the origin, or
+// `None` if the points are not affinely independent.
+fn affine_coordinates<N: Real>(points: &[Vector<N>]) -> Option<Vec<N>> {
+    let n = points.len() - 1;
+
+    if n == 0 {
+        return Some(vec![N::one()]);
+    }
+
+    let origin = points[0];
+    let edges: Vec<_> = points[1..].iter().map(|p| p - origin).collect();
+    let gram = DMatrix::from_fn(n, n, |i, j| edges[i].dot(&edges[j]));
+    let rhs = DVector::from_fn(n, |i, _| -edges[i].dot(&origin));
+    let mu = gram.lu().solve(&rhs)?;
+
+    let mut res = Vec::with_capacity(n + 1);
+    res.push(N::one() - mu.iter().fold(N::zero(), |acc, x| acc + *x));
+    res.extend(mu.iter().cloned());
+
+    Some(res)
+}
+
+// The point of the convex hull of `simplex` closest to the origin, and the smallest sub-simplex
+// containing it.
+fn closest_point_on_simplex<N: Real>(
+    simplex: &[SupportPoint<N>],
+) -> (Vector<N>, Vec<SupportPoint<N>>) {
+    let eps = N::default_epsilon().sqrt();
+    let mut best: Option<(N, Vector<N>, Vec<SupportPoint<N>>)> = None;
+
+    // The simplex has at most `DIM + 1` vertices so all its faces can be tested.
+    for mask in 1..(1usize << simplex.len()) {
+        let face: Vec<_> = (0..simplex.len())
+            .filter(|i| mask & (1 << *i) != 0)
+            .map(|i| simplex[i])
+            .collect();
+        let points: Vec<_> = face.iter().map(|p| p.w).collect();
+
+        if let Some(bcoords) = affine_coordinates(&points) {
+            if bcoords.iter().all(|lambda| *lambda >= -eps) {
+                let v = points
+                    .iter()
+                    .zip(bcoords.iter())
+                    .fold(Vector::zeros(), |acc, (p, lambda)| acc + p * *lambda);
+                let dist = v.norm_squared();
+
+                if best.as_ref().map_or(true, |best| dist < best.0) {
+                    best = Some((dist, v, face));
+                }
+            }
+        }
+    }
+
+    let (_, v, face) = best.expect("Internal error: a simplex vertex is always a candidate.");
+    (v, face)
+}
+
+// Adds points of the Minkowski difference to `simplex` until it has `DIM + 1` affinely
+// independent vertices. Returns `false` if the Minkowski difference is flat.
+fn blow_up<N: Real, S: Fn(&Vector<N>) -> SupportPoint<N>>(
+    simplex: &mut Vec<SupportPoint<N>>,
+    support: &S,
+) -> bool {
+    let eps = N::default_epsilon().sqrt();
+
+    while simplex.len() < DIM + 1 {
+        // An orthonormal basis of the directions of the affine hull of the simplex, keeping only
+        // its affinely independent vertices.
+        let origin = simplex[0].w;
+        let mut basis: Vec<Vector<N>> = Vec::new();
+        let mut independent = vec![simplex[0]];
+        for p in &simplex[1..] {
+            let e = basis
+                .iter()
+                .fold(p.w - origin, |e, u| e - u * u.dot(&(p.w - origin)));
+
+            if let Some(u) = Unit::try_new(e, eps) {
+                basis.push(u.into_inner());
+                independent.push(*p);
+            }
+        }
+
+        let mut complement = Vec::new();
+        if basis.is_empty() {
+            for i in 0..DIM {
+                let mut dir = Vector::zeros();
+                dir[i] = N::one();
+                complement.push(dir);
+            }
+        } else {
+            Vector::orthonormal_subspace_basis(&basis[..], |dir| {
+                complement.push(*dir);
+                true
+            });
+        }
+
+        let offset = |p: &SupportPoint<N>| {
+            basis
+                .iter()
+                .fold(p.w - origin, |e, u| e - u * u.dot(&(p.w - origin)))
+                .norm()
+        };
+
+        let new_point = complement
+            .iter()
+            .flat_map(|dir| vec![support(dir), support(&-*dir)])
+            .find(|p| offset(p) > eps);
+
+        match new_point {
+            Some(p) => {
+                independent.push(p);
+                *simplex = independent;
+            }
+            None => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(feature = "dim3")]
+fn epa<N: Real, S: Fn(&Vector<N>) -> SupportPoint<N>>(
+    mut simplex: Vec<SupportPoint<N>>,
+    support: &S,
+    max_iterations: usize,
+    tolerance: N,
+) -> Option<Contact<N>> {
+    if !blow_up(&mut simplex, support) {
+        return None;
+    }
+
+    let sum = simplex.iter().fold(Vector::zeros(), |acc, p| acc + p.w);
+    let center = sum / na::convert::<f64, N>(4.0);
+    let make_face = |vertices: &[SupportPoint<N>], ids: [usize; 3]| {
+        let (p0, p1, p2) = (vertices[ids[0]].w, vertices[ids[1]].w, vertices[ids[2]].w);
+        let mut normal = (p1 - p0).cross(&(p2 - p0));
+
+        // Orient the faces outward, the polytope being convex.
+        if normal.dot(&(p0 - center)) < N::zero() {
+            normal = -normal;
+        }
+
+        match Unit::try_new(normal, N::default_epsilon()) {
+            Some(normal) => (ids, normal.into_inner(), normal.dot(&p0)),
+            // Degenerate faces are never selected nor removed.
+            None => (ids, Vector::zeros(), N::max_value()),
+        }
+    };
+
+    let mut vertices = simplex;
+    let mut faces: Vec<_> = [[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]]
+        .iter()
+        .map(|ids| make_face(&vertices, *ids))
+        .collect();
+
+    let closest_face = |faces: &[([usize; 3], Vector<N>, N)]| {
+        (1..faces.len()).fold(0, |best, i| {
+            if faces[i].2 < faces[best].2 {
+                i
+            } else {
+                best
+            }
+        })
+    };
+
+    for _ in 0..max_iterations {
+        let (_, normal, dist) = faces[closest_face(&faces)];
+        let w = support(&normal);
+
+        if w.w.dot(&normal) - dist <= tolerance {
+            break;
+        }
+
+        // Remove the faces visible from the new vertex and close the hole they leave.
+        let new_id = vertices.len();
+        vertices.push(w);
+
+        let mut visible = Vec::new();
+        faces.retain(|face| {
+            let is_visible = face.1.dot(&(w.w - vertices[face.0[0]].w)) > N::zero();
+            if is_visible {
+                visible.push(face.0);
+            }
+            !is_visible
+        });
+
+        let mut horizon = Vec::new();
+        for ids in &visible {
+            for i in 0..3 {
+                let edge = (ids[i], ids[(i + 1) % 3]);
+                let shared = |other: &[usize; 3]| {
+                    other != ids && other.contains(&edge.0) && other.contains(&edge.1)
+                };
+
+                if !visible.iter().any(shared) {
+                    horizon.push(edge);
+                }
+            }
+        }
+
+        for edge in horizon {
+            faces.push(make_face(&vertices, [edge.0, edge.1, new_id]));
+        }
+    }
+
+    let (ids, normal, dist) = faces[closest_face(&faces)];
+    let face = [vertices[ids[0]], vertices[ids[1]], vertices[ids[2]]];
+    penetration_contact(&face, normal, dist)
+}
+
+#[cfg(feature = "dim2")]
+fn epa<N: Real, S: Fn(&Vector<N>) -> SupportPoint<N>>(
+    mut simplex: Vec<SupportPoint<N>>,
+    support: &S,
+    max_iterations: usize,
+    tolerance: N,
+) -> Option<Contact<N>> {
+    if !blow_up(&mut simplex, support) {
+        return None;
+    }
+
+    // Order the polygon counterclockwise so the outward normal of each edge is on its right.
+    let (e1, e2) = (simplex[1].w - simplex[0].w, simplex[2].w - simplex[0].w);
+    if e1.x * e2.y - e1.y * e2.x < N::zero() {
+        simplex.swap(1, 2);
+    }
+
+    let edge = |polygon: &[SupportPoint<N>], i: usize| {
+        let (a, b) = (polygon[i].w, polygon[(i + 1) % polygon.len()].w);
+        let dir = b - a;
+
+        match Unit::try_new(Vector::new(dir.y, -dir.x), N::default_epsilon()) {
+            Some(normal) => (normal.into_inner(), normal.dot(&a)),
+            None => (Vector::zeros(), N::max_value()),
+        }
+    };
+
+    let closest_edge = |polygon: &[SupportPoint<N>]| {
+        (1..polygon.len()).fold(0, |best, i| {
+            if edge(polygon, i).1 < edge(polygon, best).1 {
+                i
+            } else {
+                best
+            }
+        })
+    };
+
+    let mut polygon = simplex;
+
+    for _ in 0..max_iterations {
+        let closest = closest_edge(&polygon);
+        let (normal, dist) = edge(&polygon, closest);
+        let w = support(&normal);
+
+        if w.w.dot(&normal) - dist <= tolerance {
+            break;
+        }
+
+        polygon.insert(closest + 1, w);
+    }
+
+    let closest = closest_edge(&polygon);
+    let (normal, dist) = edge(&polygon, closest);
+    let face = [polygon[closest], polygon[(closest + 1) % polygon.len()]];
+    penetration_contact(&face, normal, dist)
+}
+
+// The contact corresponding to the projection of the origin on the `face` of the boundary of
+// the Minkowski difference.
+fn penetration_contact<N: Real>(
+    face: &[SupportPoint<N>],
+    normal: Vector<N>,
+    dist: N,
+) -> Option<Contact<N>> {
+    let normal = Unit::try_new(normal, N::default_epsilon())?;
+    let (a, b) = witness_points(face, &(*normal * dist));
+
+    Some(Contact::new(a, b, normal, dist))
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::{gjk_epa_contact, NarrowPhaseAlgorithm};
+    use math::{Isometry, Vector};
+    use object::{BodyHandle, Material};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn gjk_epa_contacts_match_the_distance_between_two_boxes() {
+        let big = Cuboid::new(Vector::repeat(1.0));
+        let small = Cuboid::new(Vector::repeat(0.5));
+        let contact = |height: f32| {
+            let m2 = Isometry::new(Vector::y() * height, na::zero());
+            gjk_epa_contact(&Isometry::identity(), &big, &m2, &small, 0.5, 100, 1.0e-6)
+        };
+
+        // Penetrating, separated, and farther than the prediction distance.
+        let penetrating = contact(1.3).unwrap();
+        assert!((penetrating.normal.into_inner() - Vector::y()).norm() < 1.0e-4);
+        assert!((penetrating.depth - 0.2).abs() < 1.0e-4, "Depth: {}", penetrating.depth);
+        assert!((penetrating.world1.y - 1.0).abs() < 1.0e-4);
+        assert!((penetrating.world2.y - 0.8).abs() < 1.0e-4);
+        let separated = contact(1.8).unwrap();
+        assert!((separated.normal.into_inner() - Vector::y()).norm() < 1.0e-4);
+        assert!((separated.depth + 0.3).abs() < 1.0e-4, "Depth: {}", separated.depth);
+        assert!(contact(2.1).is_none());
+
+        // A box resting on a thin plate.
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.set_narrow_phase_algorithm(NarrowPhaseAlgorithm::GjkEpa {
+            max_iterations: 50,
+            tolerance: 1.0e-5,
+        });
+        let mut half_extents = Vector::repeat(2.0);
+        half_extents.y = 0.01;
+        let plate = ShapeHandle::new(Cuboid::new(half_extents));
+        let _ = world.add_collider(
+            0.01,
+            plate,
+            BodyHandle::ground(),
+            Isometry::identity(),
+            Material::default(),
+        );
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.2)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * 0.22, na::zero()),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, cuboid, body, Isometry::identity(), Material::default());
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let rb = world.rigid_body(body).unwrap();
+        let center = rb.position().translation.vector;
+        assert!((center.y - 0.21).abs() < 3.0e-2, "Height: {}", center.y);
+        assert!(center.x.abs() < 1.0e-2 && rb.velocity().linear.norm() < 1.0e-1);
+    }
+}
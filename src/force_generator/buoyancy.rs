@@ -0,0 +1,596 @@
+use na::{self, Real, Unit};
+#[cfg(feature = "dim3")]
+use ncollide::procedural::IndexBuffer;
+#[cfg(feature = "dim2")]
+use ncollide::shape::ConvexPolygon;
+use ncollide::shape::{Ball, Cuboid, ShapeHandle};
+#[cfg(feature = "dim3")]
+use ncollide::shape::ConvexHull;
+#[cfg(feature = "dim3")]
+use ncollide::transformation;
+
+use force_generator::ForceGenerator;
+use math::{Force, Isometry, Point, Vector, DIM};
+use object::{BodyHandle, BodySet};
+use solver::IntegrationParameters;
+use volumetric::Volumetric;
+
+// The description of a shape used to compute its submerged volume.
+enum BuoyantGeometry<N: Real> {
+    // A ball of the given radius, handled analytically.
+    Ball(N),
+    // The boundary of a convex polytope, clipped by the fluid surface. Each element is a segment
+    // in 2D or a triangle in 3D, oriented toward the exterior of the polytope. The submerged
+    // volume is multiplied by `scale` to obtain the volume of the actual shape.
+    Polytope {
+        boundary: Vec<[Point<N>; DIM]>,
+        volume: N,
+        scale: N,
+    },
+    // The samples inside of a shape that is not convex, and the volume of this shape.
+    Samples { samples: Vec<Point<N>>, volume: N },
+}
+
+struct BuoyantPart<N: Real> {
+    handle: BodyHandle,
+    shape: ShapeHandle<N>,
+    shape_to_body: Isometry<N>,
+    geometry: BuoyantGeometry<N>,
+}
+
+/// Force generator applying the Archimedes' principle to a set of body parts.
+///
+/// The fluid occupies the half-space below a plane. The submerged volume of each body part and
+/// its centroid (the center of buoyancy) are exact for balls, cuboids, and convex polygons or
+/// polyhedra, which are clipped by the fluid surface. The submerged volume of other shapes is
+/// estimated by sampling them on a regular grid: a larger sampling resolution improves the
+/// accuracy of the buoyant force and of its application point.
+pub struct BuoyancyForce<N: Real> {
+    parts: Vec<BuoyantPart<N>>,
+    surface_point: Point<N>,
+    surface_normal: Unit<Vector<N>>,
+    density: N,
+    gravity: Vector<N>,
+    linear_drag: N,
+    angular_drag: N,
+    resolution: usize,
+}
+
+impl<N: Real> BuoyancyForce<N> {
+    /// Creates a buoyancy force generator for a fluid of the given density.
+    ///
+    /// The fluid surface is the plane passing through `surface_point` with the outward normal
+    /// `surface_normal`. The `gravity` should be the same as the gravity of the physics world.
+    pub fn new(
+        surface_point: Point<N>,
+        surface_normal: Unit<Vector<N>>,
+        density: N,
+        gravity: Vector<N>,
+    ) -> Self {
+        BuoyancyForce {
+            parts: Vec::new(),
+            surface_point,
+            surface_normal,
+            density,
+            gravity,
+            linear_drag: N::zero(),
+            angular_drag: N::zero(),
+            resolution: 8,
+        }
+    }
+
+    /// Sets the linear drag coefficient of the fluid.
+    ///
+    /// The drag force is proportional to this coefficient, the linear velocity, and the submerged fraction of the body part.
+    pub fn set_linear_drag(&mut self, drag: N) {
+        self.linear_drag = drag
+    }
+
+    /// Sets the angular drag coefficient of the fluid.
+    ///
+    /// The drag torque is proportional to this coefficient, the angular velocity, and the submerged fraction of the body part.
+    pub fn set_angular_drag(&mut self, drag: N) {
+        self.angular_drag = drag
+    }
+
+    /// Sets the number of samples along each dimension used to estimate the submerged volume
+    /// of the shapes that are not handled exactly (default: `8`).
+    ///
+    /// The body parts already added to this force generator are sampled again.
+    pub fn set_resolution(&mut self, resolution: usize) {
+        assert!(resolution > 0, "The sampling resolution must be non-zero.");
+        self.resolution = resolution;
+
+        for part in &mut self.parts {
+            if let BuoyantGeometry::Samples { .. } = part.geometry {
+                part.geometry = buoyant_geometry(&part.shape, resolution);
+            }
+        }
+    }
+
+    /// Add a body part to be affected by this force generator.
+    ///
+    /// The `shape`, located at `shape_to_body` relative to the body part, is used to
+    /// compute the submerged volume. It is usually the shape of a collider attached to
+    /// this body part. The submerged part of shapes that are neither convex nor support point
+    /// containment queries is approximated by the submerged part of their bounding box.
+    pub fn add_body_part(
+        &mut self,
+        body: BodyHandle,
+        shape: ShapeHandle<N>,
+        shape_to_body: Isometry<N>,
+    ) {
+        let geometry = buoyant_geometry(&shape, self.resolution);
+
+        self.parts.push(BuoyantPart {
+            handle: body,
+            shape,
+            shape_to_body,
+            geometry,
+        })
+    }
+}
+
+impl<N: Real> ForceGenerator<N> for BuoyancyForce<N> {
+    fn apply(&mut self, _: &IntegrationParameters<N>, bodies: &mut BodySet<N>) -> bool {
+        let mut i = 0;
+
+        while i < self.parts.len() {
+            if !bodies.contains(self.parts[i].handle) {
+                let _ = self.parts.swap_remove(i);
+                continue;
+            }
+
+            let buoyant = &self.parts[i];
+            i += 1;
+
+            let mut part = bodies.body_part_mut(buoyant.handle);
+            let pos = part.as_ref().position() * buoyant.shape_to_body;
+            let submerged = submerged_volume(
+                &buoyant.geometry,
+                &pos,
+                &self.surface_point,
+                &self.surface_normal,
+            );
+
+            let (volume, fraction, center) = match submerged {
+                Some(submerged) => submerged,
+                None => continue,
+            };
+
+            let com = part.as_ref().center_of_mass();
+            let vel = part.as_ref().velocity();
+            let buoyancy = -self.gravity * (self.density * volume);
+            let mut force =
+                Force::linear_at_point(buoyancy, &Point::from(center - com));
+            force += Force::new(
+                -vel.linear * (self.linear_drag * fraction),
+                -vel.angular * (self.angular_drag * fraction),
+            );
+
+            part.apply_force(&force);
+        }
+
+        true
+    }
+}
+
+// The description of `shape` used to compute its submerged volume.
+fn buoyant_geometry<N: Real>(shape: &ShapeHandle<N>, resolution: usize) -> BuoyantGeometry<N> {
+    if let Some(s) = shape.as_shape::<Ball<N>>() {
+        return BuoyantGeometry::Ball(s.radius());
+    }
+
+    if let Some(s) = shape.as_shape::<Cuboid<N>>() {
+        let half_extents = *s.half_extents();
+        let vertices = box_vertices(
+            &Point::from(-half_extents),
+            &Point::from(half_extents),
+        );
+        return polytope(&vertices, N::one());
+    }
+
+    #[cfg(feature = "dim2")]
+    {
+        if let Some(s) = shape.as_shape::<ConvexPolygon<N>>() {
+            return polytope(s.points(), N::one());
+        }
+    }
+
+    #[cfg(feature = "dim3")]
+    {
+        if let Some(s) = shape.as_shape::<ConvexHull<N>>() {
+            return polytope(s.points(), N::one());
+        }
+    }
+
+    let aabb = shape.aabb(&Isometry::identity());
+
+    let query = match shape.as_point_query() {
+        Some(query) => query,
+        None => {
+            let vertices = box_vertices(aabb.mins(), aabb.maxs());
+            let aabb_volume = (aabb.maxs() - aabb.mins())
+                .iter()
+                .fold(N::one(), |acc, e| acc * *e);
+            return polytope(&vertices, shape.volume() / aabb_volume);
+        }
+    };
+
+    let mins = *aabb.mins();
+    let extents = aabb.maxs() - aabb.mins();
+    let nsamples = (0..DIM).fold(1, |acc, _| acc * resolution);
+    let subdivisions: N = na::convert(resolution as f64);
+    let mut samples = Vec::new();
+
+    for k in 0..nsamples {
+        let mut pt = mins;
+        let mut rem = k;
+
+        for i in 0..DIM {
+            let id: N = na::convert((rem % resolution) as f64);
+            pt[i] += extents[i] * (id + na::convert(0.5)) / subdivisions;
+            rem /= resolution;
+        }
+
+        if query.contains_point(&Isometry::identity(), &pt) {
+            samples.push(pt);
+        }
+    }
+
+    BuoyantGeometry::Samples {
+        samples,
+        volume: shape.volume(),
+    }
+}
+
+// The vertices of the axis-aligned box with the given corners, in counterclockwise order.
+#[cfg(feature = "dim2")]
+fn box_vertices<N: Real>(mins: &Point<N>, maxs: &Point<N>) -> Vec<Point<N>> {
+    vec![
+        *mins,
+        Point::new(maxs.x, mins.y),
+        *maxs,
+        Point::new(mins.x, maxs.y),
+    ]
+}
+
+// The vertices of the axis-aligned box with the given corners.
+#[cfg(feature = "dim3")]
+fn box_vertices<N: Real>(mins: &Point<N>, maxs: &Point<N>) -> Vec<Point<N>> {
+    (0..8)
+        .map(|k| {
+            Point::new(
+                if k & 1 == 0 { mins.x } else { maxs.x },
+                if k & 2 == 0 { mins.y } else { maxs.y },
+                if k & 4 == 0 { mins.z } else { maxs.z },
+            )
+        })
+        .collect()
+}
+
+// The boundary of the convex polygon with the given vertices, given in a consistent order.
+#[cfg(feature = "dim2")]
+fn polytope<N: Real>(vertices: &[Point<N>], scale: N) -> BuoyantGeometry<N> {
+    let mut boundary: Vec<_> = (0..vertices.len())
+        .map(|i| [vertices[i], vertices[(i + 1) % vertices.len()]])
+        .collect();
+    let mut volume = boundary.iter().fold(N::zero(), |acc, s| {
+        acc + triangle_area(&vertices[0], &s[0], &s[1])
+    });
+
+    // Make the vertices counterclockwise.
+    if volume < N::zero() {
+        for segment in &mut boundary {
+            segment.swap(0, 1);
+        }
+
+        volume = -volume;
+    }
+
+    BuoyantGeometry::Polytope {
+        boundary,
+        volume,
+        scale,
+    }
+}
+
+// The boundary of the convex hull of the given vertices.
+#[cfg(feature = "dim3")]
+fn polytope<N: Real>(vertices: &[Point<N>], scale: N) -> BuoyantGeometry<N> {
+    let mesh = transformation::convex_hull(vertices);
+    let sum = mesh
+        .coords
+        .iter()
+        .fold(Vector::zeros(), |acc, pt| acc + pt.coords);
+    let center = Point::from(sum / na::convert::<f64, N>(mesh.coords.len() as f64));
+    let mut boundary = Vec::new();
+    let mut volume = N::zero();
+
+    match mesh.indices {
+        IndexBuffer::Unified(ref idx) => {
+            for t in idx.iter() {
+                let mut triangle = [
+                    mesh.coords[t.x as usize],
+                    mesh.coords[t.y as usize],
+                    mesh.coords[t.z as usize],
+                ];
+                let mut tet_volume = tetrahedron_volume(&center, &triangle);
+
+                // Orient the triangle toward the exterior.
+                if tet_volume < N::zero() {
+                    triangle.swap(1, 2);
+                    tet_volume = -tet_volume;
+                }
+
+                volume += tet_volume;
+                boundary.push(triangle);
+            }
+        }
+        IndexBuffer::Split(_) => unreachable!(),
+    }
+
+    BuoyantGeometry::Polytope {
+        boundary,
+        volume,
+        scale,
+    }
+}
+
+// The buoyant volume, the submerged fraction, and the center of buoyancy of a shape with the
+// given geometry and position. Returns `None` if the shape is not submerged.
+fn submerged_volume<N: Real>(
+    geometry: &BuoyantGeometry<N>,
+    pos: &Isometry<N>,
+    surface_point: &Point<N>,
+    surface_normal: &Unit<Vector<N>>,
+) -> Option<(N, N, Point<N>)> {
+    let depth = |pt: &Point<N>| surface_normal.dot(&(*pt - *surface_point));
+
+    match *geometry {
+        BuoyantGeometry::Ball(radius) => {
+            let center = pos * Point::origin();
+            let height = (radius - depth(&center)).min(radius * na::convert(2.0));
+
+            if height <= N::zero() {
+                return None;
+            }
+
+            let (volume, total, offset) = ball_cap(radius, height);
+
+            if volume <= N::zero() {
+                return None;
+            }
+
+            let centroid = center - **surface_normal * offset;
+
+            Some((volume, volume / total, centroid))
+        }
+        BuoyantGeometry::Polytope {
+            ref boundary,
+            volume,
+            scale,
+        } => {
+            // The submerged part is decomposed into simplices sharing a vertex on the surface.
+            // The simplices built with the section of the shape by the surface are flat so they
+            // do not need to be computed.
+            let shape_center = pos * Point::origin();
+            let origin = shape_center - **surface_normal * depth(&shape_center);
+            let mut submerged = N::zero();
+            let mut moment = Vector::zeros();
+
+            for element in boundary {
+                let mut world_element = *element;
+                for pt in &mut world_element {
+                    *pt = pos * *pt;
+                }
+
+                let (v, m) = clipped_simplex(&origin, &world_element, &depth);
+                submerged += v;
+                moment += m;
+            }
+
+            if submerged <= N::zero() {
+                return None;
+            }
+
+            let centroid = Point::from(moment / submerged);
+            Some((submerged * scale, submerged / volume, centroid))
+        }
+        BuoyantGeometry::Samples {
+            ref samples,
+            volume,
+        } => {
+            let mut nsubmerged = 0usize;
+            let mut center = Vector::zeros();
+
+            for sample in samples {
+                let world_sample = pos * sample;
+
+                if depth(&world_sample) < N::zero() {
+                    nsubmerged += 1;
+                    center += world_sample.coords;
+                }
+            }
+
+            if nsubmerged == 0 {
+                return None;
+            }
+
+            let nsubmerged: N = na::convert(nsubmerged as f64);
+            let fraction = nsubmerged / na::convert(samples.len() as f64);
+            let centroid = Point::from(center / nsubmerged);
+
+            Some((volume * fraction, fraction, centroid))
+        }
+    }
+}
+
+// The volume of the submerged cap of the given height of a ball, the volume of the ball, and
+// the distance from the center of the ball to the centroid of the cap.
+#[cfg(feature = "dim2")]
+fn ball_cap<N: Real>(radius: N, height: N) -> (N, N, N) {
+    let _2: N = na::convert(2.0);
+    let _3: N = na::convert(3.0);
+    let _4: N = na::convert(4.0);
+    let angle = ((radius - height) / radius).acos() * _2;
+    let sin = angle.sin();
+    let volume = radius * radius * (angle - sin) / _2;
+    let total = N::pi() * radius * radius;
+    let half_sin = (angle / _2).sin();
+    let offset = _4 * radius * half_sin * half_sin * half_sin / (_3 * (angle - sin));
+
+    (volume, total, offset)
+}
+
+// The volume of the submerged cap of the given height of a ball, the volume of the ball, and
+// the distance from the center of the ball to the centroid of the cap.
+#[cfg(feature = "dim3")]
+fn ball_cap<N: Real>(radius: N, height: N) -> (N, N, N) {
+    let _2: N = na::convert(2.0);
+    let _3: N = na::convert(3.0);
+    let _4: N = na::convert(4.0);
+    let volume = N::pi() * height * height * (_3 * radius - height) / _3;
+    let total = _4 * N::pi() * radius * radius * radius / _3;
+    let rest = _2 * radius - height;
+    let offset = _3 * rest * rest / (_4 * (_3 * radius - height));
+
+    (volume, total, offset)
+}
+
+// The signed area and first moment of the part of the triangle made of `origin` and `segment`
+// having a negative depth.
+#[cfg(feature = "dim2")]
+fn clipped_simplex<N: Real>(
+    origin: &Point<N>,
+    segment: &[Point<N>; DIM],
+    depth: &Fn(&Point<N>) -> N,
+) -> (N, Vector<N>) {
+    let (a, b) = (segment[0], segment[1]);
+    let (da, db) = (depth(&a), depth(&b));
+
+    let (a, b) = if da < N::zero() && db < N::zero() {
+        (a, b)
+    } else if da < N::zero() {
+        (a, a + (b - a) * (da / (da - db)))
+    } else if db < N::zero() {
+        (a + (b - a) * (da / (da - db)), b)
+    } else {
+        return (N::zero(), Vector::zeros());
+    };
+
+    let area = triangle_area(origin, &a, &b);
+    let centroid = (origin.coords + a.coords + b.coords) / na::convert::<f64, N>(3.0);
+
+    (area, centroid * area)
+}
+
+// The signed volume and first moment of the part of the tetrahedron made of `origin` and
+// `triangle` having a negative depth.
+#[cfg(feature = "dim3")]
+fn clipped_simplex<N: Real>(
+    origin: &Point<N>,
+    triangle: &[Point<N>; DIM],
+    depth: &Fn(&Point<N>) -> N,
+) -> (N, Vector<N>) {
+    // Clip the triangle with the Sutherland-Hodgman algorithm, keeping its orientation.
+    let mut polygon = Vec::with_capacity(4);
+
+    for i in 0..3 {
+        let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+        let (da, db) = (depth(&a), depth(&b));
+
+        if da < N::zero() {
+            polygon.push(a);
+        }
+
+        if (da < N::zero()) != (db < N::zero()) {
+            polygon.push(a + (b - a) * (da / (da - db)));
+        }
+    }
+
+    let mut volume = N::zero();
+    let mut moment = Vector::zeros();
+
+    for i in 1..polygon.len().saturating_sub(1) {
+        let tet = [polygon[0], polygon[i], polygon[i + 1]];
+        let v = tetrahedron_volume(origin, &tet);
+        let centroid = (origin.coords + tet[0].coords + tet[1].coords + tet[2].coords)
+            / na::convert::<f64, N>(4.0);
+
+        volume += v;
+        moment += centroid * v;
+    }
+
+    (volume, moment)
+}
+
+// The signed area of a triangle, positive if its vertices are counterclockwise.
+#[cfg(feature = "dim2")]
+fn triangle_area<N: Real>(a: &Point<N>, b: &Point<N>, c: &Point<N>) -> N {
+    (*b - *a).perp(&(*c - *a)) / na::convert(2.0)
+}
+
+// The signed volume of the tetrahedron made of `a` and `triangle`, positive if the triangle is
+// counterclockwise when seen from the side opposite to `a`.
+#[cfg(feature = "dim3")]
+fn tetrahedron_volume<N: Real>(a: &Point<N>, triangle: &[Point<N>; DIM]) -> N {
+    let ab = triangle[0] - *a;
+    let ac = triangle[1] - *a;
+    let ad = triangle[2] - *a;
+
+    ab.dot(&ac.cross(&ad)) / na::convert(6.0)
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::BuoyancyForce;
+    use math::{Isometry, Point, Vector};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn floating_box_rests_at_the_depth_given_by_archimedes_principle() {
+        let mut world = World::<f32>::new();
+        let gravity = Vector::y() * -9.81;
+        world.set_gravity(gravity);
+
+        // A flat box, stable in its upright position, with a density of 0.4 in a fluid of
+        // density 1.0: 40% of its height of 0.5 is submerged at equilibrium.
+        let mut half_extents = Vector::repeat(1.0);
+        half_extents.y = 0.25;
+        let plank = ShapeHandle::new(Cuboid::new(half_extents));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * 0.5, na::zero()),
+            plank.inertia(0.4),
+            plank.center_of_mass(),
+        );
+        world
+            .rigid_body_mut(body)
+            .unwrap()
+            .activation_status_mut()
+            .set_deactivation_threshold(None);
+
+        let mut buoyancy = BuoyancyForce::new(Point::origin(), Vector::y_axis(), 1.0, gravity);
+        buoyancy.set_linear_drag(2.0);
+        buoyancy.set_angular_drag(2.0);
+        buoyancy.add_body_part(body, plank, Isometry::identity());
+        let _ = world.add_force_generator(buoyancy);
+
+        for _ in 0..1200 {
+            world.step();
+        }
+
+        let expected_y = 0.25 - 0.4 * 0.5;
+        let y = world.rigid_body(body).unwrap().position().translation.vector.y;
+        assert!(
+            (y - expected_y).abs() < 1.0e-3,
+            "The box floats at {} instead of {}",
+            y,
+            expected_y
+        );
+    }
+}
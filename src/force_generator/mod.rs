@@ -1,9 +1,13 @@
 //! Persistent force generation.
 
 pub use self::force_generator::{ForceGenerator, ForceGeneratorHandle};
+pub use self::buoyancy::BuoyancyForce;
 pub use self::constant_acceleration::ConstantAcceleration;
 pub use self::spring::Spring;
+pub use self::wind::WindForce;
 
 mod force_generator;
+mod buoyancy;
 mod constant_acceleration;
 mod spring;
+mod wind;
@@ -0,0 +1,188 @@
+use na::{self, Real};
+use ncollide::bounding_volume::AABB;
+use ncollide::shape::ShapeHandle;
+
+use force_generator::ForceGenerator;
+use math::{Force, Isometry, Vector, DIM};
+use object::{BodyHandle, BodySet};
+use solver::IntegrationParameters;
+
+struct WindPart<N: Real> {
+    handle: BodyHandle,
+    shape: ShapeHandle<N>,
+    shape_to_body: Isometry<N>,
+}
+
+/// Force generator applying wind to the body parts located inside of a region.
+///
+/// The force applied to a body part is the force density multiplied by the area of the
+/// cross-section of the body part perpendicular to the wind direction. This cross-section
+/// is approximated from the world-space bounding box of the body part's shape.
+pub struct WindForce<N: Real> {
+    parts: Vec<WindPart<N>>,
+    region: AABB<N>,
+    force_density: Vector<N>,
+    turbulence: N,
+    turbulence_frequency: N,
+    time: N,
+}
+
+impl<N: Real> WindForce<N> {
+    /// Creates a wind force generator affecting the bodies with a center of mass inside of `region`.
+    ///
+    /// The `force_density` is the force applied per unit of cross-sectional area.
+    pub fn new(region: AABB<N>, force_density: Vector<N>) -> Self {
+        WindForce {
+            parts: Vec::new(),
+            region,
+            force_density,
+            turbulence: N::zero(),
+            turbulence_frequency: N::one(),
+            time: N::zero(),
+        }
+    }
+
+    /// The region affected by the wind.
+    pub fn region(&self) -> &AABB<N> {
+        &self.region
+    }
+
+    /// Sets the region affected by the wind.
+    pub fn set_region(&mut self, region: AABB<N>) {
+        self.region = region
+    }
+
+    /// The force applied per unit of cross-sectional area, without turbulence.
+    pub fn force_density(&self) -> &Vector<N> {
+        &self.force_density
+    }
+
+    /// Sets the force applied per unit of cross-sectional area.
+    pub fn set_force_density(&mut self, force_density: Vector<N>) {
+        self.force_density = force_density
+    }
+
+    /// Sets the turbulence of the wind.
+    ///
+    /// The turbulence is the magnitude, relative to the force density magnitude, of the
+    /// noise added to the force density. The noise changes smoothly over time, at the rate
+    /// given by `frequency` (in Hertz).
+    pub fn set_turbulence(&mut self, turbulence: N, frequency: N) {
+        self.turbulence = turbulence;
+        self.turbulence_frequency = frequency;
+    }
+
+    /// Add a body part to be affected by this force generator.
+    ///
+    /// The `shape`, located at `shape_to_body` relative to the body part, is used to
+    /// estimate the cross-section exposed to the wind.
+    pub fn add_body_part(
+        &mut self,
+        body: BodyHandle,
+        shape: ShapeHandle<N>,
+        shape_to_body: Isometry<N>,
+    ) {
+        self.parts.push(WindPart {
+            handle: body,
+            shape,
+            shape_to_body,
+        })
+    }
+
+    fn turbulent_force_density(&self) -> Vector<N> {
+        if self.turbulence.is_zero() {
+            return self.force_density;
+        }
+
+        let t = self.time * self.turbulence_frequency;
+        let magnitude = self.force_density.norm() * self.turbulence;
+        let mut noise = Vector::zeros();
+
+        for i in 0..DIM {
+            // Use a different offset for each component so they are uncorrelated.
+            let offset: N = na::convert(31.7 * (i as f64 + 1.0));
+            noise[i] = perlin_noise(t + offset);
+        }
+
+        self.force_density + noise * magnitude
+    }
+
+    fn contains(&self, pt: &Vector<N>) -> bool {
+        let mins = self.region.mins();
+        let maxs = self.region.maxs();
+
+        (0..DIM).all(|i| pt[i] >= mins[i] && pt[i] <= maxs[i])
+    }
+}
+
+impl<N: Real> ForceGenerator<N> for WindForce<N> {
+    fn apply(&mut self, params: &IntegrationParameters<N>, bodies: &mut BodySet<N>) -> bool {
+        self.time += params.dt;
+
+        let force_density = self.turbulent_force_density();
+        let dir = if let Some(dir) = force_density.try_normalize(N::default_epsilon()) {
+            dir
+        } else {
+            return true;
+        };
+
+        let mut i = 0;
+
+        while i < self.parts.len() {
+            if !bodies.contains(self.parts[i].handle) {
+                let _ = self.parts.swap_remove(i);
+                continue;
+            }
+
+            let part = &self.parts[i];
+            i += 1;
+
+            let mut body = bodies.body_part_mut(part.handle);
+            let com = body.as_ref().center_of_mass();
+
+            if !self.contains(&com.coords) {
+                continue;
+            }
+
+            let aabb = part
+                .shape
+                .aabb(&(body.as_ref().position() * part.shape_to_body));
+            let extents = aabb.maxs() - aabb.mins();
+            let area = cross_section_area(&extents, &dir);
+
+            body.apply_force(&Force::linear(force_density * area));
+        }
+
+        true
+    }
+}
+
+/// The area of the projection of a box with the given extents on a plane orthogonal to `dir`.
+#[cfg(feature = "dim3")]
+fn cross_section_area<N: Real>(extents: &Vector<N>, dir: &Vector<N>) -> N {
+    dir.x.abs() * extents.y * extents.z
+        + dir.y.abs() * extents.x * extents.z
+        + dir.z.abs() * extents.x * extents.y
+}
+
+/// The length of the projection of a box with the given extents on a line orthogonal to `dir`.
+#[cfg(feature = "dim2")]
+fn cross_section_area<N: Real>(extents: &Vector<N>, dir: &Vector<N>) -> N {
+    dir.x.abs() * extents.y + dir.y.abs() * extents.x
+}
+
+/// One-dimensional Perlin noise, with values in `[-1, 1]`.
+fn perlin_noise<N: Real>(x: N) -> N {
+    fn gradient<N: Real>(i: N) -> N {
+        let h = (i * na::convert(12.9898)).sin() * na::convert(43758.5453);
+        (h - h.floor()) * na::convert(2.0) - N::one()
+    }
+
+    let i0 = x.floor();
+    let f = x - i0;
+    let n0 = gradient(i0) * f;
+    let n1 = gradient(i0 + N::one()) * (f - N::one());
+    let fade = f * f * f * (f * (f * na::convert(6.0) - na::convert(15.0)) + na::convert(10.0));
+
+    (n0 + (n1 - n0) * fade) * na::convert(2.0)
+}
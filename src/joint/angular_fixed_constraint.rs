@@ -0,0 +1,181 @@
+use na::{DVector, Real};
+use std::ops::Range;
+
+use joint::{JointConstraint, JointConstraintParameters};
+use math::{AngularVector, Rotation, ANGULAR_DIM};
+use object::{BodyHandle, BodyPart, BodySet};
+use solver::helper;
+use solver::{
+    ConstraintSet, GenericNonlinearConstraint, IntegrationParameters, NonlinearConstraintGenerator,
+};
+
+/// A constraint that locks the relative orientation of two body parts.
+///
+/// This is the rotational counterpart of the ball constraint: the body parts keep the same
+/// relative orientation while their relative translations are left free.
+pub struct AngularFixedConstraint<N: Real> {
+    b1: BodyHandle,
+    b2: BodyHandle,
+    target: Rotation<N>,
+    ang_impulses: AngularVector<N>,
+    bilateral_ground_rng: Range<usize>,
+    bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
+}
+
+impl<N: Real> AngularFixedConstraint<N> {
+    /// Creates a constraint keeping the orientation of `b2` equal to `target` when expressed
+    /// in the local frame of `b1`.
+    pub fn new(b1: BodyHandle, b2: BodyHandle, target: Rotation<N>) -> Self {
+        AngularFixedConstraint {
+            b1,
+            b2,
+            target,
+            ang_impulses: AngularVector::zeros(),
+            bilateral_ground_rng: 0..0,
+            bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
+        }
+    }
+
+    /// Creates a constraint keeping the orientation of the body part `b` equal to the
+    /// world-space rotation `target`.
+    pub fn new_with_ground(b: BodyHandle, target: Rotation<N>) -> Self {
+        Self::new(BodyHandle::ground(), b, target)
+    }
+
+    /// Creates a constraint freezing the current relative orientation of two body parts.
+    pub fn from_body_parts(b1: &BodyPart<N>, b2: &BodyPart<N>) -> Self {
+        let target = b1.position().rotation.inverse() * b2.position().rotation;
+        Self::new(b1.handle(), b2.handle(), target)
+    }
+
+    /// The orientation of the second body part, expressed in the local frame of the first one.
+    pub fn target(&self) -> &Rotation<N> {
+        &self.target
+    }
+
+    /// Sets the orientation of the second body part, expressed in the local frame of the first one.
+    pub fn set_target(&mut self, target: Rotation<N>) {
+        self.target = target
+    }
+}
+
+impl<N: Real> JointConstraint<N> for AngularFixedConstraint<N> {
+    fn num_velocity_constraints(&self) -> usize {
+        ANGULAR_DIM
+    }
+
+    fn anchors(&self) -> (BodyHandle, BodyHandle) {
+        (self.b1, self.b2)
+    }
+
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
+    fn velocity_constraints(
+        &mut self,
+        _: &IntegrationParameters<N>,
+        bodies: &BodySet<N>,
+        ext_vels: &DVector<N>,
+        ground_j_id: &mut usize,
+        j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        let body1 = bodies.body_part(self.b1);
+        let body2 = bodies.body_part(self.b2);
+
+        // The angular jacobians do not depend on the application points.
+        let anchor1 = body1.center_of_mass();
+        let anchor2 = body2.center_of_mass();
+
+        let assembly_id1 = body1.parent_companion_id();
+        let assembly_id2 = body2.parent_companion_id();
+
+        let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
+        let first_bilateral = constraints.velocity.bilateral.len();
+
+        helper::cancel_relative_angular_velocity(
+            &body1,
+            &body2,
+            assembly_id1,
+            assembly_id2,
+            &anchor1,
+            &anchor2,
+            ext_vels,
+            &self.ang_impulses,
+            0,
+            ground_j_id,
+            j_id,
+            jacobians,
+            constraints,
+        );
+
+        self.bilateral_ground_rng =
+            first_bilateral_ground..constraints.velocity.bilateral_ground.len();
+        self.bilateral_rng = first_bilateral..constraints.velocity.bilateral.len();
+    }
+
+    fn cache_impulses(&mut self, constraints: &ConstraintSet<N>) {
+        for c in &constraints.velocity.bilateral_ground[self.bilateral_ground_rng.clone()] {
+            self.ang_impulses[c.impulse_id] = c.impulse;
+        }
+
+        for c in &constraints.velocity.bilateral[self.bilateral_rng.clone()] {
+            self.ang_impulses[c.impulse_id] = c.impulse;
+        }
+    }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.ang_impulses.iter().cloned().collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        self.ang_impulses.copy_from_slice(impulses)
+    }
+}
+
+impl<N: Real> NonlinearConstraintGenerator<N> for AngularFixedConstraint<N> {
+    fn num_position_constraints(&self, bodies: &BodySet<N>) -> usize {
+        // FIXME: calling this at each iteration of the non-linear resolution is costly.
+        if self.is_active(bodies) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn position_constraint(
+        &self,
+        params: &IntegrationParameters<N>,
+        _: usize,
+        bodies: &mut BodySet<N>,
+        jacobians: &mut [N],
+    ) -> Option<GenericNonlinearConstraint<N>> {
+        let body1 = bodies.body_part(self.b1);
+        let body2 = bodies.body_part(self.b2);
+
+        let anchor1 = body1.center_of_mass();
+        let anchor2 = body2.center_of_mass();
+
+        let rotation1 = body1.position().rotation * self.target;
+        let rotation2 = body2.position().rotation;
+
+        helper::cancel_relative_rotation(
+            params,
+            &body1,
+            &body2,
+            &anchor1,
+            &anchor2,
+            &rotation1,
+            &rotation2,
+            jacobians,
+        )
+    }
+}
@@ -5,7 +5,7 @@ use object::{BodyHandle, BodySet};
 use solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
              NonlinearConstraintGenerator};
 use solver::helper;
-use joint::JointConstraint;
+use joint::{JointConstraint, JointConstraintParameters};
 use math::{Point, Vector, DIM};
 
 /// A constraint that removes all relative linear motion between two body parts.
@@ -17,6 +17,7 @@ pub struct BallConstraint<N: Real> {
     impulses: Vector<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
 }
 
 impl<N: Real> BallConstraint<N> {
@@ -33,9 +34,18 @@ impl<N: Real> BallConstraint<N> {
             impulses: Vector::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
         }
     }
 
+    /// Creates a ball constraint between the body part `b` and the ground.
+    ///
+    /// The `world_anchor` is expressed in world-space, while `anchor` is expressed in the
+    /// local-space of `b`. Only the jacobians of `b` are computed at each timestep.
+    pub fn new_with_ground(b: BodyHandle, world_anchor: Point<N>, anchor: Point<N>) -> Self {
+        Self::new(BodyHandle::ground(), b, world_anchor, anchor)
+    }
+
     /// Change the first anchor, expressed in the local space of the first body part.
     pub fn set_anchor_1(&mut self, anchor1: Point<N>) {
         self.anchor1 = anchor1;
@@ -45,6 +55,36 @@ impl<N: Real> BallConstraint<N> {
     pub fn set_anchor_2(&mut self, anchor2: Point<N>) {
         self.anchor2 = anchor2;
     }
+
+    // Velocity constraints when the first body part is the ground.
+    fn ground_velocity_constraints(
+        &mut self,
+        bodies: &BodySet<N>,
+        ext_vels: &DVector<N>,
+        ground_j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        let body = bodies.body_part(self.b2);
+        let anchor = body.position() * self.anchor2;
+        let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
+
+        helper::cancel_linear_velocity_wrt_ground(
+            &body,
+            body.parent_companion_id(),
+            &anchor,
+            ext_vels,
+            &self.impulses,
+            0,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        self.bilateral_ground_rng =
+            first_bilateral_ground..constraints.velocity.bilateral_ground.len();
+        self.bilateral_rng = 0..0;
+    }
 }
 
 impl<N: Real> JointConstraint<N> for BallConstraint<N> {
@@ -56,6 +96,14 @@ impl<N: Real> JointConstraint<N> for BallConstraint<N> {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
     fn velocity_constraints(
         &mut self,
         _: &IntegrationParameters<N>,
@@ -66,6 +114,11 @@ impl<N: Real> JointConstraint<N> for BallConstraint<N> {
         jacobians: &mut [N],
         constraints: &mut ConstraintSet<N>,
     ) {
+        if self.b1.is_ground() {
+            self.ground_velocity_constraints(bodies, ext_vels, ground_j_id, jacobians, constraints);
+            return;
+        }
+
         let body1 = bodies.body_part(self.b1);
         let body2 = bodies.body_part(self.b2);
 
@@ -116,6 +169,14 @@ impl<N: Real> JointConstraint<N> for BallConstraint<N> {
             self.impulses[c.impulse_id] = c.impulse;
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.impulses.iter().cloned().collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        self.impulses.copy_from_slice(impulses)
+    }
 }
 
 impl<N: Real> NonlinearConstraintGenerator<N> for BallConstraint<N> {
@@ -1,20 +1,47 @@
+use alga::linear::FiniteDimInnerSpace;
 use na::{
-    self, DVectorSliceMut, Isometry3, Matrix3, Real, Translation3, UnitQuaternion, Vector3,
-    VectorSlice3, U3,
+    self, DVectorSlice, DVectorSliceMut, Isometry3, Matrix3, Real, Translation3, Unit,
+    UnitQuaternion, Vector3, VectorSlice3, U3,
 };
 
-use joint::Joint;
+use joint::{Joint, JointMotor};
 use math::{JacobianSliceMut, Velocity};
-use solver::IntegrationParameters;
+use object::{BodyHandle, MultibodyLinkRef};
+use solver::{
+    BilateralGroundConstraint, ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
+    UnilateralGroundConstraint,
+};
 use utils::GeneralizedCross;
 
 /// A joint that allows only all rotational degrees of freedom between two multibody links.
+///
+/// The relative orientation of the links can be limited by a swing-twist decomposition wrt. the
+/// twist axis: the swing is the rotation moving the twist axis to its current orientation, and
+/// the twist is the remaining rotation around the twist axis.
 #[derive(Copy, Clone, Debug)]
 pub struct BallJoint<N: Real> {
     rot: UnitQuaternion<N>,
 
     jacobian_v: Matrix3<N>,
     jacobian_dot_v: Matrix3<N>,
+
+    twist_axis: Unit<Vector3<N>>,
+    max_swing_angle: Option<N>,
+    min_twist_angle: Option<N>,
+    max_twist_angle: Option<N>,
+
+    motor: JointMotor<Vector3<N>, N>,
+    desired_orientation: Option<UnitQuaternion<N>>,
+    damping: N,
+}
+
+// The state of one of the limits of a ball joint.
+struct BallJointLimit<N: Real> {
+    // The limit violation, positive when the limit is exceeded.
+    err: N,
+    // The generalized direction, along which a positive velocity moves the joint back toward its admissible range.
+    dir: Vector3<N>,
+    impulse_id: usize,
 }
 
 impl<N: Real> BallJoint<N> {
@@ -24,8 +51,300 @@ impl<N: Real> BallJoint<N> {
             rot: UnitQuaternion::new(axisangle),
             jacobian_v: na::zero(),
             jacobian_dot_v: na::zero(),
+            twist_axis: Vector3::x_axis(),
+            max_swing_angle: None,
+            min_twist_angle: None,
+            max_twist_angle: None,
+            motor: JointMotor::new(),
+            desired_orientation: None,
+            damping: N::zero(),
+        }
+    }
+
+    /// The twist axis, expressed in the local frame of the parent link (default: `x`).
+    pub fn twist_axis(&self) -> Unit<Vector3<N>> {
+        self.twist_axis
+    }
+
+    /// Sets the twist axis, expressed in the local frame of the parent link.
+    pub fn set_twist_axis(&mut self, axis: Unit<Vector3<N>>) {
+        self.twist_axis = axis
+    }
+
+    /// The maximum angle between the twist axis and its image by the joint rotation.
+    pub fn max_swing_angle(&self) -> Option<N> {
+        self.max_swing_angle
+    }
+
+    /// Disable the swing limit of this joint.
+    pub fn disable_max_swing_angle(&mut self) {
+        self.max_swing_angle = None;
+    }
+
+    /// Limit the angle between the twist axis and its image by the joint rotation.
+    ///
+    /// This limits the motion of the twist axis to a cone with half-angle `limit`.
+    pub fn enable_max_swing_angle(&mut self, limit: N) {
+        self.max_swing_angle = Some(limit);
+    }
+
+    /// The lower limit of the twist angle.
+    pub fn min_twist_angle(&self) -> Option<N> {
+        self.min_twist_angle
+    }
+
+    /// The upper limit of the twist angle.
+    pub fn max_twist_angle(&self) -> Option<N> {
+        self.max_twist_angle
+    }
+
+    /// Disable the lower limit of the twist angle.
+    pub fn disable_min_twist_angle(&mut self) {
+        self.min_twist_angle = None;
+    }
+
+    /// Disable the upper limit of the twist angle.
+    pub fn disable_max_twist_angle(&mut self) {
+        self.max_twist_angle = None;
+    }
+
+    /// Enable the lower limit of the twist angle.
+    pub fn enable_min_twist_angle(&mut self, limit: N) {
+        self.min_twist_angle = Some(limit);
+        self.assert_twist_limits();
+    }
+
+    /// Enable the upper limit of the twist angle.
+    pub fn enable_max_twist_angle(&mut self, limit: N) {
+        self.max_twist_angle = Some(limit);
+        self.assert_twist_limits();
+    }
+
+    /// The viscous damping coefficient of this joint.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Set the viscous damping coefficient of this joint.
+    ///
+    /// The joint is subject to a torque equal to `-damping` times its angular velocity. This torque
+    /// is integrated implicitly so large coefficients remain stable. Set to zero to disable it.
+    pub fn set_damping(&mut self, damping: N) {
+        assert!(
+            damping >= N::zero(),
+            "The joint damping must be positive or zero."
+        );
+        self.damping = damping;
+    }
+
+    /// The relative rotation of the links attached to this joint.
+    pub fn rotation(&self) -> &UnitQuaternion<N> {
+        &self.rot
+    }
+
+    /// The angle between the twist axis and its image by the joint rotation.
+    pub fn swing_angle(&self) -> N {
+        let image = self.rot * self.twist_axis.into_inner();
+        let sin = self.twist_axis.cross(&image).norm();
+        sin.atan2(self.twist_axis.dot(&image))
+    }
+
+    /// The angle of the rotation around the twist axis remaining once the swing is removed, in `[-π, π]`.
+    ///
+    /// Returns `None` if the swing angle is close to `π`, where the twist is not well defined.
+    pub fn twist_angle(&self) -> Option<N> {
+        let q = self.rot.as_ref();
+        let along = q.vector().dot(&*self.twist_axis);
+        let w = q.scalar();
+
+        if along * along + w * w < N::default_epsilon() {
+            return None;
+        }
+
+        let mut angle = along.atan2(w) * na::convert(2.0);
+
+        if angle > N::pi() {
+            angle -= N::two_pi();
+        } else if angle < -N::pi() {
+            angle += N::two_pi();
+        }
+
+        Some(angle)
+    }
+
+    /// Return `true` if the angular motor of this joint is enabled.
+    pub fn is_angular_motor_enabled(&self) -> bool {
+        self.motor.enabled
+    }
+
+    /// Enable the angular motor of this joint.
+    pub fn enable_angular_motor(&mut self) {
+        self.motor.enabled = true
+    }
+
+    /// Disable the angular motor of this joint.
+    pub fn disable_angular_motor(&mut self) {
+        self.motor.enabled = false;
+    }
+
+    /// The desired angular velocity of the joint motor, expressed in the local frame of the parent link.
+    pub fn desired_angular_motor_velocity(&self) -> Vector3<N> {
+        self.motor.desired_velocity
+    }
+
+    /// Set the desired angular velocity of the joint motor, expressed in the local frame of the parent link.
+    pub fn set_desired_angular_motor_velocity(&mut self, vel: Vector3<N>) {
+        self.motor.desired_velocity = vel;
+    }
+
+    /// The maximum torque that can be delivered by the joint motor along each axis.
+    pub fn max_angular_motor_torque(&self) -> N {
+        self.motor.max_force
+    }
+
+    /// Set the maximum torque that can be delivered by the joint motor along each axis.
+    pub fn set_max_angular_motor_torque(&mut self, torque: N) {
+        self.motor.max_force = torque;
+    }
+
+    /// The orientation the joint motor attempts to reach, if it is used as an orientation servo.
+    pub fn desired_orientation(&self) -> Option<&UnitQuaternion<N>> {
+        self.desired_orientation.as_ref()
+    }
+
+    /// Use the joint motor as an orientation servo targeting the given relative rotation.
+    ///
+    /// The motor then applies the torque `kp * error - kd * velocity`, where `error` is the
+    /// rotation vector from the current joint rotation to `orientation`, and the gains are set by
+    /// `self.set_angular_motor_stiffness` and `self.set_angular_motor_damping`. The torque is
+    /// clamped by the maximum motor torque, so the joint lags behind the target if this maximum is
+    /// too low. The target can be changed at each timestep. The motor must still be enabled with
+    /// `self.enable_angular_motor()`.
+    pub fn set_desired_orientation(&mut self, orientation: UnitQuaternion<N>) {
+        self.desired_orientation = Some(orientation);
+    }
+
+    /// Stop using the joint motor as an orientation servo and revert to the desired velocity.
+    pub fn disable_desired_orientation(&mut self) {
+        self.desired_orientation = None;
+    }
+
+    /// The proportional gain of the joint motor orientation servo.
+    pub fn angular_motor_stiffness(&self) -> N {
+        self.motor.stiffness
+    }
+
+    /// Set the proportional gain of the joint motor orientation servo.
+    pub fn set_angular_motor_stiffness(&mut self, kp: N) {
+        self.motor.stiffness = kp;
+    }
+
+    /// The derivative gain of the joint motor orientation servo.
+    pub fn angular_motor_damping(&self) -> N {
+        self.motor.damping
+    }
+
+    /// Set the derivative gain of the joint motor orientation servo.
+    pub fn set_angular_motor_damping(&mut self, kd: N) {
+        self.motor.damping = kd;
+    }
+
+    // Computes the angular velocity targeted by the motor, and the compliance of the motor constraints.
+    //
+    // In servo mode, the PD torque is applied implicitly, as for unit joints.
+    fn motor_target(&self, params: &IntegrationParameters<N>) -> (Vector3<N>, N) {
+        if let Some(target) = self.desired_orientation {
+            let denom = params.dt * (params.dt * self.motor.stiffness + self.motor.damping);
+
+            if !denom.is_zero() {
+                let err = (target * self.rot.inverse()).scaled_axis();
+                return (
+                    err * (self.motor.stiffness * params.dt / denom),
+                    N::one() / denom,
+                );
+            }
+        }
+
+        (self.motor.desired_velocity, N::zero())
+    }
+
+    fn assert_twist_limits(&self) {
+        if let (Some(min), Some(max)) = (self.min_twist_angle, self.max_twist_angle) {
+            assert!(min <= max, "BallJoint constraint limits: the min angle must be smaller than (or equal to) the max angle.");
         }
     }
+
+    // The swing limit, if it is violated.
+    fn swing_limit(&self) -> Option<BallJointLimit<N>> {
+        let max = self.max_swing_angle?;
+        let err = self.swing_angle() - max;
+
+        if err < N::zero() {
+            return None;
+        }
+
+        // Near a swing of `π` the swing axis is not well defined, so we just pick any axis
+        // orthogonal to the twist axis.
+        let image = self.rot * self.twist_axis.into_inner();
+        let axis = Unit::try_new(self.twist_axis.cross(&image), N::default_epsilon())
+            .map(|axis| axis.into_inner())
+            .unwrap_or_else(|| {
+                let mut orth = Vector3::zeros();
+                Vector3::orthonormal_subspace_basis(&[self.twist_axis.into_inner()], |v| {
+                    orth = *v;
+                    false
+                });
+                orth
+            });
+
+        Some(BallJointLimit {
+            err,
+            dir: -axis,
+            impulse_id: 0,
+        })
+    }
+
+    // The violated twist limit, if any.
+    fn twist_limit(&self) -> Option<BallJointLimit<N>> {
+        if self.min_twist_angle.is_none() && self.max_twist_angle.is_none() {
+            return None;
+        }
+
+        let angle = self.twist_angle()?;
+        let image = self.rot * self.twist_axis.into_inner();
+        let denom = N::one() + self.twist_axis.dot(&image);
+
+        if denom < N::default_epsilon() {
+            return None;
+        }
+
+        // Rate of change of the twist angle wrt. the joint angular velocity.
+        let rate = (self.twist_axis.into_inner() + image) / denom;
+
+        if let Some(min) = self.min_twist_angle {
+            let err = min - angle;
+            if err >= N::zero() {
+                return Some(BallJointLimit {
+                    err,
+                    dir: rate,
+                    impulse_id: 1,
+                });
+            }
+        }
+
+        if let Some(max) = self.max_twist_angle {
+            let err = angle - max;
+            if err >= N::zero() {
+                return Some(BallJointLimit {
+                    err,
+                    dir: -rate,
+                    impulse_id: 2,
+                });
+            }
+        }
+
+        None
+    }
 }
 
 impl<N: Real> Joint<N> for BallJoint<N> {
@@ -35,7 +354,7 @@ impl<N: Real> Joint<N> for BallJoint<N> {
     }
 
     fn body_to_parent(&self, parent_shift: &Vector3<N>, body_shift: &Vector3<N>) -> Isometry3<N> {
-        let trans = Translation3::from_vector(parent_shift - self.rot * body_shift);
+        let trans = Translation3::from(parent_shift - self.rot * body_shift);
         Isometry3::from_parts(trans, self.rot)
     }
 
@@ -89,6 +408,10 @@ impl<N: Real> Joint<N> for BallJoint<N> {
         out.fill(na::convert(0.1f64))
     }
 
+    fn viscous_damping(&self, out: &mut DVectorSliceMut<N>) {
+        out.fill(self.damping)
+    }
+
     fn integrate(&mut self, params: &IntegrationParameters<N>, vels: &[N]) {
         let angvel = Vector3::from_row_slice(&vels[..3]);
         let disp = UnitQuaternion::new_eps(angvel * params.dt, N::zero());
@@ -100,4 +423,247 @@ impl<N: Real> Joint<N> for BallJoint<N> {
         let disp = UnitQuaternion::new(angle);
         self.rot = disp * self.rot;
     }
+
+    fn generalized_position(&self, out: &mut [N]) {
+        out[..3].copy_from_slice(self.rot.scaled_axis().as_slice())
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        self.rot = UnitQuaternion::new(Vector3::from_row_slice(&coords[..3]));
+    }
+
+    fn num_velocity_constraints(&self) -> usize {
+        let mut nconstraints = 0;
+
+        if self.motor.enabled {
+            nconstraints += 3;
+        }
+        if self.max_swing_angle.is_some() {
+            nconstraints += 1;
+        }
+        if self.min_twist_angle.is_some() || self.max_twist_angle.is_some() {
+            nconstraints += 1;
+        }
+
+        nconstraints
+    }
+
+    fn velocity_constraints(
+        &self,
+        params: &IntegrationParameters<N>,
+        link: &MultibodyLinkRef<N>,
+        assembly_id: usize,
+        dof_id: usize,
+        ext_vels: &[N],
+        ground_j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        let ndofs = link.multibody().ndofs();
+        let impulses = link.multibody().impulses();
+        let first_dof = link.assembly_id() + dof_id;
+
+        if self.motor.enabled {
+            let (desired_velocity, cfm) = self.motor_target(params);
+            let joint_vel = link.joint_velocity();
+
+            for i in 0..3 {
+                let dvel = joint_vel[dof_id + i] + ext_vels[assembly_id + first_dof + i];
+
+                DVectorSliceMut::from_slice(&mut jacobians[*ground_j_id..], ndofs).fill(N::zero());
+                jacobians[*ground_j_id + first_dof + i] = N::one();
+
+                let wj_id = *ground_j_id + ndofs;
+                link.inv_mass_mul_unit_joint_force(dof_id + i, N::one(), &mut jacobians[wj_id..]);
+
+                let inv_r = jacobians[wj_id + first_dof + i]; // = J^t * M^-1 J
+                let impulse_id = link.impulse_id() + (dof_id + i) * 4 + 3;
+
+                let constraint = BilateralGroundConstraint {
+                    impulse: impulses[impulse_id] * params.warmstart_coeff,
+                    r: N::one() / (inv_r + cfm),
+                    rhs: dvel - desired_velocity[i],
+                    cfm,
+                    limits: self.motor.impulse_limits(),
+                    is_angular: false,
+                    impulse_id,
+                    assembly_id,
+                    j_id: *ground_j_id,
+                    wj_id,
+                    ndofs,
+                };
+
+                constraints.velocity.bilateral_ground.push(constraint);
+                *ground_j_id += 2 * ndofs;
+            }
+        }
+
+        for limit in self.swing_limit().into_iter().chain(self.twist_limit()) {
+            let joint_vel = link.joint_velocity();
+            let mut dvel = N::zero();
+
+            for i in 0..3 {
+                dvel +=
+                    limit.dir[i] * (joint_vel[dof_id + i] + ext_vels[assembly_id + first_dof + i]);
+            }
+
+            DVectorSliceMut::from_slice(&mut jacobians[*ground_j_id..], ndofs).fill(N::zero());
+            jacobians[*ground_j_id + first_dof..*ground_j_id + first_dof + 3]
+                .copy_from_slice(limit.dir.as_slice());
+
+            let wj_id = *ground_j_id + ndofs;
+            link.inv_mass_mul_joint_force(
+                DVectorSlice::from_slice(limit.dir.as_slice(), 3),
+                &mut jacobians[wj_id..],
+            );
+
+            let inv_r = limit.dir.dot(&Vector3::from_row_slice(
+                &jacobians[wj_id + first_dof..wj_id + first_dof + 3],
+            )); // = J^t * M^-1 J
+            let impulse_id = link.impulse_id() + dof_id * 4 + limit.impulse_id;
+
+            let constraint = UnilateralGroundConstraint {
+                impulse: impulses[impulse_id] * params.warmstart_coeff,
+                r: N::one() / inv_r,
+                rhs: dvel,
+                cfm: N::zero(),
+                impulse_id,
+                assembly_id,
+                j_id: *ground_j_id,
+                wj_id,
+                ndofs,
+            };
+
+            constraints.velocity.unilateral_ground.push(constraint);
+            *ground_j_id += 2 * ndofs;
+        }
+    }
+
+    fn num_position_constraints(&self) -> usize {
+        let has_swing_limit = self.max_swing_angle.is_some();
+        let has_twist_limits = self.min_twist_angle.is_some() || self.max_twist_angle.is_some();
+
+        has_swing_limit as usize + has_twist_limits as usize
+    }
+
+    fn position_constraint(
+        &self,
+        i: usize,
+        link: &MultibodyLinkRef<N>,
+        dof_id: usize,
+        jacobians: &mut [N],
+    ) -> Option<GenericNonlinearConstraint<N>> {
+        // The swing limit comes first, if it is set.
+        let limit = if i == 0 && self.max_swing_angle.is_some() {
+            self.swing_limit()?
+        } else {
+            self.twist_limit()?
+        };
+
+        let mb = link.multibody();
+        let ndofs = mb.ndofs();
+        let first_dof = link.assembly_id() + dof_id;
+
+        link.inv_mass_mul_joint_force(DVectorSlice::from_slice(limit.dir.as_slice(), 3), jacobians);
+
+        let inv_r = limit
+            .dir
+            .dot(&Vector3::from_row_slice(&jacobians[first_dof..first_dof + 3])); // = J^t * M^-1 J
+
+        Some(GenericNonlinearConstraint::new(
+            mb.handle(),
+            BodyHandle::ground(),
+            true,
+            ndofs,
+            0,
+            0,
+            0,
+            -limit.err,
+            N::one() / inv_r,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Ball, ShapeHandle};
+
+    use super::BallJoint;
+    use math::Vector;
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn ball_joint_swing_limit_stops_the_pendulum() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::x() * -9.81);
+
+        let mut joint = BallJoint::new(na::zero());
+        joint.set_twist_axis(-Vector::y_axis());
+        joint.enable_max_swing_angle(0.5);
+        joint.enable_min_twist_angle(-0.1);
+        joint.enable_max_twist_angle(0.1);
+
+        let ball = ShapeHandle::new(Ball::new(0.1));
+        let link = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            Vector::y(),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+
+        for _ in 0..240 {
+            world.step();
+        }
+
+        let rotation = world.body_part(link).position().rotation;
+        let dir = rotation * -Vector::y();
+        let swing = dir.angle(&-Vector::y());
+        assert!(swing < 0.55, "Swing angle: {}", swing);
+        assert!(swing > 0.4, "Swing angle: {}", swing);
+    }
+
+    #[test]
+    fn ball_joint_motor_tracks_the_desired_orientation() {
+        use na::UnitQuaternion;
+
+        let target = UnitQuaternion::new(Vector::new(0.3, -0.2, 0.5));
+        let build = |world: &mut World<f32>, max_torque: f32| {
+            let mut joint = BallJoint::new(na::zero());
+            joint.enable_angular_motor();
+            joint.set_desired_orientation(target);
+            joint.set_angular_motor_stiffness(200.0);
+            joint.set_angular_motor_damping(20.0);
+            joint.set_max_angular_motor_torque(max_torque);
+
+            let ball = ShapeHandle::new(Ball::new(0.1));
+            world.add_multibody_link(
+                BodyHandle::ground(),
+                joint,
+                na::zero(),
+                Vector::y(),
+                ball.inertia(1.0),
+                ball.center_of_mass(),
+            )
+        };
+
+        let mut world = World::<f32>::new();
+        let strong = build(&mut world, 1.0e6);
+        // The balls weigh about 4 grams so the weak motor has to be very weak.
+        let weak = build(&mut world, 1.0e-6);
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let strong_rot = world.body_part(strong).position().rotation;
+        let weak_rot = world.body_part(weak).position().rotation;
+        assert!(strong_rot.angle_to(&target) < 1.0e-2);
+        assert!(weak_rot.angle_to(&target) > 0.1);
+        assert!(weak_rot.angle_to(&target).is_finite());
+    }
 }
@@ -1,7 +1,7 @@
 use na::{DVector, Real};
 use std::ops::Range;
 
-use joint::JointConstraint;
+use joint::{JointConstraint, JointConstraintParameters};
 use math::{AngularVector, Isometry, Point, ANGULAR_DIM};
 use object::{BodyHandle, BodySet};
 use solver::helper;
@@ -17,6 +17,7 @@ pub struct CartesianConstraint<N: Real> {
     ang_impulses: AngularVector<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
 }
 
 impl<N: Real> CartesianConstraint<N> {
@@ -38,6 +39,7 @@ impl<N: Real> CartesianConstraint<N> {
             ang_impulses: AngularVector::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
         }
     }
 
@@ -61,6 +63,14 @@ impl<N: Real> JointConstraint<N> for CartesianConstraint<N> {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
     fn velocity_constraints(
         &mut self,
         _: &IntegrationParameters<N>,
@@ -77,8 +87,8 @@ impl<N: Real> JointConstraint<N> for CartesianConstraint<N> {
         let pos1 = body1.position() * self.joint_to_b1;
         let pos2 = body2.position() * self.joint_to_b2;
 
-        let anchor1 = Point::from_coordinates(pos1.translation.vector);
-        let anchor2 = Point::from_coordinates(pos2.translation.vector);
+        let anchor1 = Point::from(pos1.translation.vector);
+        let anchor2 = Point::from(pos2.translation.vector);
 
         let assembly_id1 = body1.parent_companion_id();
         let assembly_id2 = body2.parent_companion_id();
@@ -116,6 +126,14 @@ impl<N: Real> JointConstraint<N> for CartesianConstraint<N> {
             self.ang_impulses[c.impulse_id] = c.impulse;
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.ang_impulses.iter().cloned().collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        self.ang_impulses.copy_from_slice(impulses)
+    }
 }
 
 impl<N: Real> NonlinearConstraintGenerator<N> for CartesianConstraint<N> {
@@ -141,8 +159,8 @@ impl<N: Real> NonlinearConstraintGenerator<N> for CartesianConstraint<N> {
         let pos1 = body1.position() * self.joint_to_b1;
         let pos2 = body2.position() * self.joint_to_b2;
 
-        let anchor1 = Point::from_coordinates(pos1.translation.vector);
-        let anchor2 = Point::from_coordinates(pos2.translation.vector);
+        let anchor1 = Point::from(pos1.translation.vector);
+        let anchor2 = Point::from(pos2.translation.vector);
 
         let rotation1 = pos1.rotation;
         let rotation2 = pos2.rotation;
@@ -159,3 +177,61 @@ impl<N: Real> NonlinearConstraintGenerator<N> for CartesianConstraint<N> {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::CartesianConstraint;
+    use math::{AngularVector, Isometry, Vector};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn cartesian_constraint_locks_orientation_but_not_translation() {
+        use math::Velocity;
+
+        let mut world = World::<f32>::new();
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+        let body1 = world.add_rigid_body(
+            Isometry::identity(),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let body2 = world.add_rigid_body(
+            Isometry::new(Vector::x() * 2.0, na::zero()),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let _ = world.add_constraint(CartesianConstraint::new(
+            body1,
+            body2,
+            Isometry::identity(),
+            Isometry::identity(),
+        ));
+
+        {
+            let rb1 = world.rigid_body_mut(body1).unwrap();
+            rb1.set_velocity(Velocity::new_with_vectors(
+                Vector::zeros(),
+                AngularVector::from_element(1.0),
+            ));
+        }
+        {
+            let rb2 = world.rigid_body_mut(body2).unwrap();
+            rb2.set_linear_velocity(Vector::y() * 1.0);
+        }
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let pos1 = world.rigid_body(body1).unwrap().position();
+        let pos2 = world.rigid_body(body2).unwrap().position();
+        assert!(pos1.rotation.angle() > 0.1, "The first body should rotate.");
+        assert!((pos1.rotation.inverse() * pos2.rotation).angle() < 1.0e-2);
+        assert!((pos2.translation.vector - (Vector::x() * 2.0 + Vector::y())).norm() < 1.0e-2);
+    }
+}
@@ -2,18 +2,71 @@ use na::{self, DVectorSliceMut, Real};
 
 use joint::Joint;
 use math::{Isometry, JacobianSliceMut, Translation, Vector, Velocity, DIM};
-use solver::IntegrationParameters;
+use object::{BodyHandle, MultibodyLinkRef};
+use solver::{
+    ConstraintSet, GenericNonlinearConstraint, IntegrationParameters, UnilateralGroundConstraint,
+};
 
 /// A joint that allows only all the translational degrees of freedom between two multibody links.
 #[derive(Copy, Clone, Debug)]
 pub struct CartesianJoint<N: Real> {
     position: Vector<N>,
+    limits: [Option<(N, N)>; DIM],
 }
 
 impl<N: Real> CartesianJoint<N> {
     /// Create a cartesian joint with an initial position given by `position`.
     pub fn new(position: Vector<N>) -> Self {
-        CartesianJoint { position }
+        CartesianJoint {
+            position,
+            limits: [None; DIM],
+        }
+    }
+
+    /// The translation of the child link wrt. its parent.
+    pub fn position(&self) -> &Vector<N> {
+        &self.position
+    }
+
+    /// The translation of the child link wrt. its parent, along the `axis`-th coordinate axis.
+    pub fn displacement(&self, axis: usize) -> N {
+        self.position[axis]
+    }
+
+    /// The lower and upper limits of the displacement along the `axis`-th coordinate axis.
+    pub fn axis_limits(&self, axis: usize) -> Option<(N, N)> {
+        self.limits[axis]
+    }
+
+    /// Limit the displacement along the `axis`-th coordinate axis to the interval `[min, max]`.
+    pub fn set_axis_limits(&mut self, axis: usize, min: N, max: N) {
+        assert!(
+            min <= max,
+            "CartesianJoint constraint limits: the min displacement must be smaller than (or equal to) the max displacement."
+        );
+        self.limits[axis] = Some((min, max));
+    }
+
+    /// Remove the limits of the displacement along the `axis`-th coordinate axis.
+    pub fn disable_axis_limits(&mut self, axis: usize) {
+        self.limits[axis] = None;
+    }
+
+    // The violated limit along the given axis, if any, as the violation and the sign of the
+    // constraint jacobian.
+    //
+    // The violation is positive and the jacobian points toward the admissible range.
+    fn axis_limit(&self, axis: usize) -> Option<(N, N, usize)> {
+        let (min, max) = self.limits[axis]?;
+        let displacement = self.position[axis];
+
+        if displacement <= min {
+            Some((min - displacement, N::one(), 1))
+        } else if displacement >= max {
+            Some((displacement - max, -N::one(), 2))
+        } else {
+            None
+        }
     }
 }
 
@@ -24,7 +77,7 @@ impl<N: Real> Joint<N> for CartesianJoint<N> {
     }
 
     fn body_to_parent(&self, parent_shift: &Vector<N>, body_shift: &Vector<N>) -> Isometry<N> {
-        let t = Translation::from_vector(parent_shift - body_shift + self.position);
+        let t = Translation::from(parent_shift - body_shift + self.position);
         Isometry::from_parts(t, na::one())
     }
 
@@ -62,4 +115,149 @@ impl<N: Real> Joint<N> for CartesianJoint<N> {
     fn apply_displacement(&mut self, disp: &[N]) {
         self.position += Vector::from_row_slice(&disp[..DIM]);
     }
+
+    fn generalized_position(&self, out: &mut [N]) {
+        out[..DIM].copy_from_slice(self.position.as_slice())
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        self.position = Vector::from_row_slice(&coords[..DIM]);
+    }
+
+    fn num_velocity_constraints(&self) -> usize {
+        self.limits.iter().filter(|limits| limits.is_some()).count()
+    }
+
+    fn velocity_constraints(
+        &self,
+        params: &IntegrationParameters<N>,
+        link: &MultibodyLinkRef<N>,
+        assembly_id: usize,
+        dof_id: usize,
+        ext_vels: &[N],
+        ground_j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        let ndofs = link.multibody().ndofs();
+        let impulses = link.multibody().impulses();
+
+        for axis in 0..DIM {
+            if let Some((_, sign, slot)) = self.axis_limit(axis) {
+                let id = link.assembly_id() + dof_id + axis;
+                let dvel =
+                    sign * (link.joint_velocity()[dof_id + axis] + ext_vels[assembly_id + id]);
+
+                DVectorSliceMut::from_slice(&mut jacobians[*ground_j_id..], ndofs)
+                    .fill(N::zero());
+                jacobians[*ground_j_id + id] = sign;
+
+                let wj_id = *ground_j_id + ndofs;
+                link.inv_mass_mul_unit_joint_force(dof_id + axis, sign, &mut jacobians[wj_id..]);
+
+                let inv_r = sign * jacobians[wj_id + id]; // = J^t * M^-1 J
+                let impulse_id = link.impulse_id() + (dof_id + axis) * 4 + slot;
+
+                let constraint = UnilateralGroundConstraint {
+                    impulse: impulses[impulse_id] * params.warmstart_coeff,
+                    r: N::one() / inv_r,
+                    rhs: dvel,
+                    cfm: N::zero(),
+                    impulse_id,
+                    assembly_id,
+                    j_id: *ground_j_id,
+                    wj_id,
+                    ndofs,
+                };
+
+                constraints.velocity.unilateral_ground.push(constraint);
+                *ground_j_id += 2 * ndofs;
+            }
+        }
+    }
+
+    fn num_position_constraints(&self) -> usize {
+        // NOTE: we don't test which limits exist to simplify indexing.
+        if self.limits.iter().any(|limits| limits.is_some()) {
+            DIM
+        } else {
+            0
+        }
+    }
+
+    fn position_constraint(
+        &self,
+        i: usize,
+        link: &MultibodyLinkRef<N>,
+        dof_id: usize,
+        jacobians: &mut [N],
+    ) -> Option<GenericNonlinearConstraint<N>> {
+        let (err, sign, _) = self.axis_limit(i)?;
+        let mb = link.multibody();
+        let ndofs = mb.ndofs();
+
+        link.inv_mass_mul_unit_joint_force(dof_id + i, sign, jacobians);
+
+        let inv_r = sign * jacobians[link.assembly_id() + dof_id + i]; // = J^t * M^-1 J
+
+        Some(GenericNonlinearConstraint::new(
+            mb.handle(),
+            BodyHandle::ground(),
+            false,
+            ndofs,
+            0,
+            0,
+            0,
+            -err,
+            N::one() / inv_r,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Ball, ShapeHandle};
+
+    use super::CartesianJoint;
+    use math::Vector;
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn cartesian_joint_rests_in_the_corner_of_its_limits() {
+        let mut world = World::<f32>::new();
+        // Only the first two axes are limited.
+        world.set_gravity((Vector::x() + Vector::y()) * -9.81);
+
+        let mut joint = CartesianJoint::new(na::zero());
+        joint.set_axis_limits(0, -0.5, 0.5);
+        joint.set_axis_limits(1, -0.3, 0.3);
+
+        let ball = ShapeHandle::new(Ball::new(0.1));
+        let link = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            na::zero(),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let rest = world.body_part(link).position().translation.vector;
+
+        for _ in 0..30 {
+            world.step();
+            let pos = world.body_part(link).position().translation.vector;
+            assert!((pos - rest).norm() < 1.0e-3, "The joint jitters: {:?}", pos);
+        }
+
+        assert!((rest.x + 0.5).abs() < 1.0e-2, "Position: {:?}", rest);
+        assert!((rest.y + 0.3).abs() < 1.0e-2, "Position: {:?}", rest);
+    }
 }
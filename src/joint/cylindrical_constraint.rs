@@ -5,7 +5,7 @@ use object::{BodyHandle, BodySet};
 use solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
              NonlinearConstraintGenerator};
 use solver::helper;
-use joint::JointConstraint;
+use joint::{JointConstraint, JointConstraintParameters};
 use math::{AngularVector, Point, Vector, DIM, SPATIAL_DIM};
 
 /// A constraint that removes all degrees of freedom (of one body part relative to a second one) except one translation along an axis and one rotation along the same axis.
@@ -20,6 +20,7 @@ pub struct CylindricalConstraint<N: Real> {
     ang_impulses: AngularVector<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
 
     // min_offset: Option<N>,
     // max_offset: Option<N>,
@@ -52,6 +53,7 @@ impl<N: Real> CylindricalConstraint<N> {
             ang_impulses: AngularVector::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
             // min_offset,
             // max_offset,
         }
@@ -101,6 +103,14 @@ impl<N: Real> JointConstraint<N> for CylindricalConstraint<N> {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
     fn velocity_constraints(
         &mut self,
         _: &IntegrationParameters<N>,
@@ -195,6 +205,20 @@ impl<N: Real> JointConstraint<N> for CylindricalConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.lin_impulses
+            .iter()
+            .chain(self.ang_impulses.iter())
+            .cloned()
+            .collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let (lin, ang) = impulses.split_at(self.lin_impulses.len());
+        self.lin_impulses.copy_from_slice(lin);
+        self.ang_impulses.copy_from_slice(ang);
+    }
 }
 
 impl<N: Real> NonlinearConstraintGenerator<N> for CylindricalConstraint<N> {
@@ -251,6 +275,6 @@ impl<N: Real> NonlinearConstraintGenerator<N> for CylindricalConstraint<N> {
             );
         }
 
-        return None;
+        None
     }
 }
@@ -10,6 +10,7 @@ use math::{JacobianSliceMut, Velocity};
 pub struct CylindricalJoint<N: Real> {
     prism: PrismaticJoint<N>,
     revo: RevoluteJoint<N>,
+    damping: N,
 }
 
 impl<N: Real> CylindricalJoint<N> {
@@ -20,7 +21,27 @@ impl<N: Real> CylindricalJoint<N> {
         let prism = PrismaticJoint::new(axis, position);
         let revo = RevoluteJoint::new(axis, angle);
 
-        CylindricalJoint { prism, revo }
+        CylindricalJoint {
+            prism,
+            revo,
+            damping: N::zero(),
+        }
+    }
+
+    /// The viscous damping coefficient of both the translation and the rotation of this joint.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Set the viscous damping coefficient of both the translation and the rotation of this joint.
+    ///
+    /// See `PrismaticJoint::set_damping` and `RevoluteJoint::set_damping` for details.
+    pub fn set_damping(&mut self, damping: N) {
+        assert!(
+            damping >= N::zero(),
+            "The joint damping must be positive or zero."
+        );
+        self.damping = damping;
     }
 }
 
@@ -84,6 +105,10 @@ impl<N: Real> Joint<N> for CylindricalJoint<N> {
         self.revo.default_damping(&mut out.rows_mut(1, 1));
     }
 
+    fn viscous_damping(&self, out: &mut DVectorSliceMut<N>) {
+        out.fill(self.damping)
+    }
+
     fn integrate(&mut self, params: &IntegrationParameters<N>, vels: &[N]) {
         self.prism.integrate(params, vels);
         self.revo.integrate(params, &[vels[1]]);
@@ -94,6 +119,21 @@ impl<N: Real> Joint<N> for CylindricalJoint<N> {
         self.revo.apply_displacement(&[disp[1]]);
     }
 
+    fn generalized_position(&self, out: &mut [N]) {
+        self.prism.generalized_position(out);
+        self.revo.generalized_position(&mut out[1..]);
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        self.prism.set_generalized_position(coords);
+        self.revo.set_generalized_position(&coords[1..]);
+    }
+
+    fn clamp_to_limits(&mut self) {
+        self.prism.clamp_to_limits();
+        self.revo.clamp_to_limits();
+    }
+
     fn num_velocity_constraints(&self) -> usize {
         self.prism.num_velocity_constraints() + self.revo.num_velocity_constraints()
     }
@@ -154,3 +194,63 @@ impl<N: Real> Joint<N> for CylindricalJoint<N> {
 
 prismatic_motor_limit_methods!(CylindricalJoint, prism);
 revolute_motor_limit_methods!(CylindricalJoint, revo);
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Ball, ShapeHandle};
+
+    use super::CylindricalJoint;
+    use math::Vector;
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn cylindrical_joint_motors_are_independent() {
+        let mut world = World::<f32>::new();
+        let mut joint = CylindricalJoint::new(Vector::y_axis(), 0.0, 0.0);
+        joint.enable_linear_motor();
+        joint.set_desired_linear_motor_velocity(0.5);
+        joint.enable_angular_motor();
+        joint.set_desired_angular_motor_velocity(3.0);
+        joint.enable_max_offset(0.4);
+
+        let ball = ShapeHandle::new(Ball::new(0.1));
+        let link = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            Vector::x(),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+
+        for _ in 0..30 {
+            world.step();
+        }
+
+        {
+            let mb_link = world.multibody_link(link).unwrap();
+            let vels = mb_link.joint_velocity();
+            assert!((vels[0] - 0.5).abs() < 1.0e-2, "Linear velocity: {}", vels[0]);
+            assert!((vels[1] - 3.0).abs() < 1.0e-2, "Angular velocity: {}", vels[1]);
+        }
+
+        {
+            let mut link = world.multibody_link_mut(link).unwrap();
+            let joint = link.joint_mut().downcast_mut::<CylindricalJoint<f32>>().unwrap();
+            joint.disable_linear_motor();
+        }
+
+        for _ in 0..90 {
+            world.step();
+        }
+
+        let mb_link = world.multibody_link(link).unwrap();
+        let vels = mb_link.joint_velocity();
+        let height = world.body_part(link).position().translation.vector.y;
+        assert!((vels[1] - 3.0).abs() < 1.0e-2, "Angular velocity: {}", vels[1]);
+        assert!(height < 0.4 + 1.0e-2, "Height: {}", height);
+    }
+}
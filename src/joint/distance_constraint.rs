@@ -0,0 +1,368 @@
+use na::{DVector, Real, Unit};
+use std::ops::Range;
+
+use joint::{unit_constraint, JointConstraint, JointConstraintParameters, LimitState};
+use math::{Point, Vector};
+use object::{BodyHandle, BodyPart, BodySet};
+use solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
+             NonlinearConstraintGenerator};
+
+/// A constraint that keeps the distance between two points of two body parts fixed or bounded.
+///
+/// With equal limits, this behaves like a massless rigid rod linking both anchors. With distinct
+/// limits, this behaves like a rope or a cable: the anchors can move freely as long as their
+/// distance stays within the limits.
+pub struct DistanceConstraint<N: Real> {
+    b1: BodyHandle,
+    b2: BodyHandle,
+    anchor1: Point<N>,
+    anchor2: Point<N>,
+    min_distance: N,
+    max_distance: N,
+    // The direction used when both anchors coincide.
+    last_dir: Unit<Vector<N>>,
+    impulse: N,
+    limit_side: LimitState,
+    limit_state: LimitState,
+    bilateral_ground_rng: Range<usize>,
+    bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
+}
+
+impl<N: Real> DistanceConstraint<N> {
+    /// Creates a constraint keeping the distance between `anchor1` and `anchor2` to `distance`.
+    ///
+    /// Both anchors are given in the local-space of their corresponding body part.
+    pub fn new(
+        b1: BodyHandle,
+        b2: BodyHandle,
+        anchor1: Point<N>,
+        anchor2: Point<N>,
+        distance: N,
+    ) -> Self {
+        Self::new_with_limits(b1, b2, anchor1, anchor2, distance, distance)
+    }
+
+    /// Creates a constraint keeping the distance between `anchor1` and `anchor2` within the
+    /// interval `[min_distance, max_distance]`.
+    ///
+    /// Both anchors are given in the local-space of their corresponding body part.
+    pub fn new_with_limits(
+        b1: BodyHandle,
+        b2: BodyHandle,
+        anchor1: Point<N>,
+        anchor2: Point<N>,
+        min_distance: N,
+        max_distance: N,
+    ) -> Self {
+        let mut res = DistanceConstraint {
+            b1,
+            b2,
+            anchor1,
+            anchor2,
+            min_distance: N::zero(),
+            max_distance: N::zero(),
+            last_dir: Vector::x_axis(),
+            impulse: N::zero(),
+            limit_side: LimitState::Inactive,
+            limit_state: LimitState::Inactive,
+            bilateral_ground_rng: 0..0,
+            bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
+        };
+
+        res.set_distance_limits(min_distance, max_distance);
+        res
+    }
+
+    /// Change the first anchor, expressed in the local space of the first body part.
+    pub fn set_anchor_1(&mut self, anchor1: Point<N>) {
+        self.anchor1 = anchor1;
+    }
+
+    /// Change the second anchor, expressed in the local space of the second body part.
+    pub fn set_anchor_2(&mut self, anchor2: Point<N>) {
+        self.anchor2 = anchor2;
+    }
+
+    /// The smallest distance allowed between both anchors.
+    pub fn min_distance(&self) -> N {
+        self.min_distance
+    }
+
+    /// The largest distance allowed between both anchors.
+    pub fn max_distance(&self) -> N {
+        self.max_distance
+    }
+
+    /// Sets the distance between both anchors to a fixed value.
+    pub fn set_distance(&mut self, distance: N) {
+        self.set_distance_limits(distance, distance)
+    }
+
+    /// Sets the interval the distance between both anchors must lie in.
+    pub fn set_distance_limits(&mut self, min_distance: N, max_distance: N) {
+        assert!(
+            min_distance >= N::zero() && min_distance <= max_distance,
+            "DistanceConstraint limits: the min distance must be positive and smaller than (or equal to) the max distance."
+        );
+
+        self.min_distance = min_distance;
+        self.max_distance = max_distance;
+    }
+
+    // The world-space anchors, and the unit direction from the first anchor to the second one.
+    fn anchors_and_dir(
+        &self,
+        body1: &BodyPart<N>,
+        body2: &BodyPart<N>,
+    ) -> (Point<N>, Point<N>, Unit<Vector<N>>) {
+        let anchor1 = body1.position() * self.anchor1;
+        let anchor2 = body2.position() * self.anchor2;
+        let dir = Unit::try_new(anchor2 - anchor1, N::default_epsilon()).unwrap_or(self.last_dir);
+
+        (anchor1, anchor2, dir)
+    }
+}
+
+impl<N: Real> JointConstraint<N> for DistanceConstraint<N> {
+    fn num_velocity_constraints(&self) -> usize {
+        1
+    }
+
+    fn anchors(&self) -> (BodyHandle, BodyHandle) {
+        (self.b1, self.b2)
+    }
+
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
+    fn velocity_constraints(
+        &mut self,
+        _: &IntegrationParameters<N>,
+        bodies: &BodySet<N>,
+        ext_vels: &DVector<N>,
+        ground_j_id: &mut usize,
+        j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        let body1 = bodies.body_part(self.b1);
+        let body2 = bodies.body_part(self.b2);
+
+        let (anchor1, anchor2, dir) = self.anchors_and_dir(&body1, &body2);
+        self.last_dir = dir;
+
+        let assembly_id1 = body1.parent_companion_id();
+        let assembly_id2 = body2.parent_companion_id();
+
+        let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
+        let first_bilateral = constraints.velocity.bilateral.len();
+
+        let distance = dir.dot(&(anchor2 - anchor1));
+        let limit_side = if distance <= self.min_distance {
+            LimitState::AtLower
+        } else if distance >= self.max_distance {
+            LimitState::AtUpper
+        } else {
+            LimitState::Inactive
+        };
+
+        // The impulse of one limit is meaningless for the other one.
+        if limit_side != self.limit_side && self.min_distance != self.max_distance {
+            self.impulse = N::zero();
+        }
+        self.limit_side = limit_side;
+
+        unit_constraint::build_linear_limits_velocity_constraint(
+            &body1,
+            &body2,
+            assembly_id1,
+            assembly_id2,
+            &anchor1,
+            &anchor2,
+            &dir,
+            Some(self.min_distance),
+            Some(self.max_distance),
+            ext_vels,
+            self.impulse,
+            0,
+            ground_j_id,
+            j_id,
+            jacobians,
+            constraints,
+        );
+
+        self.bilateral_ground_rng =
+            first_bilateral_ground..constraints.velocity.bilateral_ground.len();
+        self.bilateral_rng = first_bilateral..constraints.velocity.bilateral.len();
+    }
+
+    fn cache_impulses(&mut self, constraints: &ConstraintSet<N>) {
+        self.limit_state = LimitState::Inactive;
+        self.impulse = N::zero();
+
+        for c in &constraints.velocity.bilateral_ground[self.bilateral_ground_rng.clone()] {
+            self.impulse = c.impulse;
+        }
+
+        for c in &constraints.velocity.bilateral[self.bilateral_rng.clone()] {
+            self.impulse = c.impulse;
+        }
+
+        if self.limit_side != LimitState::Inactive && !self.impulse.is_zero() {
+            self.limit_state = self.limit_side;
+        }
+    }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        vec![self.impulse]
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        assert_eq!(impulses.len(), 1, "Invalid number of cached impulses.");
+        self.impulse = impulses[0];
+    }
+
+    fn limit_state(&self) -> LimitState {
+        self.limit_state
+    }
+}
+
+impl<N: Real> NonlinearConstraintGenerator<N> for DistanceConstraint<N> {
+    fn num_position_constraints(&self, bodies: &BodySet<N>) -> usize {
+        // FIXME: calling this at each iteration of the non-linear resolution is costly.
+        if self.is_active(bodies) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn position_constraint(
+        &self,
+        params: &IntegrationParameters<N>,
+        _: usize,
+        bodies: &mut BodySet<N>,
+        jacobians: &mut [N],
+    ) -> Option<GenericNonlinearConstraint<N>> {
+        let body1 = bodies.body_part(self.b1);
+        let body2 = bodies.body_part(self.b2);
+
+        let (anchor1, anchor2, dir) = self.anchors_and_dir(&body1, &body2);
+
+        unit_constraint::build_linear_limits_position_constraint(
+            params,
+            &body1,
+            &body2,
+            &anchor1,
+            &anchor2,
+            &dir,
+            Some(self.min_distance),
+            Some(self.max_distance),
+            jacobians,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Ball, ShapeHandle};
+
+    use super::DistanceConstraint;
+    use math::{Isometry, Point, Vector};
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn distance_rod_pendulum_swings_at_the_expected_period() {
+        use std::f32::consts::PI;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let length = 1.0f32;
+        let angle = 0.1f32;
+        let ball = ShapeHandle::new(Ball::new(0.05));
+        let start = (Vector::x() * angle.sin() - Vector::y() * angle.cos()) * length;
+        let body = world.add_rigid_body(
+            Isometry::new(start, na::zero()),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+
+        let ground = BodyHandle::ground();
+        let rod = DistanceConstraint::new(ground, body, Point::origin(), Point::origin(), length);
+        let _ = world.add_constraint(rod);
+
+        // Record the times when the pendulum swings through the vertical, toward positive x.
+        let mut crossings = Vec::new();
+        let mut last_x = angle.sin();
+
+        while world.time() < 5.0 {
+            world.step();
+            let pos = world.rigid_body(body).unwrap().position().translation.vector;
+            assert!((pos.norm() - length).abs() < 1.0e-2);
+
+            if last_x < 0.0 && pos.x >= 0.0 {
+                crossings.push(world.time());
+            }
+            last_x = pos.x;
+        }
+
+        let expected_period = 2.0 * PI * (length / 9.81).sqrt();
+        assert!(crossings.len() >= 2);
+        let period = crossings[1] - crossings[0];
+        assert!((period - expected_period).abs() < 0.03, "{} vs {}", period, expected_period);
+    }
+
+    #[test]
+    fn distance_rope_is_slack_until_fully_extended() {
+        use joint::LimitState;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ball = ShapeHandle::new(Ball::new(0.05));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * -0.5, na::zero()),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+
+        let rope = DistanceConstraint::new_with_limits(
+            BodyHandle::ground(),
+            body,
+            Point::origin(),
+            Point::origin(),
+            0.0,
+            1.0,
+        );
+        let rope = world.add_constraint(rope);
+
+        // The body falls freely while the rope is slack.
+        for _ in 0..12 {
+            world.step();
+        }
+
+        let vel = world.rigid_body(body).unwrap().velocity().linear;
+        assert!((vel - Vector::y() * -9.81 * 0.2).norm() < 1.0e-3);
+        assert_eq!(world.constraint(rope).limit_state(), LimitState::Inactive);
+
+        // The taut rope then holds the body at its length.
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let pos = world.rigid_body(body).unwrap().position().translation.vector;
+        assert!((pos - Vector::y() * -1.0).norm() < 1.0e-2);
+        assert_eq!(world.constraint(rope).limit_state(), LimitState::AtUpper);
+    }
+}
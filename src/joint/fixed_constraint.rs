@@ -1,7 +1,7 @@
 use na::{DVector, Real};
 use std::ops::Range;
 
-use joint::JointConstraint;
+use joint::{JointConstraint, JointConstraintParameters};
 use math::{AngularVector, Isometry, Point, Vector, DIM, SPATIAL_DIM};
 use object::{BodyHandle, BodySet};
 use solver::helper;
@@ -18,6 +18,7 @@ pub struct FixedConstraint<N: Real> {
     ang_impulses: AngularVector<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
 }
 
 impl<N: Real> FixedConstraint<N> {
@@ -40,9 +41,23 @@ impl<N: Real> FixedConstraint<N> {
             ang_impulses: AngularVector::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
         }
     }
 
+    /// Create a fixed constraint between the body part `b` and the ground.
+    ///
+    /// This will ensure the frame `joint_to_b` attached to `b` always coincides with
+    /// the world-space frame `joint_to_world`. Only the jacobians of `b` are computed at each
+    /// timestep.
+    pub fn new_with_ground(
+        b: BodyHandle,
+        joint_to_world: Isometry<N>,
+        joint_to_b: Isometry<N>,
+    ) -> Self {
+        Self::new(BodyHandle::ground(), b, joint_to_world, joint_to_b)
+    }
+
     /// Changes the frame attached to the first body part.
     pub fn set_anchor_1(&mut self, local1: Isometry<N>) {
         self.joint_to_b1 = local1
@@ -52,6 +67,49 @@ impl<N: Real> FixedConstraint<N> {
     pub fn set_anchor_2(&mut self, local2: Isometry<N>) {
         self.joint_to_b2 = local2
     }
+
+    // Velocity constraints when the first body part is the ground.
+    fn ground_velocity_constraints(
+        &mut self,
+        bodies: &BodySet<N>,
+        ext_vels: &DVector<N>,
+        ground_j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        let body = bodies.body_part(self.b2);
+        let assembly_id = body.parent_companion_id();
+        let anchor = Point::from((body.position() * self.joint_to_b2).translation.vector);
+        let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
+
+        helper::cancel_linear_velocity_wrt_ground(
+            &body,
+            assembly_id,
+            &anchor,
+            ext_vels,
+            &self.lin_impulses,
+            0,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        helper::cancel_angular_velocity_wrt_ground(
+            &body,
+            assembly_id,
+            &anchor,
+            ext_vels,
+            &self.ang_impulses,
+            DIM,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        self.bilateral_ground_rng =
+            first_bilateral_ground..constraints.velocity.bilateral_ground.len();
+        self.bilateral_rng = 0..0;
+    }
 }
 
 impl<N: Real> JointConstraint<N> for FixedConstraint<N> {
@@ -63,6 +121,14 @@ impl<N: Real> JointConstraint<N> for FixedConstraint<N> {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
     fn velocity_constraints(
         &mut self,
         _: &IntegrationParameters<N>,
@@ -73,14 +139,19 @@ impl<N: Real> JointConstraint<N> for FixedConstraint<N> {
         jacobians: &mut [N],
         constraints: &mut ConstraintSet<N>,
     ) {
+        if self.b1.is_ground() {
+            self.ground_velocity_constraints(bodies, ext_vels, ground_j_id, jacobians, constraints);
+            return;
+        }
+
         let b1 = bodies.body_part(self.b1);
         let b2 = bodies.body_part(self.b2);
 
         let pos1 = b1.position() * self.joint_to_b1;
         let pos2 = b2.position() * self.joint_to_b2;
 
-        let anchor1 = Point::from_coordinates(pos1.translation.vector);
-        let anchor2 = Point::from_coordinates(pos2.translation.vector);
+        let anchor1 = Point::from(pos1.translation.vector);
+        let anchor2 = Point::from(pos2.translation.vector);
 
         let assembly_id1 = b1.parent_companion_id();
         let assembly_id2 = b2.parent_companion_id();
@@ -142,6 +213,20 @@ impl<N: Real> JointConstraint<N> for FixedConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.lin_impulses
+            .iter()
+            .chain(self.ang_impulses.iter())
+            .cloned()
+            .collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let (lin, ang) = impulses.split_at(self.lin_impulses.len());
+        self.lin_impulses.copy_from_slice(lin);
+        self.ang_impulses.copy_from_slice(ang);
+    }
 }
 
 impl<N: Real> NonlinearConstraintGenerator<N> for FixedConstraint<N> {
@@ -167,8 +252,8 @@ impl<N: Real> NonlinearConstraintGenerator<N> for FixedConstraint<N> {
         let pos1 = body1.position() * self.joint_to_b1;
         let pos2 = body2.position() * self.joint_to_b2;
 
-        let anchor1 = Point::from_coordinates(pos1.translation.vector);
-        let anchor2 = Point::from_coordinates(pos2.translation.vector);
+        let anchor1 = Point::from(pos1.translation.vector);
+        let anchor2 = Point::from(pos2.translation.vector);
 
         if i == 0 {
             let rotation1 = pos1.rotation;
@@ -18,6 +18,18 @@ impl<N: Real> FreeJoint<N> {
         FreeJoint { position }
     }
 
+    /// The position of the descendent, relative to the ground.
+    pub fn position(&self) -> &Isometry<N> {
+        &self.position
+    }
+
+    /// Sets the position of the descendent, relative to the ground.
+    ///
+    /// The multibody kinematics must be updated afterwards for this to affect the link position.
+    pub fn set_position(&mut self, position: Isometry<N>) {
+        self.position = position
+    }
+
     fn apply_displacement(&mut self, disp: &Velocity<N>) {
         let disp = Isometry::new(disp.linear, disp.angular);
         self.position = Isometry::from_parts(
@@ -62,6 +74,19 @@ impl<N: Real> Joint<N> for FreeJoint<N> {
         self.apply_displacement(&disp);
     }
 
+    fn generalized_position(&self, out: &mut [N]) {
+        let coords = Velocity::new_with_vectors(
+            self.position.translation.vector,
+            self.position.rotation.scaled_axis(),
+        );
+        out[..SPATIAL_DIM].copy_from_slice(coords.as_slice())
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        let coords = Velocity::from_slice(coords);
+        self.position = Isometry::new(coords.linear, coords.angular);
+    }
+
     fn jacobian_mul_coordinates(&self, vels: &[N]) -> Velocity<N> {
         Velocity::from_slice(vels)
     }
@@ -73,3 +98,75 @@ impl<N: Real> Joint<N> for FreeJoint<N> {
 
     fn default_damping(&self, _: &mut DVectorSliceMut<N>) {}
 }
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::FreeJoint;
+    use math::{Isometry, Vector};
+    use object::{BodyHandle, Material};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn free_joint_root_pose_and_velocity_can_be_set_directly() {
+        use joint::FixedJoint;
+        use math::Velocity;
+
+        let mut world = World::<f32>::new();
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+        let root = world.add_multibody_link(
+            BodyHandle::ground(),
+            FreeJoint::new(Isometry::identity()),
+            na::zero(),
+            na::zero(),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let child = world.add_multibody_link(
+            root,
+            FixedJoint::new(Isometry::identity()),
+            Vector::x() * 2.0,
+            na::zero(),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let collider = world.add_collider(
+            0.01,
+            cuboid,
+            child,
+            Isometry::identity(),
+            Material::default(),
+        );
+
+        let target = Isometry::new(Vector::y() * 5.0, na::zero());
+        let velocity = Velocity::new_with_vectors(Vector::x() * 3.0, na::zero());
+        world.set_multibody_root_position(root, target, Some(velocity));
+
+        // The links and colliders are moved without stepping.
+        let expected = Vector::x() * 2.0 + Vector::y() * 5.0;
+        let child_pos = world.multibody_link(child).unwrap().position();
+        let collider_pos = *world.collider(collider).unwrap().position();
+        assert!((child_pos.translation.vector - expected).norm() < 1.0e-5);
+        assert!((collider_pos.translation.vector - expected).norm() < 1.0e-5);
+
+        // So are the link velocities.
+        let child_vel = world.multibody_link(child).unwrap().velocity().linear;
+        assert!((child_vel - Vector::x() * 3.0).norm() < 1.0e-5);
+
+        world.step();
+
+        // NOTE: the link positions are only updated at the beginning of the next step, so the
+        // integrated pose is read from the joint.
+        let dt = world.integration_parameters().dt;
+        let root_link = world.multibody_link(root).unwrap();
+        let root_pos = *root_link.joint().downcast_ref::<FreeJoint<f32>>().unwrap().position();
+        let root_vel = world.multibody(root).unwrap().generalized_velocity();
+        let expected = Vector::x() * 3.0 * dt + Vector::y() * 5.0;
+        assert!((root_pos.translation.vector - expected).norm() < 1.0e-3);
+        assert!((root_vel[0] - 3.0).abs() < 1.0e-3);
+    }
+}
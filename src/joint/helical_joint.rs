@@ -2,8 +2,10 @@ use na::{self, DVectorSliceMut, Isometry3, Real, Translation3, Unit, Vector3};
 
 use joint::{Joint, JointMotor, RevoluteJoint, UnitJoint};
 use math::{JacobianSliceMut, Velocity};
-use object::MultibodyLinkRef;
-use solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters};
+use object::{BodyHandle, MultibodyLinkRef};
+use solver::{
+    ConstraintSet, GenericNonlinearConstraint, IntegrationParameters, UnilateralGroundConstraint,
+};
 
 /// A joint that allows one degree of freedom between two multibody links.
 /// 
@@ -13,6 +15,9 @@ use solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters};
 pub struct HelicalJoint<N: Real> {
     revo: RevoluteJoint<N>,
     pitch: N,
+    offset: N,
+    min_offset: Option<N>,
+    max_offset: Option<N>,
 }
 
 impl<N: Real> HelicalJoint<N> {
@@ -23,13 +28,98 @@ impl<N: Real> HelicalJoint<N> {
     pub fn new(axis: Unit<Vector3<N>>, pitch: N, angle: N) -> Self {
         HelicalJoint {
             revo: RevoluteJoint::new(axis, angle),
-            pitch: pitch,
+            pitch,
+            offset: angle * pitch,
+            min_offset: None,
+            max_offset: None,
         }
     }
 
     /// The translational displacement along the joint axis.
+    ///
+    /// This is the sum of the rotational displacements weighted by the pitch at the time they
+    /// occured. It is equal to `self.angle() * self.pitch()` if the pitch never changed.
     pub fn offset(&self) -> N {
-        self.revo.angle() * self.pitch
+        self.offset
+    }
+
+    /// How much translation is generated for how much rotation.
+    pub fn pitch(&self) -> N {
+        self.pitch
+    }
+
+    /// Sets how much translation is generated for how much rotation.
+    ///
+    /// This only affects the subsequent motions: the current translational and rotational
+    /// displacements are left unchanged.
+    pub fn set_pitch(&mut self, pitch: N) {
+        self.pitch = pitch
+    }
+
+    /// The lower limit of the translational displacement along the joint axis.
+    pub fn min_offset(&self) -> Option<N> {
+        self.min_offset
+    }
+
+    /// The upper limit of the translational displacement along the joint axis.
+    pub fn max_offset(&self) -> Option<N> {
+        self.max_offset
+    }
+
+    /// Disable the lower limit of the translational displacement along the joint axis.
+    pub fn disable_min_offset(&mut self) {
+        self.min_offset = None;
+    }
+
+    /// Disable the upper limit of the translational displacement along the joint axis.
+    pub fn disable_max_offset(&mut self) {
+        self.max_offset = None;
+    }
+
+    /// Enable the lower limit of the translational displacement along the joint axis.
+    pub fn enable_min_offset(&mut self, limit: N) {
+        self.min_offset = Some(limit);
+        self.assert_offset_limits();
+    }
+
+    /// Enable the upper limit of the translational displacement along the joint axis.
+    pub fn enable_max_offset(&mut self, limit: N) {
+        self.max_offset = Some(limit);
+        self.assert_offset_limits();
+    }
+
+    fn assert_offset_limits(&self) {
+        if let (Some(min), Some(max)) = (self.min_offset, self.max_offset) {
+            assert!(
+                min <= max,
+                "HelicalJoint constraint limits: the min offset must be smaller than (or equal to) the max offset."
+            );
+        }
+    }
+
+    // The violated offset limit, if any, as the violation and the sign of the constraint jacobian.
+    //
+    // The violation is positive and the jacobian points toward the admissible range.
+    fn offset_limit(&self) -> Option<(N, N, usize)> {
+        if self.pitch.is_zero() {
+            return None;
+        }
+
+        if let Some(min_offset) = self.min_offset {
+            let err = min_offset - self.offset;
+            if err >= N::zero() {
+                return Some((err, self.pitch, 4));
+            }
+        }
+
+        if let Some(max_offset) = self.max_offset {
+            let err = self.offset - max_offset;
+            if err >= N::zero() {
+                return Some((err, -self.pitch, 5));
+            }
+        }
+
+        None
     }
 
     /// The rotational displacement along the joint axis.
@@ -45,7 +135,7 @@ impl<N: Real> Joint<N> for HelicalJoint<N> {
     }
 
     fn body_to_parent(&self, parent_shift: &Vector3<N>, body_shift: &Vector3<N>) -> Isometry3<N> {
-        Translation3::from_vector(self.revo.axis().as_ref() * self.revo.angle())
+        Translation3::from(self.revo.axis().as_ref() * self.offset)
             * self.revo.body_to_parent(parent_shift, body_shift)
     }
 
@@ -88,15 +178,45 @@ impl<N: Real> Joint<N> for HelicalJoint<N> {
     }
 
     fn integrate(&mut self, params: &IntegrationParameters<N>, vels: &[N]) {
+        self.offset += vels[0] * params.dt * self.pitch;
         self.revo.integrate(params, vels)
     }
 
     fn apply_displacement(&mut self, disp: &[N]) {
+        self.offset += disp[0] * self.pitch;
         self.revo.apply_displacement(disp)
     }
 
+    fn generalized_position(&self, out: &mut [N]) {
+        self.revo.generalized_position(out)
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        // Move along the screw so the translation remains consistent with the rotation.
+        let disp = [coords[0] - self.revo.angle()];
+        self.apply_displacement(&disp)
+    }
+
+    fn nimpulses(&self) -> usize {
+        // The two additional impulses are for the offset limits.
+        6
+    }
+
+    fn clamp_to_limits(&mut self) {
+        self.revo.clamp_to_limits();
+    }
+
     fn num_velocity_constraints(&self) -> usize {
-        self.revo.num_velocity_constraints()
+        let mut nconstraints = self.revo.num_velocity_constraints();
+
+        if self.min_offset.is_some() {
+            nconstraints += 1;
+        }
+        if self.max_offset.is_some() {
+            nconstraints += 1;
+        }
+
+        nconstraints
     }
 
     fn velocity_constraints(
@@ -121,22 +241,76 @@ impl<N: Real> Joint<N> for HelicalJoint<N> {
             jacobians,
             constraints,
         );
+
+        if let Some((_, dir, slot)) = self.offset_limit() {
+            let ndofs = link.multibody().ndofs();
+            let impulses = link.multibody().impulses();
+            let dvel = dir
+                * (link.joint_velocity()[dof_id]
+                    + ext_vels[assembly_id + link.assembly_id() + dof_id]);
+
+            DVectorSliceMut::from_slice(&mut jacobians[*ground_j_id..], ndofs).fill(N::zero());
+            jacobians[*ground_j_id + link.assembly_id() + dof_id] = dir;
+
+            let wj_id = *ground_j_id + ndofs;
+            link.inv_mass_mul_unit_joint_force(dof_id, dir, &mut jacobians[wj_id..]);
+
+            let inv_r = dir * jacobians[wj_id + link.assembly_id() + dof_id]; // = J^t * M^-1 J
+            let impulse_id = link.impulse_id() + dof_id * 4 + slot;
+
+            let constraint = UnilateralGroundConstraint {
+                impulse: impulses[impulse_id] * params.warmstart_coeff,
+                r: N::one() / inv_r,
+                rhs: dvel,
+                cfm: N::zero(),
+                impulse_id,
+                assembly_id,
+                j_id: *ground_j_id,
+                wj_id,
+                ndofs,
+            };
+
+            constraints.velocity.unilateral_ground.push(constraint);
+            *ground_j_id += 2 * ndofs;
+        }
     }
 
     fn num_position_constraints(&self) -> usize {
         // NOTE: we don't test if constraints exist to simplify indexing.
-        1
+        2
     }
 
     fn position_constraint(
         &self,
-        _: usize,
+        i: usize,
         link: &MultibodyLinkRef<N>,
         dof_id: usize,
         jacobians: &mut [N],
     ) -> Option<GenericNonlinearConstraint<N>> {
-        // XXX: is this correct even though we don't have the same jacobian?
-        self.revo.position_constraint(0, link, dof_id, jacobians)
+        if i == 0 {
+            // XXX: is this correct even though we don't have the same jacobian?
+            return self.revo.position_constraint(0, link, dof_id, jacobians);
+        }
+
+        let (err, dir, _) = self.offset_limit()?;
+        let mb = link.multibody();
+        let ndofs = mb.ndofs();
+
+        link.inv_mass_mul_unit_joint_force(dof_id, dir, jacobians);
+
+        let inv_r = dir * jacobians[link.assembly_id() + dof_id]; // = J^t * M^-1 J
+
+        Some(GenericNonlinearConstraint::new(
+            mb.handle(),
+            BodyHandle::ground(),
+            false,
+            ndofs,
+            0,
+            0,
+            0,
+            -err,
+            N::one() / inv_r,
+        ))
     }
 }
 
@@ -149,6 +323,18 @@ impl<N: Real> UnitJoint<N> for HelicalJoint<N> {
         self.revo.motor()
     }
 
+    fn friction(&self) -> N {
+        self.revo.friction_torque()
+    }
+
+    fn limit_softness(&self) -> (N, N) {
+        self.revo.limit_softness()
+    }
+
+    fn limit_restitution(&self) -> N {
+        self.revo.limit_restitution()
+    }
+
     fn min_position(&self) -> Option<N> {
         self.revo.min_angle()
     }
@@ -159,3 +345,60 @@ impl<N: Real> UnitJoint<N> for HelicalJoint<N> {
 }
 
 revolute_motor_limit_methods!(HelicalJoint, revo);
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Ball, ShapeHandle};
+
+    use super::HelicalJoint;
+    use math::Vector;
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn helical_joint_pitch_change_is_smooth_and_limited() {
+        let mut world = World::<f32>::new();
+        let mut joint = HelicalJoint::new(Vector::y_axis(), 0.1, 0.0);
+        joint.enable_max_offset(0.5);
+
+        let ball = ShapeHandle::new(Ball::new(0.1));
+        let link = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            Vector::x(),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+        world.multibody_link_mut(link).unwrap().joint_velocity_mut()[0] = 2.0;
+
+        let offset = |world: &World<f32>| {
+            let link = world.multibody_link(link).unwrap();
+            link.joint().downcast_ref::<HelicalJoint<f32>>().unwrap().offset()
+        };
+
+        let mut last_y = world.body_part(link).position().translation.vector.y;
+
+        for i in 0..240 {
+            if i == 30 {
+                let mut link = world.multibody_link_mut(link).unwrap();
+                let joint = link.joint_mut().downcast_mut::<HelicalJoint<f32>>().unwrap();
+                joint.set_pitch(0.3);
+            }
+
+            world.step();
+            // Refresh the link position so it reflects the integrated joint.
+            world.multibody_mut(link).unwrap().update_kinematics();
+
+            let angvel = world.multibody_link(link).unwrap().joint_velocity()[0];
+            let y = world.body_part(link).position().translation.vector.y;
+            assert!(angvel.abs() <= 2.0 + 1.0e-3, "Joint velocity: {}", angvel);
+            assert!((y - last_y).abs() <= 2.0 * 0.3 * world.timestep() + 1.0e-3);
+            assert!((y - offset(&world)).abs() < 1.0e-3);
+            assert!(offset(&world) < 0.5 + 1.0e-2, "Offset: {}", offset(&world));
+            last_y = y;
+        }
+    }
+}
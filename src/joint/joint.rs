@@ -9,7 +9,7 @@ use object::MultibodyLinkRef;
 use solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters};
 
 /// Trait implemented by all joints following the reduced-coordinate formation.
-pub trait Joint<N: Real>: Any + Send + Sync {
+pub trait Joint<N: Real>: JointClone<N> + Any + Send + Sync {
     /// The number of degrees of freedom allowed by the joint.
     fn ndofs(&self) -> usize;
     /// The position of the multibody link containing this joint relative to its parent.
@@ -20,6 +20,34 @@ pub trait Joint<N: Real>: Any + Send + Sync {
     fn integrate(&mut self, params: &IntegrationParameters<N>, vels: &[N]);
     /// Apply a displacement to the joint.
     fn apply_displacement(&mut self, disp: &[N]);
+    /// Writes the `self.ndofs()` generalized coordinates of this joint into `out`.
+    ///
+    /// Rotations with more than one degree of freedom are represented by their scaled axis,
+    /// i.e., the rotation axis multiplied by the rotation angle in `[0, pi]`. Any scaled axis
+    /// can be given to `self.set_generalized_position`, but only the equivalent one with an angle
+    /// in `[0, pi]` is read back. Thus, reading the coordinates of a joint and setting them back
+    /// leaves the joint unchanged.
+    ///
+    /// The default implementation only supports joints without any degree of freedom.
+    fn generalized_position(&self, _: &mut [N]) {
+        assert_eq!(
+            self.ndofs(),
+            0,
+            "This joint does not expose its generalized coordinates."
+        );
+    }
+    /// Sets the generalized coordinates of this joint to the first `self.ndofs()` entries of `coords`.
+    ///
+    /// The default implementation only supports joints without any degree of freedom.
+    fn set_generalized_position(&mut self, _: &[N]) {
+        assert_eq!(
+            self.ndofs(),
+            0,
+            "This joint does not expose its generalized coordinates."
+        );
+    }
+    /// Clamp the generalized coordinates of this joint so they satisfy its limits, if any.
+    fn clamp_to_limits(&mut self) {}
 
     /// Sets in `out` the non-zero entries of the joint jacobian transformed by `transform`.
     fn jacobian(&self, transform: &Isometry<N>, out: &mut JacobianSliceMut<N>);
@@ -43,11 +71,17 @@ pub trait Joint<N: Real>: Any + Send + Sync {
     /// Fill `out` with the non-zero entries of a damping that can be applied by default to ensure a good stability of the joint.
     fn default_damping(&self, out: &mut DVectorSliceMut<N>);
 
+    /// Fill `out` with the viscous damping coefficient of each degree of freedom of this joint.
+    ///
+    /// Unlike the default damping, this is read at each timestep so it can be modified after the
+    /// joint has been added to a multibody. `out` is filled with zeros by the caller.
+    fn viscous_damping(&self, _: &mut DVectorSliceMut<N>) {}
+
     /// The maximum number of impulses needed by this joints for
     /// its constraints.
     fn nimpulses(&self) -> usize {
         // FIXME: keep this?
-        self.ndofs() * 3
+        self.ndofs() * 4
     }
 
     /// Maximum number of velocity constrains that can be generated by this joint.
@@ -86,4 +120,18 @@ pub trait Joint<N: Real>: Any + Send + Sync {
     }
 }
 
+/// Trait allowing boxed joints to be copied.
+///
+/// This is automatically implemented for every joint implementing `Clone`.
+pub trait JointClone<N: Real> {
+    /// A boxed copy of this joint.
+    fn clone_box(&self) -> Box<Joint<N>>;
+}
+
+impl<N: Real, T: Joint<N> + Clone> JointClone<N> for T {
+    fn clone_box(&self) -> Box<Joint<N>> {
+        Box::new(self.clone())
+    }
+}
+
 downcast!(<N> Joint<N> where N: Real);
@@ -9,6 +9,41 @@ use solver::{ConstraintSet, IntegrationParameters, NonlinearConstraintGenerator}
 /// The handle of a consraint.
 pub type ConstraintHandle = usize;
 
+/// The state of the limits of a joint.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LimitState {
+    /// No limit applied any impulse during the last timestep.
+    Inactive,
+    /// The lower limit applied a non-zero impulse during the last timestep.
+    AtLower,
+    /// The upper limit applied a non-zero impulse during the last timestep.
+    AtUpper,
+}
+
+/// The solver parameters a joint constraint can override.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct JointConstraintParameters<N: Real> {
+    /// The error reduction parameter used instead of `IntegrationParameters::erp`, if any.
+    pub erp: Option<N>,
+    /// The compliance (inverse stiffness) of the joint, if any.
+    pub cfm: Option<N>,
+    /// The maximum force the linear velocity constraints of the joint can apply, if any.
+    pub max_force: Option<N>,
+    /// The maximum torque the angular velocity constraints of the joint can apply, if any.
+    pub max_torque: Option<N>,
+}
+
+impl<N: Real> Default for JointConstraintParameters<N> {
+    fn default() -> Self {
+        JointConstraintParameters {
+            erp: None,
+            cfm: None,
+            max_force: None,
+            max_torque: None,
+        }
+    }
+}
+
 /// Trait implemented by joint that operate by generating constraints to restrict the relative motion of two body parts.
 pub trait JointConstraint<N: Real>: NonlinearConstraintGenerator<N> + Any + Send + Sync {
     /// Return `true` if the constraint is active.
@@ -29,6 +64,68 @@ pub trait JointConstraint<N: Real>: NonlinearConstraintGenerator<N> + Any + Send
     fn num_velocity_constraints(&self) -> usize;
     /// The two body parts affected by this joint.
     fn anchors(&self) -> (BodyHandle, BodyHandle);
+    /// The solver parameters overridden by this joint, if it supports overriding them.
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        None
+    }
+    /// The mutable solver parameters overridden by this joint, if it supports overriding them.
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        None
+    }
+    /// The error reduction parameter used by this joint instead of `IntegrationParameters::erp`, if any.
+    fn position_correction(&self) -> Option<N> {
+        self.parameters().and_then(|params| params.erp)
+    }
+    /// Sets the error reduction parameter used by this joint instead of `IntegrationParameters::erp`.
+    ///
+    /// Set it to `None` to use the global error reduction parameter. This does nothing if the
+    /// joint does not support overriding its solver parameters.
+    fn set_position_correction(&mut self, erp: Option<N>) {
+        if let Some(params) = self.parameters_mut() {
+            params.erp = erp
+        }
+    }
+    /// The compliance (inverse stiffness) of this joint, if any.
+    fn compliance(&self) -> Option<N> {
+        self.parameters().and_then(|params| params.cfm)
+    }
+    /// Sets the compliance (inverse stiffness) of this joint.
+    ///
+    /// Set it to `None` to make this joint perfectly rigid. This does nothing if the joint does
+    /// not support overriding its solver parameters.
+    fn set_compliance(&mut self, cfm: Option<N>) {
+        if let Some(params) = self.parameters_mut() {
+            params.cfm = cfm
+        }
+    }
+    /// The maximum force the linear velocity constraints of this joint can apply, if any.
+    fn max_force(&self) -> Option<N> {
+        self.parameters().and_then(|params| params.max_force)
+    }
+    /// Sets the maximum force the linear velocity constraints of this joint can apply.
+    ///
+    /// The joint yields whenever a larger force would be needed to satisfy it.
+    /// Set it to `None` to remove this limit. This does nothing if the joint does not support
+    /// overriding its solver parameters.
+    fn set_max_force(&mut self, max_force: Option<N>) {
+        if let Some(params) = self.parameters_mut() {
+            params.max_force = max_force
+        }
+    }
+    /// The maximum torque the angular velocity constraints of this joint can apply, if any.
+    fn max_torque(&self) -> Option<N> {
+        self.parameters().and_then(|params| params.max_torque)
+    }
+    /// Sets the maximum torque the angular velocity constraints of this joint can apply.
+    ///
+    /// The joint yields whenever a larger torque would be needed to satisfy it.
+    /// Set it to `None` to remove this limit. This does nothing if the joint does not support
+    /// overriding its solver parameters.
+    fn set_max_torque(&mut self, max_torque: Option<N>) {
+        if let Some(params) = self.parameters_mut() {
+            params.max_torque = max_torque
+        }
+    }
     /// Initialize and retrieve all the constraints appied to the bodies attached to this joint.
     fn velocity_constraints(
         &mut self,
@@ -42,6 +139,85 @@ pub trait JointConstraint<N: Real>: NonlinearConstraintGenerator<N> + Any + Send
     );
     /// Called after velocity constraint resolution, allows the joint to keep a cache of impulses generated for each constraint.
     fn cache_impulses(&mut self, constraints: &ConstraintSet<N>);
+    /// The impulses cached by this joint to warm-start the next timestep.
+    ///
+    /// This is empty for joints that do not cache any impulse.
+    fn cached_impulses(&self) -> Vec<N> {
+        Vec::new()
+    }
+    /// Sets the impulses cached by this joint to warm-start the next timestep.
+    ///
+    /// Panics if `impulses` does not have the same length as `self.cached_impulses()`.
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        assert!(impulses.is_empty(), "Invalid number of cached impulses.");
+    }
+    /// The state of the limits of this joint after the last timestep.
+    ///
+    /// This is always `LimitState::Inactive` for joints without limits.
+    fn limit_state(&self) -> LimitState {
+        LimitState::Inactive
+    }
 }
 
 downcast!(<N> JointConstraint<N> where N: Real);
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::JointConstraint;
+    use math::{Isometry, Point, Vector};
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn overloaded_joint_yields_and_is_reported_as_saturated() {
+        #[cfg(feature = "dim2")]
+        use joint::RevoluteConstraint as BallConstraint;
+        #[cfg(feature = "dim3")]
+        use joint::BallConstraint;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        // Two bodies with a unit mass hang from the ground. Holding one of them requires a force
+        // of about 9.81 so the first joint yields while the second holds.
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let anchor = Point::from(Vector::y());
+        let ((weak_body, weak), (strong_body, strong)) = {
+            let mut hang = |x: f32, max_force: f32| {
+                let body = world.add_rigid_body(
+                    Isometry::new(Vector::x() * x - Vector::y(), na::zero()),
+                    cuboid.inertia(1.0),
+                    cuboid.center_of_mass(),
+                );
+                world
+                    .rigid_body_mut(body)
+                    .unwrap()
+                    .activation_status_mut()
+                    .set_deactivation_threshold(None);
+
+                let ground_anchor = Point::from(Vector::x() * x);
+                let mut constraint =
+                    BallConstraint::new(BodyHandle::ground(), body, ground_anchor, anchor);
+                constraint.set_max_force(Some(max_force));
+                (body, world.add_constraint(constraint))
+            };
+
+            (hang(0.0, 1.0), hang(5.0, 100.0))
+        };
+
+        for _ in 0..60 {
+            world.step();
+            assert!(world.saturated_constraints().contains(&weak));
+            assert!(!world.saturated_constraints().contains(&strong));
+        }
+
+        let weak_y = world.rigid_body(weak_body).unwrap().position().translation.vector.y;
+        let strong_y = world.rigid_body(strong_body).unwrap().position().translation.vector.y;
+        assert!(weak_y < -2.0, "The overloaded joint did not yield: {}", weak_y);
+        assert!((strong_y + 1.0).abs() < 1.0e-2, "The joint did not hold: {}", strong_y);
+    }
+}
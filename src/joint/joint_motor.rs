@@ -11,17 +11,31 @@ pub struct JointMotor<V, N: Real> {
     pub max_force: N,
     /// Whether or not the motor is active.
     pub enabled: bool,
+    /// The position the motor will attempt to reach, if any.
+    ///
+    /// If set, the motor behaves as a servo: the desired velocity is ignored and the motor
+    /// generates the force `stiffness * (desired_position - position) - damping * velocity`,
+    /// clamped by `max_force`.
+    pub desired_position: Option<V>,
+    /// The proportional gain of the servo.
+    pub stiffness: N,
+    /// The derivative gain of the servo.
+    pub damping: N,
 }
 
 impl<V: Zero, N: Real> JointMotor<V, N> {
     /// Create a disable motor with zero desired velocity.
     ///
     /// The max force is initialized to a virtually infinite value, i.e., `N::max_value()`.
+    /// No desired position is set and both servo gains are zero.
     pub fn new() -> Self {
         JointMotor {
             desired_velocity: V::zero(),
             max_force: N::max_value(),
             enabled: false,
+            desired_position: None,
+            stiffness: N::zero(),
+            damping: N::zero(),
         }
     }
 
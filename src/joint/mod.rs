@@ -3,7 +3,7 @@
 pub use self::cartesian_joint::CartesianJoint;
 pub use self::fixed_joint::FixedJoint;
 pub use self::free_joint::FreeJoint;
-pub use self::joint::Joint;
+pub use self::joint::{Joint, JointClone};
 pub use self::prismatic_joint::PrismaticJoint;
 pub use self::revolute_joint::RevoluteJoint;
 pub use self::unit_joint::{unit_joint_num_velocity_constraints, unit_joint_position_constraint,
@@ -24,13 +24,18 @@ pub use self::rectangular_joint::RectangularJoint;
 #[cfg(feature = "dim3")]
 pub use self::universal_joint::UniversalJoint;
 
+pub use self::angular_fixed_constraint::AngularFixedConstraint;
 pub use self::cartesian_constraint::CartesianConstraint;
+pub use self::distance_constraint::DistanceConstraint;
 pub use self::fixed_constraint::FixedConstraint;
-pub use self::joint_constraint::{ConstraintHandle, JointConstraint};
+pub use self::joint_constraint::{
+    ConstraintHandle, JointConstraint, JointConstraintParameters, LimitState,
+};
 pub use self::joint_motor::JointMotor;
 pub use self::mouse_constraint::MouseConstraint;
 pub use self::prismatic_constraint::PrismaticConstraint;
 pub use self::revolute_constraint::RevoluteConstraint;
+pub use self::upright_constraint::UprightConstraint;
 
 #[cfg(feature = "dim3")]
 pub use self::ball_constraint::BallConstraint;
@@ -68,13 +73,16 @@ mod rectangular_joint;
 #[cfg(feature = "dim3")]
 mod universal_joint;
 
+mod angular_fixed_constraint;
 mod cartesian_constraint;
+mod distance_constraint;
 mod fixed_constraint;
 mod joint_constraint;
 mod joint_motor;
 mod mouse_constraint;
 mod prismatic_constraint;
 mod revolute_constraint;
+mod upright_constraint;
 mod unit_constraint;
 
 #[cfg(feature = "dim3")]
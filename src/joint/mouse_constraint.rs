@@ -1,7 +1,7 @@
 use alga::linear::FiniteDimVectorSpace;
 use na::{DVector, Real, Unit};
 
-use joint::JointConstraint;
+use joint::{JointConstraint, JointConstraintParameters};
 use math::{Point, Vector, DIM};
 use object::{BodyHandle, BodySet};
 use solver::{helper, BilateralConstraint, BilateralGroundConstraint, ForceDirection, ImpulseLimits};
@@ -15,6 +15,9 @@ pub struct MouseConstraint<N: Real> {
     anchor1: Point<N>,
     anchor2: Point<N>,
     limit: N,
+    stiffness: Option<N>,
+    damping: N,
+    params: JointConstraintParameters<N>,
 }
 
 impl<N: Real> MouseConstraint<N> {
@@ -35,6 +38,9 @@ impl<N: Real> MouseConstraint<N> {
             anchor1,
             anchor2,
             limit,
+            stiffness: None,
+            damping: N::zero(),
+            params: JointConstraintParameters::default(),
         }
     }
 
@@ -47,6 +53,53 @@ impl<N: Real> MouseConstraint<N> {
     pub fn set_anchor_2(&mut self, anchor2: Point<N>) {
         self.anchor2 = anchor2;
     }
+
+    /// The point the anchor of the second body part is driven toward.
+    ///
+    /// This is the same as the first anchor, so it is a world-space point if the first body part
+    /// is the ground.
+    pub fn target(&self) -> &Point<N> {
+        &self.anchor1
+    }
+
+    /// Moves the point the anchor of the second body part is driven toward.
+    ///
+    /// This is typically called at each frame with the position of the mouse, the first body
+    /// part being the ground.
+    pub fn set_target(&mut self, target: Point<N>) {
+        self.anchor1 = target;
+    }
+
+    /// The stiffness of the spring pulling the second anchor toward the target, if any.
+    pub fn stiffness(&self) -> Option<N> {
+        self.stiffness
+    }
+
+    /// The damping of the spring pulling the second anchor toward the target.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Makes this constraint behave like a damped spring between the target and the second anchor.
+    ///
+    /// The stiffness and the damping are independent from the timestep, and override both the
+    /// position correction and the compliance of this constraint. Once the spring is at rest,
+    /// an anchor dragged at a constant velocity `v` lags behind the target by
+    /// `v * damping / stiffness`. Use `set_max_force` to bound the force of the spring.
+    pub fn set_stiffness_and_damping(&mut self, stiffness: N, damping: N) {
+        assert!(
+            stiffness > N::zero() && damping >= N::zero(),
+            "MouseConstraint: the stiffness must be positive and the damping positive or zero."
+        );
+        self.stiffness = Some(stiffness);
+        self.damping = damping;
+    }
+
+    /// Makes this constraint rigid again, with its position correction and compliance.
+    pub fn disable_spring(&mut self) {
+        self.stiffness = None;
+        self.damping = N::zero();
+    }
 }
 
 impl<N: Real> JointConstraint<N> for MouseConstraint<N> {
@@ -58,6 +111,23 @@ impl<N: Real> JointConstraint<N> for MouseConstraint<N> {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
+    fn compliance(&self) -> Option<N> {
+        // The spring sets its own compliance.
+        if self.stiffness.is_some() {
+            None
+        } else {
+            self.params.cfm
+        }
+    }
+
     fn velocity_constraints(
         &mut self,
         params: &IntegrationParameters<N>,
@@ -79,6 +149,17 @@ impl<N: Real> JointConstraint<N> for MouseConstraint<N> {
         let pos1 = body1.position();
         let pos2 = body2.position();
 
+        // A spring with stiffness `k` and damping `c` is equivalent to a constraint with an
+        // erp of `dt * k / (dt * k + c)` and a cfm of `1 / (dt * (dt * k + c))`.
+        let (erp, cfm) = match self.stiffness {
+            Some(stiffness) => {
+                let dt_k = params.dt * stiffness;
+                let cfm = N::one() / (params.dt * (dt_k + self.damping));
+                (dt_k / (dt_k + self.damping), Some(cfm))
+            }
+            None => (self.params.erp.unwrap_or(params.erp), None),
+        };
+
         let anchor1 = pos1 * self.anchor1;
         let anchor2 = pos2 * self.anchor2;
 
@@ -91,6 +172,8 @@ impl<N: Real> JointConstraint<N> for MouseConstraint<N> {
         };
 
         let error = anchor2 - anchor1;
+        let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
+        let first_bilateral = constraints.velocity.bilateral.len();
 
         let mut i = 0;
         Vector::canonical_basis(|dir| {
@@ -117,7 +200,7 @@ impl<N: Real> JointConstraint<N> for MouseConstraint<N> {
                 ext_vels,
                 jacobians,
                 &geom,
-            ) - error.dot(&*dir) * params.erp / params.dt;
+            ) - error.dot(&*dir) * erp / params.dt;
 
             if geom.ndofs1 == 0 || geom.ndofs2 == 0 {
                 constraints
@@ -151,6 +234,20 @@ impl<N: Real> JointConstraint<N> for MouseConstraint<N> {
 
             true
         });
+
+        if let Some(cfm) = cfm {
+            let velocity = &mut constraints.velocity;
+
+            for c in &mut velocity.bilateral_ground[first_bilateral_ground..] {
+                c.r = N::one() / (N::one() / c.r + cfm);
+                c.cfm = cfm;
+            }
+
+            for c in &mut velocity.bilateral[first_bilateral..] {
+                c.r = N::one() / (N::one() / c.r + cfm);
+                c.cfm = cfm;
+            }
+        }
     }
 
     fn cache_impulses(&mut self, _: &ConstraintSet<N>) {}
@@ -171,3 +268,59 @@ impl<N: Real> NonlinearConstraintGenerator<N> for MouseConstraint<N> {
         None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::MouseConstraint;
+    use math::{Isometry, Point, Vector};
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn mouse_constraint_drags_a_box_corner_with_the_expected_lag() {
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = world.add_rigid_body(
+            Isometry::identity(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+
+        let corner = Point::from(Vector::repeat(0.5));
+        let mut mouse = MouseConstraint::new(BodyHandle::ground(), body, corner, corner, 1.0e3);
+        mouse.set_stiffness_and_damping(100.0, 10.0);
+        let mouse = world.add_constraint(mouse);
+
+        // Drag the corner along the `x` axis at a constant speed.
+        let speed = 1.0;
+
+        for _ in 0..600 {
+            let target = corner + Vector::x() * speed * (world.time() + world.timestep());
+            world
+                .constraint_mut(mouse)
+                .downcast_mut::<MouseConstraint<f32>>()
+                .unwrap()
+                .set_target(target);
+            world.step();
+        }
+
+        let target = *world
+            .constraint(mouse)
+            .downcast_ref::<MouseConstraint<f32>>()
+            .unwrap()
+            .target();
+        let anchor = world.rigid_body(body).unwrap().position() * corner;
+        let lag = target - anchor;
+        let expected = Vector::x() * speed * 10.0 / 100.0;
+
+        assert!(
+            (lag - expected).norm() < 1.0e-2,
+            "Lag: {}, expected: {}",
+            lag,
+            expected
+        );
+    }
+}
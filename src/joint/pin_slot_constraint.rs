@@ -1,7 +1,7 @@
 use na::{DVector, Real, Unit};
 use std::ops::Range;
 
-use joint::JointConstraint;
+use joint::{JointConstraint, JointConstraintParameters};
 use math::{AngularVector, Point, Vector, DIM, SPATIAL_DIM};
 use object::{BodyHandle, BodySet};
 use solver::helper;
@@ -24,6 +24,7 @@ pub struct PinSlotConstraint<N: Real> {
     ang_impulses: AngularVector<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
     // min_offset: Option<N>,
     // max_offset: Option<N>,
 }
@@ -59,11 +60,37 @@ impl<N: Real> PinSlotConstraint<N> {
             ang_impulses: AngularVector::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
             // min_offset,
             // max_offset,
         }
     }
 
+    /// Change the first anchor, expressed in the local space of the first body part.
+    pub fn set_anchor_1(&mut self, anchor1: Point<N>) {
+        self.anchor1 = anchor1;
+    }
+
+    /// Change the second anchor, expressed in the local space of the second body part.
+    pub fn set_anchor_2(&mut self, anchor2: Point<N>) {
+        self.anchor2 = anchor2;
+    }
+
+    /// Change the axis the relative translations are restricted to, expressed in the local space of the first body part.
+    pub fn set_slide_axis_1(&mut self, axis_v1: Unit<Vector<N>>) {
+        self.axis_v1 = axis_v1;
+    }
+
+    /// Change the rotation axis, expressed in the local space of the first body part.
+    pub fn set_pivot_axis_1(&mut self, axis_w1: Unit<Vector<N>>) {
+        self.axis_w1 = axis_w1;
+    }
+
+    /// Change the rotation axis, expressed in the local space of the second body part.
+    pub fn set_pivot_axis_2(&mut self, axis_w2: Unit<Vector<N>>) {
+        self.axis_w2 = axis_w2;
+    }
+
     // pub fn min_offset(&self) -> Option<N> {
     //     self.min_offset
     // }
@@ -108,6 +135,14 @@ impl<N: Real> JointConstraint<N> for PinSlotConstraint<N> {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
     fn velocity_constraints(
         &mut self,
         _: &IntegrationParameters<N>,
@@ -168,7 +203,7 @@ impl<N: Real> JointConstraint<N> for PinSlotConstraint<N> {
             &anchor2,
             ext_vels,
             self.ang_impulses.as_slice(),
-            DIM - 1,
+            DIM,
             ground_j_id,
             j_id,
             jacobians,
@@ -203,6 +238,20 @@ impl<N: Real> JointConstraint<N> for PinSlotConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.lin_impulses
+            .iter()
+            .chain(self.ang_impulses.iter())
+            .cloned()
+            .collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let (lin, ang) = impulses.split_at(self.lin_impulses.len());
+        self.lin_impulses.copy_from_slice(lin);
+        self.ang_impulses.copy_from_slice(ang);
+    }
 }
 
 impl<N: Real> NonlinearConstraintGenerator<N> for PinSlotConstraint<N> {
@@ -261,6 +310,59 @@ impl<N: Real> NonlinearConstraintGenerator<N> for PinSlotConstraint<N> {
             );
         }
 
-        return None;
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::PinSlotConstraint;
+    use math::{Isometry, Point, Vector};
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn pin_slot_follower_slides_and_pivots() {
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let follower = world.add_rigid_body(
+            Isometry::identity(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_constraint(PinSlotConstraint::new(
+            BodyHandle::ground(),
+            follower,
+            Point::origin(),
+            Vector::x_axis(),
+            Vector::z_axis(),
+            Point::origin(),
+            Vector::z_axis(),
+        ));
+
+        // Emulate a turning cam pushing the follower off-center with a rotating direction.
+        for i in 0..60 {
+            let angle = i as f32 * 0.1;
+            let dir = Vector::new(angle.cos(), angle.sin(), 0.3) * 0.05;
+            let contact = world.rigid_body(follower).unwrap().position()
+                * (Point::origin() + Vector::new(-0.5, 0.5, 0.5));
+            world
+                .rigid_body_mut(follower)
+                .unwrap()
+                .apply_impulse_at_point(dir, &contact);
+            world.step();
+        }
+
+        let pos = world.rigid_body(follower).unwrap().position();
+        let z_axis = pos.rotation * Vector::z();
+
+        assert!(pos.translation.vector.x.abs() > 1.0e-2, "The follower should slide.");
+        assert!(pos.translation.vector.y.abs() < 1.0e-2);
+        assert!(pos.translation.vector.z.abs() < 1.0e-2);
+        assert!(pos.rotation.angle() > 1.0e-2, "The follower should pivot.");
+        assert!((z_axis - Vector::z()).norm() < 1.0e-2);
     }
 }
@@ -7,7 +7,9 @@ use solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters};
 
 /// A joint that allows one translational and one rotational degrees of freedom.
 ///
-/// Both are not required to be along the same direction.
+/// Both are not required to be along the same direction. The first generalized coordinate of
+/// this joint is the translation along `axis_v` and the second is the rotation around `axis_w`,
+/// in that order for the velocities returned by `MultibodyLink::joint_velocity`.
 #[derive(Copy, Clone, Debug)]
 pub struct PinSlotJoint<N: Real> {
     prism: PrismaticJoint<N>,
@@ -105,6 +107,21 @@ impl<N: Real> Joint<N> for PinSlotJoint<N> {
         self.revo.apply_displacement(&[disp[1]]);
     }
 
+    fn generalized_position(&self, out: &mut [N]) {
+        self.prism.generalized_position(out);
+        self.revo.generalized_position(&mut out[1..]);
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        self.prism.set_generalized_position(coords);
+        self.revo.set_generalized_position(&coords[1..]);
+    }
+
+    fn clamp_to_limits(&mut self) {
+        self.prism.clamp_to_limits();
+        self.revo.clamp_to_limits();
+    }
+
     fn num_velocity_constraints(&self) -> usize {
         self.prism.num_velocity_constraints() + self.revo.num_velocity_constraints()
     }
@@ -165,3 +182,50 @@ impl<N: Real> Joint<N> for PinSlotJoint<N> {
 
 prismatic_motor_limit_methods!(PinSlotJoint, prism);
 revolute_motor_limit_methods!(PinSlotJoint, revo);
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Ball, ShapeHandle};
+
+    use super::PinSlotJoint;
+    use math::Vector;
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn pin_slot_joint_drives_while_resting_on_its_slide_limit() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let mut joint = PinSlotJoint::new(Vector::y_axis(), Vector::x_axis(), 0.0, 0.0);
+        joint.enable_min_offset(-0.2);
+        joint.enable_max_offset(0.0);
+        joint.enable_angular_motor();
+        joint.set_desired_angular_motor_velocity(2.0);
+        joint.set_max_angular_motor_torque(100.0);
+
+        let wheel = ShapeHandle::new(Ball::new(0.3));
+        let link = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            na::zero(),
+            wheel.inertia(1.0),
+            wheel.center_of_mass(),
+        );
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let link = world.multibody_link(link).unwrap();
+        let joint = link.joint().downcast_ref::<PinSlotJoint<f32>>().unwrap();
+        let vels = link.joint_velocity();
+        assert!((joint.offset() + 0.2).abs() < 1.0e-2, "Offset: {}", joint.offset());
+        assert!(vels[0].abs() < 1.0e-2, "Linear velocity: {}", vels[0]);
+        assert!((vels[1] - 2.0).abs() < 1.0e-2, "Angular velocity: {}", vels[1]);
+        assert!(joint.angle() > 1.0, "Angle: {}", joint.angle());
+    }
+}
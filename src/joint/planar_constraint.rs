@@ -1,16 +1,21 @@
+use alga::linear::FiniteDimInnerSpace;
 use na::{DVector, Real, Unit};
 use std::ops::Range;
 
-use joint::JointConstraint;
-use math::{AngularVector, Point};
+use joint::{JointConstraint, JointConstraintParameters, JointMotor};
+use math::{AngularVector, Point, Vector};
 use object::{BodyHandle, BodySet};
 use solver::helper;
-use solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
+use solver::{ConstraintSet, ForceDirection, GenericNonlinearConstraint, IntegrationParameters,
              NonlinearConstraintGenerator};
 
 /// A constraint that removes one relative translational degree of freedom, and all but one rotational degrees of freedom.
 ///
 /// This ensures a body moves only on a plane wrt. its parent.
+///
+/// The two translations along the plane and the rotation around its normal can each be driven
+/// by a motor. The translations are measured along two orthogonal axes of the plane, see
+/// `self.plane_axes()`.
 pub struct PlanarConstraint<N: Real> {
     b1: BodyHandle,
     b2: BodyHandle,
@@ -18,10 +23,15 @@ pub struct PlanarConstraint<N: Real> {
     anchor2: Point<N>,
     axis1: Unit<AngularVector<N>>,
     axis2: Unit<AngularVector<N>>,
+    plane_axis: Unit<Vector<N>>,
+    lin_motors: [JointMotor<N, N>; 2],
+    ang_motor: JointMotor<N, N>,
     lin_impulse: N,
     ang_impulses: [N; 2],
+    motor_impulses: [N; 3],
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
 }
 
 impl<N: Real> PlanarConstraint<N> {
@@ -36,6 +46,12 @@ impl<N: Real> PlanarConstraint<N> {
         anchor2: Point<N>,
         axis2: Unit<AngularVector<N>>,
     ) -> Self {
+        let mut plane_axis = None;
+        Vector::orthonormal_subspace_basis(&[*axis1], |basis| {
+            plane_axis = Some(Unit::new_unchecked(*basis));
+            false
+        });
+
         PlanarConstraint {
             b1,
             b2,
@@ -43,23 +59,168 @@ impl<N: Real> PlanarConstraint<N> {
             anchor2,
             axis1,
             axis2,
+            plane_axis: plane_axis.expect("Internal error: the plane has no tangent."),
+            lin_motors: [JointMotor::new(), JointMotor::new()],
+            ang_motor: JointMotor::new(),
             lin_impulse: N::zero(),
             ang_impulses: [N::zero(), N::zero()],
+            motor_impulses: [N::zero(); 3],
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
+        }
+    }
+
+    /// The two orthogonal axes of the plane along which the linear motors are applied.
+    ///
+    /// They are expressed in the local coordinate frame of the first body part.
+    pub fn plane_axes(&self) -> (Unit<Vector<N>>, Unit<Vector<N>>) {
+        let second = Unit::new_normalize(self.axis1.cross(&*self.plane_axis));
+        (self.plane_axis, second)
+    }
+
+    /// Sets the first axis of the plane along which the linear motors are applied.
+    ///
+    /// The axis is expressed in the local coordinate frame of the first body part, and is
+    /// projected on the plane. The second axis is orthogonal to it. Panics if `axis` is
+    /// orthogonal to the plane.
+    pub fn set_plane_axis(&mut self, axis: Unit<Vector<N>>) {
+        let projected = *axis - *self.axis1 * self.axis1.dot(&axis);
+        self.plane_axis = Unit::try_new(projected, N::default_epsilon())
+            .expect("The plane axis must not be orthogonal to the plane.");
+    }
+
+    /// Returns `true` if the motor along the first plane axis is enabled.
+    pub fn is_linear_motor_enabled_1(&self) -> bool {
+        self.lin_motors[0].enabled
+    }
+
+    /// Enable the motor along the first plane axis.
+    pub fn enable_linear_motor_1(&mut self) {
+        self.lin_motors[0].enabled = true
+    }
+
+    /// Disable the motor along the first plane axis.
+    pub fn disable_linear_motor_1(&mut self) {
+        self.lin_motors[0].enabled = false
+    }
+
+    /// The desired relative velocity along the first plane axis to be enforced by its motor.
+    pub fn desired_linear_motor_velocity_1(&self) -> N {
+        self.lin_motors[0].desired_velocity
+    }
+
+    /// Set the desired relative velocity along the first plane axis to be enforced by its motor.
+    pub fn set_desired_linear_motor_velocity_1(&mut self, vel: N) {
+        self.lin_motors[0].desired_velocity = vel
+    }
+
+    /// The maximum force that can be output by the motor along the first plane axis.
+    pub fn max_linear_motor_force_1(&self) -> N {
+        self.lin_motors[0].max_force
+    }
+
+    /// Set the maximum force that can be output by the motor along the first plane axis.
+    pub fn set_max_linear_motor_force_1(&mut self, force: N) {
+        self.lin_motors[0].max_force = force
+    }
+
+    /// Returns `true` if the motor along the second plane axis is enabled.
+    pub fn is_linear_motor_enabled_2(&self) -> bool {
+        self.lin_motors[1].enabled
+    }
+
+    /// Enable the motor along the second plane axis.
+    pub fn enable_linear_motor_2(&mut self) {
+        self.lin_motors[1].enabled = true
+    }
+
+    /// Disable the motor along the second plane axis.
+    pub fn disable_linear_motor_2(&mut self) {
+        self.lin_motors[1].enabled = false
+    }
+
+    /// The desired relative velocity along the second plane axis to be enforced by its motor.
+    pub fn desired_linear_motor_velocity_2(&self) -> N {
+        self.lin_motors[1].desired_velocity
+    }
+
+    /// Set the desired relative velocity along the second plane axis to be enforced by its motor.
+    pub fn set_desired_linear_motor_velocity_2(&mut self, vel: N) {
+        self.lin_motors[1].desired_velocity = vel
+    }
+
+    /// The maximum force that can be output by the motor along the second plane axis.
+    pub fn max_linear_motor_force_2(&self) -> N {
+        self.lin_motors[1].max_force
+    }
+
+    /// Set the maximum force that can be output by the motor along the second plane axis.
+    pub fn set_max_linear_motor_force_2(&mut self, force: N) {
+        self.lin_motors[1].max_force = force
+    }
+
+    /// Returns `true` if the motor around the plane normal is enabled.
+    pub fn is_angular_motor_enabled(&self) -> bool {
+        self.ang_motor.enabled
+    }
+
+    /// Enable the motor around the plane normal.
+    pub fn enable_angular_motor(&mut self) {
+        self.ang_motor.enabled = true
+    }
+
+    /// Disable the motor around the plane normal.
+    pub fn disable_angular_motor(&mut self) {
+        self.ang_motor.enabled = false
+    }
+
+    /// The desired relative angular velocity around the plane normal to be enforced by the motor.
+    pub fn desired_angular_motor_velocity(&self) -> N {
+        self.ang_motor.desired_velocity
+    }
+
+    /// Set the desired relative angular velocity around the plane normal to be enforced by the motor.
+    pub fn set_desired_angular_motor_velocity(&mut self, vel: N) {
+        self.ang_motor.desired_velocity = vel
+    }
+
+    /// The maximum torque that can be output by the motor around the plane normal.
+    pub fn max_angular_motor_torque(&self) -> N {
+        self.ang_motor.max_force
+    }
+
+    /// Set the maximum torque that can be output by the motor around the plane normal.
+    pub fn set_max_angular_motor_torque(&mut self, torque: N) {
+        self.ang_motor.max_force = torque
+    }
+
+    fn cache_impulse(&mut self, impulse_id: usize, impulse: N) {
+        match impulse_id {
+            0 => self.lin_impulse = impulse,
+            1 | 2 => self.ang_impulses[impulse_id - 1] = impulse,
+            _ => self.motor_impulses[impulse_id - 3] = impulse,
         }
     }
 }
 
 impl<N: Real> JointConstraint<N> for PlanarConstraint<N> {
     fn num_velocity_constraints(&self) -> usize {
-        3
+        6
     }
 
     fn anchors(&self) -> (BodyHandle, BodyHandle) {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
     fn velocity_constraints(
         &mut self,
         _: &IntegrationParameters<N>,
@@ -128,9 +289,57 @@ impl<N: Real> JointConstraint<N> for PlanarConstraint<N> {
 
         /*
          *
-         * Limit constraints.
+         * Motor constraints.
          *
          */
+        let (plane_axis1, plane_axis2) = self.plane_axes();
+        let plane_axes = [pos1 * plane_axis1, pos1 * plane_axis2];
+
+        for i in 0..2 {
+            let motor = &self.lin_motors[i];
+
+            if motor.enabled {
+                helper::drive_relative_velocity_wrt_direction(
+                    &b1,
+                    &b2,
+                    assembly_id1,
+                    assembly_id2,
+                    &anchor1,
+                    &anchor2,
+                    &ForceDirection::Linear(plane_axes[i]),
+                    motor.desired_velocity,
+                    motor.impulse_limits(),
+                    ext_vels,
+                    self.motor_impulses[i],
+                    3 + i,
+                    ground_j_id,
+                    j_id,
+                    jacobians,
+                    constraints,
+                );
+            }
+        }
+
+        if self.ang_motor.enabled {
+            helper::drive_relative_velocity_wrt_direction(
+                &b1,
+                &b2,
+                assembly_id1,
+                assembly_id2,
+                &anchor1,
+                &anchor2,
+                &ForceDirection::Angular(axis1),
+                self.ang_motor.desired_velocity,
+                self.ang_motor.impulse_limits(),
+                ext_vels,
+                self.motor_impulses[2],
+                5,
+                ground_j_id,
+                j_id,
+                jacobians,
+                constraints,
+            );
+        }
 
         self.bilateral_ground_rng =
             first_bilateral_ground..constraints.velocity.bilateral_ground.len();
@@ -139,21 +348,27 @@ impl<N: Real> JointConstraint<N> for PlanarConstraint<N> {
 
     fn cache_impulses(&mut self, constraints: &ConstraintSet<N>) {
         for c in &constraints.velocity.bilateral_ground[self.bilateral_ground_rng.clone()] {
-            if c.impulse_id == 0 {
-                self.lin_impulse = c.impulse
-            } else {
-                self.ang_impulses[c.impulse_id - 1] = c.impulse;
-            }
+            self.cache_impulse(c.impulse_id, c.impulse);
         }
 
         for c in &constraints.velocity.bilateral[self.bilateral_rng.clone()] {
-            if c.impulse_id == 0 {
-                self.lin_impulse = c.impulse
-            } else {
-                self.ang_impulses[c.impulse_id - 1] = c.impulse;
-            }
+            self.cache_impulse(c.impulse_id, c.impulse);
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        let mut impulses = vec![self.lin_impulse];
+        impulses.extend_from_slice(&self.ang_impulses);
+        impulses.extend_from_slice(&self.motor_impulses);
+        impulses
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        assert_eq!(impulses.len(), 6, "Invalid number of cached impulses.");
+        self.lin_impulse = impulses[0];
+        self.ang_impulses.copy_from_slice(&impulses[1..3]);
+        self.motor_impulses.copy_from_slice(&impulses[3..]);
+    }
 }
 
 impl<N: Real> NonlinearConstraintGenerator<N> for PlanarConstraint<N> {
@@ -211,6 +426,70 @@ impl<N: Real> NonlinearConstraintGenerator<N> for PlanarConstraint<N> {
             );
         }
 
-        return None;
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::PlanarConstraint;
+    use math::{Isometry, Point, Vector};
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn planar_constraint_motors_track_velocities_under_load() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::new(0.5, 0.1, 0.5)));
+
+        let carriage = world.add_rigid_body(
+            Isometry::identity(),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+
+        let mut constraint = PlanarConstraint::new(
+            BodyHandle::ground(),
+            carriage,
+            Point::origin(),
+            Vector::y_axis(),
+            Point::origin(),
+            Vector::y_axis(),
+        );
+        constraint.set_plane_axis(Vector::x_axis());
+        let (axis_u, axis_v) = constraint.plane_axes();
+        assert!((*axis_u - Vector::x()).norm() < 1.0e-6);
+        assert!((*axis_v + Vector::z()).norm() < 1.0e-6);
+
+        constraint.enable_linear_motor_1();
+        constraint.set_desired_linear_motor_velocity_1(1.0);
+        constraint.set_max_linear_motor_force_1(100.0);
+        constraint.enable_linear_motor_2();
+        constraint.set_desired_linear_motor_velocity_2(-0.5);
+        constraint.set_max_linear_motor_force_2(100.0);
+        constraint.enable_angular_motor();
+        constraint.set_desired_angular_motor_velocity(2.0);
+        constraint.set_max_angular_motor_torque(100.0);
+        let _ = world.add_constraint(constraint);
+
+        for _ in 0..120 {
+            {
+                // Drag resisting the motors.
+                let rb = world.rigid_body_mut(carriage).unwrap();
+                rb.apply_linear_force(Vector::new(-2.0, 0.0, -2.0));
+                rb.apply_torque(Vector::y() * -1.0);
+            }
+            world.step();
+        }
+
+        let rb = world.rigid_body(carriage).unwrap();
+        let vel = rb.velocity();
+        assert!((vel.linear - Vector::new(1.0, 0.0, 0.5)).norm() < 1.0e-2);
+        assert!((vel.angular - Vector::y() * 2.0).norm() < 1.0e-2);
+        assert!(rb.position().translation.vector.y.abs() < 1.0e-2);
     }
 }
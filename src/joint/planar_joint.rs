@@ -40,6 +40,21 @@ impl<N: Real> PlanarJoint<N> {
             revo,
         }
     }
+
+    /// The linear displacement along the first axis.
+    pub fn offset_1(&self) -> N {
+        self.prism1.offset()
+    }
+
+    /// The linear displacement along the second axis.
+    pub fn offset_2(&self) -> N {
+        self.prism2.offset()
+    }
+
+    /// The angular displacement around the plane normal.
+    pub fn angle(&self) -> N {
+        self.revo.angle()
+    }
 }
 
 impl<N: Real> Joint<N> for PlanarJoint<N> {
@@ -128,6 +143,24 @@ impl<N: Real> Joint<N> for PlanarJoint<N> {
         self.revo.apply_displacement(&[disp[2]]);
     }
 
+    fn generalized_position(&self, out: &mut [N]) {
+        self.prism1.generalized_position(out);
+        self.prism2.generalized_position(&mut out[1..]);
+        self.revo.generalized_position(&mut out[2..]);
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        self.prism1.set_generalized_position(coords);
+        self.prism2.set_generalized_position(&coords[1..]);
+        self.revo.set_generalized_position(&coords[2..]);
+    }
+
+    fn clamp_to_limits(&mut self) {
+        self.prism1.clamp_to_limits();
+        self.prism2.clamp_to_limits();
+        self.revo.clamp_to_limits();
+    }
+
     fn num_velocity_constraints(&self) -> usize {
         self.prism1.num_velocity_constraints() + self.prism2.num_velocity_constraints()
             + self.revo.num_velocity_constraints()
@@ -203,3 +236,59 @@ impl<N: Real> Joint<N> for PlanarJoint<N> {
 prismatic_motor_limit_methods_1!(PlanarJoint, prism1);
 prismatic_motor_limit_methods_2!(PlanarJoint, prism2);
 revolute_motor_limit_methods!(PlanarJoint, revo);
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::PlanarJoint;
+    use math::Vector;
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn planar_joint_stays_within_its_workspace_limits() {
+        use std::f32::consts::FRAC_PI_4;
+
+        let mut world = World::<f32>::new();
+        let mut joint = PlanarJoint::new(Vector::x_axis(), Vector::y_axis(), 0.0, 0.0, 0.0);
+        joint.enable_min_offset_1(-0.5);
+        joint.enable_max_offset_1(0.5);
+        joint.enable_min_offset_2(-0.5);
+        joint.enable_max_offset_2(0.5);
+        joint.enable_min_angle(-FRAC_PI_4);
+        joint.enable_max_angle(FRAC_PI_4);
+
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.1)));
+        let link = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            na::zero(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+
+        // Throw the piece toward a corner of the board while spinning it. The default damping
+        // would stop the spinning of such a light piece almost immediately.
+        {
+            let mb = world.multibody_mut(link).unwrap();
+            mb.damping_mut().fill(0.0);
+            mb.set_generalized_velocity(&[2.0, 3.0, 4.0]);
+        }
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let link = world.multibody_link(link).unwrap();
+        let joint = link.joint().downcast_ref::<PlanarJoint<f32>>().unwrap();
+        let speed = link.multibody().generalized_velocity().norm();
+        assert!((joint.offset_1() - 0.5).abs() < 1.0e-2, "Offset 1: {}", joint.offset_1());
+        assert!((joint.offset_2() - 0.5).abs() < 1.0e-2, "Offset 2: {}", joint.offset_2());
+        assert!((joint.angle() - FRAC_PI_4).abs() < 1.0e-2, "Angle: {}", joint.angle());
+        assert!(speed < 1.0e-2, "Joint speed: {}", speed);
+    }
+}
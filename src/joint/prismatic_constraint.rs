@@ -1,7 +1,7 @@
 use na::{DVector, Real, Unit};
 use std::ops::Range;
 
-use joint::{unit_constraint, JointConstraint};
+use joint::{unit_constraint, JointConstraint, JointConstraintParameters, LimitState};
 use math::{AngularVector, Point, Vector, DIM, SPATIAL_DIM};
 use object::{BodyHandle, BodySet};
 use solver::helper;
@@ -18,8 +18,11 @@ pub struct PrismaticConstraint<N: Real> {
     lin_impulses: Vector<N>,
     ang_impulses: AngularVector<N>,
     limit_impulse: N,
+    limit_side: LimitState,
+    limit_state: LimitState,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
 
     min_offset: Option<N>,
     max_offset: Option<N>,
@@ -48,13 +51,110 @@ impl<N: Real> PrismaticConstraint<N> {
             lin_impulses: Vector::zeros(),
             ang_impulses: AngularVector::zeros(),
             limit_impulse: N::zero(),
+            limit_side: LimitState::Inactive,
+            limit_state: LimitState::Inactive,
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
             min_offset,
             max_offset,
         }
     }
 
+    /// Create a new prismatic constraint between the body part `b` and the ground.
+    ///
+    /// The body part's motion is restricted to a translation along `world_axis`. Both
+    /// `world_anchor` and `world_axis` are expressed in world-space, while `anchor`
+    /// is expressed in the local coordinates frame of `b`. Only the jacobians of `b` are
+    /// computed at each timestep.
+    pub fn new_with_ground(
+        b: BodyHandle,
+        world_anchor: Point<N>,
+        world_axis: Unit<Vector<N>>,
+        anchor: Point<N>,
+    ) -> Self {
+        Self::new(BodyHandle::ground(), b, world_anchor, world_axis, anchor)
+    }
+
+    // Velocity constraints when the first body part is the ground.
+    fn ground_velocity_constraints(
+        &mut self,
+        bodies: &BodySet<N>,
+        ext_vels: &DVector<N>,
+        ground_j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        let body = bodies.body_part(self.b2);
+        let assembly_id = body.parent_companion_id();
+        let anchor = body.position() * self.anchor2;
+        let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
+
+        // The first body part is the ground so `anchor1` and `axis1` are already expressed
+        // in world-space.
+        helper::restrict_linear_velocity_to_axis_wrt_ground(
+            &body,
+            assembly_id,
+            &anchor,
+            &self.axis1,
+            ext_vels,
+            self.lin_impulses.as_slice(),
+            0,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        helper::cancel_angular_velocity_wrt_ground(
+            &body,
+            assembly_id,
+            &anchor,
+            ext_vels,
+            &self.ang_impulses,
+            DIM - 1,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        let offset = self.axis1.dot(&(anchor - self.anchor1));
+        self.limit_side = match (self.min_offset, self.max_offset) {
+            (Some(min), _) if offset <= min => LimitState::AtLower,
+            (_, Some(max)) if offset >= max => LimitState::AtUpper,
+            _ => LimitState::Inactive,
+        };
+
+        // The limits are the only constraints that need the ground body part.
+        if self.min_offset.is_some() || self.max_offset.is_some() {
+            let ground = bodies.body_part(self.b1);
+            // Unused since constraints with the ground are written starting at `ground_j_id`.
+            let mut j_id = 0;
+
+            unit_constraint::build_linear_limits_velocity_constraint(
+                &ground,
+                &body,
+                ground.parent_companion_id(),
+                assembly_id,
+                &self.anchor1,
+                &anchor,
+                &self.axis1,
+                self.min_offset,
+                self.max_offset,
+                ext_vels,
+                self.limit_impulse,
+                SPATIAL_DIM - 1,
+                ground_j_id,
+                &mut j_id,
+                jacobians,
+                constraints,
+            );
+        }
+
+        self.bilateral_ground_rng =
+            first_bilateral_ground..constraints.velocity.bilateral_ground.len();
+        self.bilateral_rng = 0..0;
+    }
+
     /// The lower limit, if any, of the relative translation (along the joint axis) of the body parts attached to this joint.
     pub fn min_offset(&self) -> Option<N> {
         self.min_offset
@@ -105,6 +205,14 @@ impl<N: Real> JointConstraint<N> for PrismaticConstraint<N> {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
     fn velocity_constraints(
         &mut self,
         _: &IntegrationParameters<N>,
@@ -115,6 +223,11 @@ impl<N: Real> JointConstraint<N> for PrismaticConstraint<N> {
         jacobians: &mut [N],
         constraints: &mut ConstraintSet<N>,
     ) {
+        if self.b1.is_ground() {
+            self.ground_velocity_constraints(bodies, ext_vels, ground_j_id, jacobians, constraints);
+            return;
+        }
+
         let b1 = bodies.body_part(self.b1);
         let b2 = bodies.body_part(self.b2);
 
@@ -175,6 +288,13 @@ impl<N: Real> JointConstraint<N> for PrismaticConstraint<N> {
          * Limit constraints.
          *
          */
+        let offset = axis.dot(&(anchor2 - anchor1));
+        self.limit_side = match (self.min_offset, self.max_offset) {
+            (Some(min), _) if offset <= min => LimitState::AtLower,
+            (_, Some(max)) if offset >= max => LimitState::AtUpper,
+            _ => LimitState::Inactive,
+        };
+
         unit_constraint::build_linear_limits_velocity_constraint(
             &b1,
             &b2,
@@ -200,13 +320,19 @@ impl<N: Real> JointConstraint<N> for PrismaticConstraint<N> {
     }
 
     fn cache_impulses(&mut self, constraints: &ConstraintSet<N>) {
+        self.limit_state = LimitState::Inactive;
+
         for c in &constraints.velocity.bilateral_ground[self.bilateral_ground_rng.clone()] {
             if c.impulse_id < DIM - 1 {
                 self.lin_impulses[c.impulse_id] = c.impulse;
             } else if c.impulse_id < SPATIAL_DIM - 1 {
-                self.ang_impulses[c.impulse_id - DIM + 1] = c.impulse;
+                self.ang_impulses[c.impulse_id + 1 - DIM] = c.impulse;
             } else {
-                self.limit_impulse = c.impulse
+                self.limit_impulse = c.impulse;
+
+                if !c.impulse.is_zero() {
+                    self.limit_state = self.limit_side;
+                }
             }
         }
 
@@ -214,12 +340,38 @@ impl<N: Real> JointConstraint<N> for PrismaticConstraint<N> {
             if c.impulse_id < DIM - 1 {
                 self.lin_impulses[c.impulse_id] = c.impulse;
             } else if c.impulse_id < SPATIAL_DIM - 1 {
-                self.ang_impulses[c.impulse_id - DIM + 1] = c.impulse;
+                self.ang_impulses[c.impulse_id + 1 - DIM] = c.impulse;
             } else {
-                self.limit_impulse = c.impulse
+                self.limit_impulse = c.impulse;
+
+                if !c.impulse.is_zero() {
+                    self.limit_state = self.limit_side;
+                }
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.lin_impulses
+            .iter()
+            .chain(self.ang_impulses.iter())
+            .cloned()
+            .chain(Some(self.limit_impulse))
+            .collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let (lin, rest) = impulses.split_at(self.lin_impulses.len());
+        let (ang, limit) = rest.split_at(self.ang_impulses.len());
+        assert_eq!(limit.len(), 1, "Invalid number of cached impulses.");
+        self.lin_impulses.copy_from_slice(lin);
+        self.ang_impulses.copy_from_slice(ang);
+        self.limit_impulse = limit[0];
+    }
+
+    fn limit_state(&self) -> LimitState {
+        self.limit_state
+    }
 }
 
 impl<N: Real> NonlinearConstraintGenerator<N> for PrismaticConstraint<N> {
@@ -291,6 +443,53 @@ impl<N: Real> NonlinearConstraintGenerator<N> for PrismaticConstraint<N> {
             );
         }
 
-        return None;
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::Cuboid;
+
+    use joint::PrismaticConstraint;
+    use math::{Isometry, Point, Vector, Velocity};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn prismatic_constraint_with_ground_slides_down_to_its_limit() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let cube = Cuboid::new(Vector::repeat(0.5));
+        let body = world.add_rigid_body(
+            Isometry::identity(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        // The sideways velocity is cancelled since the body may only slide along the `y` axis.
+        world
+            .rigid_body_mut(body)
+            .unwrap()
+            .set_velocity(Velocity::new_with_vectors(Vector::x(), na::zero()));
+
+        let mut constraint = PrismaticConstraint::new_with_ground(
+            body,
+            Point::origin(),
+            Vector::y_axis(),
+            Point::origin(),
+        );
+        constraint.enable_min_offset(-1.0);
+        let _ = world.add_constraint(constraint);
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let pos = world.rigid_body(body).unwrap().position();
+        assert_relative_eq!(pos.translation.vector.y, -1.0, epsilon = 1.0e-2);
+        assert_relative_eq!(pos.translation.vector.x, 0.0, epsilon = 1.0e-3);
+        assert!(pos.rotation.angle() < 1.0e-3);
     }
 }
@@ -18,6 +18,11 @@ pub struct PrismaticJoint<N: Real> {
     min_offset: Option<N>,
     max_offset: Option<N>,
     motor: JointMotor<N, N>,
+    friction: N,
+    damping: N,
+    limit_stiffness: N,
+    limit_damping: N,
+    limit_restitution: N,
 }
 
 impl<N: Real> PrismaticJoint<N> {
@@ -27,12 +32,17 @@ impl<N: Real> PrismaticJoint<N> {
     #[cfg(feature = "dim2")]
     pub fn new(axis: Unit<Vector<N>>, offset: N) -> Self {
         PrismaticJoint {
-            axis: axis,
+            axis,
             jacobian: Velocity::zero(),
-            offset: offset,
+            offset,
             min_offset: None,
             max_offset: None,
             motor: JointMotor::new(),
+            friction: N::zero(),
+            damping: N::zero(),
+            limit_stiffness: N::zero(),
+            limit_damping: N::zero(),
+            limit_restitution: N::zero(),
         }
     }
 
@@ -42,12 +52,17 @@ impl<N: Real> PrismaticJoint<N> {
     #[cfg(feature = "dim3")]
     pub fn new(axis: Unit<Vector<N>>, offset: N) -> Self {
         PrismaticJoint {
-            axis: axis,
+            axis,
             jacobian: Velocity::zero(),
-            offset: offset,
+            offset,
             min_offset: None,
             max_offset: None,
             motor: JointMotor::new(),
+            friction: N::zero(),
+            damping: N::zero(),
+            limit_stiffness: N::zero(),
+            limit_damping: N::zero(),
+            limit_restitution: N::zero(),
         }
     }
 
@@ -58,7 +73,7 @@ impl<N: Real> PrismaticJoint<N> {
 
     /// The relative translation of the attached multibody links along the joint axis.
     pub fn translation(&self) -> Translation<N> {
-        Translation::from_vector(*self.axis * self.offset)
+        Translation::from(*self.axis * self.offset)
     }
 
     /// The lower limit of the relative displacement of the attached multibody links along the joint axis.
@@ -128,6 +143,105 @@ impl<N: Real> PrismaticJoint<N> {
         self.motor.max_force = force;
     }
 
+    /// The offset the joint motor attempts to reach, if it is used as a position servo.
+    pub fn desired_linear_motor_position(&self) -> Option<N> {
+        self.motor.desired_position
+    }
+
+    /// Use the joint motor as a position servo targeting the given offset.
+    ///
+    /// The motor then applies the force `kp * (offset - self.offset()) - kd * velocity`,
+    /// where the gains are set by `self.set_linear_motor_stiffness` and
+    /// `self.set_linear_motor_damping`, clamped by the maximum motor force. The target can
+    /// be changed at each timestep and is clamped to the joint limits. The motor must still
+    /// be enabled with `self.enable_linear_motor()`.
+    pub fn set_desired_linear_motor_position(&mut self, offset: N) {
+        self.motor.desired_position = Some(offset);
+    }
+
+    /// Stop using the joint motor as a position servo and revert to the desired velocity.
+    pub fn disable_linear_motor_position(&mut self) {
+        self.motor.desired_position = None;
+    }
+
+    /// The proportional gain of the joint motor position servo.
+    pub fn linear_motor_stiffness(&self) -> N {
+        self.motor.stiffness
+    }
+
+    /// Set the proportional gain of the joint motor position servo.
+    pub fn set_linear_motor_stiffness(&mut self, kp: N) {
+        self.motor.stiffness = kp;
+    }
+
+    /// The derivative gain of the joint motor position servo.
+    pub fn linear_motor_damping(&self) -> N {
+        self.motor.damping
+    }
+
+    /// Set the derivative gain of the joint motor position servo.
+    pub fn set_linear_motor_damping(&mut self, kd: N) {
+        self.motor.damping = kd;
+    }
+
+    /// The maximum force resisting the translation of this joint.
+    pub fn friction_force(&self) -> N {
+        self.friction
+    }
+
+    /// Set the maximum force resisting the translation of this joint.
+    ///
+    /// This models dry friction: the joint does not slide unless the force applied to it
+    /// exceeds this threshold. Set to zero to disable friction.
+    pub fn set_friction_force(&mut self, force: N) {
+        self.friction = force;
+    }
+
+    /// The viscous damping coefficient of this joint.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Set the viscous damping coefficient of this joint.
+    ///
+    /// The joint is subject to a force equal to `-damping` times its linear velocity. This force
+    /// is integrated implicitly so large coefficients remain stable. Set to zero to disable it.
+    pub fn set_damping(&mut self, damping: N) {
+        assert!(
+            damping >= N::zero(),
+            "The joint damping must be positive or zero."
+        );
+        self.damping = damping;
+    }
+
+    /// The stiffness and damping of the joint limits.
+    pub fn limit_softness(&self) -> (N, N) {
+        (self.limit_stiffness, self.limit_damping)
+    }
+
+    /// Soften the joint limits.
+    ///
+    /// Once the offset goes past a limit, the joint is pushed back by a spring-damper with the given
+    /// stiffness and damping instead of being stopped instantly. Setting both to zero makes the
+    /// limits hard again.
+    pub fn set_limit_softness(&mut self, stiffness: N, damping: N) {
+        self.limit_stiffness = stiffness;
+        self.limit_damping = damping;
+    }
+
+    /// The restitution coefficient of the joint limits.
+    pub fn limit_restitution(&self) -> N {
+        self.limit_restitution
+    }
+
+    /// Set the restitution coefficient applied when the joint hits one of its limits.
+    ///
+    /// Like for contacts, this is ignored if the impact velocity is smaller than the
+    /// `restitution_velocity_threshold` of the integration parameters.
+    pub fn set_limit_restitution(&mut self, restitution: N) {
+        self.limit_restitution = restitution;
+    }
+
     fn assert_limits(&self) {
         if let (Some(min_offset), Some(max_offset)) = (self.min_offset, self.max_offset) {
             assert!(
@@ -146,14 +260,14 @@ impl<N: Real> Joint<N> for PrismaticJoint<N> {
     #[cfg(feature = "dim3")]
     fn body_to_parent(&self, parent_shift: &Vector<N>, body_shift: &Vector<N>) -> Isometry<N> {
         let trans =
-            Translation::from_vector(parent_shift - body_shift + self.axis.as_ref() * self.offset);
+            Translation::from(parent_shift - body_shift + self.axis.as_ref() * self.offset);
         Isometry::from_parts(trans, Rotation::identity())
     }
 
     #[cfg(feature = "dim2")]
     fn body_to_parent(&self, parent_shift: &Vector<N>, body_shift: &Vector<N>) -> Isometry<N> {
         let trans =
-            Translation::from_vector(parent_shift - body_shift + self.axis.as_ref() * self.offset);
+            Translation::from(parent_shift - body_shift + self.axis.as_ref() * self.offset);
         Isometry::from_parts(trans, Rotation::identity())
     }
 
@@ -177,6 +291,10 @@ impl<N: Real> Joint<N> for PrismaticJoint<N> {
 
     fn default_damping(&self, _: &mut DVectorSliceMut<N>) {}
 
+    fn viscous_damping(&self, out: &mut DVectorSliceMut<N>) {
+        out.fill(self.damping)
+    }
+
     fn integrate(&mut self, params: &IntegrationParameters<N>, vels: &[N]) {
         self.offset += vels[0] * params.dt
     }
@@ -185,6 +303,24 @@ impl<N: Real> Joint<N> for PrismaticJoint<N> {
         self.offset += disp[0]
     }
 
+    fn generalized_position(&self, out: &mut [N]) {
+        out[0] = self.offset;
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        self.offset = coords[0];
+    }
+
+    fn clamp_to_limits(&mut self) {
+        if let Some(min_offset) = self.min_offset {
+            self.offset = self.offset.max(min_offset);
+        }
+
+        if let Some(max_offset) = self.max_offset {
+            self.offset = self.offset.min(max_offset);
+        }
+    }
+
     fn jacobian_mul_coordinates(&self, acc: &[N]) -> Velocity<N> {
         Velocity::new(self.axis.as_ref() * acc[0], na::zero())
     }
@@ -249,6 +385,18 @@ impl<N: Real> UnitJoint<N> for PrismaticJoint<N> {
         &self.motor
     }
 
+    fn friction(&self) -> N {
+        self.friction
+    }
+
+    fn limit_softness(&self) -> (N, N) {
+        (self.limit_stiffness, self.limit_damping)
+    }
+
+    fn limit_restitution(&self) -> N {
+        self.limit_restitution
+    }
+
     fn min_position(&self) -> Option<N> {
         self.min_offset
     }
@@ -276,7 +424,9 @@ macro_rules! prismatic_motor_limit_methods(
             desired_linear_motor_velocity,
             set_desired_linear_motor_velocity,
             max_linear_motor_force,
-            set_max_linear_motor_force);
+            set_max_linear_motor_force,
+            friction_force,
+            set_friction_force);
     }
 );
 
@@ -298,7 +448,9 @@ macro_rules! prismatic_motor_limit_methods_1(
             desired_linear_motor_velocity_1,
             set_desired_linear_motor_velocity_1,
             max_linear_motor_force_1,
-            set_max_linear_motor_force_1);
+            set_max_linear_motor_force_1,
+            friction_force_1,
+            set_friction_force_1);
     }
 );
 
@@ -319,8 +471,10 @@ macro_rules! prismatic_motor_limit_methods_2(
             disable_linear_motor_2,
             desired_linear_motor_velocity_2,
             set_desired_linear_motor_velocity_2,
-            max_linear_motor_force2,
-            set_max_linear_motor_force_2);
+            max_linear_motor_force_2,
+            set_max_linear_motor_force_2,
+            friction_force_2,
+            set_friction_force_2);
     }
 );
 
@@ -339,7 +493,9 @@ macro_rules! _prismatic_motor_limit_methods(
      $desired_motor_velocity:     ident,
      $set_desired_motor_velocity: ident,
      $max_motor_force:           ident,
-     $set_max_motor_force:       ident
+     $set_max_motor_force:       ident,
+     $friction_force:            ident,
+     $set_friction_force:        ident
      ) => {
         impl<N: Real> $ty<N> {
             /// The lower limit of the relative translational displacement of the attached multibody links along the joint axis.
@@ -354,7 +510,7 @@ macro_rules! _prismatic_motor_limit_methods(
 
             /// Disable the lower limit of the relative translational displacement of the attached multibody links along the joint axis.
             pub fn $disable_min_offset(&mut self) {
-                self.$prism.disable_max_offset();
+                self.$prism.disable_min_offset();
             }
 
             /// Disable the upper limit of the relative translational displacement of the attached multibody links along the joint axis.
@@ -406,6 +562,16 @@ macro_rules! _prismatic_motor_limit_methods(
             pub fn $set_max_motor_force(&mut self, force: N) {
                 self.$prism.set_max_linear_motor_force(force)
             }
+
+            /// The maximum force resisting the translation of this joint.
+            pub fn $friction_force(&self) -> N {
+                self.$prism.friction_force()
+            }
+
+            /// Set the maximum force resisting the translation of this joint.
+            pub fn $set_friction_force(&mut self, force: N) {
+                self.$prism.set_friction_force(force)
+            }
         }
     }
 );
@@ -1,7 +1,7 @@
 use na::{DVector, Real, Unit, Vector3};
 use std::ops::Range;
 
-use joint::JointConstraint;
+use joint::{JointConstraint, JointConstraintParameters};
 use math::{AngularVector, Point};
 use object::{BodyHandle, BodySet};
 use solver::helper;
@@ -19,6 +19,7 @@ pub struct RectangularConstraint<N: Real> {
     ang_impulses: Vector3<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
 }
 
 impl<N: Real> RectangularConstraint<N> {
@@ -43,6 +44,7 @@ impl<N: Real> RectangularConstraint<N> {
             ang_impulses: Vector3::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
         }
     }
 }
@@ -56,6 +58,14 @@ impl<N: Real> JointConstraint<N> for RectangularConstraint<N> {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
     fn velocity_constraints(
         &mut self,
         _: &IntegrationParameters<N>,
@@ -149,6 +159,19 @@ impl<N: Real> JointConstraint<N> for RectangularConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        Some(self.lin_impulse)
+            .into_iter()
+            .chain(self.ang_impulses.iter().cloned())
+            .collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        assert!(!impulses.is_empty(), "Invalid number of cached impulses.");
+        self.lin_impulse = impulses[0];
+        self.ang_impulses.copy_from_slice(&impulses[1..]);
+    }
 }
 
 impl<N: Real> NonlinearConstraintGenerator<N> for RectangularConstraint<N> {
@@ -204,6 +227,6 @@ impl<N: Real> NonlinearConstraintGenerator<N> for RectangularConstraint<N> {
             );
         }
 
-        return None;
+        None
     }
 }
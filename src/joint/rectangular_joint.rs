@@ -22,6 +22,69 @@ impl<N: Real> RectangularJoint<N> {
             prism2: PrismaticJoint::new(axis2, offset2),
         }
     }
+
+    /// The linear displacement along the first axis.
+    pub fn offset_1(&self) -> N {
+        self.prism1.offset()
+    }
+
+    /// The linear displacement along the second axis.
+    pub fn offset_2(&self) -> N {
+        self.prism2.offset()
+    }
+
+    /// The offsets the joint motors attempt to reach, if they are used as a position servo.
+    pub fn desired_linear_motor_position(&self) -> Option<(N, N)> {
+        match (
+            self.prism1.desired_linear_motor_position(),
+            self.prism2.desired_linear_motor_position(),
+        ) {
+            (Some(u), Some(v)) => Some((u, v)),
+            _ => None,
+        }
+    }
+
+    /// Use both joint motors as a position servo targeting the offsets `u` and `v` along the
+    /// first and second axis.
+    ///
+    /// Each motor applies the force `kp * (target - offset) - kd * velocity` along its axis,
+    /// clamped by its maximum motor force. The gains are set by `self.set_linear_motor_gains`.
+    /// Both motors must still be enabled with `self.enable_linear_motor_1()` and
+    /// `self.enable_linear_motor_2()`.
+    pub fn set_desired_linear_motor_position(&mut self, u: N, v: N) {
+        self.prism1.set_desired_linear_motor_position(u);
+        self.prism2.set_desired_linear_motor_position(v);
+    }
+
+    /// Stop using the joint motors as a position servo and revert to their desired velocities.
+    pub fn disable_linear_motor_position(&mut self) {
+        self.prism1.disable_linear_motor_position();
+        self.prism2.disable_linear_motor_position();
+    }
+
+    /// The proportional and derivative gains of the position servo of the first joint motor.
+    pub fn linear_motor_gains_1(&self) -> (N, N) {
+        (
+            self.prism1.linear_motor_stiffness(),
+            self.prism1.linear_motor_damping(),
+        )
+    }
+
+    /// The proportional and derivative gains of the position servo of the second joint motor.
+    pub fn linear_motor_gains_2(&self) -> (N, N) {
+        (
+            self.prism2.linear_motor_stiffness(),
+            self.prism2.linear_motor_damping(),
+        )
+    }
+
+    /// Set the proportional and derivative gains of both joint motors position servo.
+    pub fn set_linear_motor_gains(&mut self, kp: N, kd: N) {
+        self.prism1.set_linear_motor_stiffness(kp);
+        self.prism1.set_linear_motor_damping(kd);
+        self.prism2.set_linear_motor_stiffness(kp);
+        self.prism2.set_linear_motor_damping(kd);
+    }
 }
 
 impl<N: Real> Joint<N> for RectangularJoint<N> {
@@ -31,7 +94,7 @@ impl<N: Real> Joint<N> for RectangularJoint<N> {
     }
 
     fn body_to_parent(&self, parent_shift: &Vector3<N>, body_shift: &Vector3<N>) -> Isometry3<N> {
-        let t = Translation3::from_vector(parent_shift - body_shift) * self.prism1.translation()
+        let t = Translation3::from(parent_shift - body_shift) * self.prism1.translation()
             * self.prism2.translation();
         Isometry3::from_parts(t, na::one())
     }
@@ -81,6 +144,21 @@ impl<N: Real> Joint<N> for RectangularJoint<N> {
         self.prism2.apply_displacement(&[disp[1]]);
     }
 
+    fn generalized_position(&self, out: &mut [N]) {
+        self.prism1.generalized_position(out);
+        self.prism2.generalized_position(&mut out[1..]);
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        self.prism1.set_generalized_position(coords);
+        self.prism2.set_generalized_position(&coords[1..]);
+    }
+
+    fn clamp_to_limits(&mut self) {
+        self.prism1.clamp_to_limits();
+        self.prism2.clamp_to_limits();
+    }
+
     fn num_velocity_constraints(&self) -> usize {
         self.prism1.num_velocity_constraints() + self.prism2.num_velocity_constraints()
     }
@@ -141,3 +219,55 @@ impl<N: Real> Joint<N> for RectangularJoint<N> {
 
 prismatic_motor_limit_methods_1!(RectangularJoint, prism1);
 prismatic_motor_limit_methods_2!(RectangularJoint, prism2);
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::RectangularJoint;
+    use math::Vector;
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn rectangular_joint_servo_moves_a_loaded_plotter_head() {
+        let mut world = World::<f32>::new();
+        let mut joint = RectangularJoint::new(Vector::x_axis(), Vector::y_axis(), 0.0, 0.0);
+        joint.enable_linear_motor_1();
+        joint.enable_linear_motor_2();
+        joint.set_max_linear_motor_force_1(5.0);
+        joint.set_max_linear_motor_force_2(5.0);
+        joint.set_linear_motor_gains(100.0, 40.0);
+        assert_eq!(joint.linear_motor_gains_1(), (100.0, 40.0));
+        assert_eq!(joint.linear_motor_gains_2(), (100.0, 40.0));
+        joint.set_desired_linear_motor_position(0.3, -0.2);
+
+        // The payload is heavy enough for the motors to saturate at the start of the motion.
+        let payload = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let head = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            na::zero(),
+            payload.inertia(1.0),
+            payload.center_of_mass(),
+        );
+
+        let mut peak = (0.0f32, 0.0f32);
+
+        for _ in 0..300 {
+            world.step();
+            let link = world.multibody_link(head).unwrap();
+            let joint = link.joint().downcast_ref::<RectangularJoint<f32>>().unwrap();
+            peak = (peak.0.max(joint.offset_1()), peak.1.min(joint.offset_2()));
+        }
+
+        let link = world.multibody_link(head).unwrap();
+        let joint = link.joint().downcast_ref::<RectangularJoint<f32>>().unwrap();
+        assert!((joint.offset_1() - 0.3).abs() < 1.0e-2, "Offset 1: {}", joint.offset_1());
+        assert!((joint.offset_2() + 0.2).abs() < 1.0e-2, "Offset 2: {}", joint.offset_2());
+        assert!(peak.0 < 0.3 + 5.0e-3 && peak.1 > -0.2 - 5.0e-3, "Overshoot: {:?}", peak);
+    }
+}
@@ -3,7 +3,7 @@ use na::Unit;
 use na::{DVector, Real};
 use std::ops::Range;
 
-use joint::JointConstraint;
+use joint::{JointConstraint, JointConstraintParameters};
 use math::{AngularVector, Point, Vector, DIM, SPATIAL_DIM};
 use object::{BodyHandle, BodySet};
 use solver::helper;
@@ -21,6 +21,7 @@ pub struct RevoluteConstraint<N: Real> {
     ang_impulses: AngularVector<N>, // FIXME: not actually needed in 2D.
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
     // min_angle: Option<N>,
     // max_angle: Option<N>,
 }
@@ -38,6 +39,7 @@ pub struct RevoluteConstraint<N: Real> {
     ang_impulses: AngularVector<N>,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
     // min_angle: Option<N>,
     // max_angle: Option<N>,
 }
@@ -68,6 +70,7 @@ impl<N: Real> RevoluteConstraint<N> {
             ang_impulses: AngularVector::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
             // min_angle,
             // max_angle,
         }
@@ -90,11 +93,83 @@ impl<N: Real> RevoluteConstraint<N> {
             ang_impulses: AngularVector::zeros(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
             // min_angle,
             // max_angle,
         }
     }
 
+    /// Create a new revolute constraint between the body part `b` and the ground.
+    ///
+    /// The `world_anchor` and `world_axis` are expressed in world-space, while `anchor` and `axis`
+    /// are expressed in the local coordinate system of `b`. Only the jacobians of `b` are computed
+    /// at each timestep.
+    #[cfg(feature = "dim3")]
+    pub fn new_with_ground(
+        b: BodyHandle,
+        world_anchor: Point<N>,
+        world_axis: Unit<AngularVector<N>>,
+        anchor: Point<N>,
+        axis: Unit<AngularVector<N>>,
+    ) -> Self {
+        Self::new(BodyHandle::ground(), b, world_anchor, world_axis, anchor, axis)
+    }
+
+    /// Create a new revolute constraint between the body part `b` and the ground.
+    ///
+    /// The `world_anchor` is expressed in world-space, while `anchor` is expressed in the
+    /// local coordinate system of `b`. Only the jacobians of `b` are computed at each timestep.
+    #[cfg(feature = "dim2")]
+    pub fn new_with_ground(b: BodyHandle, world_anchor: Point<N>, anchor: Point<N>) -> Self {
+        Self::new(BodyHandle::ground(), b, world_anchor, anchor)
+    }
+
+    // Velocity constraints when the first body part is the ground.
+    fn ground_velocity_constraints(
+        &mut self,
+        bodies: &BodySet<N>,
+        ext_vels: &DVector<N>,
+        ground_j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        let body = bodies.body_part(self.b2);
+        let assembly_id = body.parent_companion_id();
+        let anchor = body.position() * self.anchor2;
+        let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
+
+        helper::cancel_linear_velocity_wrt_ground(
+            &body,
+            assembly_id,
+            &anchor,
+            ext_vels,
+            &self.lin_impulses,
+            0,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        // The first body part is the ground so `axis1` is already expressed in world-space.
+        #[cfg(feature = "dim3")]
+        helper::restrict_angular_velocity_to_axis_wrt_ground(
+            &body,
+            assembly_id,
+            &self.axis1,
+            &anchor,
+            ext_vels,
+            self.ang_impulses.as_slice(),
+            DIM,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        self.bilateral_ground_rng =
+            first_bilateral_ground..constraints.velocity.bilateral_ground.len();
+        self.bilateral_rng = 0..0;
+    }
+
     // pub fn min_angle(&self) -> Option<N> {
     //     self.min_angle
     // }
@@ -139,6 +214,14 @@ impl<N: Real> JointConstraint<N> for RevoluteConstraint<N> {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
     fn velocity_constraints(
         &mut self,
         _: &IntegrationParameters<N>,
@@ -149,6 +232,11 @@ impl<N: Real> JointConstraint<N> for RevoluteConstraint<N> {
         jacobians: &mut [N],
         constraints: &mut ConstraintSet<N>,
     ) {
+        if self.b1.is_ground() {
+            self.ground_velocity_constraints(bodies, ext_vels, ground_j_id, jacobians, constraints);
+            return;
+        }
+
         let b1 = bodies.body_part(self.b1);
         let b2 = bodies.body_part(self.b2);
 
@@ -235,6 +323,20 @@ impl<N: Real> JointConstraint<N> for RevoluteConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.lin_impulses
+            .iter()
+            .chain(self.ang_impulses.iter())
+            .cloned()
+            .collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let (lin, ang) = impulses.split_at(self.lin_impulses.len());
+        self.lin_impulses.copy_from_slice(lin);
+        self.ang_impulses.copy_from_slice(ang);
+    }
 }
 
 impl<N: Real> NonlinearConstraintGenerator<N> for RevoluteConstraint<N> {
@@ -297,6 +399,6 @@ impl<N: Real> NonlinearConstraintGenerator<N> for RevoluteConstraint<N> {
             }
         }
 
-        return None;
+        None
     }
 }
@@ -22,6 +22,11 @@ pub struct RevoluteJoint<N: Real> {
     min_angle: Option<N>,
     max_angle: Option<N>,
     motor: JointMotor<N, N>,
+    friction: N,
+    damping: N,
+    limit_stiffness: N,
+    limit_damping: N,
+    limit_restitution: N,
 }
 
 impl<N: Real> RevoluteJoint<N> {
@@ -34,10 +39,15 @@ impl<N: Real> RevoluteJoint<N> {
             jacobian_dot: Velocity::zero(),
             jacobian_dot_veldiff: Velocity::zero(),
             rot: Rotation::new(angle),
-            angle: angle,
+            angle,
             min_angle: None,
             max_angle: None,
             motor: JointMotor::new(),
+            friction: N::zero(),
+            damping: N::zero(),
+            limit_stiffness: N::zero(),
+            limit_damping: N::zero(),
+            limit_restitution: N::zero(),
         }
     }
 
@@ -48,15 +58,20 @@ impl<N: Real> RevoluteJoint<N> {
     #[cfg(feature = "dim3")]
     pub fn new(axis: Unit<AngularVector<N>>, angle: N) -> Self {
         RevoluteJoint {
-            axis: axis,
+            axis,
             jacobian: Velocity::zero(),
             jacobian_dot: Velocity::zero(),
             jacobian_dot_veldiff: Velocity::zero(),
             rot: Rotation::from_axis_angle(&axis, angle),
-            angle: angle,
+            angle,
             min_angle: None,
             max_angle: None,
             motor: JointMotor::new(),
+            friction: N::zero(),
+            damping: N::zero(),
+            limit_stiffness: N::zero(),
+            limit_damping: N::zero(),
+            limit_restitution: N::zero(),
         }
     }
 
@@ -167,6 +182,105 @@ impl<N: Real> RevoluteJoint<N> {
         self.motor.max_force = torque;
     }
 
+    /// The angle the joint motor attempts to reach, if it is used as a position servo.
+    pub fn desired_angular_motor_position(&self) -> Option<N> {
+        self.motor.desired_position
+    }
+
+    /// Use the joint motor as a position servo targeting the given angle.
+    ///
+    /// The motor then applies the torque `kp * (angle - self.angle()) - kd * velocity`,
+    /// where the gains are set by `self.set_angular_motor_stiffness` and
+    /// `self.set_angular_motor_damping`, clamped by the maximum motor torque. The target can
+    /// be changed at each timestep and is clamped to the joint limits. The motor must still
+    /// be enabled with `self.enable_angular_motor()`.
+    pub fn set_desired_angular_motor_position(&mut self, angle: N) {
+        self.motor.desired_position = Some(angle);
+    }
+
+    /// Stop using the joint motor as a position servo and revert to the desired velocity.
+    pub fn disable_angular_motor_position(&mut self) {
+        self.motor.desired_position = None;
+    }
+
+    /// The proportional gain of the joint motor position servo.
+    pub fn angular_motor_stiffness(&self) -> N {
+        self.motor.stiffness
+    }
+
+    /// Set the proportional gain of the joint motor position servo.
+    pub fn set_angular_motor_stiffness(&mut self, kp: N) {
+        self.motor.stiffness = kp;
+    }
+
+    /// The derivative gain of the joint motor position servo.
+    pub fn angular_motor_damping(&self) -> N {
+        self.motor.damping
+    }
+
+    /// Set the derivative gain of the joint motor position servo.
+    pub fn set_angular_motor_damping(&mut self, kd: N) {
+        self.motor.damping = kd;
+    }
+
+    /// The maximum torque resisting the rotation of this joint.
+    pub fn friction_torque(&self) -> N {
+        self.friction
+    }
+
+    /// Set the maximum torque resisting the rotation of this joint.
+    ///
+    /// This models dry friction: the joint does not rotate unless the torque applied to it
+    /// exceeds this threshold. Set to zero to disable friction.
+    pub fn set_friction_torque(&mut self, torque: N) {
+        self.friction = torque;
+    }
+
+    /// The viscous damping coefficient of this joint.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Set the viscous damping coefficient of this joint.
+    ///
+    /// The joint is subject to a torque equal to `-damping` times its angular velocity. This torque
+    /// is integrated implicitly so large coefficients remain stable. Set to zero to disable it.
+    pub fn set_damping(&mut self, damping: N) {
+        assert!(
+            damping >= N::zero(),
+            "The joint damping must be positive or zero."
+        );
+        self.damping = damping;
+    }
+
+    /// The stiffness and damping of the joint limits.
+    pub fn limit_softness(&self) -> (N, N) {
+        (self.limit_stiffness, self.limit_damping)
+    }
+
+    /// Soften the joint limits.
+    ///
+    /// Once the angle goes past a limit, the joint is pushed back by a spring-damper with the given
+    /// stiffness and damping instead of being stopped instantly. Setting both to zero makes the
+    /// limits hard again.
+    pub fn set_limit_softness(&mut self, stiffness: N, damping: N) {
+        self.limit_stiffness = stiffness;
+        self.limit_damping = damping;
+    }
+
+    /// The restitution coefficient of the joint limits.
+    pub fn limit_restitution(&self) -> N {
+        self.limit_restitution
+    }
+
+    /// Set the restitution coefficient applied when the joint hits one of its limits.
+    ///
+    /// Like for contacts, this is ignored if the impact velocity is smaller than the
+    /// `restitution_velocity_threshold` of the integration parameters.
+    pub fn set_limit_restitution(&mut self, restitution: N) {
+        self.limit_restitution = restitution;
+    }
+
     fn assert_limits(&self) {
         if let (Some(min_angle), Some(max_angle)) = (self.min_angle, self.max_angle) {
             assert!(
@@ -184,13 +298,13 @@ impl<N: Real> Joint<N> for RevoluteJoint<N> {
 
     #[cfg(feature = "dim3")]
     fn body_to_parent(&self, parent_shift: &Vector<N>, body_shift: &Vector<N>) -> Isometry<N> {
-        let trans = Translation::from_vector(parent_shift - self.rot * body_shift);
+        let trans = Translation::from(parent_shift - self.rot * body_shift);
         Isometry::from_parts(trans, self.rot)
     }
 
     #[cfg(feature = "dim2")]
     fn body_to_parent(&self, parent_shift: &Vector<N>, body_shift: &Vector<N>) -> Isometry<N> {
-        let trans = Translation::from_vector(parent_shift - self.rot * body_shift);
+        let trans = Translation::from(parent_shift - self.rot * body_shift);
         Isometry::from_parts(trans, self.rot)
     }
 
@@ -198,7 +312,7 @@ impl<N: Real> Joint<N> for RevoluteJoint<N> {
         let shift = self.rot * -body_shift;
         let shift_dot_veldiff = self.axis.gcross(&shift);
 
-        self.jacobian = Velocity::new_with_vectors(self.axis.gcross(&shift), self.axis.unwrap());
+        self.jacobian = Velocity::new_with_vectors(self.axis.gcross(&shift), self.axis.into_inner());
         self.jacobian_dot_veldiff.linear = self.axis.gcross(&shift_dot_veldiff);
         self.jacobian_dot.linear = self.jacobian_dot_veldiff.linear * vels[0];
     }
@@ -229,6 +343,19 @@ impl<N: Real> Joint<N> for RevoluteJoint<N> {
         out.fill(na::convert(0.1f64))
     }
 
+    fn viscous_damping(&self, out: &mut DVectorSliceMut<N>) {
+        out.fill(self.damping)
+    }
+
+    fn generalized_position(&self, out: &mut [N]) {
+        out[0] = self.angle;
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        self.angle = coords[0];
+        self.update_rot();
+    }
+
     fn apply_displacement(&mut self, disp: &[N]) {
         // println!("Applying displacement: {}", disp[0]);
         // println!(
@@ -240,6 +367,18 @@ impl<N: Real> Joint<N> for RevoluteJoint<N> {
         self.update_rot();
     }
 
+    fn clamp_to_limits(&mut self) {
+        if let Some(min_angle) = self.min_angle {
+            self.angle = self.angle.max(min_angle);
+        }
+
+        if let Some(max_angle) = self.max_angle {
+            self.angle = self.angle.min(max_angle);
+        }
+
+        self.update_rot();
+    }
+
     fn jacobian_mul_coordinates(&self, acc: &[N]) -> Velocity<N> {
         self.jacobian * acc[0]
     }
@@ -304,6 +443,18 @@ impl<N: Real> UnitJoint<N> for RevoluteJoint<N> {
         &self.motor
     }
 
+    fn friction(&self) -> N {
+        self.friction
+    }
+
+    fn limit_softness(&self) -> (N, N) {
+        (self.limit_stiffness, self.limit_damping)
+    }
+
+    fn limit_restitution(&self) -> N {
+        self.limit_restitution
+    }
+
     fn min_position(&self) -> Option<N> {
         self.min_angle
     }
@@ -331,7 +482,9 @@ macro_rules! revolute_motor_limit_methods(
             desired_angular_motor_velocity,
             set_desired_angular_motor_velocity,
             max_angular_motor_torque,
-            set_max_angular_motor_torque);
+            set_max_angular_motor_torque,
+            friction_torque,
+            set_friction_torque);
     }
 );
 
@@ -353,7 +506,9 @@ macro_rules! revolute_motor_limit_methods_1(
             desired_angular_motor_velocity_1,
             set_desired_angular_motor_velocity_1,
             max_angular_motor_torque_1,
-            set_max_angular_motor_torque_1);
+            set_max_angular_motor_torque_1,
+            friction_torque_1,
+            set_friction_torque_1);
     }
 );
 
@@ -375,7 +530,9 @@ macro_rules! revolute_motor_limit_methods_2(
             desired_angular_motor_velocity_2,
             set_desired_angular_motor_velocity_2,
             max_angular_motor_torque_2,
-            set_max_angular_motor_torque_2);
+            set_max_angular_motor_torque_2,
+            friction_torque_2,
+            set_friction_torque_2);
     }
 );
 
@@ -394,7 +551,9 @@ macro_rules! _revolute_motor_limit_methods(
      $desired_motor_velocity:     ident,
      $set_desired_motor_velocity: ident,
      $max_motor_torque:           ident,
-     $set_max_motor_torque:       ident
+     $set_max_motor_torque:       ident,
+     $friction_torque:            ident,
+     $set_friction_torque:        ident
      ) => {
         impl<N: Real> $ty<N> {
             /// The lower limit of the rotation angle.
@@ -409,7 +568,7 @@ macro_rules! _revolute_motor_limit_methods(
 
             /// Disable the lower limit of the rotation angle.
             pub fn $disable_min_angle(&mut self) {
-                self.$revo.disable_max_angle();
+                self.$revo.disable_min_angle();
             }
 
             /// Disable the upper limit of the rotation angle.
@@ -461,6 +620,102 @@ macro_rules! _revolute_motor_limit_methods(
             pub fn $set_max_motor_torque(&mut self, torque: N) {
                 self.$revo.set_max_angular_motor_torque(torque)
             }
+
+            /// The maximum torque resisting the rotation of this joint.
+            pub fn $friction_torque(&self) -> N {
+                self.$revo.friction_torque()
+            }
+
+            /// Set the maximum torque resisting the rotation of this joint.
+            pub fn $set_friction_torque(&mut self, torque: N) {
+                self.$revo.set_friction_torque(torque)
+            }
         }
     }
 );
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::RevoluteJoint;
+    use math::Vector;
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn joint_damping_makes_the_energy_of_a_swinging_chain_decay() {
+        let energies = |damping: f32| {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+            let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+            let joint = || {
+                #[cfg(feature = "dim2")]
+                let mut joint = RevoluteJoint::new(0.0);
+                #[cfg(feature = "dim3")]
+                let mut joint = RevoluteJoint::new(Vector::z_axis(), 0.0);
+                joint.set_damping(damping);
+                assert_relative_eq!(joint.damping(), damping);
+                joint
+            };
+
+            // A horizontal chain of three links swinging under gravity.
+            let mut parent = BodyHandle::ground();
+            let mut links = Vec::new();
+            for i in 0..3 {
+                let shift = if i == 0 { na::zero() } else { Vector::x() };
+                parent = world.add_multibody_link(
+                    parent,
+                    joint(),
+                    shift,
+                    -Vector::x(),
+                    cube.inertia(1.0),
+                    cube.center_of_mass(),
+                );
+                links.push(parent);
+            }
+
+            world
+                .multibody_mut(links[0])
+                .unwrap()
+                .activation_status_mut()
+                .set_deactivation_threshold(None);
+
+            let mut energies = Vec::new();
+            for _ in 0..180 {
+                world.step();
+
+                let energy: f32 = links
+                    .iter()
+                    .map(|handle| {
+                        let link = world.multibody_link(*handle).unwrap();
+                        let vel = link.velocity();
+                        let momentum = *link.inertia() * *vel;
+                        let kinetic = vel.as_vector().dot(momentum.as_vector()) * 0.5;
+                        kinetic + link.inertia().mass() * 9.81 * link.center_of_mass().y
+                    })
+                    .sum();
+                energies.push(energy);
+            }
+
+            energies
+        };
+
+        let damped = energies(2.0);
+        let undamped = energies(0.0);
+
+        for pair in damped.windows(2) {
+            assert!(pair[1] <= pair[0] + 1.0e-3, "Energy increased: {:?}", pair);
+        }
+
+        assert!(
+            damped[damped.len() - 1] < undamped[undamped.len() - 1] - 1.0,
+            "Damped: {}, undamped: {}",
+            damped[damped.len() - 1],
+            undamped[undamped.len() - 1]
+        );
+    }
+}
@@ -32,14 +32,12 @@ pub fn build_linear_limits_velocity_constraint<N: Real>(
         (Some(min), Some(max)) => {
             if relative_eq!(min, max) {
                 (false, *axis)
+            } else if offset <= min {
+                (true, -*axis)
+            } else if offset >= max {
+                (true, *axis)
             } else {
-                if offset <= min {
-                    (true, -*axis)
-                } else if offset >= max {
-                    (true, *axis)
-                } else {
-                    return;
-                }
+                return;
             }
         }
         (Some(min), None) => {
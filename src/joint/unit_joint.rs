@@ -5,8 +5,8 @@ use na::{DVectorSliceMut, Real};
 use joint::{Joint, JointMotor};
 use object::{BodyHandle, MultibodyLinkRef};
 use solver::{
-    BilateralGroundConstraint, ConstraintSet, GenericNonlinearConstraint, IntegrationParameters,
-    UnilateralGroundConstraint,
+    BilateralGroundConstraint, ConstraintSet, GenericNonlinearConstraint, ImpulseLimits,
+    IntegrationParameters, UnilateralGroundConstraint,
 };
 
 /// Trait implemented by joints using the reduced-coordinates approach and allowing only one degree of freedom.
@@ -15,6 +15,12 @@ pub trait UnitJoint<N: Real>: Joint<N> {
     fn position(&self) -> N;
     /// The motor applied to the degree of freedom of the unit joitn.
     fn motor(&self) -> &JointMotor<N, N>;
+    /// The maximum generalized force resisting the motion of the degree of freedom of this unit joint.
+    fn friction(&self) -> N;
+    /// The stiffness and damping of the limits of this unit joint; both zero for hard limits.
+    fn limit_softness(&self) -> (N, N);
+    /// The restitution coefficient of the limits of this unit joint.
+    fn limit_restitution(&self) -> N;
     /// The lower limit, if any, set to the generalized coordinate of this unit joint.
     fn min_position(&self) -> Option<N>;
     /// The upper limit, if any, set to the generalized coordinate of this unit joint.
@@ -31,6 +37,9 @@ pub fn unit_joint_num_velocity_constraints<N: Real, J: UnitJoint<N>>(joint: &J)
     if joint.motor().enabled {
         nconstraints += 1;
     }
+    if !joint.friction().is_zero() {
+        nconstraints += 1;
+    }
     if joint.min_position().is_some() {
         nconstraints += 1;
     }
@@ -41,6 +50,63 @@ pub fn unit_joint_num_velocity_constraints<N: Real, J: UnitJoint<N>>(joint: &J)
     nconstraints
 }
 
+// Computes the velocity targeted by the motor of a unit joint, and the compliance of the motor constraint.
+//
+// In servo mode, the PD force `stiffness * (target - position) - damping * velocity` is applied
+// implicitly, i.e., wrt. the position and velocity at the end of the timestep. The target is
+// clamped to the joint limits so the motor never pushes against an active limit.
+fn unit_joint_motor_target<N: Real, J: UnitJoint<N>>(
+    joint: &J,
+    params: &IntegrationParameters<N>,
+) -> (N, N) {
+    let motor = joint.motor();
+
+    if let Some(mut target) = motor.desired_position {
+        if let Some(min_position) = joint.min_position() {
+            target = target.max(min_position);
+        }
+
+        if let Some(max_position) = joint.max_position() {
+            target = target.min(max_position);
+        }
+
+        let denom = params.dt * (params.dt * motor.stiffness + motor.damping);
+
+        if !denom.is_zero() {
+            let err = target - joint.position();
+            return (motor.stiffness * err * params.dt / denom, N::one() / denom);
+        }
+    }
+
+    (motor.desired_velocity, N::zero())
+}
+
+// Computes the right-hand side and the compliance of a limit constraint.
+//
+// Both the limit violation `err` and the velocity `dvel` are positive when the joint moves
+// back toward its admissible range.
+fn unit_joint_limit_rhs<N: Real, J: UnitJoint<N>>(
+    joint: &J,
+    params: &IntegrationParameters<N>,
+    err: N,
+    dvel: N,
+) -> (N, N) {
+    let mut rhs = dvel;
+
+    if rhs < -params.restitution_velocity_threshold {
+        rhs += joint.limit_restitution() * rhs;
+    }
+
+    let (stiffness, damping) = joint.limit_softness();
+    let denom = params.dt * (params.dt * stiffness + damping);
+
+    if denom.is_zero() {
+        (rhs, N::zero())
+    } else {
+        (rhs - stiffness * err * params.dt / denom, N::one() / denom)
+    }
+}
+
 /// Initializes and generate the velocity constraints applicable to the multibody links attached
 /// to this joint.
 pub fn unit_joint_velocity_constraints<N: Real, J: UnitJoint<N>>(
@@ -59,7 +125,8 @@ pub fn unit_joint_velocity_constraints<N: Real, J: UnitJoint<N>>(
     let mut is_min_constraint_active = false;
 
     if joint.motor().enabled {
-        let dvel = link.joint_velocity()[dof_id] + ext_vels[assembly_id + link.assembly_id()];
+        let dvel =
+            link.joint_velocity()[dof_id] + ext_vels[assembly_id + link.assembly_id() + dof_id];
 
         DVectorSliceMut::from_slice(&mut jacobians[*ground_j_id..], ndofs).fill(N::zero());
         jacobians[*ground_j_id + link.assembly_id() + dof_id] = N::one();
@@ -68,20 +135,59 @@ pub fn unit_joint_velocity_constraints<N: Real, J: UnitJoint<N>>(
         link.inv_mass_mul_unit_joint_force(dof_id, N::one(), &mut jacobians[wj_id..]);
 
         let inv_r = jacobians[wj_id + link.assembly_id() + dof_id]; // = J^t * M^-1 J
-        let rhs = dvel - joint.motor().desired_velocity;
+        let (desired_velocity, cfm) = unit_joint_motor_target(joint, params);
+        let rhs = dvel - desired_velocity;
         let limits = joint.motor().impulse_limits();
-        let impulse_id = link.impulse_id() + dof_id * 3;
+        let impulse_id = link.impulse_id() + dof_id * 4;
 
         let constraint = BilateralGroundConstraint {
             impulse: impulses[impulse_id] * params.warmstart_coeff,
-            r: N::one() / inv_r,
+            r: N::one() / (inv_r + cfm),
             rhs,
+            cfm,
             limits,
+            is_angular: false,
             impulse_id,
-            assembly_id: assembly_id,
+            assembly_id,
             j_id: *ground_j_id,
             wj_id: *ground_j_id + ndofs,
-            ndofs: ndofs,
+            ndofs,
+        };
+
+        constraints.velocity.bilateral_ground.push(constraint);
+        *ground_j_id += 2 * ndofs;
+    }
+
+    if !joint.friction().is_zero() {
+        // Dry friction: drive the joint velocity to zero with a bounded impulse.
+        let dvel =
+            link.joint_velocity()[dof_id] + ext_vels[assembly_id + link.assembly_id() + dof_id];
+
+        DVectorSliceMut::from_slice(&mut jacobians[*ground_j_id..], ndofs).fill(N::zero());
+        jacobians[*ground_j_id + link.assembly_id() + dof_id] = N::one();
+
+        let wj_id = *ground_j_id + ndofs;
+        link.inv_mass_mul_unit_joint_force(dof_id, N::one(), &mut jacobians[wj_id..]);
+
+        let inv_r = jacobians[wj_id + link.assembly_id() + dof_id]; // = J^t * M^-1 J
+        let max_impulse = joint.friction() * params.dt;
+        let impulse_id = link.impulse_id() + dof_id * 4 + 3;
+
+        let constraint = BilateralGroundConstraint {
+            impulse: impulses[impulse_id] * params.warmstart_coeff,
+            r: N::one() / inv_r,
+            rhs: dvel,
+            cfm: N::zero(),
+            limits: ImpulseLimits::Independent {
+                min: -max_impulse,
+                max: max_impulse,
+            },
+            is_angular: false,
+            impulse_id,
+            assembly_id,
+            j_id: *ground_j_id,
+            wj_id: *ground_j_id + ndofs,
+            ndofs,
         };
 
         constraints.velocity.bilateral_ground.push(constraint);
@@ -103,11 +209,13 @@ pub fn unit_joint_velocity_constraints<N: Real, J: UnitJoint<N>>(
 
             let inv_r = jacobians[wj_id + link.assembly_id() + dof_id]; // = J^t * M^-1 J
 
-            let impulse_id = link.impulse_id() + dof_id * 3 + 1;
+            let impulse_id = link.impulse_id() + dof_id * 4 + 1;
+            let (rhs, cfm) = unit_joint_limit_rhs(joint, params, err, dvel);
             let constraint = UnilateralGroundConstraint {
                 impulse: impulses[impulse_id] * params.warmstart_coeff,
-                r: N::one() / inv_r,
-                rhs: dvel,
+                r: N::one() / (inv_r + cfm),
+                rhs,
+                cfm,
                 impulse_id,
                 assembly_id,
                 j_id: *ground_j_id,
@@ -141,16 +249,18 @@ pub fn unit_joint_velocity_constraints<N: Real, J: UnitJoint<N>>(
 
             let inv_r = -jacobians[wj_id + link.assembly_id() + dof_id]; // = J^t * M^-1 J
 
-            let impulse_id = link.impulse_id() + dof_id * 3 + 2;
+            let impulse_id = link.impulse_id() + dof_id * 4 + 2;
+            let (rhs, cfm) = unit_joint_limit_rhs(joint, params, err, dvel);
             let constraint = UnilateralGroundConstraint {
                 impulse: impulses[impulse_id] * params.warmstart_coeff,
-                r: N::one() / inv_r,
-                rhs: dvel,
+                r: N::one() / (inv_r + cfm),
+                rhs,
+                cfm,
                 impulse_id,
-                assembly_id: assembly_id,
+                assembly_id,
                 j_id: *ground_j_id,
                 wj_id: *ground_j_id + ndofs,
-                ndofs: ndofs,
+                ndofs,
             };
 
             constraints.velocity.unilateral_ground.push(constraint);
@@ -168,6 +278,13 @@ pub fn unit_joint_position_constraint<N: Real, J: UnitJoint<N>>(
     is_angular: bool,
     jacobians: &mut [N],
 ) -> Option<GenericNonlinearConstraint<N>> {
+    let (stiffness, damping) = joint.limit_softness();
+
+    // Soft limits are only enforced at the velocity level.
+    if !stiffness.is_zero() || !damping.is_zero() {
+        return None;
+    }
+
     let mut sign = N::one();
     let mut rhs = None;
 
@@ -211,3 +328,45 @@ pub fn unit_joint_position_constraint<N: Real, J: UnitJoint<N>>(
 
     None
 }
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Ball, ShapeHandle};
+
+    use math::Vector;
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn pendulum_with_joint_friction_stays_at_rest() {
+        use joint::RevoluteJoint;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        #[cfg(feature = "dim2")]
+        let mut joint = RevoluteJoint::new(0.3);
+        #[cfg(feature = "dim3")]
+        let mut joint = RevoluteJoint::new(Vector::z_axis(), 0.3);
+        joint.set_friction_torque(0.5);
+
+        let ball = ShapeHandle::new(Ball::new(0.1));
+        let link = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            Vector::y(),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let angle = world.body_part(link).position().rotation.angle();
+        assert!((angle - 0.3).abs() < 1.0e-2, "Angle: {}", angle);
+    }
+}
@@ -1,7 +1,7 @@
 use na::{DVector, Real, Unit};
 use std::ops::Range;
 
-use joint::JointConstraint;
+use joint::{JointConstraint, JointConstraintParameters};
 use math::{AngularVector, Point, Vector, DIM};
 use object::{BodyHandle, BodySet};
 use solver::helper;
@@ -21,6 +21,7 @@ pub struct UniversalConstraint<N: Real> {
     ang_impulse: N,
     bilateral_ground_rng: Range<usize>,
     bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
 }
 
 impl<N: Real> UniversalConstraint<N> {
@@ -48,6 +49,7 @@ impl<N: Real> UniversalConstraint<N> {
             ang_impulse: N::zero(),
             bilateral_ground_rng: 0..0,
             bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
         }
     }
 }
@@ -61,6 +63,14 @@ impl<N: Real> JointConstraint<N> for UniversalConstraint<N> {
         (self.b1, self.b2)
     }
 
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
     fn velocity_constraints(
         &mut self,
         _: &IntegrationParameters<N>,
@@ -156,6 +166,21 @@ impl<N: Real> JointConstraint<N> for UniversalConstraint<N> {
             }
         }
     }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.lin_impulses
+            .iter()
+            .cloned()
+            .chain(Some(self.ang_impulse))
+            .collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        let (lin, ang) = impulses.split_at(self.lin_impulses.len());
+        assert_eq!(ang.len(), 1, "Invalid number of cached impulses.");
+        self.lin_impulses.copy_from_slice(lin);
+        self.ang_impulse = ang[0];
+    }
 }
 
 impl<N: Real> NonlinearConstraintGenerator<N> for UniversalConstraint<N> {
@@ -212,6 +237,6 @@ impl<N: Real> NonlinearConstraintGenerator<N> for UniversalConstraint<N> {
             );
         }
 
-        return None;
+        None
     }
 }
@@ -10,6 +10,7 @@ use solver::{ConstraintSet, GenericNonlinearConstraint, IntegrationParameters};
 pub struct UniversalJoint<N: Real> {
     revo1: RevoluteJoint<N>,
     revo2: RevoluteJoint<N>,
+    damping: N,
 
     coupling_dot: Velocity<N>,
     coupling_dot_veldiff: Velocity<N>,
@@ -23,10 +24,37 @@ impl<N: Real> UniversalJoint<N> {
         UniversalJoint {
             revo1: RevoluteJoint::new(axis1, angle1),
             revo2: RevoluteJoint::new(axis2, angle2),
+            damping: N::zero(),
             coupling_dot: Velocity::zero(),
             coupling_dot_veldiff: Velocity::zero(),
         }
     }
+
+    /// The rotation angle around the first axis.
+    pub fn angle_1(&self) -> N {
+        self.revo1.angle()
+    }
+
+    /// The rotation angle around the second axis.
+    pub fn angle_2(&self) -> N {
+        self.revo2.angle()
+    }
+
+    /// The viscous damping coefficient of both rotations of this joint.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Set the viscous damping coefficient of both rotations of this joint.
+    ///
+    /// See `RevoluteJoint::set_damping` for details.
+    pub fn set_damping(&mut self, damping: N) {
+        assert!(
+            damping >= N::zero(),
+            "The joint damping must be positive or zero."
+        );
+        self.damping = damping;
+    }
 }
 
 impl<N: Real> Joint<N> for UniversalJoint<N> {
@@ -36,7 +64,7 @@ impl<N: Real> Joint<N> for UniversalJoint<N> {
     }
 
     fn body_to_parent(&self, parent_shift: &Vector3<N>, body_shift: &Vector3<N>) -> Isometry3<N> {
-        Translation3::from_vector(*parent_shift) * self.revo1.rotation()
+        Translation3::from(*parent_shift) * self.revo1.rotation()
             * self.revo2.body_to_parent(&na::zero(), body_shift)
     }
 
@@ -105,6 +133,10 @@ impl<N: Real> Joint<N> for UniversalJoint<N> {
         self.revo2.default_damping(&mut out.rows_mut(1, 1));
     }
 
+    fn viscous_damping(&self, out: &mut DVectorSliceMut<N>) {
+        out.fill(self.damping)
+    }
+
     fn integrate(&mut self, params: &IntegrationParameters<N>, vels: &[N]) {
         self.revo1.integrate(params, vels);
         self.revo2.integrate(params, &[vels[1]]);
@@ -115,6 +147,21 @@ impl<N: Real> Joint<N> for UniversalJoint<N> {
         self.revo2.apply_displacement(&[disp[1]]);
     }
 
+    fn generalized_position(&self, out: &mut [N]) {
+        self.revo1.generalized_position(out);
+        self.revo2.generalized_position(&mut out[1..]);
+    }
+
+    fn set_generalized_position(&mut self, coords: &[N]) {
+        self.revo1.set_generalized_position(coords);
+        self.revo2.set_generalized_position(&coords[1..]);
+    }
+
+    fn clamp_to_limits(&mut self) {
+        self.revo1.clamp_to_limits();
+        self.revo2.clamp_to_limits();
+    }
+
     fn num_velocity_constraints(&self) -> usize {
         self.revo1.num_velocity_constraints() + self.revo2.num_velocity_constraints()
     }
@@ -175,3 +222,55 @@ impl<N: Real> Joint<N> for UniversalJoint<N> {
 
 revolute_motor_limit_methods_1!(UniversalJoint, revo1);
 revolute_motor_limit_methods_2!(UniversalJoint, revo2);
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::UniversalJoint;
+    use math::Vector;
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn universal_joint_limits_hold_at_extreme_articulation() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        // The second limit is close to the configuration where both axes align.
+        let mut joint = UniversalJoint::new(Vector::x_axis(), Vector::z_axis(), 0.0, 0.0);
+        joint.enable_min_angle_1(-0.5);
+        joint.enable_max_angle_1(0.5);
+        joint.enable_min_angle_2(-1.5);
+        joint.enable_max_angle_2(1.5);
+        joint.enable_angular_motor_1();
+        joint.set_desired_angular_motor_velocity_1(2.0);
+        joint.set_max_angular_motor_torque_1(10.0);
+        joint.enable_angular_motor_2();
+        joint.set_desired_angular_motor_velocity_2(4.0);
+        joint.set_max_angular_motor_torque_2(10.0);
+
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.1)));
+        let shaft = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            Vector::y(),
+            cube.inertia(100.0),
+            cube.center_of_mass(),
+        );
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let link = world.multibody_link(shaft).unwrap();
+        let joint = link.joint().downcast_ref::<UniversalJoint<f32>>().unwrap();
+        let vels = link.joint_velocity();
+        assert!((joint.angle_1() - 0.5).abs() < 1.0e-2, "Angle 1: {}", joint.angle_1());
+        assert!((joint.angle_2() - 1.5).abs() < 1.0e-2, "Angle 2: {}", joint.angle_2());
+        assert!(vels[0].abs() < 1.0e-2 && vels[1].abs() < 1.0e-2, "Velocities: {:?}", vels);
+    }
+}
@@ -0,0 +1,287 @@
+#[cfg(feature = "dim3")]
+use alga::linear::FiniteDimInnerSpace;
+use na::{self, DVector, Real, Unit};
+use std::ops::Range;
+
+use joint::{JointConstraint, JointConstraintParameters};
+use math::{AngularVector, Rotation, Vector, ANGULAR_DIM, DIM};
+use object::{BodyHandle, BodySet};
+use solver::helper;
+use solver::{
+    BilateralConstraint, BilateralGroundConstraint, ConstraintSet, ForceDirection,
+    GenericNonlinearConstraint, ImpulseLimits, IntegrationParameters, NonlinearConstraintGenerator,
+};
+
+/// A constraint that keeps an axis of a body part aligned with a world-space direction.
+///
+/// The alignment is soft by default: the body part is rotated back toward the target
+/// direction with an angular velocity proportional to the misalignment, using a torque
+/// bounded by `JointConstraint::max_torque`. The rotations about the target direction (in 3D) and
+/// all the translations are left free.
+pub struct UprightConstraint<N: Real> {
+    body: BodyHandle,
+    local_axis: Unit<Vector<N>>,
+    world_axis: Unit<Vector<N>>,
+    stiffness: N,
+    hard: bool,
+    impulses: AngularVector<N>,
+    bilateral_ground_rng: Range<usize>,
+    bilateral_rng: Range<usize>,
+    params: JointConstraintParameters<N>,
+}
+
+impl<N: Real> UprightConstraint<N> {
+    /// Creates a constraint keeping `local_axis`, expressed in the local-space of `body`,
+    /// aligned with the world-space direction `world_axis`.
+    pub fn new(body: BodyHandle, local_axis: Unit<Vector<N>>, world_axis: Unit<Vector<N>>) -> Self {
+        UprightConstraint {
+            body,
+            local_axis,
+            world_axis,
+            stiffness: na::convert(5.0),
+            hard: false,
+            impulses: AngularVector::zeros(),
+            bilateral_ground_rng: 0..0,
+            bilateral_rng: 0..0,
+            params: JointConstraintParameters::default(),
+        }
+    }
+
+    /// The target direction, in world-space.
+    pub fn world_axis(&self) -> &Unit<Vector<N>> {
+        &self.world_axis
+    }
+
+    /// Sets the target direction, in world-space.
+    pub fn set_world_axis(&mut self, world_axis: Unit<Vector<N>>) {
+        self.world_axis = world_axis
+    }
+
+    /// The ratio between the target angular velocity and the misalignment angle (default: `5.0`).
+    pub fn stiffness(&self) -> N {
+        self.stiffness
+    }
+
+    /// Sets the ratio between the target angular velocity and the misalignment angle.
+    pub fn set_stiffness(&mut self, stiffness: N) {
+        self.stiffness = stiffness
+    }
+
+    /// Whether the alignment is enforced rigidly.
+    pub fn is_hard(&self) -> bool {
+        self.hard
+    }
+
+    /// Enables or disables the rigid enforcement of the alignment.
+    ///
+    /// If enabled, the stiffness is ignored and the misalignment is corrected by the
+    /// non-linear position solver.
+    pub fn set_hard(&mut self, hard: bool) {
+        self.hard = hard
+    }
+
+    /// The rotation that would bring the current body axis to the target direction, as a scaled axis.
+    #[cfg(feature = "dim3")]
+    fn alignment_error(&self, bodies: &BodySet<N>) -> AngularVector<N> {
+        let current = bodies.body_part(self.body).position() * self.local_axis.into_inner();
+        let cross = current.cross(&*self.world_axis);
+        let sin = cross.norm();
+        let cos = current.dot(&*self.world_axis);
+
+        if sin > N::default_epsilon() {
+            cross * (sin.atan2(cos) / sin)
+        } else if cos < N::zero() {
+            // Opposite directions, select one orthogonal direction.
+            let imin = current.iamin();
+            let mut ortho = Vector::zeros();
+            ortho[imin] = N::one();
+            ortho.cross(&current).normalize() * N::pi()
+        } else {
+            AngularVector::zeros()
+        }
+    }
+
+    /// The rotation that would bring the current body axis to the target direction, as a scaled axis.
+    #[cfg(feature = "dim2")]
+    fn alignment_error(&self, bodies: &BodySet<N>) -> AngularVector<N> {
+        let current = bodies.body_part(self.body).position() * self.local_axis.into_inner();
+        let sin = current.perp(&*self.world_axis);
+        let cos = current.dot(&*self.world_axis);
+
+        AngularVector::new(sin.atan2(cos))
+    }
+}
+
+impl<N: Real> JointConstraint<N> for UprightConstraint<N> {
+    fn num_velocity_constraints(&self) -> usize {
+        ANGULAR_DIM + 2 - DIM
+    }
+
+    fn anchors(&self) -> (BodyHandle, BodyHandle) {
+        (BodyHandle::ground(), self.body)
+    }
+
+    fn parameters(&self) -> Option<&JointConstraintParameters<N>> {
+        Some(&self.params)
+    }
+
+    fn parameters_mut(&mut self) -> Option<&mut JointConstraintParameters<N>> {
+        Some(&mut self.params)
+    }
+
+    fn velocity_constraints(
+        &mut self,
+        _: &IntegrationParameters<N>,
+        bodies: &BodySet<N>,
+        ext_vels: &DVector<N>,
+        ground_j_id: &mut usize,
+        j_id: &mut usize,
+        jacobians: &mut [N],
+        constraints: &mut ConstraintSet<N>,
+    ) {
+        let body1 = bodies.body_part(BodyHandle::ground());
+        let body2 = bodies.body_part(self.body);
+        let anchor = body2.center_of_mass();
+
+        let assembly_id1 = body1.parent_companion_id();
+        let assembly_id2 = body2.parent_companion_id();
+
+        let first_bilateral_ground = constraints.velocity.bilateral_ground.len();
+        let first_bilateral = constraints.velocity.bilateral.len();
+
+        let error = self.alignment_error(bodies);
+        // The solver clamps these limits to `JointConstraint::max_torque`.
+        let limits = ImpulseLimits::Independent {
+            min: -N::max_value(),
+            max: N::max_value(),
+        };
+
+        #[cfg(feature = "dim3")]
+        let mut axes = [AngularVector::zeros(); 2];
+        #[cfg(feature = "dim3")]
+        {
+            let mut i = 0;
+            AngularVector::orthonormal_subspace_basis(&[self.world_axis.into_inner()], |dir| {
+                axes[i] = *dir;
+                i += 1;
+                true
+            });
+        }
+        #[cfg(feature = "dim2")]
+        let axes = [AngularVector::x()];
+
+        for (i, axis) in axes.iter().enumerate() {
+            let dir = ForceDirection::Angular(Unit::new_unchecked(*axis));
+            let geom = helper::constraint_pair_geometry(
+                &body1,
+                &body2,
+                &anchor,
+                &anchor,
+                &dir,
+                ground_j_id,
+                j_id,
+                jacobians,
+            );
+
+            let mut rhs = helper::constraint_pair_velocity(
+                &body1,
+                &body2,
+                assembly_id1,
+                assembly_id2,
+                &anchor,
+                &anchor,
+                &dir,
+                ext_vels,
+                jacobians,
+                &geom,
+            );
+
+            if !self.hard {
+                rhs += error.dot(axis) * self.stiffness;
+            }
+
+            if geom.is_ground_constraint() {
+                constraints
+                    .velocity
+                    .bilateral_ground
+                    .push(BilateralGroundConstraint::new(
+                        geom,
+                        assembly_id1,
+                        assembly_id2,
+                        limits,
+                        rhs,
+                        self.impulses[i],
+                        i,
+                    ));
+            } else {
+                constraints
+                    .velocity
+                    .bilateral
+                    .push(BilateralConstraint::new(
+                        geom,
+                        assembly_id1,
+                        assembly_id2,
+                        limits,
+                        rhs,
+                        self.impulses[i],
+                        i,
+                    ));
+            }
+        }
+
+        self.bilateral_ground_rng =
+            first_bilateral_ground..constraints.velocity.bilateral_ground.len();
+        self.bilateral_rng = first_bilateral..constraints.velocity.bilateral.len();
+    }
+
+    fn cache_impulses(&mut self, constraints: &ConstraintSet<N>) {
+        for c in &constraints.velocity.bilateral_ground[self.bilateral_ground_rng.clone()] {
+            self.impulses[c.impulse_id] = c.impulse;
+        }
+
+        for c in &constraints.velocity.bilateral[self.bilateral_rng.clone()] {
+            self.impulses[c.impulse_id] = c.impulse;
+        }
+    }
+
+    fn cached_impulses(&self) -> Vec<N> {
+        self.impulses.iter().cloned().collect()
+    }
+
+    fn set_cached_impulses(&mut self, impulses: &[N]) {
+        self.impulses.copy_from_slice(impulses)
+    }
+}
+
+impl<N: Real> NonlinearConstraintGenerator<N> for UprightConstraint<N> {
+    fn num_position_constraints(&self, bodies: &BodySet<N>) -> usize {
+        // FIXME: calling this at each iteration of the non-linear resolution is costly.
+        if self.hard && self.is_active(bodies) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn position_constraint(
+        &self,
+        params: &IntegrationParameters<N>,
+        _: usize,
+        bodies: &mut BodySet<N>,
+        jacobians: &mut [N],
+    ) -> Option<GenericNonlinearConstraint<N>> {
+        let error = self.alignment_error(bodies);
+        let body1 = bodies.body_part(BodyHandle::ground());
+        let body2 = bodies.body_part(self.body);
+        let anchor = body2.center_of_mass();
+
+        // The target frame is the identity, and the body axis frame is the
+        // rotation bringing the target direction to the current body axis.
+        let rotation1 = Rotation::identity();
+        let rotation2 = Rotation::from_scaled_axis(-error);
+
+        helper::cancel_relative_rotation(
+            params, &body1, &body2, &anchor, &anchor, &rotation1, &rotation2, jacobians,
+        )
+    }
+}
@@ -90,6 +90,17 @@ The libraries needed to compile the examples are:
 #![deny(unused_results)]
 #![warn(non_camel_case_types)]
 #![allow(missing_copy_implementations)]
+#![allow(clippy::module_inception)]
+#![allow(clippy::just_underscores_and_digits)]
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::cyclomatic_complexity)]
+#![allow(clippy::type_complexity)]
+#![allow(clippy::transmute_ptr_to_ptr)]
+#![allow(clippy::needless_range_loop)]
+#![allow(clippy::len_without_is_empty)]
+#![allow(clippy::new_without_default)]
+#![allow(clippy::trivially_copy_pass_by_ref)]
+#![allow(clippy::useless_let_if_seq)]
 #![doc(html_root_url = "http://nphysics.org/rustdoc/")]
 
 
@@ -105,6 +116,13 @@ extern crate ncollide2d as ncollide;
 #[cfg(feature = "dim3")]
 extern crate ncollide3d as ncollide;
 extern crate num_traits as num;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "serde-serialize")]
+extern crate serde;
+#[cfg(feature = "serde-serialize")]
+#[macro_use]
+extern crate serde_derive;
 extern crate slab;
 
 /*
@@ -1,6 +1,6 @@
 use na::{self, DVectorSlice, DVectorSliceMut, Real};
 
-use math::{Force, Inertia, Isometry, Point, Velocity};
+use math::{Force, Inertia, Isometry, Point, Vector, Velocity};
 use object::{BodyHandle, Ground, Multibody, MultibodyLinkMut, MultibodyLinkRef, RigidBody};
 use solver::IntegrationParameters;
 
@@ -295,6 +295,18 @@ impl<'a, N: Real> BodyPart<'a, N> {
         }
     }
 
+    /// The handle of the body containing this body part.
+    ///
+    /// For a multibody link, this is the handle of the multibody root.
+    #[inline]
+    pub fn parent_handle(&self) -> BodyHandle {
+        match *self {
+            BodyPart::RigidBody(ref rb) => rb.handle(),
+            BodyPart::MultibodyLink(ref mb) => mb.multibody().handle(),
+            BodyPart::Ground(ref g) => g.handle(),
+        }
+    }
+
     /// The number of degrees of freedom of the body containing this body part.
     #[inline]
     pub fn parent_ndofs(&self) -> usize {
@@ -389,6 +401,23 @@ impl<'a, N: Real> BodyPart<'a, N> {
         }
     }
 
+    /// The world-space velocity of the world-space point `point` assumed to be attached to this body part.
+    #[inline]
+    pub fn velocity_at_point(&self, point: &Point<N>) -> Vector<N> {
+        let velocity = self.velocity();
+        let shift = point - self.center_of_mass();
+
+        #[cfg(feature = "dim2")]
+        {
+            velocity.linear + Vector::new(-shift.y, shift.x) * velocity.angular
+        }
+
+        #[cfg(feature = "dim3")]
+        {
+            velocity.linear + velocity.angular.cross(&shift)
+        }
+    }
+
     /// The world-space inertia of this body part.
     #[inline]
     pub fn inertia(&self) -> Inertia<N> {
@@ -485,3 +514,43 @@ impl<'a, N: Real> BodyPartMut<'a, N> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use math::{AngularVector, Isometry, Point, Vector};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn velocity_at_point_matches_finite_differences() {
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = world.add_rigid_body(
+            Isometry::identity(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+
+        {
+            let rb = world.rigid_body_mut(body).unwrap();
+            rb.apply_impulse(Vector::x());
+            rb.apply_angular_impulse(AngularVector::repeat(0.2));
+        }
+
+        let local_point = Point::origin() + Vector::x();
+        let point0 = world.rigid_body(body).unwrap().position() * local_point;
+        let velocity0 = world.body_part(body).velocity_at_point(&point0);
+
+        world.step();
+
+        let point1 = world.rigid_body(body).unwrap().position() * local_point;
+        let velocity1 = world.body_part(body).velocity_at_point(&point1);
+        let finite_difference = (point1 - point0) / world.timestep();
+        let error = finite_difference - (velocity0 + velocity1) * 0.5;
+
+        assert!(velocity0.norm() > 0.5);
+        assert!(error.norm() < 1.0e-2, "Error: {}", error.norm());
+    }
+}
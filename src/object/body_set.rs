@@ -21,23 +21,29 @@ pub(crate) type MultibodiesMut<'a, N> =
     Map<IterMut<'a, Multibody<N>>, fn((usize, &mut Multibody<N>)) -> &mut Multibody<N>>;
 
 /// A unique identifier of a body added to the world.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+///
+/// Each handle carries the generation of the slot it was allocated from, so that a handle
+/// to a removed body does not silently identify another body added afterwards.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct BodyHandle {
     handle: usize,
+    generation: usize,
     reserved: usize, // NOTE: reserved for future use (e.g. for soft bodies).
 }
 
 impl BodyHandle {
-    pub(crate) fn new(handle: usize) -> Self {
+    pub(crate) fn new(handle: usize, generation: usize) -> Self {
         BodyHandle {
-            handle: handle,
+            handle,
+            generation,
             reserved: 0,
         }
     }
 
     /// The unique identifier of the ground.
     pub fn ground() -> Self {
-        Self::new(usize::max_value())
+        Self::new(usize::max_value(), 0)
     }
 
     /// Tests if this handle corresponds to the ground.
@@ -69,6 +75,7 @@ impl BodyId {
 pub struct BodySet<N: Real> {
     ground: Ground<N>,
     ids: Slab<BodyId>,
+    generations: Vec<usize>,
     mbs: Slab<Multibody<N>>,
     rbs: Slab<RigidBody<N>>,
 }
@@ -79,11 +86,46 @@ impl<N: Real> BodySet<N> {
         BodySet {
             ground: Ground::new(),
             ids: Slab::new(),
+            generations: Vec::new(),
             mbs: Slab::new(),
             rbs: Slab::new(),
         }
     }
 
+    fn insert_id(&mut self, id: BodyId) -> BodyHandle {
+        let key = self.ids.insert(id);
+
+        if key == self.generations.len() {
+            self.generations.push(0);
+        }
+
+        BodyHandle::new(key, self.generations[key])
+    }
+
+    fn remove_id(&mut self, key: usize) -> BodyId {
+        // Invalidate all the handles pointing to this slot.
+        self.generations[key] += 1;
+        self.ids.remove(key)
+    }
+
+    fn get_id(&self, body: BodyHandle) -> Option<BodyId> {
+        if self.generations.get(body.handle) == Some(&body.generation) {
+            self.ids.get(body.handle).cloned()
+        } else {
+            None
+        }
+    }
+
+    fn id(&self, body: BodyHandle) -> BodyId {
+        match self.get_id(body) {
+            Some(id) => id,
+            None => panic!(
+                "Invalid body handle {:?}: the body does not exist or has been removed.",
+                body
+            ),
+        }
+    }
+
     /// The number of bodies in this set.
     pub fn len(&self) -> usize {
         self.mbs.len() + self.rbs.len()
@@ -98,7 +140,7 @@ impl<N: Real> BodySet<N> {
             return true;
         }
 
-        let ids = (self.ids[body1.handle], self.ids[body2.handle]);
+        let ids = (self.id(body1), self.id(body2));
         if let (BodyId::MultibodyLinkId(mb1, _), BodyId::MultibodyLinkId(mb2, _)) = ids {
             return mb1 == mb2;
         }
@@ -158,11 +200,10 @@ impl<N: Real> BodySet<N> {
         local_inertia: Inertia<N>,
         local_com: Point<N>,
     ) -> BodyHandle {
-        let rb_entry = self.rbs.vacant_entry();
-        let rb_id = rb_entry.key();
-        let rb_handle = BodyHandle::new(self.ids.insert(BodyId::RigidBodyId(rb_id)));
+        let rb_id = self.rbs.vacant_entry().key();
+        let rb_handle = self.insert_id(BodyId::RigidBodyId(rb_id));
 
-        let _ = rb_entry.insert(RigidBody::new(
+        let _ = self.rbs.insert(RigidBody::new(
             rb_handle,
             position,
             local_inertia,
@@ -182,14 +223,11 @@ impl<N: Real> BodySet<N> {
         local_com: Point<N>,
     ) -> BodyHandle {
         if parent.is_ground() {
-            let mb_entry = self.mbs.vacant_entry();
-            let mb_id = mb_entry.key();
-            let mb_handle = BodyHandle::new(
-                self.ids
-                    .insert(BodyId::MultibodyLinkId(mb_id, MultibodyLinkId::ground())),
-            );
-            let mb = mb_entry.insert(Multibody::new());
-            let id = mb
+            let mb_id = self.mbs.vacant_entry().key();
+            let mb_handle =
+                self.insert_id(BodyId::MultibodyLinkId(mb_id, MultibodyLinkId::ground()));
+            let _ = self.mbs.insert(Multibody::new());
+            let id = self.mbs[mb_id]
                 .add_link(
                     mb_handle,
                     MultibodyLinkId::ground(),
@@ -202,28 +240,24 @@ impl<N: Real> BodySet<N> {
                 .id();
             self.ids[mb_handle.handle] = BodyId::MultibodyLinkId(mb_id, id);
             mb_handle
+        } else if let BodyId::MultibodyLinkId(mb_id, parent_id) = self.id(parent) {
+            let mb_handle =
+                self.insert_id(BodyId::MultibodyLinkId(mb_id, MultibodyLinkId::ground()));
+            let id = self.mbs[mb_id]
+                .add_link(
+                    mb_handle,
+                    parent_id,
+                    joint,
+                    parent_shift,
+                    body_shift,
+                    local_inertia,
+                    local_com,
+                )
+                .id();
+            self.ids[mb_handle.handle] = BodyId::MultibodyLinkId(mb_id, id);
+            mb_handle
         } else {
-            if let BodyId::MultibodyLinkId(mb_id, parent_id) = self.ids[parent.handle] {
-                let mb_handle = BodyHandle::new(
-                    self.ids
-                        .insert(BodyId::MultibodyLinkId(mb_id, MultibodyLinkId::ground())),
-                );
-                let id = self.mbs[mb_id]
-                    .add_link(
-                        mb_handle,
-                        parent_id,
-                        joint,
-                        parent_shift,
-                        body_shift,
-                        local_inertia,
-                        local_com,
-                    )
-                    .id();
-                self.ids[mb_handle.handle] = BodyId::MultibodyLinkId(mb_id, id);
-                mb_handle
-            } else {
-                panic!("Attempting to attach a multibody link to a body that is neither a link nor the ground.")
-            }
+            panic!("Attempting to attach a multibody link to a body that is neither a link nor the ground.")
         }
     }
 
@@ -232,7 +266,7 @@ impl<N: Real> BodySet<N> {
     /// If `body` identify a mutibody link, the whole multibody is removed.
     pub fn remove_body(&mut self, body: BodyHandle) {
         if !body.is_ground() {
-            let body_id = self.ids[body.handle];
+            let body_id = self.id(body);
 
             match body_id {
                 BodyId::MultibodyLinkId(id, _) => {
@@ -243,7 +277,16 @@ impl<N: Real> BodySet<N> {
                 }
             }
 
-            self.ids.retain(|_, id| !id.is_same_body(body_id));
+            let removed: Vec<usize> = self
+                .ids
+                .iter()
+                .filter(|e| e.1.is_same_body(body_id))
+                .map(|e| e.0)
+                .collect();
+
+            for key in removed {
+                let _ = self.remove_id(key);
+            }
         }
     }
 
@@ -254,12 +297,15 @@ impl<N: Real> BodySet<N> {
     /// a free joint attached to the ground.
     pub fn remove_multibody_links(&mut self, body_parts: &[BodyHandle]) {
         if !body_parts.is_empty() {
-            if let BodyId::MultibodyLinkId(parent_id, _) = self.ids[body_parts[0].handle] {
+            if let BodyId::MultibodyLinkId(parent_id, _) = self.id(body_parts[0]) {
                 let mb = self.mbs.remove(parent_id);
                 let mut links = Vec::with_capacity(body_parts.len());
 
                 for part in body_parts {
-                    if let BodyId::MultibodyLinkId(mb_id, id) = self.ids.remove(part.handle) {
+                    // Panics if the handle is stale.
+                    let _ = self.id(*part);
+
+                    if let BodyId::MultibodyLinkId(mb_id, id) = self.remove_id(part.handle) {
                         if mb_id != parent_id {
                             panic!("Multibody link removal: all multibody link must belong to the same multibody.")
                         }
@@ -286,23 +332,60 @@ impl<N: Real> BodySet<N> {
         }
     }
 
+    /// Remove a multibody link together with all its descendants.
+    pub fn remove_multibody_subtree(&mut self, link: BodyHandle) {
+        let subtree: Vec<BodyHandle> = {
+            let link = self
+                .multibody_link(link)
+                .expect("Multibody subtree removal: the body must identify a multibody link.");
+            let mb = link.multibody();
+            mb.subtree(link.id())
+                .into_iter()
+                .map(|id| mb.link(id).handle())
+                .collect()
+        };
+
+        self.remove_multibody_links(&subtree)
+    }
+
+    /// Detach a multibody link together with all its descendants from their multibody.
+    ///
+    /// The detached link becomes the root of a new multibody and its joint is replaced by a free
+    /// joint attached to the ground. The handles of all the links are left unchanged.
+    pub fn detach_multibody_link(&mut self, link: BodyHandle) {
+        if let BodyId::MultibodyLinkId(mb_id, link_id) = self.id(link) {
+            let mb = self.mbs.remove(mb_id);
+
+            for mb in mb.detach_link(link_id) {
+                let mb_key = self.mbs.insert(mb);
+
+                for link in self.mbs[mb_key].links() {
+                    self.ids[link.handle().handle] = BodyId::MultibodyLinkId(mb_key, link.id());
+                }
+            }
+        } else {
+            panic!("Multibody link detachment: the body must identify a multibody link.")
+        }
+    }
+
     /// Checks that the given handle identifies a valid body part.
     ///
-    /// Returns `true` if `body.is_ground()` too.
+    /// Returns `true` if `body.is_ground()` too. Returns `false` if `body` identifies a body part
+    /// that has been removed, even if its slot has been reused by another body part since.
     pub fn contains(&self, body: BodyHandle) -> bool {
         // FIXME: do we have to take body.reserved into account?
-        body.is_ground() || self.ids.contains(body.handle)
+        body.is_ground() || self.get_id(body).is_some()
     }
 
     /// Reference to the body identified by `body`.
     ///
-    /// Panics if the body part is not found.
+    /// Panics if the body part is not found or has been removed.
     #[inline]
     pub fn body(&self, body: BodyHandle) -> Body<N> {
         if body.is_ground() {
             Body::Ground(&self.ground)
         } else {
-            match self.ids[body.handle] {
+            match self.id(body) {
                 BodyId::MultibodyLinkId(mb_id, _) => Body::Multibody(&self.mbs[mb_id]),
                 BodyId::RigidBodyId(id) => Body::RigidBody(&self.rbs[id]),
             }
@@ -311,13 +394,13 @@ impl<N: Real> BodySet<N> {
 
     /// Mutable reference to the body identified by `body`.
     ///
-    /// Panics if the body part is not found.
+    /// Panics if the body part is not found or has been removed.
     #[inline]
     pub fn body_mut(&mut self, body: BodyHandle) -> BodyMut<N> {
         if body.is_ground() {
             BodyMut::Ground(&mut self.ground)
         } else {
-            match self.ids[body.handle] {
+            match self.id(body) {
                 BodyId::MultibodyLinkId(mb_id, _) => BodyMut::Multibody(&mut self.mbs[mb_id]),
                 BodyId::RigidBodyId(id) => BodyMut::RigidBody(&mut self.rbs[id]),
             }
@@ -326,13 +409,13 @@ impl<N: Real> BodySet<N> {
 
     /// Reference to the body part identified by `body`.
     ///
-    /// Panics if the body part is not found.
+    /// Panics if the body part is not found or has been removed.
     #[inline]
     pub fn body_part(&self, body: BodyHandle) -> BodyPart<N> {
         if body.is_ground() {
             BodyPart::Ground(&self.ground)
         } else {
-            match self.ids[body.handle] {
+            match self.id(body) {
                 BodyId::MultibodyLinkId(mb_id, link_id) => {
                     BodyPart::MultibodyLink(self.mbs[mb_id].link(link_id))
                 }
@@ -343,13 +426,13 @@ impl<N: Real> BodySet<N> {
 
     /// Mutable reference to the body part identified by `body`.
     ///
-    /// Panics if the body part is not found.
+    /// Panics if the body part is not found or has been removed.
     #[inline]
     pub fn body_part_mut(&mut self, body: BodyHandle) -> BodyPartMut<N> {
         if body.is_ground() {
             BodyPartMut::Ground(&mut self.ground)
         } else {
-            match self.ids[body.handle] {
+            match self.id(body) {
                 BodyId::MultibodyLinkId(mb_id, link_id) => {
                     BodyPartMut::MultibodyLink(self.mbs[mb_id].link_mut(link_id))
                 }
@@ -363,7 +446,7 @@ impl<N: Real> BodySet<N> {
     /// Returns `None` if it is not found or does not identify a multibody link.
     #[inline]
     pub fn multibody(&self, body: BodyHandle) -> Option<&Multibody<N>> {
-        if let Some(&BodyId::MultibodyLinkId(mb_id, _)) = self.ids.get(body.handle) {
+        if let Some(BodyId::MultibodyLinkId(mb_id, _)) = self.get_id(body) {
             Some(&self.mbs[mb_id])
         } else {
             None
@@ -375,7 +458,7 @@ impl<N: Real> BodySet<N> {
     /// Returns `None` if it is not found or does not identify a multibody link.
     #[inline]
     pub fn multibody_mut(&mut self, body: BodyHandle) -> Option<&mut Multibody<N>> {
-        if let Some(&BodyId::MultibodyLinkId(mb_id, _)) = self.ids.get(body.handle) {
+        if let Some(BodyId::MultibodyLinkId(mb_id, _)) = self.get_id(body) {
             Some(&mut self.mbs[mb_id])
         } else {
             None
@@ -387,7 +470,7 @@ impl<N: Real> BodySet<N> {
     /// Returns `None` if it is not found or does not identify a multibody link.
     #[inline]
     pub fn multibody_link(&self, body: BodyHandle) -> Option<MultibodyLinkRef<N>> {
-        if let Some(&BodyId::MultibodyLinkId(mb_id, link_id)) = self.ids.get(body.handle) {
+        if let Some(BodyId::MultibodyLinkId(mb_id, link_id)) = self.get_id(body) {
             Some(self.mbs[mb_id].link(link_id))
         } else {
             None
@@ -399,7 +482,7 @@ impl<N: Real> BodySet<N> {
     /// Returns `None` if it is not found or does not identify a multibody link.
     #[inline]
     pub fn multibody_link_mut(&mut self, body: BodyHandle) -> Option<MultibodyLinkMut<N>> {
-        if let Some(&BodyId::MultibodyLinkId(mb_id, link_id)) = self.ids.get(body.handle) {
+        if let Some(BodyId::MultibodyLinkId(mb_id, link_id)) = self.get_id(body) {
             Some(self.mbs[mb_id].link_mut(link_id))
         } else {
             None
@@ -411,7 +494,7 @@ impl<N: Real> BodySet<N> {
     /// Returns `None` if it is not found or does not identify a rigid body.
     #[inline]
     pub fn rigid_body(&self, body: BodyHandle) -> Option<&RigidBody<N>> {
-        if let Some(&BodyId::RigidBodyId(id)) = self.ids.get(body.handle) {
+        if let Some(BodyId::RigidBodyId(id)) = self.get_id(body) {
             Some(&self.rbs[id])
         } else {
             None
@@ -423,7 +506,7 @@ impl<N: Real> BodySet<N> {
     /// Returns `None` if it is not found or does not identify a rigid body.
     #[inline]
     pub fn rigid_body_mut(&mut self, body: BodyHandle) -> Option<&mut RigidBody<N>> {
-        if let Some(&BodyId::RigidBodyId(id)) = self.ids.get(body.handle) {
+        if let Some(BodyId::RigidBodyId(id)) = self.get_id(body) {
             Some(&mut self.rbs[id])
         } else {
             None
@@ -493,7 +576,7 @@ impl<'a, N: Real> Iterator for Bodies<'a, N> {
             return Some(Body::Multibody(res.1));
         }
 
-        return None;
+        None
     }
 }
 
@@ -517,3 +600,34 @@ impl<'a, N: Real> Iterator for BodiesMut<'a, N> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use ncollide::shape::{Ball, ShapeHandle};
+
+    use math::Isometry;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn removed_body_handle_is_not_reused() {
+        let mut world = World::<f32>::new();
+        let ball = ShapeHandle::new(Ball::new(0.5));
+        let old = world.add_rigid_body(
+            Isometry::identity(),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+        world.remove_bodies(&[old]);
+
+        let new = world.add_rigid_body(
+            Isometry::identity(),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+
+        assert!(old != new);
+        assert!(world.rigid_body(old).is_none());
+        assert!(world.rigid_body(new).is_some());
+    }
+}
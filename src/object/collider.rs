@@ -27,6 +27,7 @@ pub struct ColliderData<N: Real> {
     body_status_dependent_ndofs: usize,
     position_wrt_body: Isometry<N>,
     material: Material<N>,
+    is_sensor: bool,
 }
 
 impl<N: Real> ColliderData<N> {
@@ -44,6 +45,7 @@ impl<N: Real> ColliderData<N> {
             body_status_dependent_ndofs,
             position_wrt_body,
             material,
+            is_sensor: false,
         }
     }
 
@@ -71,6 +73,25 @@ impl<N: Real> ColliderData<N> {
         &self.material
     }
 
+    /// Sets the material of this collider.
+    #[inline]
+    pub fn set_material(&mut self, material: Material<N>) {
+        self.material = material
+    }
+
+    /// Whether this collider is a sensor.
+    ///
+    /// Sensors only generate proximity events and never generate contacts.
+    #[inline]
+    pub fn is_sensor(&self) -> bool {
+        self.is_sensor
+    }
+
+    #[inline]
+    pub(crate) fn set_sensor(&mut self, is_sensor: bool) {
+        self.is_sensor = is_sensor
+    }
+
     #[inline]
     pub(crate) fn body_status_dependent_ndofs(&self) -> usize {
         self.body_status_dependent_ndofs
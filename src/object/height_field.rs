@@ -0,0 +1,265 @@
+use alga::general::SupersetOf;
+#[cfg(feature = "dim3")]
+use na::{DMatrix, Point3};
+#[cfg(feature = "dim2")]
+use na::DVector;
+use na::{self, Real, Unit};
+#[cfg(feature = "dim2")]
+use ncollide::shape::Polyline;
+use ncollide::shape::ShapeHandle;
+#[cfg(feature = "dim3")]
+use ncollide::shape::TriMesh;
+
+use math::{Point, Vector};
+
+/// A static terrain described by the heights of a regular grid.
+///
+/// The grid is centered at the origin of the local frame of the height field and spans
+/// `scale.x` along the `x` axis (and `scale.z` along the `z` axis in 3D). The heights are
+/// multiplied by `scale.y` and measured along the `y` axis. Height fields are meant to be
+/// attached to the ground with `World::add_height_field`: they have an infinite mass and cannot
+/// move.
+///
+/// The surface is triangulated with two triangles per grid cell in 3D, and one segment per cell
+/// in 2D. Contacts are computed with the cells below the colliding shapes, so their normals are
+/// the precise normals of the corresponding triangles (or segments).
+#[derive(Clone, Debug)]
+pub struct HeightField<N: Real> {
+    #[cfg(feature = "dim3")]
+    heights: DMatrix<N>,
+    #[cfg(feature = "dim2")]
+    heights: DVector<N>,
+    scale: Vector<N>,
+}
+
+#[cfg(feature = "dim3")]
+impl<N: Real> HeightField<N> {
+    /// Creates a height field from a grid of heights.
+    ///
+    /// The rows of `heights` are along the `z` axis and its columns along the `x` axis. There
+    /// must be at least two rows and two columns.
+    pub fn new(heights: DMatrix<N>, scale: Vector<N>) -> Self {
+        assert!(
+            heights.nrows() > 1 && heights.ncols() > 1,
+            "A height field must have at least two rows and two columns."
+        );
+
+        HeightField { heights, scale }
+    }
+
+    /// The heights of this height field, before scaling.
+    pub fn heights(&self) -> &DMatrix<N> {
+        &self.heights
+    }
+
+    /// The row and the column of the grid cell containing the projection of `point` on the
+    /// `xz` plane, if any.
+    pub fn cell_at(&self, point: &Point<N>) -> Option<(usize, usize)> {
+        let (i, _) = cell_coordinate(point.z, self.scale.z, self.heights.nrows())?;
+        let (j, _) = cell_coordinate(point.x, self.scale.x, self.heights.ncols())?;
+
+        Some((i, j))
+    }
+
+    /// The height of the surface right above or below `point`, if it projects inside of the grid.
+    pub fn height_at(&self, point: &Point<N>) -> Option<N> {
+        let (a, b, c) = self.triangle_at(point)?;
+
+        // Interpolate the height on the plane of the triangle.
+        let n = (b - a).cross(&(c - a));
+        Some(a.y - (n.x * (point.x - a.x) + n.z * (point.z - a.z)) / n.y)
+    }
+
+    /// The normal of the surface right above or below `point`, if it projects inside of the grid.
+    pub fn normal_at(&self, point: &Point<N>) -> Option<Unit<Vector<N>>> {
+        let (a, b, c) = self.triangle_at(point)?;
+        Unit::try_new((b - a).cross(&(c - a)), N::zero())
+    }
+
+    /// The shape of this height field, as a triangle mesh.
+    pub fn to_shape(&self) -> ShapeHandle<N> {
+        let (nrows, ncols) = self.heights.shape();
+        let mut vertices = Vec::with_capacity(nrows * ncols);
+        let mut indices = Vec::with_capacity((nrows - 1) * (ncols - 1) * 2);
+
+        for i in 0..nrows {
+            for j in 0..ncols {
+                vertices.push(self.vertex(i, j));
+            }
+        }
+
+        for i in 0..nrows - 1 {
+            for j in 0..ncols - 1 {
+                let id = |i, j| i * ncols + j;
+                indices.push(Point3::new(id(i, j), id(i + 1, j), id(i + 1, j + 1)));
+                indices.push(Point3::new(id(i, j), id(i + 1, j + 1), id(i, j + 1)));
+            }
+        }
+
+        ShapeHandle::new(TriMesh::new(vertices, indices, None))
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Point<N> {
+        let (nrows, ncols) = self.heights.shape();
+
+        Point::new(
+            sample_coordinate(j, self.scale.x, ncols),
+            self.heights[(i, j)] * self.scale.y,
+            sample_coordinate(i, self.scale.z, nrows),
+        )
+    }
+
+    // The triangle of the grid cell containing the projection of `point`, with an upward normal.
+    fn triangle_at(&self, point: &Point<N>) -> Option<(Point<N>, Point<N>, Point<N>)> {
+        let (i, v) = cell_coordinate(point.z, self.scale.z, self.heights.nrows())?;
+        let (j, u) = cell_coordinate(point.x, self.scale.x, self.heights.ncols())?;
+
+        // Each cell is split along its diagonal from `(i, j)` to `(i + 1, j + 1)`.
+        if v >= u {
+            Some((self.vertex(i, j), self.vertex(i + 1, j), self.vertex(i + 1, j + 1)))
+        } else {
+            Some((self.vertex(i, j), self.vertex(i + 1, j + 1), self.vertex(i, j + 1)))
+        }
+    }
+}
+
+#[cfg(feature = "dim2")]
+impl<N: Real> HeightField<N> {
+    /// Creates a height field from a row of heights along the `x` axis.
+    ///
+    /// There must be at least two heights.
+    pub fn new(heights: DVector<N>, scale: Vector<N>) -> Self {
+        assert!(
+            heights.len() > 1,
+            "A height field must have at least two heights."
+        );
+
+        HeightField { heights, scale }
+    }
+
+    /// The heights of this height field, before scaling.
+    pub fn heights(&self) -> &DVector<N> {
+        &self.heights
+    }
+
+    /// The index of the grid cell containing the projection of `point` on the `x` axis, if any.
+    pub fn cell_at(&self, point: &Point<N>) -> Option<usize> {
+        cell_coordinate(point.x, self.scale.x, self.heights.len()).map(|(i, _)| i)
+    }
+
+    /// The height of the surface right above or below `point`, if it projects inside of the grid.
+    pub fn height_at(&self, point: &Point<N>) -> Option<N> {
+        let (i, t) = cell_coordinate(point.x, self.scale.x, self.heights.len())?;
+        let (a, b) = (self.vertex(i), self.vertex(i + 1));
+
+        Some(a.y * (N::one() - t) + b.y * t)
+    }
+
+    /// The normal of the surface right above or below `point`, if it projects inside of the grid.
+    pub fn normal_at(&self, point: &Point<N>) -> Option<Unit<Vector<N>>> {
+        let i = self.cell_at(point)?;
+        let dir = self.vertex(i + 1) - self.vertex(i);
+
+        Unit::try_new(Vector::new(-dir.y, dir.x), N::zero())
+    }
+
+    /// The shape of this height field, as a polyline.
+    pub fn to_shape(&self) -> ShapeHandle<N> {
+        let vertices = (0..self.heights.len()).map(|i| self.vertex(i)).collect();
+        ShapeHandle::new(Polyline::new(vertices))
+    }
+
+    fn vertex(&self, i: usize) -> Point<N> {
+        Point::new(
+            sample_coordinate(i, self.scale.x, self.heights.len()),
+            self.heights[i] * self.scale.y,
+        )
+    }
+}
+
+impl<N: Real> HeightField<N> {
+    /// The scaling factors of this height field.
+    pub fn scale(&self) -> &Vector<N> {
+        &self.scale
+    }
+}
+
+// The coordinate of the `i`-th of `nsamples` samples regularly spaced on an interval of length
+// `extent` centered at zero.
+fn sample_coordinate<N: Real>(i: usize, extent: N, nsamples: usize) -> N {
+    let step = extent / na::convert((nsamples - 1) as f64);
+    step * na::convert(i as f64) - extent * na::convert(0.5f64)
+}
+
+// The index of the cell containing `coord` among the cells between `nsamples` samples regularly
+// spaced on an interval of length `extent` centered at zero, and the position of `coord` in this
+// cell, in `[0, 1]`.
+fn cell_coordinate<N: Real>(coord: N, extent: N, nsamples: usize) -> Option<(usize, N)> {
+    let ncells = nsamples - 1;
+    let t = (coord / extent + na::convert(0.5f64)) * na::convert(ncells as f64);
+
+    if t < N::zero() || t > na::convert(ncells as f64) {
+        return None;
+    }
+
+    // Points on the last sample belong to the last cell.
+    let cell = na::inf(&t.floor(), &na::convert((ncells - 1) as f64));
+    let i = SupersetOf::<f64>::to_subset(&cell).unwrap_or(0.0) as usize;
+
+    Some((i, t - cell))
+}
+
+#[cfg(all(test, feature = "dim3"))]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::HeightField;
+    use math::{Isometry, Vector};
+    use object::Material;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn box_rests_on_a_height_field_along_its_normal() {
+        use na::{DMatrix, Point3, Vector3};
+
+        // A surface rising along `x` with a slope of `0.25`, and which is flat along `z`.
+        let heights = DMatrix::from_fn(5, 9, |_, j| j as f32 * 0.25);
+        let height_field = HeightField::new(heights, Vector3::new(8.0, 1.0, 4.0));
+
+        let point = Point3::new(0.5, 10.0, 0.3);
+        assert_eq!(height_field.cell_at(&point), Some((2, 4)));
+        assert!((height_field.height_at(&point).unwrap() - 1.125).abs() < 1.0e-5);
+        let normal = height_field.normal_at(&point).unwrap();
+        assert!((normal.into_inner() - Vector3::new(-0.25, 1.0, 0.0).normalize()).norm() < 1.0e-5);
+        assert!(height_field.height_at(&Point3::new(4.5, 0.0, 0.0)).is_none());
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let mut material = Material::default();
+        material.friction = 1.0;
+        let _ = world.add_height_field(0.01, &height_field, Isometry::identity(), material.clone());
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.2)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::new(0.5, 1.5, 0.3), na::zero()),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, cuboid, body, Isometry::identity(), material);
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        // The box lies flat on the slope without sliding down.
+        let center = Point3::from(
+            world.rigid_body(body).unwrap().position().translation.vector,
+        );
+        let surface = Point3::new(center.x, height_field.height_at(&center).unwrap(), center.z);
+        let distance = normal.dot(&(center - surface));
+        assert!((distance - 0.2).abs() < 2.0e-2, "Distance to the surface: {}", distance);
+        assert!((center.x - 0.5).abs() < 0.2 && (center.z - 0.3).abs() < 1.0e-2);
+    }
+}
@@ -1,17 +1,105 @@
-use na::{self, Real};
+use na::{self, Real, Unit};
+
+use math::{Isometry, Vector};
 
 /// Description of the state of surface of a solid.
 ///
 /// Strictly speaking, the coefficient provided here only exist
 /// when considering a pair of touching surfaces. In practive, nphysics
-/// will average the coefficient of the two surfaces in contact in order
-/// to deduce the restitution/friction coefficient.
+/// will combine the coefficient of the two surfaces in contact in order
+/// to deduce the restitution/friction coefficient. By default, they are averaged.
 #[derive(Clone)]
 pub struct Material<N: Real> {
     /// Restitution coefficient of the surface.
     pub restitution: N,
     /// Friction coefficient of the surface.
+    ///
+    /// Ignored if `anisotropic_friction` is set.
     pub friction: N,
+    /// Direction-dependent friction coefficients of the surface, if any.
+    pub anisotropic_friction: Option<AnisotropicFriction<N>>,
+    /// Rolling friction coefficient of the surface (default: `0`).
+    ///
+    /// The maximum torque resisting rolling is this coefficient times the normal force.
+    pub rolling_friction: N,
+    /// Spinning friction coefficient of the surface (default: `0`).
+    ///
+    /// The maximum torque resisting spinning around the contact normal is this coefficient
+    /// times the normal force. This has no effect in 2D.
+    pub spinning_friction: N,
+    /// How the restitution coefficients of the two surfaces in contact are combined (default: `Average`).
+    pub restitution_combine_mode: RestitutionCombineMode,
+    /// How the friction coefficients of the two surfaces in contact are combined (default: `Average`).
+    ///
+    /// This applies to the sliding, rolling, and spinning friction coefficients.
+    pub friction_combine_mode: FrictionCombineMode,
+}
+
+/// Rule used to deduce the restitution or friction coefficient of a pair of surfaces in contact.
+///
+/// If the two surfaces use different rules, the one that appears last in this enumeration wins.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CombineMode {
+    /// The mean of the two coefficients.
+    Average,
+    /// The square root of the product of the two coefficients.
+    GeometricMean,
+    /// The smallest of the two coefficients.
+    Min,
+    /// The product of the two coefficients.
+    Multiply,
+    /// The largest of the two coefficients.
+    Max,
+}
+
+/// Rule used to deduce the restitution coefficient of a pair of surfaces in contact.
+pub type RestitutionCombineMode = CombineMode;
+
+/// Rule used to deduce the friction coefficients of a pair of surfaces in contact.
+pub type FrictionCombineMode = CombineMode;
+
+impl CombineMode {
+    /// Combines the coefficients of two surfaces in contact.
+    pub fn combine<N: Real>(self, a: N, b: N) -> N {
+        match self {
+            CombineMode::Average => (a + b) * na::convert(0.5),
+            CombineMode::GeometricMean => (a * b).sqrt(),
+            CombineMode::Min => a.min(b),
+            CombineMode::Multiply => a * b,
+            CombineMode::Max => a.max(b),
+        }
+    }
+}
+
+impl Default for CombineMode {
+    fn default() -> Self {
+        CombineMode::Average
+    }
+}
+
+/// Friction coefficients depending on the sliding direction.
+///
+/// The friction along a tangent direction interpolates between `friction_along` and
+/// `friction_across` depending on the angle between this tangent and `direction`.
+#[derive(Clone)]
+pub struct AnisotropicFriction<N: Real> {
+    /// The principal friction direction, expressed in the local space of the collider.
+    pub direction: Unit<Vector<N>>,
+    /// Friction coefficient when sliding along `direction`.
+    pub friction_along: N,
+    /// Friction coefficient when sliding orthogonally to `direction`.
+    pub friction_across: N,
+}
+
+impl<N: Real> AnisotropicFriction<N> {
+    /// Initialize anisotropic friction coefficients wrt. the given local direction.
+    pub fn new(direction: Unit<Vector<N>>, friction_along: N, friction_across: N) -> Self {
+        AnisotropicFriction {
+            direction,
+            friction_along,
+            friction_across,
+        }
+    }
 }
 
 impl<N: Real> Material<N> {
@@ -20,8 +108,91 @@ impl<N: Real> Material<N> {
         Material {
             restitution,
             friction,
+            anisotropic_friction: None,
+            rolling_friction: N::zero(),
+            spinning_friction: N::zero(),
+            restitution_combine_mode: CombineMode::default(),
+            friction_combine_mode: CombineMode::default(),
         }
     }
+
+    /// Initialize a material with the specified restitution and anisotropic friction coefficients.
+    pub fn new_anisotropic(restitution: N, friction: AnisotropicFriction<N>) -> Self {
+        Material {
+            restitution,
+            friction: (friction.friction_along + friction.friction_across) * na::convert(0.5),
+            anisotropic_friction: Some(friction),
+            rolling_friction: N::zero(),
+            spinning_friction: N::zero(),
+            restitution_combine_mode: CombineMode::default(),
+            friction_combine_mode: CombineMode::default(),
+        }
+    }
+
+    /// The principal friction direction in world-space, if this material is anisotropic.
+    ///
+    /// The `position` is the world-space position of the collider this material is attached to.
+    #[inline]
+    pub fn friction_direction(&self, position: &Isometry<N>) -> Option<Vector<N>> {
+        self.anisotropic_friction
+            .as_ref()
+            .map(|aniso| position * aniso.direction.into_inner())
+    }
+
+    /// The friction coefficient of this material along the world-space unit tangent `dir`.
+    ///
+    /// The `position` is the world-space position of the collider this material is attached to.
+    pub fn friction_along(&self, position: &Isometry<N>, dir: &Vector<N>) -> N {
+        match self.anisotropic_friction {
+            Some(ref aniso) => {
+                let world_dir = position * aniso.direction.into_inner();
+                let cos = world_dir.dot(dir);
+                let cos2 = cos * cos;
+                aniso.friction_along * cos2 + aniso.friction_across * (N::one() - cos2)
+            }
+            None => self.friction,
+        }
+    }
+
+    /// The restitution coefficient of the pair of surfaces made of `self` and `other`.
+    pub fn combined_restitution(&self, other: &Material<N>) -> N {
+        let mode = self
+            .restitution_combine_mode
+            .max(other.restitution_combine_mode);
+        mode.combine(self.restitution, other.restitution)
+    }
+
+    /// The friction coefficient, along the world-space unit tangent `dir`, of the pair of surfaces
+    /// made of `self` (attached to a collider at `position`) and `other` (attached to a collider at
+    /// `other_position`).
+    pub fn combined_friction_along(
+        &self,
+        position: &Isometry<N>,
+        other: &Material<N>,
+        other_position: &Isometry<N>,
+        dir: &Vector<N>,
+    ) -> N {
+        self.combine_friction(
+            other,
+            self.friction_along(position, dir),
+            other.friction_along(other_position, dir),
+        )
+    }
+
+    /// The rolling friction coefficient of the pair of surfaces made of `self` and `other`.
+    pub fn combined_rolling_friction(&self, other: &Material<N>) -> N {
+        self.combine_friction(other, self.rolling_friction, other.rolling_friction)
+    }
+
+    /// The spinning friction coefficient of the pair of surfaces made of `self` and `other`.
+    pub fn combined_spinning_friction(&self, other: &Material<N>) -> N {
+        self.combine_friction(other, self.spinning_friction, other.spinning_friction)
+    }
+
+    fn combine_friction(&self, other: &Material<N>, a: N, b: N) -> N {
+        let mode = self.friction_combine_mode.max(other.friction_combine_mode);
+        mode.combine(a, b)
+    }
 }
 
 impl<N: Real> Default for Material<N> {
@@ -29,3 +200,231 @@ impl<N: Real> Default for Material<N> {
         Material::new(N::zero(), na::convert(0.5))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Ball, Cuboid, ShapeHandle};
+
+    use super::{
+        AnisotropicFriction, CombineMode, FrictionCombineMode, Material, RestitutionCombineMode,
+    };
+    use math::{Isometry, Vector};
+    use object::BodyHandle;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn anisotropic_friction_interpolates_with_the_squared_cosine() {
+        let friction = AnisotropicFriction::new(Vector::x_axis(), 1.0f32, 0.2);
+        let material = Material::new_anisotropic(0.0, friction);
+        let pos = Isometry::identity();
+
+        assert_relative_eq!(material.friction_along(&pos, &Vector::x()), 1.0);
+        assert_relative_eq!(material.friction_along(&pos, &-Vector::x()), 1.0);
+        assert_relative_eq!(material.friction_along(&pos, &Vector::y()), 0.2);
+
+        // At 45 degrees, cos² = 1/2 so both coefficients contribute equally.
+        let diagonal = (Vector::x() + Vector::y()).normalize();
+        assert_relative_eq!(material.friction_along(&pos, &diagonal), 0.6, epsilon = 1.0e-6);
+    }
+
+    #[test]
+    fn restitution_combine_mode_changes_bounce_height() {
+        let bounce_height = |mode: RestitutionCombineMode| {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+
+            let mut rigid = Material::new(0.2, 0.0);
+            rigid.restitution_combine_mode = mode;
+            let mut bouncy = Material::new(0.8, 0.0);
+            bouncy.restitution_combine_mode = mode;
+
+            let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = world.add_collider(
+                0.01,
+                floor,
+                BodyHandle::ground(),
+                Isometry::new(Vector::y() * -10.0, na::zero()),
+                rigid,
+            );
+
+            let ball = ShapeHandle::new(Ball::new(0.5));
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::y() * 3.0, na::zero()),
+                ball.inertia(1.0),
+                ball.center_of_mass(),
+            );
+            let _ = world.add_collider(0.01, ball, body, Isometry::identity(), bouncy);
+
+            let mut bounced = false;
+            let mut max_height = 0.0f32;
+
+            for _ in 0..120 {
+                world.step();
+                let rb = world.rigid_body(body).unwrap();
+                bounced = bounced || rb.velocity().linear.y > 0.0;
+
+                if bounced {
+                    max_height = max_height.max(rb.center_of_mass().coords.y);
+                }
+            }
+
+            max_height
+        };
+
+        let average = bounce_height(CombineMode::Average);
+        let min = bounce_height(CombineMode::Min);
+        let multiply = bounce_height(CombineMode::Multiply);
+        let max = bounce_height(CombineMode::Max);
+
+        assert!(max > average, "{} <= {}", max, average);
+        assert!(average > min, "{} <= {}", average, min);
+        assert!(min > multiply, "{} <= {}", min, multiply);
+    }
+
+    #[test]
+    fn coulomb_friction_stops_a_sliding_box() {
+        let sliding_distance = |friction1: f32, friction2: f32, mode: CombineMode| {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+
+            let mut floor_material = Material::new(0.0, friction1);
+            floor_material.friction_combine_mode = mode;
+            let mut box_material = Material::new(0.0, friction2);
+            box_material.friction_combine_mode = mode;
+
+            let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = world.add_collider(
+                0.01,
+                floor,
+                BodyHandle::ground(),
+                Isometry::new(Vector::y() * -10.0, na::zero()),
+                floor_material,
+            );
+
+            let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::y() * 0.5, na::zero()),
+                cuboid.inertia(1.0),
+                cuboid.center_of_mass(),
+            );
+            let _ = world.add_collider(0.01, cuboid, body, Isometry::identity(), box_material);
+
+            world.step();
+            world.rigid_body_mut(body).unwrap().set_linear_velocity(Vector::x() * 3.0);
+
+            for _ in 0..120 {
+                world.step();
+            }
+
+            let rb = world.rigid_body(body).unwrap();
+            assert!(rb.velocity().linear.x.abs() < 1.0e-2);
+            rb.position().translation.vector.x
+        };
+
+        // The stopping distance is `v0² / (2 * μ * g)`.
+        let average = sliding_distance(0.1, 0.9, CombineMode::Average);
+        let geometric = sliding_distance(0.1, 0.9, CombineMode::GeometricMean);
+        assert!((average - 9.0 / (2.0 * 0.5 * 9.81)).abs() < 0.15, "Distance: {}", average);
+        assert!((geometric - 9.0 / (2.0 * 0.3 * 9.81)).abs() < 0.2, "Distance: {}", geometric);
+    }
+
+    #[test]
+    fn anisotropic_friction_depends_on_sliding_direction() {
+        let sliding_distance = |principal_dir: Vector<f32>| {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+
+            let dir = na::Unit::new_normalize(principal_dir);
+            let friction = AnisotropicFriction::new(dir, 0.1, 0.9);
+            let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = world.add_collider(
+                0.01,
+                floor,
+                BodyHandle::ground(),
+                Isometry::new(Vector::y() * -10.0, na::zero()),
+                Material::new_anisotropic(0.0, friction),
+            );
+
+            let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::y() * 0.5, na::zero()),
+                cuboid.inertia(1.0),
+                cuboid.center_of_mass(),
+            );
+            let material = Material::new(0.0, 0.1);
+            let _ = world.add_collider(0.01, cuboid, body, Isometry::identity(), material);
+
+            world.step();
+            world.rigid_body_mut(body).unwrap().set_linear_velocity(Vector::x() * 2.0);
+
+            for _ in 0..180 {
+                world.step();
+            }
+
+            world.rigid_body(body).unwrap().position().translation.vector.x
+        };
+
+        // The combined coefficients are `(0.1 + 0.1) / 2` along the principal direction,
+        // and `(0.9 + 0.1) / 2` across.
+        let along = sliding_distance(Vector::x());
+        // In 3D, the principal direction must lie on the floor: if it matched the contact
+        // normal the tangent basis would be arbitrary.
+        #[cfg(feature = "dim2")]
+        let across = sliding_distance(Vector::y());
+        #[cfg(feature = "dim3")]
+        let across = sliding_distance(Vector::z());
+        assert!((along - 4.0 / (2.0 * 0.1 * 9.81)).abs() < 0.2, "Distance: {}", along);
+        assert!((across - 4.0 / (2.0 * 0.5 * 9.81)).abs() < 0.1, "Distance: {}", across);
+    }
+
+    #[test]
+    fn friction_combine_mode_changes_sliding_distance() {
+        let sliding_distance = |mode: FrictionCombineMode| {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+
+            let mut rough = Material::new(0.0, 1.0);
+            rough.friction_combine_mode = mode;
+            let mut slippery = Material::new(0.0, 0.1);
+            slippery.friction_combine_mode = mode;
+
+            let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = world.add_collider(
+                0.01,
+                floor,
+                BodyHandle::ground(),
+                Isometry::new(Vector::y() * -10.0, na::zero()),
+                rough,
+            );
+
+            let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::y() * 0.5, na::zero()),
+                cube.inertia(1.0),
+                cube.center_of_mass(),
+            );
+            let _ = world.add_collider(0.01, cube, body, Isometry::identity(), slippery);
+            world
+                .rigid_body_mut(body)
+                .unwrap()
+                .set_linear_velocity(Vector::x() * 3.0);
+
+            for _ in 0..240 {
+                world.step();
+            }
+
+            world.rigid_body(body).unwrap().position().translation.vector.x
+        };
+
+        let max = sliding_distance(CombineMode::Max);
+        let average = sliding_distance(CombineMode::Average);
+        let geometric_mean = sliding_distance(CombineMode::GeometricMean);
+        let min = sliding_distance(CombineMode::Min);
+
+        assert!(max < average, "{} >= {}", max, average);
+        assert!(average < geometric_mean, "{} >= {}", average, geometric_mean);
+        assert!(geometric_mean < min, "{} >= {}", geometric_mean, min);
+    }
+}
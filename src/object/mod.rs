@@ -1,15 +1,23 @@
 //! Objects that may be added to the physical world.
 
-pub use self::material::Material;
+pub use self::material::{
+    AnisotropicFriction, CombineMode, FrictionCombineMode, Material, RestitutionCombineMode,
+};
 
 pub use self::body::{ActivationStatus, Body, BodyMut, BodyPart, BodyPartMut, BodyStatus};
 pub use self::body_set::{Bodies, BodyHandle, BodySet};
 pub use self::collider::{Collider, ColliderData, ColliderHandle, Colliders, Sensor, SensorHandle};
 pub use self::ground::Ground;
-pub use self::multibody::{Multibody, MultibodyLinks, MultibodyWorkspace};
+pub use self::height_field::HeightField;
+pub use self::multibody::{IKResult, JointCoupling, Multibody, MultibodyLinks,
+                          MultibodyWorkspace};
 pub(crate) use self::multibody_link::{MultibodyLink, MultibodyLinkVec};
 pub use self::multibody_link::{MultibodyLinkId, MultibodyLinkMut, MultibodyLinkRef};
 pub use self::rigid_body::RigidBody;
+#[cfg(feature = "dim3")]
+pub use self::tri_mesh_body::TriMeshBody;
+#[cfg(feature = "dim3")]
+pub use self::voxel_shape::VoxelShape;
 
 mod material;
 
@@ -17,6 +25,11 @@ mod body;
 mod body_set;
 mod collider;
 mod ground;
+mod height_field;
 mod multibody;
 mod multibody_link;
 mod rigid_body;
+#[cfg(feature = "dim3")]
+mod tri_mesh_body;
+#[cfg(feature = "dim3")]
+mod voxel_shape;
@@ -4,7 +4,7 @@ use std::ops::Range;
 use joint::{FreeJoint, Joint};
 use math::{
     AngularDim, Dim, Force, Inertia, Isometry, Jacobian, Point, SpatialMatrix, SpatialVector,
-    Vector, Velocity, DIM,
+    Vector, Velocity, DIM, SPATIAL_DIM,
 };
 use na::{self, DMatrix, DVector, DVectorSlice, DVectorSliceMut, Dynamic, MatrixMN, Real, LU};
 use object::{
@@ -12,17 +12,30 @@ use object::{
     MultibodyLinkRef, MultibodyLinkVec,
 };
 use solver::{
-    ConstraintSet, IntegrationParameters, MultibodyJointLimitsNonlinearConstraintGenerator,
+    BilateralGroundConstraint, ConstraintSet, ImpulseLimits, IntegrationParameters,
+    MultibodyJointLimitsNonlinearConstraintGenerator,
 };
 use utils::{GeneralizedCross, IndexMut2};
 
 /// An articulated body simulated using the reduced-coordinates approach.
+///
+/// The links form a kinematic tree where each link is attached to its parent by a joint.
+/// The timestep integrates the dynamics with the joint-space augmented mass matrix, and contacts
+/// as well as joint constraints between multibodies are handled by the same constraint solver as
+/// rigid bodies. The unconstrained accelerations can also be computed in linear time with the
+/// articulated body algorithm, see `Multibody::forward_dynamics`.
 pub struct Multibody<N: Real> {
     rbs: MultibodyLinkVec<N>,
     velocities: Vec<N>,
     damping: Vec<N>,
+    // The viscous damping of the joints, refreshed at each timestep.
+    joint_damping: Vec<N>,
     accelerations: Vec<N>,
+    generalized_forces: Vec<N>,
+    persistent_generalized_forces: bool,
+    gravity_compensation: bool,
     impulses: Vec<N>,
+    couplings: Vec<JointCoupling<N>>,
     body_jacobians: Vec<Jacobian<N>>, // FIXME: use sparse matrices.
     augmented_mass: DMatrix<N>,
     inv_augmented_mass: LU<N, Dynamic, Dynamic>,
@@ -32,6 +45,7 @@ pub struct Multibody<N: Real> {
     companion_id: usize,
     unilateral_ground_rng: Range<usize>,
     bilateral_ground_rng: Range<usize>,
+    coupling_rng: Range<usize>,
 
     /*
      * Workspaces.
@@ -48,8 +62,13 @@ impl<N: Real> Multibody<N> {
             rbs: MultibodyLinkVec(Vec::new()),
             velocities: Vec::new(),
             damping: Vec::new(),
+            joint_damping: Vec::new(),
             accelerations: Vec::new(),
+            generalized_forces: Vec::new(),
+            persistent_generalized_forces: false,
+            gravity_compensation: false,
             impulses: Vec::new(),
+            couplings: Vec::new(),
             body_jacobians: Vec::new(),
             augmented_mass: DMatrix::zeros(0, 0),
             inv_augmented_mass: LU::new(DMatrix::zeros(0, 0)),
@@ -59,6 +78,7 @@ impl<N: Real> Multibody<N> {
             companion_id: 0,
             unilateral_ground_rng: 0..0,
             bilateral_ground_rng: 0..0,
+            coupling_rng: 0..0,
             coriolis_v: Vec::new(),
             coriolis_w: Vec::new(),
             i_coriolis_dt: Jacobian::zeros(0),
@@ -132,6 +152,22 @@ impl<N: Real> Multibody<N> {
         self.status = status
     }
 
+    /// Switch this multibody between the kinematic and the dynamic status.
+    ///
+    /// A kinematic multibody moves according to its generalized velocities only: it is neither
+    /// affected by forces nor by constraints, and behaves as if it had an infinite mass when it
+    /// pushes dynamic bodies.
+    #[inline]
+    pub fn set_kinematic(&mut self, kinematic: bool) {
+        if kinematic {
+            self.status = BodyStatus::Kinematic
+        } else {
+            self.status = BodyStatus::Dynamic
+        }
+
+        self.activate();
+    }
+
     /// The companion ID of this multibody.
     #[inline]
     pub fn companion_id(&self) -> usize {
@@ -198,6 +234,165 @@ impl<N: Real> Multibody<N> {
         MultibodyLinkMut::new(rb_id, self)
     }
 
+    /// The vector of generalized coordinates of this multibody.
+    ///
+    /// The coordinates of each link joint are stored in the same order as its generalized
+    /// velocities, see `Joint::generalized_position` for their meaning. The links are ordered by
+    /// increasing link identifier, so each link comes after its parent. This ordering does not
+    /// change unless links are removed from this multibody.
+    pub fn generalized_position(&self) -> DVector<N> {
+        let mut coords = DVector::zeros(self.ndofs);
+
+        for rb in self.rbs.iter() {
+            rb.dof
+                .generalized_position(&mut coords.as_mut_slice()[rb.assembly_id..]);
+        }
+
+        coords
+    }
+
+    /// Sets the generalized coordinates of this multibody and updates the positions of its links.
+    ///
+    /// The joint limits are not enforced by this method.
+    pub fn set_generalized_position(&mut self, coords: &[N]) {
+        for rb in self.rbs.iter_mut() {
+            rb.dof.set_generalized_position(&coords[rb.assembly_id..])
+        }
+
+        self.update_kinematics();
+    }
+
+    /// The world-space positions all the links of this multibody would have if its generalized
+    /// coordinates were `coords`.
+    ///
+    /// The coordinates are ordered like `self.generalized_position()`, and the positions like
+    /// the links, so the position of each link comes after the one of its parent. The state of
+    /// this multibody is left unchanged. Panics if the length of `coords` is not equal to the
+    /// number of degrees of freedom of this multibody.
+    pub fn forward_kinematics(&self, coords: &DVector<N>) -> Vec<Isometry<N>> {
+        assert_eq!(
+            coords.len(),
+            self.ndofs,
+            "Multibody::forward_kinematics: there must be one coordinate per degree of freedom."
+        );
+
+        let mut positions: Vec<Isometry<N>> = Vec::with_capacity(self.rbs.len());
+
+        for (i, rb) in self.rbs.iter().enumerate() {
+            let mut dof = rb.dof.clone_box();
+            dof.set_generalized_position(&coords.as_slice()[rb.assembly_id..]);
+            let local_to_parent = dof.body_to_parent(&rb.parent_shift, &rb.body_shift);
+
+            if i == 0 {
+                positions.push(local_to_parent);
+            } else {
+                let parent_to_world = positions[rb.parent.internal_id];
+                positions.push(parent_to_world * local_to_parent);
+            }
+        }
+
+        positions
+    }
+
+    /// Sets the generalized velocities of this multibody and updates the velocities of its links.
+    ///
+    /// The velocities are ordered like the generalized coordinates, see
+    /// `self.generalized_position()`.
+    pub fn set_generalized_velocity(&mut self, vels: &[N]) {
+        let ndofs = self.ndofs;
+        self.velocities[..ndofs].copy_from_slice(&vels[..ndofs]);
+        self.update_link_velocities();
+    }
+
+    /// Sets the position of the root link, if it is attached to the ground by a `FreeJoint`.
+    ///
+    /// The positions of all the links are updated accordingly. Panics if the root joint is not
+    /// a `FreeJoint`.
+    pub fn set_root_position(&mut self, position: Isometry<N>) {
+        self.rbs[0]
+            .dof
+            .downcast_mut::<FreeJoint<N>>()
+            .expect("The root of this multibody is not attached by a free joint.")
+            .set_position(position);
+        self.update_kinematics();
+    }
+
+    /// Sets the velocity of the root link, if it is attached to the ground by a `FreeJoint`.
+    ///
+    /// The linear velocity is the one of the origin of the root link local frame. The velocities
+    /// of all the links are updated accordingly. Panics if the root joint is not a `FreeJoint`.
+    pub fn set_root_velocity(&mut self, velocity: Velocity<N>) {
+        assert!(
+            self.rbs[0].dof.is::<FreeJoint<N>>(),
+            "The root of this multibody is not attached by a free joint."
+        );
+        self.velocities[..SPATIAL_DIM].copy_from_slice(velocity.as_slice());
+        self.update_link_velocities();
+    }
+
+    /// Couples the joint of the link `follower` to the joint of the link `leader`.
+    ///
+    /// The coordinate of the follower joint is then always equal to `ratio * q + offset` where
+    /// `q` is the coordinate of the leader joint. Both joints must have exactly one degree of
+    /// freedom. This replaces any previous coupling of `follower`. The limits of both joints
+    /// remain active, but the coupling takes precedence if they are incompatible.
+    pub fn couple_joints(
+        &mut self,
+        leader: MultibodyLinkId,
+        follower: MultibodyLinkId,
+        ratio: N,
+        offset: N,
+    ) {
+        assert!(
+            leader != follower,
+            "Multibody::couple_joints: a joint cannot be coupled to itself."
+        );
+        assert!(
+            self.rbs[leader.internal_id].dof.ndofs() == 1
+                && self.rbs[follower.internal_id].dof.ndofs() == 1,
+            "Multibody::couple_joints: only joints with one degree of freedom can be coupled."
+        );
+
+        let _ = self.uncouple_joint(follower);
+        self.couplings.push(JointCoupling {
+            leader,
+            follower,
+            ratio,
+            offset,
+            impulse: N::zero(),
+        });
+        self.enforce_couplings();
+        self.update_kinematics();
+    }
+
+    /// Removes the coupling of the joint of the link `follower`, if any.
+    ///
+    /// Returns `true` if the joint was coupled.
+    pub fn uncouple_joint(&mut self, follower: MultibodyLinkId) -> bool {
+        let len = self.couplings.len();
+        self.couplings.retain(|c| c.follower != follower);
+        self.couplings.len() != len
+    }
+
+    /// The joint couplings of this multibody.
+    pub fn joint_couplings(&self) -> &[JointCoupling<N>] {
+        &self.couplings[..]
+    }
+
+    // Moves the follower joints so their position is exactly the one required by their coupling.
+    fn enforce_couplings(&mut self) {
+        for coupling in &self.couplings {
+            let mut q = [N::zero()];
+            self.rbs[coupling.leader.internal_id]
+                .dof
+                .generalized_position(&mut q);
+            q[0] = coupling.ratio * q[0] + coupling.offset;
+            self.rbs[coupling.follower.internal_id]
+                .dof
+                .set_generalized_position(&q);
+        }
+    }
+
     /// The vector of generalized velocities of this multibody.
     #[inline]
     pub fn generalized_velocity(&self) -> DVectorSlice<N> {
@@ -322,7 +517,9 @@ impl<N: Real> Multibody<N> {
         let len = self.velocities.len();
         self.velocities.resize(len + ndofs, N::zero());
         self.damping.resize(len + ndofs, N::zero());
+        self.joint_damping.resize(len + ndofs, N::zero());
         self.accelerations.resize(len + ndofs, N::zero());
+        self.generalized_forces.resize(len + ndofs, N::zero());
         self.body_jacobians.push(Jacobian::zeros(0));
 
         let len = self.impulses.len();
@@ -362,8 +559,49 @@ impl<N: Real> Multibody<N> {
         MultibodyLinkId::new(internal_id)
     }
 
+    /// The identifiers of `link` and of all its descendants, sorted by increasing identifier.
+    pub fn subtree(&self, link: MultibodyLinkId) -> Vec<MultibodyLinkId> {
+        let mut in_subtree: Vec<_> = iter::repeat(false).take(self.rbs.len()).collect();
+        let mut subtree = Vec::new();
+
+        for (i, rb) in self.rbs.iter().enumerate() {
+            if i == link.internal_id
+                || (!rb.parent.is_ground() && in_subtree[rb.parent.internal_id])
+            {
+                in_subtree[i] = true;
+                subtree.push(MultibodyLinkId::new(i));
+            }
+        }
+
+        subtree
+    }
+
     /// Remove a set of links from this multibody.
+    ///
+    /// The remaining children of the removed links are attached to the ground by a `FreeJoint`,
+    /// each becoming the root of a new multibody.
     pub fn remove_links(self, links: &[MultibodyLinkId]) -> Vec<Multibody<N>> {
+        self.split(links, None)
+    }
+
+    /// Detaches `link` and its descendants from the rest of this multibody.
+    ///
+    /// The detached link is attached to the ground by a `FreeJoint` with its current position and
+    /// velocity, and becomes the root of a new multibody together with its descendants. Returns
+    /// the remaining multibody, followed by the detached one. Panics if `link` is the root link.
+    pub fn detach_link(self, link: MultibodyLinkId) -> Vec<Multibody<N>> {
+        assert!(
+            !link.is_root(),
+            "Multibody::detach_link: the root link cannot be detached."
+        );
+        self.split(&[], Some(link))
+    }
+
+    fn split(
+        self,
+        links: &[MultibodyLinkId],
+        detached: Option<MultibodyLinkId>,
+    ) -> Vec<Multibody<N>> {
         // FIXME: this could be optimized.
         let mut rb2mb: Vec<_> = iter::repeat(0).take(self.rbs.len()).collect();
         let mut rb2id: Vec<_> = iter::repeat(MultibodyLinkId::ground())
@@ -376,6 +614,8 @@ impl<N: Real> Multibody<N> {
             removed[link.internal_id] = true;
         }
 
+        let couplings = self.couplings;
+
         for (i, mut rb) in self.rbs.unwrap().into_iter().enumerate() {
             if !removed[i] {
                 if rb.parent.is_ground() {
@@ -385,14 +625,21 @@ impl<N: Real> Multibody<N> {
                     let mut mb = Multibody::new();
 
                     mb.status = self.status;
+                    mb.persistent_generalized_forces = self.persistent_generalized_forces;
+                    mb.gravity_compensation = self.gravity_compensation;
                     rb2id[i] = mb.take_link(rb, velocities, damping);
                     rb2mb[i] = multibodies.len();
                     multibodies.push(mb);
-                } else if removed[rb.parent.internal_id] {
+                } else if removed[rb.parent.internal_id]
+                    || detached.map_or(false, |link| link.internal_id == i)
+                {
                     let velocity = rb.velocity;
                     let damping = SpatialVector::zeros();
                     let mut mb = Multibody::new();
 
+                    mb.status = self.status;
+                    mb.persistent_generalized_forces = self.persistent_generalized_forces;
+                    mb.gravity_compensation = self.gravity_compensation;
                     rb.parent = MultibodyLinkId::ground();
                     rb.dof = Box::new(FreeJoint::new(rb.local_to_world));
                     rb.parent_shift.fill(N::zero());
@@ -416,6 +663,23 @@ impl<N: Real> Multibody<N> {
             }
         }
 
+        // Keep the couplings between joints that still belong to the same multibody.
+        for mut coupling in couplings {
+            let leader = coupling.leader.internal_id;
+            let follower = coupling.follower.internal_id;
+
+            if !removed[leader] && !removed[follower] && rb2mb[leader] == rb2mb[follower] {
+                let mb = &mut multibodies[rb2mb[leader]];
+
+                // The follower may have been re-attached with a free joint.
+                if mb.rbs[rb2id[follower].internal_id].dof.ndofs() == 1 {
+                    coupling.leader = rb2id[leader];
+                    coupling.follower = rb2id[follower];
+                    mb.couplings.push(coupling);
+                }
+            }
+        }
+
         multibodies
     }
 
@@ -424,6 +688,8 @@ impl<N: Real> Multibody<N> {
         for rb in self.rbs.iter_mut() {
             rb.dof.integrate(params, &self.velocities[rb.assembly_id..])
         }
+
+        self.enforce_couplings();
     }
 
     /// Apply a displacement to each degrees of freedom of this multibody.
@@ -432,9 +698,90 @@ impl<N: Real> Multibody<N> {
             rb.dof.apply_displacement(&disp[rb.assembly_id..])
         }
 
+        self.enforce_couplings();
         self.update_kinematics();
     }
 
+    /// Moves the joints of this multibody so the link `end_effector` reaches the `target` position.
+    ///
+    /// This uses a damped least-squares iterative solver. The joint coordinates are clamped to
+    /// their limits after each iteration. The resolution stops after `max_iters` iterations, or as
+    /// soon as the norm of the spatial error between the end-effector and the target is smaller
+    /// than `tol`. The velocities of this multibody are left unchanged.
+    pub fn solve_ik(
+        &mut self,
+        end_effector: MultibodyLinkId,
+        target: &Isometry<N>,
+        max_iters: usize,
+        tol: N,
+    ) -> IKResult<N> {
+        let damping_sq: N = na::convert(0.01);
+        let mut jacobian = Jacobian::zeros(self.ndofs);
+        let mut iterations = 0;
+
+        self.update_kinematics();
+
+        loop {
+            let error;
+            {
+                let rb = &self.rbs[end_effector.internal_id];
+                let pos = rb.local_to_world;
+
+                error = Velocity::new_with_vectors(
+                    target.translation.vector - pos.translation.vector,
+                    (target.rotation / pos.rotation).scaled_axis(),
+                );
+
+                /*
+                 * Jacobian of the end-effector frame origin (instead of its center of mass).
+                 */
+                jacobian.copy_from(&self.body_jacobians[end_effector.internal_id]);
+                let jacobian_w = jacobian.fixed_rows::<AngularDim>(DIM).into_owned();
+                let shift_tr = (pos.translation.vector - rb.com.coords).gcross_matrix_tr();
+                jacobian
+                    .fixed_rows_mut::<Dim>(0)
+                    .gemm(N::one(), &shift_tr, &jacobian_w, N::one());
+            }
+
+            let error_norm = error.as_vector().norm();
+
+            if error_norm <= tol || iterations == max_iters {
+                return IKResult {
+                    converged: error_norm <= tol,
+                    iterations,
+                    error: error_norm,
+                };
+            }
+
+            /*
+             * Damped least-squares step: J^t (J J^t + λ² I)^-1 e
+             */
+            let mut jjt: SpatialMatrix<N> = &jacobian * jacobian.transpose();
+            for i in 0..jjt.nrows() {
+                jjt[(i, i)] += damping_sq;
+            }
+
+            let mut rhs = *error.as_vector();
+            if !LU::new(jjt).solve_mut(&mut rhs) {
+                return IKResult {
+                    converged: false,
+                    iterations,
+                    error: error_norm,
+                };
+            }
+
+            let disp = jacobian.tr_mul(&rhs);
+
+            for rb in self.rbs.iter_mut() {
+                rb.dof.apply_displacement(&disp.as_slice()[rb.assembly_id..]);
+                rb.dof.clamp_to_limits();
+            }
+
+            self.update_kinematics();
+            iterations += 1;
+        }
+    }
+
     /// Reset the timestep-specific dynamic information of this multibody.
     pub fn clear_dynamics(&mut self) {
         self.augmented_mass.fill(N::zero());
@@ -443,10 +790,79 @@ impl<N: Real> Multibody<N> {
     }
 
     /// Clears the external forces applied to this multibody.
+    ///
+    /// The generalized forces are left unchanged if they are persistent.
     pub fn clear_forces(&mut self) {
         for rb in &mut *self.rbs {
             rb.external_forces = Force::zero();
         }
+
+        if !self.persistent_generalized_forces {
+            self.clear_generalized_forces();
+        }
+    }
+
+    /// Sets the generalized forces applied to this multibody to zero.
+    pub fn clear_generalized_forces(&mut self) {
+        for f in &mut self.generalized_forces {
+            *f = N::zero();
+        }
+    }
+
+    /// Whether the generalized forces applied to this multibody are kept across timesteps.
+    pub fn generalized_forces_are_persistent(&self) -> bool {
+        self.persistent_generalized_forces
+    }
+
+    /// Sets whether the generalized forces applied to this multibody are kept across timesteps.
+    ///
+    /// Persistent generalized forces are only reset by `clear_generalized_forces`. Otherwise,
+    /// they are reset at the end of each timestep, like the other external forces.
+    pub fn set_generalized_forces_persistent(&mut self, persistent: bool) {
+        self.persistent_generalized_forces = persistent
+    }
+
+    /// Whether the generalized gravity forces of this multibody are cancelled.
+    pub fn gravity_compensation_enabled(&self) -> bool {
+        self.gravity_compensation
+    }
+
+    /// Enables or disables the compensation of the gravity applied to this multibody.
+    ///
+    /// When enabled, the gravity is not applied to the links of this multibody, except the ones
+    /// opted out with `MultibodyLinkMut::set_gravity_compensated(false)`. This is equivalent to
+    /// applying the opposite of the generalized gravity forces at each timestep: an unpowered
+    /// multibody holds its pose while still reacting to contacts and external forces.
+    pub fn enable_gravity_compensation(&mut self, enabled: bool) {
+        self.gravity_compensation = enabled
+    }
+
+    /// The generalized forces that will be applied to this multibody during the next timestep.
+    pub fn generalized_forces(&self) -> DVectorSlice<N> {
+        DVectorSlice::from_slice(&self.generalized_forces, self.ndofs)
+    }
+
+    #[inline]
+    pub(crate) fn generalized_forces_mut(&mut self) -> &mut [N] {
+        &mut self.generalized_forces[..self.ndofs]
+    }
+
+    /// Apply generalized forces, e.g., joint torques, to the degrees of freedom of this multibody
+    /// for the next timestep.
+    ///
+    /// The forces are accumulated with the ones applied previously during the same timestep, and
+    /// are ordered like the generalized velocities. Panics if the length of `force` is not
+    /// equal to the number of degrees of freedom of this multibody.
+    pub fn apply_generalized_force(&mut self, force: &DVector<N>) {
+        assert_eq!(
+            force.len(),
+            self.ndofs,
+            "Multibody::apply_generalized_force: the force must have one component per degree of freedom."
+        );
+
+        for (f, df) in self.generalized_forces.iter_mut().zip(force.iter()) {
+            *f += *df;
+        }
     }
 
     // FIXME: keep this name?
@@ -479,16 +895,8 @@ impl<N: Real> Multibody<N> {
         self.update_body_jacobians();
     }
 
-    /// Computes the constant terms of the dynamics.
-    pub fn update_dynamics(
-        &mut self,
-        gravity: &Vector<N>,
-        params: &IntegrationParameters<N>,
-        workspace: &mut MultibodyWorkspace<N>,
-    ) {
-        /*
-         * Compute velocities.
-         */
+    // Computes the world-space velocities of all the links from the generalized velocities.
+    fn update_link_velocities(&mut self) {
         {
             let rb = &mut self.rbs[0];
             let velocity_wrt_joint = rb
@@ -521,6 +929,16 @@ impl<N: Real> Multibody<N> {
             let shift = rb.center_of_mass() - parent_rb.center_of_mass();
             rb.velocity.linear += parent_rb.velocity.angular_vector().gcross(&shift);
         }
+    }
+
+    /// Computes the constant terms of the dynamics.
+    pub fn update_dynamics(
+        &mut self,
+        gravity: &Vector<N>,
+        params: &IntegrationParameters<N>,
+        workspace: &mut MultibodyWorkspace<N>,
+    ) {
+        self.update_link_velocities();
 
         if self.status != BodyStatus::Dynamic {
             return;
@@ -529,6 +947,7 @@ impl<N: Real> Multibody<N> {
         /*
          * Update augmented mass matrix.
          */
+        self.update_joint_damping();
         self.update_inertias(params);
 
         /*
@@ -569,7 +988,11 @@ impl<N: Real> Multibody<N> {
 
                 workspace.accs[i] = acc;
 
-                let gravity_force = gravity * rb.inertia.mass();
+                let gravity_force = if self.gravity_compensation && rb.gravity_compensated {
+                    Vector::zeros()
+                } else {
+                    gravity * rb.inertia.mass()
+                };
                 let gyroscopic;
 
                 #[cfg(feature = "dim3")]
@@ -585,19 +1008,25 @@ impl<N: Real> Multibody<N> {
                 }
 
                 external_forces = Force::new(gravity_force, -gyroscopic) - rb.inertia * acc;
+                let applied_forces = external_forces + rb.external_forces;
                 accs.gemv_tr(
                     N::one(),
                     &self.body_jacobians[i],
-                    external_forces.as_vector(),
+                    applied_forces.as_vector(),
                     N::one(),
                 );
             }
             self.rbs[i].external_forces += external_forces;
         }
 
+        let generalized_forces = DVectorSlice::from_slice(&self.generalized_forces, self.ndofs);
+        accs.axpy(N::one(), &generalized_forces, N::one());
+
         let damping = DVectorSlice::from_slice(&self.damping, self.ndofs);
+        let joint_damping = DVectorSlice::from_slice(&self.joint_damping, self.ndofs);
         let vels = DVectorSlice::from_slice(&self.velocities, self.ndofs);
         accs.cmpy(-N::one(), &damping, &vels, N::one());
+        accs.cmpy(-N::one(), &joint_damping, &vels, N::one());
 
         assert!(self.inv_augmented_mass.solve_mut(&mut accs));
         // if accs.nrows() > 20 {
@@ -617,6 +1046,324 @@ impl<N: Real> Multibody<N> {
         // }
     }
 
+    /// The joint-space mass matrix of this multibody at its current configuration.
+    ///
+    /// Its rows and columns are ordered like the generalized velocities. It is computed from the
+    /// current inertia of each link, including the inertias modified with
+    /// `MultibodyLinkMut::set_local_inertia`. Unlike the augmented mass matrix used to integrate
+    /// the dynamics, it does not depend on the velocities of the links.
+    pub fn mass_matrix(&mut self) -> DMatrix<N> {
+        self.update_kinematics();
+
+        let mut mass_matrix = DMatrix::zeros(self.ndofs, self.ndofs);
+
+        for (rb, body_jacobian) in self.rbs.iter().zip(self.body_jacobians.iter()) {
+            let inertia = rb.local_inertia.transformed(&rb.local_to_world);
+            mass_matrix.quadform(N::one(), &inertia.to_matrix(), body_jacobian, N::one());
+        }
+
+        mass_matrix
+    }
+
+    /// Computes the generalized forces, e.g., joint torques, required to give the desired
+    /// accelerations to the degrees of freedom of this multibody.
+    ///
+    /// This uses the recursive Newton-Euler algorithm with the current generalized positions and
+    /// velocities of this multibody. The result accounts for the inertia of each link, the
+    /// `gravity`, the joint damping, and the external forces applied to the links since the last
+    /// timestep. The generalized forces already applied to this multibody are not accounted for.
+    /// Panics if the length of `desired_accelerations` is not equal to the number of degrees of
+    /// freedom of this multibody.
+    pub fn inverse_dynamics(
+        &mut self,
+        gravity: &Vector<N>,
+        desired_accelerations: &DVector<N>,
+    ) -> DVector<N> {
+        assert_eq!(
+            desired_accelerations.len(),
+            self.ndofs,
+            "Multibody::inverse_dynamics: the accelerations must have one component per degree of freedom."
+        );
+
+        self.update_kinematics();
+        self.update_link_velocities();
+
+        let nlinks = self.rbs.len();
+        let qdd = desired_accelerations.as_slice();
+        let mut accs: Vec<Velocity<N>> = Vec::with_capacity(nlinks);
+        let mut forces: Vec<Force<N>> = Vec::with_capacity(nlinks);
+
+        /*
+         * Forward pass: accelerations of the links and forces they require.
+         */
+        for i in 0..nlinks {
+            let rb = &self.rbs[i];
+            let acc_wrt_joint = rb.dof.jacobian_mul_coordinates(&qdd[rb.assembly_id..]);
+            let mut acc = rb.velocity_dot_wrt_joint;
+
+            if i != 0 {
+                let parent_id = rb.parent.internal_id;
+                let parent_rb = &self.rbs[parent_id];
+                let parent_vel = &parent_rb.velocity;
+
+                acc += acc_wrt_joint.transformed(&parent_rb.local_to_world);
+                acc += accs[parent_id];
+                acc.linear += parent_vel
+                    .angular_vector()
+                    .gcross(&rb.velocity_wrt_joint.linear);
+                #[cfg(feature = "dim3")]
+                {
+                    acc.angular += parent_vel.angular.cross(&rb.velocity_wrt_joint.angular);
+                }
+
+                let shift = rb.center_of_mass() - parent_rb.center_of_mass();
+                let dvel = rb.velocity.linear - parent_rb.velocity.linear;
+
+                acc.linear += parent_vel.angular_vector().gcross(&dvel);
+                acc.linear += accs[parent_id].angular_vector().gcross(&shift);
+            } else {
+                acc += acc_wrt_joint;
+            }
+
+            accs.push(acc);
+
+            let inertia = rb.local_inertia.transformed(&rb.local_to_world);
+            let gravity_force = gravity * inertia.mass();
+            let gyroscopic;
+
+            #[cfg(feature = "dim3")]
+            {
+                gyroscopic = rb
+                    .velocity
+                    .angular
+                    .cross(&(inertia.angular * rb.velocity.angular));
+            }
+            #[cfg(feature = "dim2")]
+            {
+                gyroscopic = N::zero();
+            }
+
+            let force = inertia * acc + Force::new(-gravity_force, gyroscopic);
+            forces.push(force - rb.external_forces);
+        }
+
+        /*
+         * Backward pass: forces transmitted by the joints, projected on their degrees of freedom.
+         */
+        let mut torques = DVector::zeros(self.ndofs);
+
+        for i in (0..nlinks).rev() {
+            let rb = &self.rbs[i];
+            let ndofs = rb.dof.ndofs();
+            let mut joint_j = SpatialMatrix::zeros();
+            rb.dof
+                .jacobian(&rb.parent_to_world, &mut joint_j.columns_mut(0, ndofs));
+
+            let joint_torques = joint_j.columns(0, ndofs).tr_mul(forces[i].as_vector());
+            torques
+                .rows_mut(rb.assembly_id, ndofs)
+                .copy_from(&joint_torques);
+
+            if i != 0 {
+                let parent_id = rb.parent.internal_id;
+                let shift = rb.center_of_mass() - self.rbs[parent_id].center_of_mass();
+                let mut force = forces[i];
+
+                #[cfg(feature = "dim3")]
+                {
+                    force.angular += shift.cross(&force.linear);
+                }
+                #[cfg(feature = "dim2")]
+                {
+                    force.angular += shift.gcross(&force.linear).x;
+                }
+
+                forces[parent_id] += force;
+            }
+        }
+
+        self.update_joint_damping();
+        let damping = DVectorSlice::from_slice(&self.damping, self.ndofs);
+        let joint_damping = DVectorSlice::from_slice(&self.joint_damping, self.ndofs);
+        let vels = DVectorSlice::from_slice(&self.velocities, self.ndofs);
+        torques.cmpy(N::one(), &damping, &vels, N::one());
+        torques.cmpy(N::one(), &joint_damping, &vels, N::one());
+
+        torques
+    }
+
+    /// Computes the generalized accelerations of this multibody with the articulated body
+    /// algorithm of Featherstone.
+    ///
+    /// This runs in a time linear wrt. the number of links, using the current generalized positions
+    /// and velocities of this multibody. The result accounts for the inertia of each link, the
+    /// `gravity`, the generalized forces, the joint damping, and the external forces applied to
+    /// the links since the last timestep. Contacts and joint constraints are not accounted for.
+    /// This solves the same equations of motion as `mass_matrix` and `inverse_dynamics`, without
+    /// assembling nor factorizing any joint-space matrix.
+    pub fn forward_dynamics(&mut self, gravity: &Vector<N>) -> DVector<N> {
+        self.update_kinematics();
+        self.update_link_velocities();
+        self.update_joint_damping();
+
+        let nlinks = self.rbs.len();
+        let mut shifts: Vec<SpatialMatrix<N>> = Vec::with_capacity(nlinks);
+        let mut biases: Vec<SpatialVector<N>> = Vec::with_capacity(nlinks);
+        let mut inertias: Vec<SpatialMatrix<N>> = Vec::with_capacity(nlinks);
+        let mut forces: Vec<SpatialVector<N>> = Vec::with_capacity(nlinks);
+
+        /*
+         * First pass: velocity-dependent accelerations and forces of the isolated links.
+         */
+        for i in 0..nlinks {
+            let rb = &self.rbs[i];
+            // Maps the acceleration of the parent to the center of mass of this link.
+            let mut shift = SpatialMatrix::identity();
+            let mut bias = rb.velocity_dot_wrt_joint;
+
+            if i != 0 {
+                let parent_rb = &self.rbs[rb.parent.internal_id];
+                let parent_vel = &parent_rb.velocity;
+
+                bias.linear += parent_vel
+                    .angular_vector()
+                    .gcross(&rb.velocity_wrt_joint.linear);
+                #[cfg(feature = "dim3")]
+                {
+                    bias.angular += parent_vel.angular.cross(&rb.velocity_wrt_joint.angular);
+                }
+
+                let dvel = rb.velocity.linear - parent_vel.linear;
+                bias.linear += parent_vel.angular_vector().gcross(&dvel);
+
+                let shift_tr =
+                    (rb.center_of_mass() - parent_rb.center_of_mass()).gcross_matrix_tr();
+                shift.fixed_slice_mut::<Dim, AngularDim>(0, DIM).copy_from(&shift_tr);
+            }
+
+            let inertia = rb.local_inertia.transformed(&rb.local_to_world);
+            let gravity_force = if self.gravity_compensation && rb.gravity_compensated {
+                Vector::zeros()
+            } else {
+                gravity * inertia.mass()
+            };
+            let gyroscopic;
+
+            #[cfg(feature = "dim3")]
+            {
+                gyroscopic = rb
+                    .velocity
+                    .angular
+                    .cross(&(inertia.angular * rb.velocity.angular));
+            }
+            #[cfg(feature = "dim2")]
+            {
+                gyroscopic = N::zero();
+            }
+
+            let force = Force::new(-gravity_force, gyroscopic) - rb.external_forces;
+
+            shifts.push(shift);
+            biases.push(*bias.as_vector());
+            inertias.push(inertia.to_matrix());
+            forces.push(*force.as_vector());
+        }
+
+        /*
+         * Backward pass: articulated inertias and forces, from the leaves to the root.
+         */
+        let mut joint_jacobians: Vec<Jacobian<N>> = Vec::with_capacity(nlinks);
+        let mut inv_joint_inertias: Vec<DMatrix<N>> = Vec::with_capacity(nlinks);
+        let mut joint_forces: Vec<DVector<N>> = Vec::with_capacity(nlinks);
+
+        for i in (0..nlinks).rev() {
+            let rb = &self.rbs[i];
+            let ndofs = rb.dof.ndofs();
+            let mut joint_j = Jacobian::zeros(ndofs);
+            rb.dof.jacobian(&rb.parent_to_world, &mut joint_j.columns_mut(0, ndofs));
+
+            let inertia_j = inertias[i] * &joint_j;
+            let inv_joint_inertia = if ndofs == 0 {
+                DMatrix::zeros(0, 0)
+            } else {
+                joint_j
+                    .tr_mul(&inertia_j)
+                    .try_inverse()
+                    .expect("Multibody::forward_dynamics: singular joint-space inertia.")
+            };
+
+            let mut joint_force = DVector::from_column_slice(
+                ndofs,
+                &self.generalized_forces[rb.assembly_id..rb.assembly_id + ndofs],
+            );
+            for k in 0..ndofs {
+                let dof = rb.assembly_id + k;
+                let damping = self.damping[dof] + self.joint_damping[dof];
+                joint_force[k] -= damping * self.velocities[dof];
+            }
+            joint_force -= joint_j.tr_mul(&forces[i]);
+
+            if i != 0 {
+                let parent_id = rb.parent.internal_id;
+                let articulated_inertia =
+                    inertias[i] - &inertia_j * &inv_joint_inertia * inertia_j.transpose();
+                let articulated_force = forces[i]
+                    + articulated_inertia * biases[i]
+                    + &inertia_j * (&inv_joint_inertia * &joint_force);
+
+                inertias[parent_id] += shifts[i].tr_mul(&articulated_inertia) * shifts[i];
+                forces[parent_id] += shifts[i].tr_mul(&articulated_force);
+            }
+
+            joint_jacobians.push(joint_j);
+            inv_joint_inertias.push(inv_joint_inertia);
+            joint_forces.push(joint_force);
+        }
+
+        // The backward pass stored the joint quantities from the last link to the first.
+        joint_jacobians.reverse();
+        inv_joint_inertias.reverse();
+        joint_forces.reverse();
+
+        /*
+         * Forward pass: accelerations of the links, from the root to the leaves.
+         */
+        let mut accelerations = DVector::zeros(self.ndofs);
+        let mut accs: Vec<SpatialVector<N>> = Vec::with_capacity(nlinks);
+
+        for i in 0..nlinks {
+            let rb = &self.rbs[i];
+            let ndofs = rb.dof.ndofs();
+            let mut acc = biases[i];
+
+            if i != 0 {
+                acc += shifts[i] * accs[rb.parent.internal_id];
+            }
+
+            let inertia_acc = joint_jacobians[i].tr_mul(&(inertias[i] * acc));
+            let joint_acc = &inv_joint_inertias[i] * (&joint_forces[i] - inertia_acc);
+            acc += &joint_jacobians[i] * &joint_acc;
+
+            accelerations
+                .rows_mut(rb.assembly_id, ndofs)
+                .copy_from(&joint_acc);
+            accs.push(acc);
+        }
+
+        accelerations
+    }
+
+    fn update_joint_damping(&mut self) {
+        let mut joint_damping = DVectorSliceMut::from_slice(&mut self.joint_damping, self.ndofs);
+        joint_damping.fill(N::zero());
+
+        for rb in self.rbs.iter() {
+            let ndofs = rb.dof.ndofs();
+            rb.dof
+                .viscous_damping(&mut joint_damping.rows_mut(rb.assembly_id, ndofs));
+        }
+    }
+
     fn update_body_jacobians(&mut self) {
         for i in 0..self.rbs.len() {
             let rb = &self.rbs[i];
@@ -837,13 +1584,36 @@ impl<N: Real> Multibody<N> {
          * Damping.
          */
         for i in 0..self.ndofs {
-            self.augmented_mass[(i, i)] += self.damping[i] * params.dt;
+            let damping = self.damping[i] + self.joint_damping[i];
+            self.augmented_mass[(i, i)] += damping * params.dt;
         }
 
         // FIXME: avoid allocation inside LU at each timestep.
         self.inv_augmented_mass = LU::new(self.augmented_mass.clone());
     }
 
+    /// The jacobian mapping the generalized velocities of this multibody to the world-space
+    /// velocity of the point `local_point` of the link `rb_id`.
+    ///
+    /// The point is expressed in the local-space of the link. The rows of the jacobian are the
+    /// linear components of the velocity followed by its angular components, and its columns are
+    /// ordered like the generalized velocities. Its transpose converts a force applied at this
+    /// point into generalized forces. This relies on the kinematics computed at the beginning of
+    /// the last timestep.
+    pub fn link_jacobian(&self, rb_id: MultibodyLinkId, local_point: &Point<N>) -> Jacobian<N> {
+        let rb = &self.rbs[rb_id.internal_id];
+        let mut jacobian = self.body_jacobians[rb_id.internal_id].clone();
+
+        let shift = rb.local_to_world * local_point - rb.center_of_mass();
+        let shift_j = shift.gcross_matrix_tr() * jacobian.fixed_rows::<AngularDim>(DIM);
+        {
+            let mut jacobian_v = jacobian.fixed_rows_mut::<Dim>(0);
+            jacobian_v += shift_j;
+        }
+
+        jacobian
+    }
+
     /// Convert a force applied to the center of mass of the link `rb_id` into generalized force.
     pub fn body_jacobian_mul_force(&self, rb_id: MultibodyLinkId, force: &Force<N>, out: &mut [N]) {
         let mut out = DVectorSliceMut::from_slice(out, self.ndofs);
@@ -936,6 +1706,67 @@ impl<N: Real> Multibody<N> {
 
         self.unilateral_ground_rng = first_unilateral..constraints.velocity.unilateral_ground.len();
         self.bilateral_ground_rng = first_bilateral..constraints.velocity.bilateral_ground.len();
+
+        let first_coupling = constraints.velocity.bilateral_ground.len();
+
+        for (i, coupling) in self.couplings.iter().enumerate() {
+            let leader_id = self.rbs[coupling.leader.internal_id].assembly_id;
+            let follower_id = self.rbs[coupling.follower.internal_id].assembly_id;
+            let dvel = self.velocities[follower_id]
+                + ext_vels[self.companion_id + follower_id]
+                - coupling.ratio
+                    * (self.velocities[leader_id] + ext_vels[self.companion_id + leader_id]);
+
+            let ndofs = self.ndofs;
+            let j_id = *ground_j_id;
+            let wj_id = j_id + ndofs;
+
+            DVectorSliceMut::from_slice(&mut jacobians[j_id..], ndofs).fill(N::zero());
+            jacobians[j_id + follower_id] = N::one();
+            jacobians[j_id + leader_id] = -coupling.ratio;
+
+            for k in 0..ndofs {
+                jacobians[wj_id + k] = jacobians[j_id + k];
+            }
+            self.inv_mass_mul_generalized_forces(&mut jacobians[wj_id..]);
+
+            // = J^t * M^-1 J
+            let inv_r =
+                jacobians[wj_id + follower_id] - coupling.ratio * jacobians[wj_id + leader_id];
+
+            let constraint = BilateralGroundConstraint {
+                impulse: coupling.impulse * params.warmstart_coeff,
+                r: N::one() / inv_r,
+                rhs: dvel,
+                cfm: N::zero(),
+                limits: ImpulseLimits::Independent {
+                    min: -N::max_value(),
+                    max: N::max_value(),
+                },
+                is_angular: false,
+                impulse_id: i,
+                assembly_id: self.companion_id,
+                j_id,
+                wj_id,
+                ndofs,
+            };
+
+            constraints.velocity.bilateral_ground.push(constraint);
+            *ground_j_id += 2 * ndofs;
+        }
+
+        self.coupling_rng = first_coupling..constraints.velocity.bilateral_ground.len();
+    }
+
+    /// The maximum number of velocity constraints generated by this multibody.
+    pub fn num_velocity_constraints(&self) -> usize {
+        let nlink_constraints: usize = self
+            .rbs
+            .iter()
+            .map(|rb| rb.dof.num_velocity_constraints())
+            .sum();
+
+        nlink_constraints + self.couplings.len()
     }
 
     /// Store impulses computed by the solver for joint limits and motors.
@@ -950,6 +1781,10 @@ impl<N: Real> Multibody<N> {
         {
             self.impulses[constraint.impulse_id] = constraint.impulse;
         }
+
+        for constraint in &constraints.velocity.bilateral_ground[self.coupling_rng.clone()] {
+            self.couplings[constraint.impulse_id].impulse = constraint.impulse;
+        }
     }
 }
 
@@ -980,6 +1815,49 @@ impl<'a, N: Real> Iterator for MultibodyLinks<'a, N> {
     }
 }
 
+/// The outcome of an inverse kinematics resolution.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct IKResult<N: Real> {
+    /// Whether or not the target was reached with the requested tolerance.
+    pub converged: bool,
+    /// The number of iterations actually performed.
+    pub iterations: usize,
+    /// The norm of the remaining spatial error between the end-effector and the target.
+    pub error: N,
+}
+
+/// A coupling between the coordinates of two joints of a multibody, see `Multibody::couple_joints`.
+#[derive(Copy, Clone, Debug)]
+pub struct JointCoupling<N: Real> {
+    leader: MultibodyLinkId,
+    follower: MultibodyLinkId,
+    ratio: N,
+    offset: N,
+    impulse: N,
+}
+
+impl<N: Real> JointCoupling<N> {
+    /// The link whose joint drives the coupled joint.
+    pub fn leader(&self) -> MultibodyLinkId {
+        self.leader
+    }
+
+    /// The link whose joint coordinate is driven by the leader joint.
+    pub fn follower(&self) -> MultibodyLinkId {
+        self.follower
+    }
+
+    /// The factor applied to the leader joint coordinate.
+    pub fn ratio(&self) -> N {
+        self.ratio
+    }
+
+    /// The offset added to the scaled leader joint coordinate.
+    pub fn offset(&self) -> N {
+        self.offset
+    }
+}
+
 /// A temporary workspace for various updates of the multibody.
 #[derive(Default)]
 pub struct MultibodyWorkspace<N: Real> {
@@ -997,3 +1875,869 @@ impl<N: Real> MultibodyWorkspace<N> {
         self.accs.resize(nlinks, Velocity::zero());
     }
 }
+
+#[cfg(test)]
+mod test {
+    use na::{self, DVector};
+    use ncollide::shape::{Ball, Cuboid, ShapeHandle};
+
+    use joint::{FreeJoint, PrismaticJoint, RevoluteJoint};
+    use math::{Isometry, Point, Vector};
+    use object::{BodyHandle, Material};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn forward_dynamics_match_the_mass_matrix_of_a_branching_multibody() {
+        let mut world = World::<f64>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let rod = ShapeHandle::new(Cuboid::new(Vector::x() * 1.0 + Vector::repeat(0.2)));
+
+        #[cfg(feature = "dim2")]
+        let (root_pos, hinge, ball) = (
+            Isometry::new(Vector::new(0.5, 2.0), 0.3),
+            RevoluteJoint::new(0.4),
+            RevoluteJoint::new(-0.8),
+        );
+        #[cfg(feature = "dim3")]
+        let (root_pos, hinge, ball) = {
+            use joint::BallJoint;
+            (
+                Isometry::new(Vector::new(0.5, 2.0, -1.0), Vector::new(0.3, -0.2, 0.1)),
+                RevoluteJoint::new(Vector::z_axis(), 0.4),
+                BallJoint::new(Vector::new(0.2, -0.8, 0.5)),
+            )
+        };
+
+        let root = world.add_multibody_link(
+            BodyHandle::ground(),
+            FreeJoint::new(root_pos),
+            na::zero(),
+            na::zero(),
+            cube.inertia(2.0),
+            cube.center_of_mass(),
+        );
+        let arm = world.add_multibody_link(
+            root,
+            hinge,
+            Vector::x() * 0.5,
+            -Vector::x() * 1.2,
+            rod.inertia(1.0),
+            rod.center_of_mass(),
+        );
+        let _ = world.add_multibody_link(
+            arm,
+            ball,
+            Vector::x() * 1.2,
+            -Vector::y() * 0.5,
+            cube.inertia(0.5),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_multibody_link(
+            root,
+            PrismaticJoint::new(Vector::y_axis(), 0.3),
+            -Vector::x() * 0.5,
+            Vector::y() * 0.7,
+            rod.inertia(3.0),
+            rod.center_of_mass(),
+        );
+
+        let gravity = Vector::y() * -9.81;
+        let mb = world.multibody_mut(root).unwrap();
+        let ndofs = mb.ndofs();
+        let vels: Vec<f64> = (0..ndofs).map(|i| (i as f64 * 1.3).sin() * 2.0).collect();
+        let forces = DVector::from_fn(ndofs, |i, _| (i as f64 * 0.7).cos() * 5.0);
+        mb.set_generalized_velocity(&vels);
+        mb.apply_generalized_force(&forces);
+
+        let accelerations = mb.forward_dynamics(&gravity);
+
+        // The bias forces are the generalized forces needed to leave the multibody unaccelerated.
+        let bias = mb.inverse_dynamics(&gravity, &DVector::zeros(ndofs));
+        let expected = mb.mass_matrix().lu().solve(&(forces - bias)).unwrap();
+
+        assert_relative_eq!(accelerations, expected, epsilon = 1.0e-8);
+    }
+
+    // Builds a two-link arm of links of unit length, returning the handles of its links. The
+    // frame of the last link is the end-effector at the tip of the arm.
+    fn build_arm(
+        world: &mut World<f32>,
+        angles: [f32; 2],
+        limit: Option<f32>,
+    ) -> [BodyHandle; 2] {
+        let ball = ShapeHandle::new(Ball::new(0.1));
+        let mut parent = BodyHandle::ground();
+        let mut links = [parent; 2];
+
+        for i in 0..2 {
+            #[cfg(feature = "dim2")]
+            let mut joint = RevoluteJoint::new(angles[i]);
+            #[cfg(feature = "dim3")]
+            let mut joint = RevoluteJoint::new(Vector::z_axis(), angles[i]);
+
+            if let Some(limit) = limit {
+                joint.enable_min_angle(-limit);
+                joint.enable_max_angle(limit);
+            }
+
+            let shift = if i == 0 { na::zero() } else { Vector::x() };
+            parent = world.add_multibody_link(
+                parent,
+                joint,
+                shift,
+                shift,
+                ball.inertia(1.0),
+                ball.center_of_mass(),
+            );
+            links[i] = parent;
+        }
+
+        links
+    }
+
+    fn arm_angles(world: &World<f32>, links: &[BodyHandle; 2]) -> [f32; 2] {
+        let angle = |link: BodyHandle| {
+            let link = world.multibody_link(link).unwrap();
+            link.joint().downcast_ref::<RevoluteJoint<f32>>().unwrap().angle()
+        };
+
+        [angle(links[0]), angle(links[1])]
+    }
+
+    #[test]
+    fn inverse_kinematics_reaches_a_reachable_target() {
+        let mut world = World::<f32>::new();
+
+        // The target is the pose of the end-effector of an identical arm with other angles.
+        let reference = build_arm(&mut world, [0.7, -0.4], None);
+        world.multibody_mut(reference[0]).unwrap().update_kinematics();
+        let target = world.multibody_link(reference[1]).unwrap().position();
+
+        let links = build_arm(&mut world, [0.0, 0.0], None);
+        let end_effector = world.multibody_link(links[1]).unwrap().id();
+        let result = world
+            .multibody_mut(links[0])
+            .unwrap()
+            .solve_ik(end_effector, &target, 100, 1.0e-4);
+
+        assert!(result.converged, "The IK did not converge: {:?}", result);
+        assert!(result.error <= 1.0e-4);
+        assert!(result.iterations < 100);
+
+        let pos = world.multibody_link(links[1]).unwrap().position();
+        assert!((pos.translation.vector - target.translation.vector).norm() < 1.0e-3);
+
+        let angles = arm_angles(&world, &links);
+        assert!((angles[0] - 0.7).abs() < 1.0e-3, "Angles: {:?}", angles);
+        assert!((angles[1] + 0.4).abs() < 1.0e-3, "Angles: {:?}", angles);
+    }
+
+    #[test]
+    fn inverse_kinematics_stops_at_the_joint_limits_of_an_unreachable_target() {
+        let mut world = World::<f32>::new();
+
+        // The target requires angles beyond the limits of the arm.
+        let reference = build_arm(&mut world, [1.0, 1.0], None);
+        world.multibody_mut(reference[0]).unwrap().update_kinematics();
+        let target = world.multibody_link(reference[1]).unwrap().position();
+
+        let links = build_arm(&mut world, [0.0, 0.0], Some(0.3));
+        let end_effector = world.multibody_link(links[1]).unwrap().id();
+        let result = world
+            .multibody_mut(links[0])
+            .unwrap()
+            .solve_ik(end_effector, &target, 100, 1.0e-4);
+
+        assert!(!result.converged, "The IK should not converge: {:?}", result);
+        assert_eq!(result.iterations, 100);
+        assert!(result.error > 1.0e-1);
+
+        let angles = arm_angles(&world, &links);
+        assert!((angles[0] - 0.3).abs() < 1.0e-3, "Angles: {:?}", angles);
+        assert!((angles[1] - 0.3).abs() < 1.0e-3, "Angles: {:?}", angles);
+    }
+
+    #[test]
+    fn multibody_generalized_position_drives_forward_kinematics() {
+        use std::f32::consts::FRAC_PI_2;
+
+        let mut world = World::<f32>::new();
+        let ball = ShapeHandle::new(Ball::new(0.1));
+
+        #[cfg(feature = "dim2")]
+        let joint = RevoluteJoint::new(0.0);
+        #[cfg(feature = "dim3")]
+        let joint = RevoluteJoint::new(Vector::z_axis(), 0.0);
+
+        let link1 = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            -Vector::x(),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+        let link2 = world.add_multibody_link(
+            link1,
+            joint,
+            Vector::x(),
+            -Vector::x(),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+
+        {
+            let mb = world.multibody_mut(link1).unwrap();
+            mb.set_generalized_position(&[FRAC_PI_2, -FRAC_PI_2]);
+            mb.set_generalized_velocity(&[1.0, 2.0]);
+
+            let coords = mb.generalized_position();
+            assert!((coords[0] - FRAC_PI_2).abs() < 1.0e-6, "Coordinates: {}", coords);
+            assert!((coords[1] + FRAC_PI_2).abs() < 1.0e-6, "Coordinates: {}", coords);
+            assert_eq!(mb.generalized_velocity_slice()[..2], [1.0, 2.0]);
+        }
+
+        // The first link is rotated by `π / 2` and the second one is back to the world orientation.
+        let pos1 = world.body_part(link1).position().translation.vector;
+        let pos2 = world.body_part(link2).position().translation.vector;
+        assert!((pos1 - Vector::y()).norm() < 1.0e-5, "First link: {}", pos1);
+        assert!((pos2 - Vector::x() - Vector::y() * 2.0).norm() < 1.0e-5, "Second link: {}", pos2);
+    }
+
+    #[test]
+    fn pd_controller_drives_a_two_link_pendulum_with_joint_torques() {
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+        #[cfg(feature = "dim2")]
+        let joint = RevoluteJoint::new(0.0);
+        #[cfg(feature = "dim3")]
+        let joint = RevoluteJoint::new(Vector::z_axis(), 0.0);
+
+        let link1 = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_multibody_link(
+            link1,
+            joint,
+            Vector::x(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+
+        // The slow convergence of the controller must not put the multibody to sleep.
+        world
+            .multibody_mut(link1)
+            .unwrap()
+            .activation_status_mut()
+            .set_deactivation_threshold(None);
+
+        let target = DVector::from_row_slice(2, &[0.8, -0.5]);
+        let (kp, kd) = (20.0, 10.0);
+
+        for _ in 0..600 {
+            let torque = {
+                let mb = world.multibody(link1).unwrap();
+                (&target - mb.generalized_position()) * kp - mb.generalized_velocity() * kd
+            };
+
+            world
+                .multibody_mut(link1)
+                .unwrap()
+                .apply_generalized_force(&torque);
+            world.step();
+        }
+
+        let mb = world.multibody(link1).unwrap();
+        let error = (&target - mb.generalized_position()).norm();
+        let speed = mb.generalized_velocity().norm();
+        assert!(error < 1.0e-2, "Configuration error: {}", error);
+        assert!(speed < 1.0e-2, "Joint speed: {}", speed);
+    }
+
+    #[test]
+    fn coupled_joints_keep_their_ratio_under_load_until_uncoupled() {
+        use joint::FixedJoint;
+        use math::Force;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::x() * -9.81);
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.1)));
+
+        let root = world.add_multibody_link(
+            BodyHandle::ground(),
+            FixedJoint::new(Isometry::identity()),
+            na::zero(),
+            na::zero(),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let mut fingers = Vec::new();
+
+        for i in 0..2 {
+            let finger = world.add_multibody_link(
+                root,
+                PrismaticJoint::new(Vector::x_axis(), 0.0),
+                Vector::y() * (i as f32 + 1.0),
+                na::zero(),
+                cuboid.inertia(1.0),
+                cuboid.center_of_mass(),
+            );
+            fingers.push(finger);
+        }
+
+        let offset = |world: &World<f32>, finger: BodyHandle| {
+            let link = world.multibody_link(finger).unwrap();
+            link.joint().downcast_ref::<PrismaticJoint<f32>>().unwrap().offset()
+        };
+
+        let leader = world.multibody_link(fingers[0]).unwrap().id();
+        let follower = world.multibody_link(fingers[1]).unwrap().id();
+        world
+            .multibody_mut(root)
+            .unwrap()
+            .couple_joints(leader, follower, 2.0, 0.5);
+        assert!((offset(&world, fingers[1]) - 0.5).abs() < 1.0e-6);
+
+        for _ in 0..60 {
+            // Squeeze the follower only: the leader must follow it.
+            world
+                .multibody_link_mut(fingers[1])
+                .unwrap()
+                .apply_force(&Force::linear(Vector::x() * 5.0));
+            world.step();
+
+            let (q_leader, q_follower) = (offset(&world, fingers[0]), offset(&world, fingers[1]));
+            assert!((q_follower - (2.0 * q_leader + 0.5)).abs() < 1.0e-5);
+        }
+
+        assert!(offset(&world, fingers[0]).abs() > 0.1, "The coupled joints should move.");
+
+        assert!(world.multibody_mut(root).unwrap().uncouple_joint(follower));
+        assert!(world.multibody(root).unwrap().joint_couplings().is_empty());
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let (q_leader, q_follower) = (offset(&world, fingers[0]), offset(&world, fingers[1]));
+        assert!((q_follower - (2.0 * q_leader + 0.5)).abs() > 0.1);
+    }
+
+    #[cfg(feature = "dim3")]
+    #[test]
+    fn multibody_generalized_coordinates_round_trip_through_rotation_charts() {
+        use joint::BallJoint;
+        use na::UnitQuaternion;
+
+        let mut world = World::<f32>::new();
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.2)));
+
+        let root = world.add_multibody_link(
+            BodyHandle::ground(),
+            FreeJoint::new(Isometry::identity()),
+            na::zero(),
+            na::zero(),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let arm = world.add_multibody_link(
+            root,
+            BallJoint::new(na::zero()),
+            na::zero(),
+            Vector::y(),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let _ = world.add_multibody_link(
+            arm,
+            RevoluteJoint::new(Vector::z_axis(), 0.0),
+            na::zero(),
+            Vector::y(),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+
+        // Free joint (translation, rotation), ball joint rotation, revolute joint angle.
+        let coords = DVector::from_row_slice(
+            10,
+            &[1.0, 2.0, 3.0, 0.0, 2.5, 0.0, 0.8, -0.8, 0.8, 2.0],
+        );
+
+        {
+            let mb = world.multibody_mut(root).unwrap();
+            mb.set_generalized_position(coords.as_slice());
+            assert!((mb.generalized_position() - &coords).norm() < 1.0e-5);
+
+            // Reading the coordinates back and setting them again is lossless.
+            let read = mb.generalized_position();
+            mb.set_generalized_position(read.as_slice());
+            assert!((mb.generalized_position() - &read).norm() < 1.0e-6);
+        }
+
+        // The link poses are refreshed immediately.
+        let root_pos = world.multibody_link(root).unwrap().position();
+        let expected_rot = UnitQuaternion::new(Vector::y() * 2.5);
+        assert!((root_pos.translation.vector - Vector::new(1.0, 2.0, 3.0)).norm() < 1.0e-5);
+        assert!(root_pos.rotation.angle_to(&expected_rot) < 1.0e-5);
+
+        let mut vels = DVector::zeros(10);
+        vels[0] = 0.5;
+        vels[9] = 1.0;
+        world
+            .multibody_mut(root)
+            .unwrap()
+            .set_generalized_velocity(vels.as_slice());
+
+        let mb = world.multibody(root).unwrap();
+        assert!((mb.generalized_velocity() - &vels).norm() < 1.0e-6);
+        let arm_vel = world.multibody_link(arm).unwrap().velocity().linear;
+        assert!((arm_vel - Vector::x() * 0.5).norm() < 1.0e-5);
+    }
+
+    #[test]
+    fn joint_forces_are_reset_after_each_step_unless_persistent() {
+        let build = || {
+            let mut world = World::<f32>::new();
+            let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+            #[cfg(feature = "dim2")]
+            let joint = RevoluteJoint::new(0.0);
+            #[cfg(feature = "dim3")]
+            let joint = RevoluteJoint::new(Vector::z_axis(), 0.0);
+
+            let link = world.add_multibody_link(
+                BodyHandle::ground(),
+                joint,
+                na::zero(),
+                -Vector::x(),
+                cube.inertia(1.0),
+                cube.center_of_mass(),
+            );
+            (world, link)
+        };
+
+        // Torques applied on each step to the multibody or to the link are equivalent.
+        let (mut world1, link1) = build();
+        let (mut world2, link2) = build();
+        let torque = DVector::from_element(1, 2.0);
+
+        for _ in 0..10 {
+            world1.multibody_mut(link1).unwrap().apply_generalized_force(&torque);
+            world1.step();
+            world2.multibody_link_mut(link2).unwrap().apply_joint_force(0, 2.0);
+            world2.step();
+            assert!(world2.multibody(link2).unwrap().generalized_forces()[0] == 0.0);
+        }
+
+        let vel1 = world1.multibody(link1).unwrap().generalized_velocity()[0];
+        let vel2 = world2.multibody(link2).unwrap().generalized_velocity()[0];
+        assert!(vel1 > 0.1);
+        assert!((vel1 - vel2).abs() < 1.0e-5);
+
+        // A persistent torque applied once keeps acting until it is cleared.
+        let (mut world3, link3) = build();
+        {
+            let mb = world3.multibody_mut(link3).unwrap();
+            mb.set_generalized_forces_persistent(true);
+            mb.apply_generalized_force(&torque);
+        }
+
+        for _ in 0..10 {
+            world3.step();
+        }
+
+        let vel3 = world3.multibody(link3).unwrap().generalized_velocity()[0];
+        assert!((vel1 - vel3).abs() < 1.0e-5);
+
+        world3.multibody_mut(link3).unwrap().clear_generalized_forces();
+        assert!(world3.multibody(link3).unwrap().generalized_forces()[0] == 0.0);
+    }
+
+    #[test]
+    fn inverse_dynamics_of_a_pendulum_match_its_analytic_torque() {
+        use math::Force;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let angle = 0.3f32;
+
+        #[cfg(feature = "dim2")]
+        let joint = RevoluteJoint::new(angle);
+        #[cfg(feature = "dim3")]
+        let joint = RevoluteJoint::new(Vector::z_axis(), angle);
+
+        // A unit mass one unit away from the pivot.
+        let link = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            na::zero(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        world.multibody_mut(link).unwrap().generalized_velocity_mut()[0] = 0.5;
+        world
+            .multibody_link_mut(link)
+            .unwrap()
+            .apply_force(&Force::linear(Vector::y() * 2.0));
+
+        let gravity = *world.gravity();
+        let mb = world.multibody_mut(link).unwrap();
+        let damping = mb.damping()[0];
+        let desired = DVector::from_element(1, 1.5);
+        let torque = mb.inverse_dynamics(&gravity, &desired)[0];
+
+        // Moment of inertia of the cube about the pivot, and torque of the gravity and the
+        // applied force about the pivot.
+        let inertia = 1.0 / 6.0 + 1.0;
+        let expected = inertia * 1.5 + (9.81 - 2.0) * angle.cos() + damping * 0.5;
+        assert!(
+            (torque - expected).abs() < 1.0e-4,
+            "Torque: {}, expected: {}",
+            torque,
+            expected
+        );
+    }
+
+    #[test]
+    fn mass_matrix_of_a_two_link_pendulum_matches_its_inverse_dynamics() {
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+        #[cfg(feature = "dim2")]
+        let joint = |angle: f32| RevoluteJoint::new(angle);
+        #[cfg(feature = "dim3")]
+        let joint = |angle: f32| RevoluteJoint::new(Vector::z_axis(), angle);
+
+        let link1 = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint(0.3),
+            na::zero(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let link2 = world.add_multibody_link(
+            link1,
+            joint(-0.7),
+            Vector::x(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+
+        {
+            let mb = world.multibody_mut(link1).unwrap();
+            let mass_matrix = mb.mass_matrix();
+            assert_eq!(mass_matrix.shape(), (2, 2));
+            assert!((&mass_matrix - mass_matrix.transpose()).norm() < 1.0e-5);
+
+            // Without velocity nor gravity, the inverse dynamics reduce to the mass matrix.
+            let accs = DVector::from_row_slice(2, &[1.5, -2.0]);
+            let torques = mb.inverse_dynamics(&Vector::zeros(), &accs);
+            assert!((&mass_matrix * &accs - torques).norm() < 1.0e-4);
+
+            // The last diagonal entry is the moment of inertia of the last link about its pivot.
+            assert!((mass_matrix[(1, 1)] - 7.0 / 6.0).abs() < 1.0e-5);
+        }
+
+        world
+            .multibody_link_mut(link2)
+            .unwrap()
+            .set_local_inertia(cube.inertia(2.0));
+        let mass_matrix = world.multibody_mut(link1).unwrap().mass_matrix();
+        assert!((mass_matrix[(1, 1)] - 14.0 / 6.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn link_jacobian_of_a_two_link_pendulum_matches_its_geometry() {
+        use math::DIM;
+
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+        #[cfg(feature = "dim2")]
+        let joint = |angle: f32| RevoluteJoint::new(angle);
+        #[cfg(feature = "dim3")]
+        let joint = |angle: f32| RevoluteJoint::new(Vector::z_axis(), angle);
+
+        let link1 = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint(0.3),
+            na::zero(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let link2 = world.add_multibody_link(
+            link1,
+            joint(-0.7),
+            Vector::x(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+
+        // The velocity of the tip of `v` due to a unit rotation around the `z` axis.
+        #[cfg(feature = "dim2")]
+        let perp = |v: Vector<f32>| Vector::new(-v.y, v.x);
+        #[cfg(feature = "dim3")]
+        let perp = |v: Vector<f32>| Vector::z().cross(&v);
+
+        // The link positions and jacobians are only computed by the kinematic update.
+        world.multibody_mut(link1).unwrap().update_kinematics();
+
+        let mut local_point = Point::origin();
+        local_point.x = 0.5;
+        local_point.y = 0.25;
+        let point = world.multibody_link(link2).unwrap().position() * local_point;
+        let joint2 = world.multibody_link(link1).unwrap().position() * Point::from(
+            Vector::x(),
+        );
+        let link2_id = world.multibody_link(link2).unwrap().id();
+
+        let jacobian = world
+            .multibody(link1)
+            .unwrap()
+            .link_jacobian(link2_id, &local_point);
+        assert_eq!(jacobian.ncols(), 2);
+
+        let columns = [perp(point.coords), perp(point - joint2)];
+
+        for (i, column) in columns.iter().enumerate() {
+            for (row, value) in column.iter().enumerate() {
+                assert!((jacobian[(row, i)] - value).abs() < 1.0e-5);
+            }
+
+            // Only the angular velocity around `z`, i.e., the last row, is not zero.
+            for row in DIM..jacobian.nrows() - 1 {
+                assert!(jacobian[(row, i)].abs() < 1.0e-5);
+            }
+            assert!((jacobian[(jacobian.nrows() - 1, i)] - 1.0).abs() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn forward_kinematics_leave_the_multibody_unchanged() {
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+        #[cfg(feature = "dim2")]
+        let joint = |angle: f32| RevoluteJoint::new(angle);
+        #[cfg(feature = "dim3")]
+        let joint = |angle: f32| RevoluteJoint::new(Vector::z_axis(), angle);
+
+        let link1 = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint(0.3),
+            na::zero(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let link2 = world.add_multibody_link(
+            link1,
+            joint(-0.7),
+            Vector::x(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+
+        let position2 = world.multibody_link(link2).unwrap().position();
+        let coords = DVector::from_row_slice(2, &[0.5, 0.2]);
+        let positions = world
+            .multibody(link1)
+            .unwrap()
+            .forward_kinematics(&coords);
+        assert_eq!(positions.len(), 2);
+
+        // The second link is one unit away from the joint at the tip of the first link.
+        let (c1, s1) = (0.5f32.cos(), 0.5f32.sin());
+        let (c2, s2) = (0.7f32.cos(), 0.7f32.sin());
+        let mut expected = Vector::zeros();
+        expected.x = 2.0 * c1 + c2;
+        expected.y = 2.0 * s1 + s2;
+        assert!((positions[1].translation.vector - expected).norm() < 1.0e-5);
+
+        // The multibody itself did not move.
+        let old_coords = DVector::from_row_slice(2, &[0.3, -0.7]);
+        let current_coords = world.multibody(link1).unwrap().generalized_position();
+        assert!((current_coords - old_coords).norm() < 1.0e-6);
+        assert_eq!(world.multibody_link(link2).unwrap().position(), position2);
+
+        // Setting the coordinates moves the links to the predicted positions.
+        let mb = world.multibody_mut(link1).unwrap();
+        mb.set_generalized_position(coords.as_slice());
+        for (link, position) in mb.links().zip(positions.iter()) {
+            let error = link.position().inverse() * position;
+            assert!(error.translation.vector.norm() < 1.0e-5);
+            assert!(error.rotation.angle() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn gravity_compensated_arm_holds_its_pose_unless_pushed() {
+        use math::Force;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+        #[cfg(feature = "dim2")]
+        let joint = || RevoluteJoint::new(0.0);
+        #[cfg(feature = "dim3")]
+        let joint = || RevoluteJoint::new(Vector::z_axis(), 0.0);
+
+        // A horizontal arm made of two links.
+        let link1 = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint(),
+            na::zero(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let link2 = world.add_multibody_link(
+            link1,
+            joint(),
+            Vector::x(),
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+
+        {
+            let mb = world.multibody_mut(link1).unwrap();
+            mb.activation_status_mut().set_deactivation_threshold(None);
+            mb.enable_gravity_compensation(true);
+            assert!(mb.gravity_compensation_enabled());
+        }
+
+        let height = |world: &World<f32>| world.multibody_link(link2).unwrap().center_of_mass().y;
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        assert!(height(&world).abs() < 1.0e-4, "Height: {}", height(&world));
+
+        // The arm still reacts to external forces.
+        world
+            .multibody_link_mut(link2)
+            .unwrap()
+            .apply_force(&Force::linear(Vector::y() * 50.0));
+        world.step();
+        let velocity = world.multibody(link1).unwrap().generalized_velocity().norm();
+        assert!(velocity > 1.0e-2, "Velocity: {}", velocity);
+
+        // Stop the arm, and let its last link sag.
+        for v in world.multibody_mut(link1).unwrap().generalized_velocity_mut().iter_mut() {
+            *v = 0.0;
+        }
+
+        {
+            let mut link = world.multibody_link_mut(link2).unwrap();
+            link.set_gravity_compensated(false);
+            assert!(!link.as_ref().is_gravity_compensated());
+        }
+
+        let start = height(&world);
+        for _ in 0..30 {
+            world.step();
+        }
+
+        assert!(height(&world) < start - 0.05, "Height: {}", height(&world));
+    }
+
+    #[test]
+    fn removing_or_detaching_a_multibody_link_affects_its_whole_subtree() {
+        let chain = || {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+            let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+            // A horizontal chain of four links, each with a collider.
+            let mut parent = BodyHandle::ground();
+            let mut links = Vec::new();
+            for i in 0..4 {
+                #[cfg(feature = "dim2")]
+                let joint = RevoluteJoint::new(0.0);
+                #[cfg(feature = "dim3")]
+                let joint = RevoluteJoint::new(Vector::z_axis(), 0.0);
+                let shift = if i == 0 { na::zero() } else { Vector::x() };
+                parent = world.add_multibody_link(
+                    parent,
+                    joint,
+                    shift,
+                    -Vector::x(),
+                    cube.inertia(1.0),
+                    cube.center_of_mass(),
+                );
+                let _ = world.add_collider(
+                    0.01,
+                    cube.clone(),
+                    parent,
+                    Isometry::identity(),
+                    Material::default(),
+                );
+                links.push(parent);
+            }
+
+            for _ in 0..10 {
+                world.step();
+            }
+
+            (world, links)
+        };
+
+        // Dropping the last two links.
+        let (mut world, links) = chain();
+        let positions: Vec<_> = links
+            .iter()
+            .map(|l| world.multibody_link(*l).unwrap().position())
+            .collect();
+        let velocity = *world.multibody_link(links[2]).unwrap().velocity();
+
+        assert_eq!(world.detach_multibody_link(links[2]), links[2]);
+        assert_eq!(world.multibody(links[1]).unwrap().handle(), links[0]);
+        assert_eq!(world.multibody(links[1]).unwrap().ndofs(), 2);
+        assert_eq!(world.multibody(links[3]).unwrap().handle(), links[2]);
+        assert_eq!(world.colliders().count(), 4);
+
+        for (link, pos) in links.iter().zip(positions.iter()) {
+            let new_pos = world.multibody_link(*link).unwrap().position();
+            assert!((new_pos.translation.vector - pos.translation.vector).norm() < 1.0e-5);
+        }
+
+        let new_velocity = *world.multibody_link(links[2]).unwrap().velocity();
+        assert!((new_velocity.as_vector() - velocity.as_vector()).norm() < 1.0e-5);
+
+        // The detached links fall freely while the remaining ones swing around their pivot.
+        for _ in 0..60 {
+            world.step();
+        }
+
+        assert!(world.multibody_link(links[2]).unwrap().center_of_mass().y < -3.0);
+        let swing = world.multibody_link(links[1]).unwrap().center_of_mass().coords;
+        assert!(swing.norm() < 3.0 + 1.0e-3);
+
+        // Removing the last three links.
+        let (mut world, links) = chain();
+        world.remove_multibody_link(links[1]);
+        assert!(world.multibody(links[0]).is_some());
+        assert_eq!(world.multibody(links[0]).unwrap().ndofs(), 1);
+        assert!(links[1..].iter().all(|l| world.multibody_link(*l).is_none()));
+        assert_eq!(world.colliders().count(), 1);
+        world.step();
+    }
+}
@@ -15,7 +15,7 @@ pub struct MultibodyLinkId {
 impl MultibodyLinkId {
     pub(crate) fn new(internal_id: usize) -> Self {
         MultibodyLinkId {
-            internal_id: internal_id,
+            internal_id,
         }
     }
 
@@ -64,6 +64,7 @@ pub(crate) struct MultibodyLink<N: Real> {
 
     pub local_inertia: Inertia<N>,
     pub local_com: Point<N>,
+    pub gravity_compensated: bool,
     // TODO: User-defined data
     // user_data:       T
 }
@@ -111,6 +112,7 @@ impl<N: Real> MultibodyLink<N> {
             local_com,
             inertia,
             com,
+            gravity_compensated: true,
         }
     }
 
@@ -161,7 +163,44 @@ impl<'a, N: Real> MultibodyLinkMut<'a, N> {
         rb.external_forces.angular += force.angular;
     }
 
-    // FIXME: add methods to modify velocities, forces, damping, etc.
+    /// Apply a generalized force, e.g., a joint torque, to the `dof_id`-th degree of freedom of
+    /// the joint attaching this link to its parent.
+    ///
+    /// The force is accumulated with the generalized forces applied to the multibody for the next
+    /// timestep. See `Multibody::apply_generalized_force` for details.
+    #[inline]
+    pub fn apply_joint_force(&mut self, dof_id: usize, force: N) {
+        let id = {
+            let rb = &self.multibody.rbs()[self.id.internal_id];
+            assert!(
+                dof_id < rb.dof.ndofs(),
+                "MultibodyLinkMut::apply_joint_force: the joint has no such degree of freedom."
+            );
+
+            rb.assembly_id + dof_id
+        };
+        self.multibody.generalized_forces_mut()[id] += force;
+    }
+
+    /// Sets the inertia of this link, expressed in its local-space.
+    #[inline]
+    pub fn set_local_inertia(&mut self, local_inertia: Inertia<N>) {
+        let rb = &mut self.multibody.rbs_mut()[self.id.internal_id];
+        rb.local_inertia = local_inertia;
+        rb.inertia = local_inertia.transformed(&rb.local_to_world);
+    }
+
+    /// Sets whether the gravity applied to this link is cancelled when the gravity compensation
+    /// of its multibody is enabled (default: `true`).
+    ///
+    /// This allows, e.g., a payload to sag while the rest of a gravity-compensated arm holds its
+    /// pose.
+    #[inline]
+    pub fn set_gravity_compensated(&mut self, compensated: bool) {
+        self.multibody.rbs_mut()[self.id.internal_id].gravity_compensated = compensated
+    }
+
+    // FIXME: add methods to modify velocities, damping, etc.
 
     /// Retriev the mutable generalized velocities of this link.
     #[inline]
@@ -233,6 +272,20 @@ impl<'a, N: Real> MultibodyLinkRef<'a, N> {
         self.id
     }
 
+    /// The identifier of the parent of this link.
+    ///
+    /// This is `MultibodyLinkId::ground()` for the root of the multibody.
+    #[inline]
+    pub fn parent_id(&self) -> MultibodyLinkId {
+        self.link.parent
+    }
+
+    /// Return `true` if no other link of the multibody is attached to this link.
+    #[inline]
+    pub fn is_leaf(&self) -> bool {
+        self.link.is_leaf
+    }
+
     /// The dynamic assembly identifier of this link.
     #[inline]
     pub fn assembly_id(&self) -> usize {
@@ -299,6 +352,13 @@ impl<'a, N: Real> MultibodyLinkRef<'a, N> {
     pub fn inertia(&self) -> &Inertia<N> {
         &self.link.inertia
     }
+
+    /// Whether the gravity applied to this link is cancelled when the gravity compensation of
+    /// its multibody is enabled.
+    #[inline]
+    pub fn is_gravity_compensated(&self) -> bool {
+        self.link.gravity_compensated
+    }
 }
 
 // FIXME: keep this even if we already have the Index2 traits?
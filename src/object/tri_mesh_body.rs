@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use na::{self, Point3, Real, Unit};
+use ncollide::shape::{ShapeHandle, TriMesh};
+
+use math::{Point, Vector};
+
+/// A static environment described by a triangle mesh.
+///
+/// Triangle meshes are meant to be attached to the ground with `World::add_tri_mesh_body`: they
+/// have an infinite mass and cannot move. The outward normal of each triangle is given by the
+/// counter-clockwise order of its vertices.
+///
+/// The normals of the contacts with a triangle mesh are smoothed to avoid the stair-step
+/// artifacts of shapes sliding across the edges shared by adjacent triangles: contacts inside of
+/// a triangle use its face normal, contacts on an edge use the normalized sum of the normals of
+/// the triangles sharing this edge, and contacts on a vertex use the angle-weighted average of
+/// the normals of the triangles around it.
+#[derive(Clone, Debug)]
+pub struct TriMeshBody<N: Real> {
+    vertices: Vec<Point<N>>,
+    indices: Vec<Point3<usize>>,
+    face_normals: Vec<Unit<Vector<N>>>,
+    // The normal of the edge from the vertex `i` to the vertex `(i + 1) % 3` of each triangle.
+    edge_normals: Vec<[Unit<Vector<N>>; 3]>,
+    vertex_normals: Vec<Unit<Vector<N>>>,
+}
+
+impl<N: Real> TriMeshBody<N> {
+    /// Creates a triangle mesh from its vertices and the indices of the vertices of each triangle.
+    ///
+    /// Panics if an index is out of bounds or if a triangle is degenerate.
+    pub fn new(vertices: Vec<Point<N>>, indices: Vec<Point3<usize>>) -> Self {
+        let face_normals: Vec<_> = indices
+            .iter()
+            .map(|idx| {
+                assert!(
+                    idx.iter().all(|i| *i < vertices.len()),
+                    "TriMeshBody::new: a vertex index is out of bounds."
+                );
+                let (a, b, c) = (vertices[idx.x], vertices[idx.y], vertices[idx.z]);
+
+                Unit::try_new((b - a).cross(&(c - a)), N::default_epsilon())
+                    .expect("TriMeshBody::new: a triangle is degenerate.")
+            })
+            .collect();
+
+        // The triangles adjacent to each edge.
+        let mut adjacent_faces = HashMap::new();
+        for (face, idx) in indices.iter().enumerate() {
+            for i in 0..3 {
+                adjacent_faces
+                    .entry(edge_key(idx[i], idx[(i + 1) % 3]))
+                    .or_insert_with(Vec::new)
+                    .push(face);
+            }
+        }
+
+        let edge_normals = indices
+            .iter()
+            .map(|idx| {
+                let normal = |i: usize| {
+                    let sum = adjacent_faces[&edge_key(idx[i], idx[(i + 1) % 3])]
+                        .iter()
+                        .fold(Vector::zeros(), |acc, f| acc + face_normals[*f].into_inner());
+
+                    Unit::new_normalize(sum)
+                };
+
+                [normal(0), normal(1), normal(2)]
+            })
+            .collect();
+
+        let mut vertex_sums = vec![Vector::zeros(); vertices.len()];
+        for (idx, normal) in indices.iter().zip(face_normals.iter()) {
+            for i in 0..3 {
+                let vertex = vertices[idx[i]];
+                let e1 = vertices[idx[(i + 1) % 3]] - vertex;
+                let e2 = vertices[idx[(i + 2) % 3]] - vertex;
+                vertex_sums[idx[i]] += normal.into_inner() * e1.angle(&e2);
+            }
+        }
+
+        // Vertices not referenced by any triangle get an arbitrary normal.
+        let vertex_normals = vertex_sums
+            .into_iter()
+            .map(|sum| {
+                Unit::try_new(sum, N::default_epsilon()).unwrap_or_else(Vector::y_axis)
+            })
+            .collect();
+
+        TriMeshBody {
+            vertices,
+            indices,
+            face_normals,
+            edge_normals,
+            vertex_normals,
+        }
+    }
+
+    /// The vertices of this triangle mesh.
+    pub fn vertices(&self) -> &[Point<N>] {
+        &self.vertices
+    }
+
+    /// The indices of the vertices of each triangle of this mesh.
+    pub fn indices(&self) -> &[Point3<usize>] {
+        &self.indices
+    }
+
+    /// The outward normal of the `face`-th triangle.
+    pub fn face_normal(&self, face: usize) -> &Unit<Vector<N>> {
+        &self.face_normals[face]
+    }
+
+    /// The smoothed normal of the `face`-th triangle at `point`, in the local frame of the mesh.
+    ///
+    /// The point is assumed to lie on the triangle. Points closer to an edge or a vertex than a
+    /// thousandth of the size of the triangle are considered to be on it.
+    pub fn smoothed_normal(&self, face: usize, point: &Point<N>) -> Unit<Vector<N>> {
+        let idx = self.indices[face];
+        let bcoords = self.barycentric_coordinates(face, point);
+        let tolerance: N = na::convert(1.0e-3);
+        let on_edge = |i: usize| bcoords[i] <= tolerance;
+
+        match (on_edge(0), on_edge(1), on_edge(2)) {
+            (false, false, false) => self.face_normals[face],
+            // On the edge opposite to the vertex with a zero coordinate.
+            (true, false, false) => self.edge_normals[face][1],
+            (false, true, false) => self.edge_normals[face][2],
+            (false, false, true) => self.edge_normals[face][0],
+            // On the vertex with the only non-zero coordinate.
+            (false, true, true) => self.vertex_normals[idx.x],
+            (true, false, true) => self.vertex_normals[idx.y],
+            _ => self.vertex_normals[idx.z],
+        }
+    }
+
+    /// The shape of this triangle mesh.
+    pub fn to_shape(&self) -> ShapeHandle<N> {
+        ShapeHandle::new(TriMesh::new(
+            self.vertices.clone(),
+            self.indices.clone(),
+            None,
+        ))
+    }
+
+    // The barycentric coordinates of the projection of `point` on the plane of the `face`-th
+    // triangle.
+    fn barycentric_coordinates(&self, face: usize, point: &Point<N>) -> [N; 3] {
+        let idx = self.indices[face];
+        let a = self.vertices[idx.x];
+        let ab = self.vertices[idx.y] - a;
+        let ac = self.vertices[idx.z] - a;
+        let ap = *point - a;
+
+        let (d00, d01, d11) = (ab.dot(&ab), ab.dot(&ac), ac.dot(&ac));
+        let (d20, d21) = (ap.dot(&ab), ap.dot(&ac));
+        let denom = d00 * d11 - d01 * d01;
+        let v = (d11 * d20 - d01 * d21) / denom;
+        let w = (d00 * d21 - d01 * d20) / denom;
+
+        [N::one() - v - w, v, w]
+    }
+}
+
+// The key of the edge between the vertices `a` and `b`, independent of their order.
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::TriMeshBody;
+    use math::{Isometry, Vector};
+    use object::Material;
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn box_slides_across_a_tri_mesh_without_hitting_its_inner_edges() {
+        use na::{Point3, Vector3};
+
+        // Two slopes sharing a ridge along the `z` axis.
+        let roof = TriMeshBody::new(
+            vec![
+                Point3::new(-1.0, 0.0, 0.0),
+                Point3::new(0.0, 1.0, -1.0),
+                Point3::new(0.0, 1.0, 1.0),
+                Point3::new(1.0, 0.0, 0.0),
+            ],
+            vec![Point3::new(0, 2, 1), Point3::new(3, 1, 2)],
+        );
+        let slope = Vector3::new(-1.0, 1.0, 0.0).normalize();
+        let centroid = Point3::new(-1.0 / 3.0, 2.0 / 3.0, 0.0);
+        assert!((roof.face_normal(0).into_inner() - slope).norm() < 1.0e-5);
+        assert!((roof.smoothed_normal(0, &centroid).into_inner() - slope).norm() < 1.0e-5);
+        let ridge = roof.smoothed_normal(0, &Point3::new(0.0, 1.0, 0.5));
+        assert!((ridge.into_inner() - Vector3::y()).norm() < 1.0e-5);
+        let corner = roof.smoothed_normal(1, &Point3::new(0.0, 1.0, -1.0));
+        assert!((corner.into_inner() - Vector3::y()).norm() < 1.0e-5);
+        let tip = roof.smoothed_normal(0, &Point3::new(-1.0, 0.0, 0.0));
+        assert!((tip.into_inner() - slope).norm() < 1.0e-5);
+
+        // A flat floor made of many small triangles.
+        let n = 16;
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for i in 0..=n {
+            for j in 0..=n {
+                vertices.push(Point3::new(j as f32 * 0.5 - 4.0, 0.0, i as f32 * 0.5 - 4.0));
+            }
+        }
+        for i in 0..n {
+            for j in 0..n {
+                let id = |i, j| i * (n + 1) + j;
+                indices.push(Point3::new(id(i, j), id(i + 1, j), id(i + 1, j + 1)));
+                indices.push(Point3::new(id(i, j), id(i + 1, j + 1), id(i, j + 1)));
+            }
+        }
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let mut material = Material::default();
+        material.friction = 0.0;
+        let floor = world.add_tri_mesh_body(
+            0.01,
+            TriMeshBody::new(vertices, indices),
+            Isometry::identity(),
+            material.clone(),
+        );
+        assert_eq!(world.tri_mesh_body(floor).unwrap().indices().len(), 2 * n * n);
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.2)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::new(-3.0, 0.21, 0.1), na::zero()),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, cuboid, body, Isometry::identity(), material);
+        world
+            .rigid_body_mut(body)
+            .unwrap()
+            .set_linear_velocity(Vector::x() * 2.0);
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        // The box kept sliding on the floor without bumping into the edges of the triangles.
+        let rb = world.rigid_body(body).unwrap();
+        let center = rb.position().translation.vector;
+        let velocity = rb.velocity().linear;
+        assert!((velocity.x - 2.0).abs() < 0.1, "Velocity: {}", velocity);
+        assert!(velocity.y.abs() < 0.1 && velocity.z.abs() < 0.1);
+        assert!((center.y - 0.2).abs() < 2.0e-2, "Height: {}", center.y);
+        assert!((center.x - 1.0).abs() < 0.2);
+    }
+}
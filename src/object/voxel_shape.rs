@@ -0,0 +1,399 @@
+use alga::general::SupersetOf;
+use na::{self, Real};
+use ncollide::shape::{Compound, Cuboid, ShapeHandle};
+use num::Zero;
+
+use math::{AngularInertia, Isometry, Point, Vector};
+use volumetric::{self, InertiaTensor, Volumetric};
+
+// The offsets of the six neighbors sharing a face with a voxel.
+const NEIGHBORS: [(isize, isize, isize); 6] = [
+    (-1, 0, 0),
+    (1, 0, 0),
+    (0, -1, 0),
+    (0, 1, 0),
+    (0, 0, -1),
+    (0, 0, 1),
+];
+
+/// A shape made of the filled cells of a regular grid of cubic voxels.
+///
+/// Each voxel is a cube with sides of length `scale`. The voxel `(i, j, k)` spans the cube from
+/// `offset + (i, j, k) * scale` to `offset + (i + 1, j + 1, k + 1) * scale` in the local frame
+/// of the shape. Voxels can be filled or emptied at any time, e.g., to model destructible
+/// environments.
+///
+/// Only the voxels of the surface, i.e., the filled voxels with at least one empty neighbor, take
+/// part in collision detection: `to_shape` builds a compound shape of their cubes, so the
+/// bounding volume tree of the compound restricts the narrow phase to the surface voxels close to
+/// the other colliders. The surface is updated incrementally each time a voxel is filled or
+/// emptied. The mass properties given by the `Volumetric` trait account for all the filled
+/// voxels.
+#[derive(Clone, Debug)]
+pub struct VoxelShape<N: Real> {
+    dims: (usize, usize, usize),
+    filled: Vec<bool>,
+    surface: Vec<bool>,
+    num_filled: usize,
+    offset: Vector<N>,
+    scale: N,
+}
+
+impl<N: Real> VoxelShape<N> {
+    /// Creates a grid of `dims.0 × dims.1 × dims.2` voxels along the `x`, `y`, and `z` axes,
+    /// with all its voxels filled.
+    pub fn new(dims: (usize, usize, usize), offset: Vector<N>, scale: N) -> Self {
+        assert!(scale > N::zero(), "The scale of a voxel shape must be positive.");
+
+        let len = dims.0 * dims.1 * dims.2;
+        let mut res = VoxelShape {
+            dims,
+            filled: vec![true; len],
+            surface: vec![false; len],
+            num_filled: len,
+            offset,
+            scale,
+        };
+
+        for i in 0..dims.0 {
+            for j in 0..dims.1 {
+                for k in 0..dims.2 {
+                    res.update_surface(i, j, k);
+                }
+            }
+        }
+
+        res
+    }
+
+    /// The number of voxels of the grid along the `x`, `y`, and `z` axes.
+    pub fn dims(&self) -> (usize, usize, usize) {
+        self.dims
+    }
+
+    /// The position of the corner of the voxel `(0, 0, 0)` with the smallest coordinates.
+    pub fn offset(&self) -> &Vector<N> {
+        &self.offset
+    }
+
+    /// The length of the sides of each voxel.
+    pub fn scale(&self) -> N {
+        self.scale
+    }
+
+    /// The number of filled voxels.
+    pub fn num_filled(&self) -> usize {
+        self.num_filled
+    }
+
+    /// Whether the voxel `(i, j, k)` is filled.
+    ///
+    /// Voxels out of the grid are considered empty.
+    pub fn is_filled(&self, i: usize, j: usize, k: usize) -> bool {
+        self.index(i, j, k).map_or(false, |id| self.filled[id])
+    }
+
+    /// Whether the voxel `(i, j, k)` is filled and has at least one empty neighbor.
+    pub fn is_surface(&self, i: usize, j: usize, k: usize) -> bool {
+        self.index(i, j, k).map_or(false, |id| self.surface[id])
+    }
+
+    /// Fills or empties the voxel `(i, j, k)`.
+    ///
+    /// Panics if the voxel is out of the grid.
+    pub fn set_filled(&mut self, i: usize, j: usize, k: usize, filled: bool) {
+        let id = self
+            .index(i, j, k)
+            .expect("VoxelShape::set_filled: the voxel is out of the grid.");
+
+        if self.filled[id] == filled {
+            return;
+        }
+
+        self.filled[id] = filled;
+
+        if filled {
+            self.num_filled += 1;
+        } else {
+            self.num_filled -= 1;
+        }
+
+        // Only this voxel and its neighbors may enter or leave the surface.
+        self.update_surface(i, j, k);
+
+        for dir in &NEIGHBORS {
+            if let Some((ni, nj, nk)) = self.neighbor(i, j, k, *dir) {
+                self.update_surface(ni, nj, nk);
+            }
+        }
+    }
+
+    /// The center of the voxel `(i, j, k)`.
+    pub fn voxel_center(&self, i: usize, j: usize, k: usize) -> Point<N> {
+        let half: N = na::convert(0.5f64);
+        let coord = |i: usize| (na::convert::<f64, N>(i as f64) + half) * self.scale;
+
+        Point::from(self.offset + Vector::new(coord(i), coord(j), coord(k)))
+    }
+
+    /// The voxel of the grid containing `point`, if any.
+    pub fn voxel_at(&self, point: &Point<N>) -> Option<(usize, usize, usize)> {
+        let coords = (point.coords - self.offset) / self.scale;
+        let index = |coord: N, dim: usize| {
+            let cell = coord.floor();
+
+            if cell < N::zero() || cell >= na::convert(dim as f64) {
+                None
+            } else {
+                Some(SupersetOf::<f64>::to_subset(&cell).unwrap_or(0.0) as usize)
+            }
+        };
+
+        Some((
+            index(coords.x, self.dims.0)?,
+            index(coords.y, self.dims.1)?,
+            index(coords.z, self.dims.2)?,
+        ))
+    }
+
+    /// An iterator through the filled voxels with at least one empty neighbor.
+    pub fn surface_voxels<'a>(&'a self) -> impl Iterator<Item = (usize, usize, usize)> + 'a {
+        self.voxels().filter(move |&(i, j, k)| self.is_surface(i, j, k))
+    }
+
+    /// An iterator through the filled voxels.
+    pub fn filled_voxels<'a>(&'a self) -> impl Iterator<Item = (usize, usize, usize)> + 'a {
+        self.voxels().filter(move |&(i, j, k)| self.is_filled(i, j, k))
+    }
+
+    /// The collision shape of this voxel grid: a compound of the cubes of its surface voxels.
+    ///
+    /// Returns `None` if no voxel is filled. The resulting shape does not change when voxels are
+    /// modified afterwards: the collider it was attached to has to be replaced by a collider with
+    /// a new shape.
+    pub fn to_shape(&self) -> Option<ShapeHandle<N>> {
+        let half_extents = Vector::repeat(self.scale * na::convert(0.5f64));
+        let cube = ShapeHandle::new(Cuboid::new(half_extents));
+        let shapes: Vec<_> = self
+            .surface_voxels()
+            .map(|(i, j, k)| {
+                let center = self.voxel_center(i, j, k);
+                (Isometry::new(center.coords, na::zero()), cube.clone())
+            })
+            .collect();
+
+        if shapes.is_empty() {
+            None
+        } else {
+            Some(ShapeHandle::new(Compound::new(shapes)))
+        }
+    }
+
+    fn voxels<'a>(&'a self) -> impl Iterator<Item = (usize, usize, usize)> + 'a {
+        let (nx, ny, nz) = self.dims;
+        (0..nx * ny * nz).map(move |id| (id / (ny * nz), (id / nz) % ny, id % nz))
+    }
+
+    fn index(&self, i: usize, j: usize, k: usize) -> Option<usize> {
+        let (nx, ny, nz) = self.dims;
+
+        if i < nx && j < ny && k < nz {
+            Some((i * ny + j) * nz + k)
+        } else {
+            None
+        }
+    }
+
+    fn neighbor(
+        &self,
+        i: usize,
+        j: usize,
+        k: usize,
+        dir: (isize, isize, isize),
+    ) -> Option<(usize, usize, usize)> {
+        let shift = |i: usize, d: isize, dim: usize| {
+            let res = i as isize + d;
+
+            if res < 0 || res >= dim as isize {
+                None
+            } else {
+                Some(res as usize)
+            }
+        };
+
+        Some((
+            shift(i, dir.0, self.dims.0)?,
+            shift(j, dir.1, self.dims.1)?,
+            shift(k, dir.2, self.dims.2)?,
+        ))
+    }
+
+    // The number of faces of the voxel `(i, j, k)` shared with an empty voxel.
+    fn num_exposed_faces(&self, i: usize, j: usize, k: usize) -> usize {
+        NEIGHBORS
+            .iter()
+            .filter(|dir| {
+                self.neighbor(i, j, k, **dir)
+                    .map_or(true, |(ni, nj, nk)| !self.is_filled(ni, nj, nk))
+            })
+            .count()
+    }
+
+    fn update_surface(&mut self, i: usize, j: usize, k: usize) {
+        let id = (i * self.dims.1 + j) * self.dims.2 + k;
+        self.surface[id] = self.filled[id] && self.num_exposed_faces(i, j, k) != 0;
+    }
+}
+
+impl<N: Real> Volumetric<N> for VoxelShape<N> {
+    fn area(&self) -> N {
+        let num_faces = self
+            .filled_voxels()
+            .fold(0, |acc, (i, j, k)| acc + self.num_exposed_faces(i, j, k));
+
+        na::convert::<f64, N>(num_faces as f64) * self.scale * self.scale
+    }
+
+    fn volume(&self) -> N {
+        na::convert::<f64, N>(self.num_filled as f64) * self.scale * self.scale * self.scale
+    }
+
+    fn center_of_mass(&self) -> Point<N> {
+        if self.num_filled == 0 {
+            return Point::from(self.offset);
+        }
+
+        let sum = self
+            .filled_voxels()
+            .fold(Vector::zeros(), |acc, (i, j, k)| {
+                acc + self.voxel_center(i, j, k).coords
+            });
+
+        Point::from(sum / na::convert::<f64, N>(self.num_filled as f64))
+    }
+
+    fn unit_angular_inertia(&self) -> AngularInertia<N> {
+        if self.num_filled == 0 {
+            return AngularInertia::zero();
+        }
+
+        let com = self.center_of_mass();
+        let half_extents = Vector::repeat(self.scale * na::convert(0.5f64));
+        let cube_inertia = volumetric::cuboid_unit_angular_inertia(&half_extents);
+
+        // Each voxel has the same mass so the result is the average of their inertias.
+        let sum = self
+            .filled_voxels()
+            .fold(AngularInertia::zero(), |acc, (i, j, k)| {
+                let shift = self.voxel_center(i, j, k) - com.coords;
+                acc + cube_inertia.to_relative_wrt_point(N::one(), &shift)
+            });
+
+        sum / na::convert::<f64, N>(self.num_filled as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Compound, Cuboid, ShapeHandle};
+
+    use super::VoxelShape;
+    use math::{Isometry, Point, Vector};
+    use object::{BodyHandle, Material};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn voxel_shape_keeps_its_surface_up_to_date() {
+        let mut voxels = VoxelShape::new((3, 3, 3), Vector::repeat(-1.5), 1.0f32);
+        assert_eq!(voxels.surface_voxels().count(), 26);
+        assert!(!voxels.is_surface(1, 1, 1));
+        assert!((voxels.volume() - 27.0).abs() < 1.0e-5);
+        assert!((voxels.area() - 54.0).abs() < 1.0e-5);
+
+        // Digging a face exposes the central voxel.
+        voxels.set_filled(1, 1, 0, false);
+        assert_eq!(voxels.num_filled(), 26);
+        assert_eq!(voxels.surface_voxels().count(), 26);
+        assert!(voxels.is_surface(1, 1, 1));
+        assert_eq!(voxels.voxel_at(&Point::new(0.2, -0.3, -1.4)), Some((1, 1, 0)));
+        assert_eq!(voxels.voxel_at(&Point::new(0.0, 0.0, 2.0)), None);
+
+        // The mass properties match the ones of the equivalent compound of cubes.
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let cubes = voxels
+            .filled_voxels()
+            .map(|(i, j, k)| {
+                let center = voxels.voxel_center(i, j, k);
+                (Isometry::new(center.coords, na::zero()), cube.clone())
+            })
+            .collect();
+        let compound = Compound::new(cubes);
+        assert!((voxels.center_of_mass() - compound.center_of_mass()).norm() < 1.0e-5);
+        let inertia = voxels.inertia(1.0).angular;
+        assert!((inertia - compound.inertia(1.0).angular).norm() < 1.0e-4);
+    }
+
+    #[test]
+    fn box_falls_through_a_voxel_floor_once_dug() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        // A slab whose top is at `y = 0`.
+        let mut floor = VoxelShape::new((8, 1, 8), Vector::new(-2.0, -0.5, -2.0), 0.5);
+        let mut floor_collider = world.add_collider(
+            0.01,
+            floor.to_shape().unwrap(),
+            BodyHandle::ground(),
+            Isometry::identity(),
+            Material::default(),
+        );
+
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.25)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * 0.5, na::zero()),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, cube, body, Isometry::identity(), Material::default());
+        world
+            .rigid_body_mut(body)
+            .unwrap()
+            .activation_status_mut()
+            .set_deactivation_threshold(None);
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let height = |world: &World<f32>| {
+            world.rigid_body(body).unwrap().position().translation.vector.y
+        };
+        // The box rests on the margins of both colliders.
+        assert!((height(&world) - 0.25).abs() < 3.0e-2);
+
+        // Dig a hole below the box, and replace the collider of the floor.
+        for i in 2..6 {
+            for k in 2..6 {
+                floor.set_filled(i, 0, k, false);
+            }
+        }
+
+        world.remove_colliders(&[floor_collider]);
+        floor_collider = world.add_collider(
+            0.01,
+            floor.to_shape().unwrap(),
+            BodyHandle::ground(),
+            Isometry::identity(),
+            Material::default(),
+        );
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        assert!(height(&world) < -1.0);
+        assert!(world.collider(floor_collider).is_some());
+    }
+}
@@ -17,6 +17,8 @@ pub struct ConstraintGeometry<N: Real> {
     pub ndofs2: usize,
     /// Scaling parameter of the SOR-prox method.
     pub r: N,
+    /// Whether this constraint applies a torque instead of a linear force.
+    pub is_angular: bool,
 }
 
 impl<N: Real> ConstraintGeometry<N> {
@@ -31,6 +33,7 @@ impl<N: Real> ConstraintGeometry<N> {
             ndofs1: 0,
             ndofs2: 0,
             r: N::zero(),
+            is_angular: false,
         }
     }
 
@@ -50,6 +53,8 @@ pub struct UnilateralConstraint<N: Real> {
     pub r: N,
     /// The target velocity change this constraint must apply.
     pub rhs: N,
+    /// The constraint force mixing term, i.e., the compliance of this constraint (default: `0`).
+    pub cfm: N,
 
     /// The index of the impulse used for its storage in an impuse cache.
     pub impulse_id: usize,
@@ -91,9 +96,10 @@ impl<N: Real> UnilateralConstraint<N> {
             impulse,
             r: geom.r,
             rhs,
+            cfm: N::zero(),
             impulse_id,
-            assembly_id1: assembly_id1,
-            assembly_id2: assembly_id2,
+            assembly_id1,
+            assembly_id2,
             j_id1: geom.j_id1,
             j_id2: geom.j_id2,
             wj_id1: geom.wj_id1,
@@ -113,6 +119,8 @@ pub struct UnilateralGroundConstraint<N: Real> {
     pub r: N,
     /// The target velocity change this constraint must apply.
     pub rhs: N,
+    /// The constraint force mixing term, i.e., the compliance of this constraint (default: `0`).
+    pub cfm: N,
 
     /// The index of the impulse used for its storage in an impuse cache.
     pub impulse_id: usize,
@@ -142,6 +150,7 @@ impl<N: Real> UnilateralGroundConstraint<N> {
                 impulse,
                 r: geom.r,
                 rhs,
+                cfm: N::zero(),
                 impulse_id,
                 assembly_id: assembly_id2,
                 j_id: geom.j_id2,
@@ -153,6 +162,7 @@ impl<N: Real> UnilateralGroundConstraint<N> {
                 impulse,
                 r: geom.r,
                 rhs,
+                cfm: N::zero(),
                 impulse_id,
                 assembly_id: assembly_id1,
                 j_id: geom.j_id1,
@@ -191,9 +201,13 @@ pub struct BilateralConstraint<N: Real> {
     pub r: N,
     /// The target velocity change this constraint must apply.
     pub rhs: N,
+    /// The constraint force mixing term, i.e., the compliance of this constraint (default: `0`).
+    pub cfm: N,
 
     /// Limits of impulse applicable by this constraint.
     pub limits: ImpulseLimits<N>,
+    /// Whether this constraint applies a torque instead of a linear force.
+    pub is_angular: bool,
 
     /// The index of the impulse used for its storage in an impuse cache.
     pub impulse_id: usize,
@@ -236,10 +250,12 @@ impl<N: Real> BilateralConstraint<N> {
             impulse,
             r: geom.r,
             rhs,
+            cfm: N::zero(),
             limits,
+            is_angular: geom.is_angular,
             impulse_id,
-            assembly_id1: assembly_id1,
-            assembly_id2: assembly_id2,
+            assembly_id1,
+            assembly_id2,
             j_id1: geom.j_id1,
             j_id2: geom.j_id2,
             wj_id1: geom.wj_id1,
@@ -259,9 +275,13 @@ pub struct BilateralGroundConstraint<N: Real> {
     pub r: N,
     /// The target velocity change this constraint must apply.
     pub rhs: N,
+    /// The constraint force mixing term, i.e., the compliance of this constraint (default: `0`).
+    pub cfm: N,
 
     /// Limits of impulse applicable by this constraint.
     pub limits: ImpulseLimits<N>,
+    /// Whether this constraint applies a torque instead of a linear force.
+    pub is_angular: bool,
 
     /// The index of the impulse used for its storage in an impuse cache.
     pub impulse_id: usize,
@@ -292,7 +312,9 @@ impl<N: Real> BilateralGroundConstraint<N> {
                 impulse,
                 r: geom.r,
                 rhs,
+                cfm: N::zero(),
                 limits,
+                is_angular: geom.is_angular,
                 impulse_id,
                 assembly_id: assembly_id2,
                 j_id: geom.j_id2,
@@ -304,7 +326,9 @@ impl<N: Real> BilateralGroundConstraint<N> {
                 impulse,
                 r: geom.r,
                 rhs,
+                cfm: N::zero(),
                 limits,
+                is_angular: geom.is_angular,
                 impulse_id,
                 assembly_id: assembly_id1,
                 j_id: geom.j_id1,
@@ -1,4 +1,8 @@
 use na::Real;
+use std::ops::Range;
+
+use joint::ConstraintHandle;
+use object::BodyHandle;
 use solver::{
     BilateralConstraint, BilateralGroundConstraint,
     MultibodyJointLimitsNonlinearConstraintGenerator, NonlinearUnilateralConstraint,
@@ -105,3 +109,63 @@ impl<N: Real> ConstraintSet<N> {
         self.position.clear();
     }
 }
+
+/// The origin of a group of velocity constraints.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConstraintOrigin {
+    /// Constraints generated by a joint constraint, identified by its handle and the two body parts it is attached to.
+    Joint(ConstraintHandle, BodyHandle, BodyHandle),
+    /// Constraints generated by the joint limits and motors of the multibody with the given handle.
+    Multibody(BodyHandle),
+    /// Constraints generated by the contact model for all the contacts.
+    Contacts,
+}
+
+/// A group of velocity constraints generated by the same origin.
+///
+/// Each range identifies the constraints of this group on the corresponding vector of a `Constraints`.
+#[derive(Clone, Debug)]
+pub struct ConstraintGroup {
+    /// The origin of this group of constraints.
+    pub origin: ConstraintOrigin,
+    /// The range of the unilateral ground constraints of this group.
+    pub unilateral_ground: Range<usize>,
+    /// The range of the unilateral constraints of this group.
+    pub unilateral: Range<usize>,
+    /// The range of the bilateral ground constraints of this group.
+    pub bilateral_ground: Range<usize>,
+    /// The range of the bilateral constraints of this group.
+    pub bilateral: Range<usize>,
+}
+
+impl ConstraintGroup {
+    // Starts a group with the constraints that will be pushed after the current end of `constraints`.
+    pub(crate) fn begin<N: Real>(origin: ConstraintOrigin, constraints: &Constraints<N>) -> Self {
+        ConstraintGroup {
+            origin,
+            unilateral_ground: constraints.unilateral_ground.len()
+                ..constraints.unilateral_ground.len(),
+            unilateral: constraints.unilateral.len()..constraints.unilateral.len(),
+            bilateral_ground: constraints.bilateral_ground.len()
+                ..constraints.bilateral_ground.len(),
+            bilateral: constraints.bilateral.len()..constraints.bilateral.len(),
+        }
+    }
+
+    // Ends this group with all the constraints pushed to `constraints` since `Self::begin`.
+    pub(crate) fn end<N: Real>(mut self, constraints: &Constraints<N>) -> Self {
+        self.unilateral_ground.end = constraints.unilateral_ground.len();
+        self.unilateral.end = constraints.unilateral.len();
+        self.bilateral_ground.end = constraints.bilateral_ground.len();
+        self.bilateral.end = constraints.bilateral.len();
+        self
+    }
+
+    /// The number of constraints in this group.
+    pub fn len(&self) -> usize {
+        self.unilateral_ground.len()
+            + self.unilateral.len()
+            + self.bilateral_ground.len()
+            + self.bilateral.len()
+    }
+}
@@ -2,6 +2,7 @@
 
 use downcast::Any;
 use na::{DVector, Real};
+use ncollide::utils::GenerationalId;
 
 use detection::ColliderContactManifold;
 use object::BodySet;
@@ -26,6 +27,14 @@ pub trait ContactModel<N: Real>: Any + Send + Sync {
 
     /// Stores all the impulses found by the solver into a cache for warmstarting.
     fn cache_impulses(&mut self, constraints: &ConstraintSet<N>);
+
+    /// The normal impulse applied at the specified contact during the last solve.
+    ///
+    /// Returns `None` if this contact was not handled by the last solve or if
+    /// this contact model does not keep track of impulses.
+    fn contact_impulse(&self, _contact_id: GenerationalId) -> Option<N> {
+        None
+    }
 }
 
 downcast!(<N> ContactModel<N> where N: Real);
@@ -1,8 +1,61 @@
 //! Utilities for computing velocity and position constraints.
+//!
+//! Those helpers are the building blocks used by the joint constraints of this crate and
+//! can be used the same way to implement new types of `JointConstraint` outside of it.
+//!
+//! # Velocity constraints
+//!
+//! The velocity constraints generated by `JointConstraint::velocity_constraints` are pushed
+//! into the `ConstraintSet` passed to this method. Their jacobians are stored into the
+//! `jacobians` buffer:
+//!
+//! * Each constraint between two body parts with respectively `ndofs1` and `ndofs2` degrees of
+//! freedom occupies `2 * (ndofs1 + ndofs2)` consecutive entries: the jacobian for the first
+//! body part, the jacobian for the second body part, and then the same two jacobians
+//! multiplied by the inverse augmented mass matrices of each body part.
+//! * Constraints involving a body part without any degree of freedom (the ground, a static or
+//! a kinematic body) are written starting at `ground_j_id`. All the others are written starting
+//! at `j_id`. Both indices are advanced by the helpers of this module and must be
+//! passed unchanged to every helper call.
+//! * The size of the `jacobians` buffer is computed by the solver from
+//! `JointConstraint::num_velocity_constraints`, so this must be an upper bound of the
+//! number of velocity constraints generated.
+//!
+//! Use `constraint_pair_geometry` and `constraint_pair_velocity` to write a single constraint
+//! along an arbitrary direction, and then push a `BilateralConstraint` or a
+//! `BilateralGroundConstraint` depending on `ConstraintGeometry::is_ground_constraint`.
+//!
+//! Constraints attaching a body part to the ground can use the helpers suffixed with
+//! `_wrt_ground` instead. They generate the same constraints as the helpers taking the ground as
+//! their first body part, but only compute the jacobians of the other body part and never
+//! access the ground.
+//!
+//! # Impulse identifiers
+//!
+//! The `impulse_id` given to a helper is not interpreted by the solver: it is stored into
+//! each generated constraint so the joint can retrieve the corresponding impulse in
+//! `JointConstraint::cache_impulses`. Helpers generating several constraints use the
+//! identifiers `impulse_id`, `impulse_id + 1`, etc. A joint typically records the ranges of
+//! `constraints.velocity.bilateral_ground` and `constraints.velocity.bilateral` filled by
+//! its calls to the helpers, and reads back the impulses of those ranges in
+//! `JointConstraint::cache_impulses`.
+//!
+//! # Position constraints
+//!
+//! The helpers returning a `GenericNonlinearConstraint` are meant to be called from
+//! `NonlinearConstraintGenerator::position_constraint`. They always write their jacobians at
+//! the beginning of the `jacobians` buffer, so only one such constraint can be generated per
+//! call to `position_constraint`. They return `None` if the positional error is smaller than the
+//! tolerances set in the `IntegrationParameters`.
+//!
+//! # Example
+//!
+//! The `custom_constraint3` example of the `examples3d` directory implements a constraint
+//! keeping a point attached to a body part on a plane attached to another body part, using
+//! only the public API of this crate together with `restrict_relative_linear_velocity_to_plane`
+//! and `cancel_relative_translation_wrt_axis`.
 
 use alga::linear::{FiniteDimInnerSpace, FiniteDimVectorSpace};
-#[cfg(feature = "dim3")]
-use na;
 use na::{DVector, DVectorSlice, Real, Unit};
 use std::ops::Neg;
 
@@ -94,6 +147,10 @@ pub fn constraint_pair_geometry<N: Real>(
 ) -> ConstraintGeometry<N> {
     let mut res = ConstraintGeometry::new();
 
+    res.is_angular = match *dir {
+        ForceDirection::Linear(_) => false,
+        ForceDirection::Angular(_) => true,
+    };
     res.ndofs1 = body1.status_dependent_parent_ndofs();
     res.ndofs2 = body2.status_dependent_parent_ndofs();
 
@@ -130,7 +187,10 @@ pub fn constraint_pair_geometry<N: Real>(
         );
     }
 
-    if body1.handle() == body2.handle() {
+    // Both body parts may belong to the same multibody (e.g. for loop closures).
+    // In that case, they share the same generalized velocities so the coupling
+    // terms must be taken into account.
+    if res.ndofs1 != 0 && body1.parent_handle() == body2.parent_handle() {
         let j1 = DVectorSlice::from_slice(&jacobians[res.j_id1..], res.ndofs1);
         let j2 = DVectorSlice::from_slice(&jacobians[res.j_id2..], res.ndofs2);
         let invm_j1 = DVectorSlice::from_slice(&jacobians[res.wj_id1..], res.ndofs1);
@@ -218,6 +278,275 @@ pub fn constraints_are_ground_constraints<N: Real>(
     body1.status_dependent_parent_ndofs() == 0 || body2.status_dependent_parent_ndofs() == 0
 }
 
+/// Fills all the jacobians (and the jacobians multiplied by the inverse augmented mass matrix)
+/// for a constraint between the ground and `body`, applying a force at the point `center` and
+/// the direction `-dir` to `body`.
+///
+/// This is equivalent to `constraint_pair_geometry` with the ground as the first body part, except
+/// that the ground is never accessed. Every input are expressed in world-space.
+#[inline]
+pub fn constraint_ground_geometry<N: Real>(
+    body: &BodyPart<N>,
+    center: &Point<N>,
+    dir: &ForceDirection<N>,
+    ground_j_id: &mut usize,
+    jacobians: &mut [N],
+) -> ConstraintGeometry<N> {
+    let mut res = ConstraintGeometry::new();
+
+    res.is_angular = match *dir {
+        ForceDirection::Linear(_) => false,
+        ForceDirection::Angular(_) => true,
+    };
+    res.ndofs2 = body.status_dependent_parent_ndofs();
+    res.j_id1 = *ground_j_id;
+    res.j_id2 = res.j_id1;
+    res.wj_id1 = res.j_id2 + res.ndofs2;
+    res.wj_id2 = res.wj_id1;
+
+    let mut inv_r = N::zero();
+
+    if res.ndofs2 != 0 {
+        fill_constraint_geometry(
+            body,
+            res.ndofs2,
+            center,
+            &dir.neg(),
+            res.j_id2,
+            res.wj_id2,
+            jacobians,
+            &mut inv_r,
+        );
+    }
+
+    if !inv_r.is_zero() {
+        res.r = N::one() / inv_r;
+    } else {
+        res.r = N::one()
+    }
+
+    *ground_j_id += res.ndofs2 * 2;
+    res
+}
+
+/// Compute the generalized velocity of the ground relative to `body` at the given point and along
+/// the given direction.
+///
+/// This is equivalent to `constraint_pair_velocity` with the ground as the first body part. Every
+/// input are expressed in world-space.
+#[inline]
+pub fn constraint_ground_velocity<N: Real>(
+    body: &BodyPart<N>,
+    assembly_id: usize,
+    center: &Point<N>,
+    dir: &ForceDirection<N>,
+    ext_vels: &DVector<N>,
+    jacobians: &[N],
+    geom: &ConstraintGeometry<N>,
+) -> N {
+    if geom.ndofs2 != 0 {
+        let j = DVectorSlice::from_slice(&jacobians[geom.j_id2..], geom.ndofs2);
+        j.dot(&body.parent_generalized_velocity()) + j.dot(&ext_vels.rows(assembly_id, geom.ndofs2))
+    } else {
+        // Adjust the rhs for kinematic bodies.
+        let vel = body.status_dependent_velocity();
+
+        match *dir {
+            ForceDirection::Linear(ref normal) => {
+                let dpos = center - body.center_of_mass();
+                -vel.shift(&dpos).linear.dot(normal)
+            }
+            ForceDirection::Angular(ref axis) => -vel.angular_vector().dot(axis),
+        }
+    }
+}
+
+/// Generates a velocity constraint to cancel the velocity of `body` at the point `anchor` along the
+/// given direction.
+///
+/// This is the constraint generated by `cancel_relative_linear_velocity_wrt_axis` or by
+/// `cancel_relative_angular_velocity_wrt_axis` with the ground as the first body part, but only
+/// the jacobians of `body` are computed. All inputs mut be given in world-space.
+pub fn cancel_velocity_wrt_ground<N: Real>(
+    body: &BodyPart<N>,
+    assembly_id: usize,
+    anchor: &Point<N>,
+    dir: &ForceDirection<N>,
+    ext_vels: &DVector<N>,
+    impulse: N,
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    let limits = ImpulseLimits::Independent {
+        min: -N::max_value(),
+        max: N::max_value(),
+    };
+
+    let geom = constraint_ground_geometry(body, anchor, dir, ground_j_id, jacobians);
+    let rhs =
+        constraint_ground_velocity(body, assembly_id, anchor, dir, ext_vels, jacobians, &geom);
+
+    constraints
+        .velocity
+        .bilateral_ground
+        .push(BilateralGroundConstraint::new(
+            geom,
+            assembly_id,
+            assembly_id,
+            limits,
+            rhs,
+            impulse,
+            impulse_id,
+        ));
+}
+
+/// Generates velocity constraints to cancel the linear velocity of `body` at the point `anchor`.
+///
+/// All inputs mut be given in world-space.
+pub fn cancel_linear_velocity_wrt_ground<N: Real>(
+    body: &BodyPart<N>,
+    assembly_id: usize,
+    anchor: &Point<N>,
+    ext_vels: &DVector<N>,
+    impulses: &Vector<N>,
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    let mut i = 0;
+    Vector::canonical_basis(|dir| {
+        cancel_velocity_wrt_ground(
+            body,
+            assembly_id,
+            anchor,
+            &ForceDirection::Linear(Unit::new_unchecked(*dir)),
+            ext_vels,
+            impulses[i],
+            impulse_id + i,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        i += 1;
+
+        true
+    });
+}
+
+/// Generates velocity constraints to cancel the angular velocity of `body`.
+///
+/// All inputs mut be given in world-space.
+pub fn cancel_angular_velocity_wrt_ground<N: Real>(
+    body: &BodyPart<N>,
+    assembly_id: usize,
+    anchor: &Point<N>,
+    ext_vels: &DVector<N>,
+    impulses: &AngularVector<N>,
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    let mut i = 0;
+    AngularVector::canonical_basis(|dir| {
+        cancel_velocity_wrt_ground(
+            body,
+            assembly_id,
+            anchor,
+            &ForceDirection::Angular(Unit::new_unchecked(*dir)),
+            ext_vels,
+            impulses[i],
+            impulse_id + i,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        i += 1;
+
+        true
+    });
+}
+
+/// Generates velocity constraints to cancel the angular velocity of `body` along all axis except
+/// the one provided.
+///
+/// All inputs mut be given in world-space.
+#[cfg(feature = "dim3")]
+pub fn restrict_angular_velocity_to_axis_wrt_ground<N: Real>(
+    body: &BodyPart<N>,
+    assembly_id: usize,
+    axis: &Unit<AngularVector<N>>,
+    anchor: &Point<N>,
+    ext_vels: &DVector<N>,
+    impulses: &[N],
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    let mut i = 0;
+    AngularVector::orthonormal_subspace_basis(&[axis.into_inner()], |dir| {
+        cancel_velocity_wrt_ground(
+            body,
+            assembly_id,
+            anchor,
+            &ForceDirection::Angular(Unit::new_unchecked(*dir)),
+            ext_vels,
+            impulses[i],
+            impulse_id + i,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        i += 1;
+
+        true
+    });
+}
+
+/// Generates velocity constraints to cancel the linear velocity of `body` at the point `anchor`
+/// along all axis except the one provided.
+///
+/// All inputs mut be given in world-space.
+pub fn restrict_linear_velocity_to_axis_wrt_ground<N: Real>(
+    body: &BodyPart<N>,
+    assembly_id: usize,
+    anchor: &Point<N>,
+    axis: &Unit<Vector<N>>,
+    ext_vels: &DVector<N>,
+    impulses: &[N],
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    let mut i = 0;
+    Vector::orthonormal_subspace_basis(&[axis.into_inner()], |dir| {
+        cancel_velocity_wrt_ground(
+            body,
+            assembly_id,
+            anchor,
+            &ForceDirection::Linear(Unit::new_unchecked(*dir)),
+            ext_vels,
+            impulses[i],
+            impulse_id + i,
+            ground_j_id,
+            jacobians,
+            constraints,
+        );
+
+        i += 1;
+
+        true
+    });
+}
+
 /// Generates velocity constraints to cancel the relative linear velocity of two body parts wrt the given axis.
 ///
 /// All inputs mut be given in world-space.
@@ -296,6 +625,83 @@ pub fn cancel_relative_linear_velocity_wrt_axis<N: Real>(
     }
 }
 
+/// Generates a velocity constraint driving the relative velocity of `body2` wrt. `body1` along
+/// the given direction toward `desired_velocity`.
+///
+/// The impulse applied by this constraint is clamped by `limits`, e.g., the impulse limits of a
+/// `JointMotor`. All inputs mut be given in world-space.
+pub fn drive_relative_velocity_wrt_direction<N: Real>(
+    body1: &BodyPart<N>,
+    body2: &BodyPart<N>,
+    assembly_id1: usize,
+    assembly_id2: usize,
+    anchor1: &Point<N>,
+    anchor2: &Point<N>,
+    dir: &ForceDirection<N>,
+    desired_velocity: N,
+    limits: ImpulseLimits<N>,
+    ext_vels: &DVector<N>,
+    impulse: N,
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    let geom = constraint_pair_geometry(
+        body1,
+        body2,
+        anchor1,
+        anchor2,
+        dir,
+        ground_j_id,
+        j_id,
+        jacobians,
+    );
+
+    // The pair velocity is the velocity of `body1` wrt. `body2`.
+    let rhs = constraint_pair_velocity(
+        &body1,
+        &body2,
+        assembly_id1,
+        assembly_id2,
+        anchor1,
+        anchor2,
+        dir,
+        ext_vels,
+        jacobians,
+        &geom,
+    ) + desired_velocity;
+
+    if geom.ndofs1 == 0 || geom.ndofs2 == 0 {
+        constraints
+            .velocity
+            .bilateral_ground
+            .push(BilateralGroundConstraint::new(
+                geom,
+                assembly_id1,
+                assembly_id2,
+                limits,
+                rhs,
+                impulse,
+                impulse_id,
+            ));
+    } else {
+        constraints
+            .velocity
+            .bilateral
+            .push(BilateralConstraint::new(
+                geom,
+                assembly_id1,
+                assembly_id2,
+                limits,
+                rhs,
+                impulse,
+                impulse_id,
+            ));
+    }
+}
+
 /// Generates velocity constraints to cancel the relative linear velocity of two body parts.
 ///
 /// All inputs mut be given in world-space.
@@ -637,7 +1043,7 @@ pub fn restrict_relative_angular_velocity_to_axis<N: Real>(
     };
 
     let mut i = 0;
-    AngularVector::orthonormal_subspace_basis(&[axis.unwrap()], |dir| {
+    AngularVector::orthonormal_subspace_basis(&[axis.into_inner()], |dir| {
         let dir = ForceDirection::Angular(Unit::new_unchecked(*dir));
         let geom = constraint_pair_geometry(
             body1,
@@ -720,7 +1126,7 @@ pub fn align_axis<N: Real>(
         let imin = axis1.iamin();
         error = Vector::zeros();
         error[imin] = N::one();
-        error = na::normalize(&error.cross(&axis1)) * N::pi();
+        error = error.cross(&axis1).normalize() * N::pi();
     }
 
     if let Some((dir, depth)) = Unit::try_new_and_get(error, params.allowed_angular_error) {
@@ -782,7 +1188,7 @@ pub fn restrict_relative_linear_velocity_to_axis<N: Real>(
     };
 
     let mut i = 0;
-    Vector::orthonormal_subspace_basis(&[axis1.unwrap()], |dir| {
+    Vector::orthonormal_subspace_basis(&[axis1.into_inner()], |dir| {
         let dir = ForceDirection::Linear(Unit::new_unchecked(*dir));
         let geom = constraint_pair_geometry(
             body1,
@@ -842,6 +1248,44 @@ pub fn restrict_relative_linear_velocity_to_axis<N: Real>(
     });
 }
 
+/// Generate velocity constraints to cancel the relative linear velocity of two bodies along the normal of a plane.
+///
+/// Only the relative linear velocity along the directions lying on the plane with normal `normal1`
+/// remains free. All inputs mut be given in world-space.
+pub fn restrict_relative_linear_velocity_to_plane<N: Real>(
+    body1: &BodyPart<N>,
+    body2: &BodyPart<N>,
+    assembly_id1: usize,
+    assembly_id2: usize,
+    anchor1: &Point<N>,
+    anchor2: &Point<N>,
+    normal1: &Unit<Vector<N>>,
+    ext_vels: &DVector<N>,
+    impulse: N,
+    impulse_id: usize,
+    ground_j_id: &mut usize,
+    j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    cancel_relative_linear_velocity_wrt_axis(
+        body1,
+        body2,
+        assembly_id1,
+        assembly_id2,
+        anchor1,
+        anchor2,
+        normal1,
+        ext_vels,
+        impulse,
+        impulse_id,
+        ground_j_id,
+        j_id,
+        jacobians,
+        constraints,
+    )
+}
+
 /// Generate position constraints to project `anchor2` into the axis with direction `axis1` and passing through the `anchor1`.
 ///
 /// All inputs mut be given in world-space.
@@ -856,7 +1300,7 @@ pub fn project_anchor_to_axis<N: Real>(
 ) -> Option<GenericNonlinearConstraint<N>> {
     // Linear regularization of a point on an axis.
     let dpt = anchor2 - anchor1;
-    let proj = anchor1 + axis1.unwrap() * axis1.dot(&dpt);
+    let proj = anchor1 + axis1.into_inner() * axis1.dot(&dpt);
     let error = anchor2 - proj;
 
     if let Some((dir, depth)) = Unit::try_new_and_get(error, params.allowed_linear_error) {
@@ -917,7 +1361,7 @@ pub fn restore_angle_between_axis<N: Real>(
         let imin = axis1.iamin();
         separation = Vector::zeros();
         separation[imin] = N::one();
-        separation = na::normalize(&separation.cross(&axis1)) * N::pi();
+        separation = separation.cross(&axis1).normalize() * N::pi();
     }
 
     if let Some((mut dir, curr_ang)) = Unit::try_new_and_get(separation, N::default_epsilon()) {
@@ -964,3 +1408,158 @@ pub fn restore_angle_between_axis<N: Real>(
         None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use na::{self, DVector, Unit};
+    use ncollide::shape::Cuboid;
+
+    use math::{AngularVector, Isometry, Point, Vector, Velocity, SPATIAL_DIM};
+    use object::{BodyHandle, BodySet, MultibodyWorkspace};
+    use solver::{ConstraintSet, IntegrationParameters};
+    use volumetric::Volumetric;
+
+    #[test]
+    fn ground_helpers_generate_the_constraints_of_the_pair_helpers() {
+        let mut bodies = BodySet::new();
+        let cuboid = Cuboid::new(Vector::x() * 0.5 + Vector::repeat(0.3));
+        let handle = bodies.add_rigid_body(
+            Isometry::new(Vector::y() * 2.0, na::zero()),
+            cuboid.inertia(2.0),
+            cuboid.center_of_mass(),
+        );
+
+        {
+            let rb = bodies.rigid_body_mut(handle).unwrap();
+            rb.set_companion_id(0);
+            rb.set_velocity(Velocity::new_with_vectors(
+                Vector::x(),
+                AngularVector::repeat(-0.5),
+            ));
+        }
+
+        let params = IntegrationParameters::default();
+        bodies.update_dynamics(&Vector::zeros(), &params, &mut MultibodyWorkspace::new());
+
+        let ground = bodies.body_part(BodyHandle::ground());
+        let body = bodies.body_part(handle);
+        let ext_vels = DVector::from_fn(SPATIAL_DIM, |i, _| i as f32 * 0.1);
+        let anchor = Point::origin() + Vector::repeat(0.7);
+        let axis = Unit::new_normalize(Vector::x() + Vector::y());
+        let lin_impulses = Vector::repeat(0.5);
+        let ang_impulses = AngularVector::repeat(-0.25);
+
+        let mut ground_j_id = 0;
+        let mut jacobians = vec![0.0; 100];
+        let mut constraints = ConstraintSet::new();
+
+        super::cancel_linear_velocity_wrt_ground(
+            &body,
+            0,
+            &anchor,
+            &ext_vels,
+            &lin_impulses,
+            0,
+            &mut ground_j_id,
+            &mut jacobians,
+            &mut constraints,
+        );
+        super::cancel_angular_velocity_wrt_ground(
+            &body,
+            0,
+            &anchor,
+            &ext_vels,
+            &ang_impulses,
+            3,
+            &mut ground_j_id,
+            &mut jacobians,
+            &mut constraints,
+        );
+        super::restrict_linear_velocity_to_axis_wrt_ground(
+            &body,
+            0,
+            &anchor,
+            &axis,
+            &ext_vels,
+            lin_impulses.as_slice(),
+            6,
+            &mut ground_j_id,
+            &mut jacobians,
+            &mut constraints,
+        );
+
+        let mut pair_ground_j_id = 0;
+        let mut pair_j_id = 0;
+        let mut pair_jacobians = vec![0.0; 100];
+        let mut pair_constraints = ConstraintSet::new();
+        let ground_id = ground.parent_companion_id();
+
+        super::cancel_relative_linear_velocity(
+            &ground,
+            &body,
+            ground_id,
+            0,
+            &anchor,
+            &anchor,
+            &ext_vels,
+            &lin_impulses,
+            0,
+            &mut pair_ground_j_id,
+            &mut pair_j_id,
+            &mut pair_jacobians,
+            &mut pair_constraints,
+        );
+        super::cancel_relative_angular_velocity(
+            &ground,
+            &body,
+            ground_id,
+            0,
+            &anchor,
+            &anchor,
+            &ext_vels,
+            &ang_impulses,
+            3,
+            &mut pair_ground_j_id,
+            &mut pair_j_id,
+            &mut pair_jacobians,
+            &mut pair_constraints,
+        );
+        super::restrict_relative_linear_velocity_to_axis(
+            &ground,
+            &body,
+            ground_id,
+            0,
+            &anchor,
+            &anchor,
+            &axis,
+            &ext_vels,
+            lin_impulses.as_slice(),
+            6,
+            &mut pair_ground_j_id,
+            &mut pair_j_id,
+            &mut pair_jacobians,
+            &mut pair_constraints,
+        );
+
+        assert_eq!(ground_j_id, pair_ground_j_id);
+        assert_eq!(pair_j_id, 0);
+        assert_eq!(jacobians, pair_jacobians);
+        assert!(pair_constraints.velocity.bilateral.is_empty());
+
+        let rows = &constraints.velocity.bilateral_ground;
+        let pair_rows = &pair_constraints.velocity.bilateral_ground;
+        assert_eq!(rows.len(), pair_rows.len());
+
+        for (row, pair_row) in rows.iter().zip(pair_rows.iter()) {
+            assert_eq!(row.is_angular, pair_row.is_angular);
+            assert_eq!(row.impulse_id, pair_row.impulse_id);
+            assert_eq!(row.assembly_id, pair_row.assembly_id);
+            assert_eq!(row.j_id, pair_row.j_id);
+            assert_eq!(row.wj_id, pair_row.wj_id);
+            assert_eq!(row.ndofs, pair_row.ndofs);
+            assert_relative_eq!(row.r, pair_row.r);
+            assert_relative_eq!(row.rhs, pair_row.rhs);
+            assert_relative_eq!(row.impulse, pair_row.impulse);
+        }
+    }
+}
@@ -1,6 +1,45 @@
 use na::{self, Real};
 
+/// The method used to solve the constraints at each time step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SolverMethod {
+    /// Constraints are solved at the velocity level with impulses, followed by
+    /// a non-linear position-based stabilization step.
+    ImpulseBased,
+    /// Constraints are projected directly on the predicted positions, and the
+    /// velocities are deduced from the resulting position changes.
+    ///
+    /// Velocity-based constraints (friction, restitution, motors) are ignored by this method.
+    #[cfg(feature = "pbd")]
+    PositionBased,
+    /// Extended position-based dynamics: like `PositionBased`, but the corrections of each joint
+    /// are computed from a Lagrange multiplier accumulated during the step.
+    ///
+    /// The stiffness of a joint is then given by its compliance, independently from the number
+    /// of position iterations and from the timestep. The `erp` is ignored by this method.
+    #[cfg(feature = "pbd")]
+    Xpbd,
+}
+
+/// The method used to correct the penetrations between colliders in contact.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PenetrationCorrectionMethod {
+    /// A fraction of the penetration depth, given by the `erp`, is added as a bias to the
+    /// relative velocity targeted by the contact velocity constraints.
+    ///
+    /// The correction impulse is part of the dynamic impulse so it increases the velocities of
+    /// the bodies, adding energy to the system.
+    Baumgarte,
+    /// The penetrations are corrected by the non-linear position solver, separately from the
+    /// velocity constraints.
+    ///
+    /// The correction impulse only displaces the bodies so it never affects their velocities,
+    /// including the ones used for restitution.
+    SplitImpulse,
+}
+
 /// Parameters for a time-step of the physics engine.
+#[derive(Clone)]
 pub struct IntegrationParameters<N: Real> {
     /// The timestep (default: `1.0 / 60.0`)
     pub dt: N,
@@ -30,9 +69,47 @@ pub struct IntegrationParameters<N: Real> {
     pub max_velocity_iterations: usize,
     /// Maximum number of iterations performed by the position-based constraints solver.
     pub max_position_iterations: usize,
+    /// The method used to solve the constraints (default: `SolverMethod::ImpulseBased`).
+    pub solver_method: SolverMethod,
+    /// The method used to correct contact penetrations
+    /// (default: `PenetrationCorrectionMethod::SplitImpulse`).
+    ///
+    /// This is ignored by the position-based solver method which always corrects penetrations
+    /// with the non-linear position solver.
+    pub penetration_correction: PenetrationCorrectionMethod,
+    /// Maximum norm of the linear velocity of rigid bodies, applied right before the position
+    /// integration (default: `None`).
+    pub max_linear_velocity: Option<N>,
+    /// Maximum norm of the angular velocity of rigid bodies, applied right before the position
+    /// integration (default: `None`).
+    pub max_angular_velocity: Option<N>,
 }
 
 impl<N: Real> IntegrationParameters<N> {
+    /// Whether the constraints are projected on the positions instead of being solved with impulses.
+    pub fn is_position_based(&self) -> bool {
+        self.solver_method != SolverMethod::ImpulseBased
+    }
+
+    /// Whether the joint position corrections use the XPBD Lagrange multiplier formulation.
+    #[cfg(feature = "pbd")]
+    pub fn is_xpbd(&self) -> bool {
+        self.solver_method == SolverMethod::Xpbd
+    }
+
+    /// Whether the joint position corrections use the XPBD Lagrange multiplier formulation.
+    #[cfg(not(feature = "pbd"))]
+    pub fn is_xpbd(&self) -> bool {
+        false
+    }
+
+    /// Whether contact penetrations are corrected by the non-linear position solver instead of
+    /// a velocity bias.
+    pub fn corrects_penetrations_with_positions(&self) -> bool {
+        self.penetration_correction == PenetrationCorrectionMethod::SplitImpulse
+            || self.is_position_based()
+    }
+
     /// Creates a set of integration parameters with the given values.
     pub fn new(
         dt: N,
@@ -59,6 +136,10 @@ impl<N: Real> IntegrationParameters<N> {
             max_stabilization_multiplier,
             max_velocity_iterations,
             max_position_iterations,
+            solver_method: SolverMethod::ImpulseBased,
+            penetration_correction: PenetrationCorrectionMethod::SplitImpulse,
+            max_linear_velocity: None,
+            max_angular_velocity: None,
         }
     }
 }
@@ -4,11 +4,13 @@ pub use self::constraint::{
     BilateralConstraint, BilateralGroundConstraint, ConstraintGeometry, ImpulseLimits,
     UnilateralConstraint, UnilateralGroundConstraint,
 };
-pub use self::constraint_set::ConstraintSet;
+pub use self::constraint_set::{ConstraintGroup, ConstraintOrigin, ConstraintSet};
 pub use self::contact_model::ContactModel;
 pub use self::helper::ForceDirection;
 pub use self::impulse_cache::ImpulseCache;
-pub use self::integration_parameters::IntegrationParameters;
+pub use self::integration_parameters::{
+    IntegrationParameters, PenetrationCorrectionMethod, SolverMethod,
+};
 pub use self::moreau_jean_solver::MoreauJeanSolver;
 pub use self::nonlinear_constraint::{
     GenericNonlinearConstraint, MultibodyJointLimitsNonlinearConstraintGenerator,
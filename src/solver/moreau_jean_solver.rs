@@ -1,20 +1,74 @@
 use slab::Slab;
+use std::collections::HashMap;
+use std::ops::Range;
 
 use na::{DVector, Real};
 
 use counters::Counters;
 use detection::ColliderContactManifold;
-use joint::JointConstraint;
+use joint::{ConstraintHandle, JointConstraint};
 use object::{BodyHandle, BodySet};
-use solver::{ConstraintSet, ContactModel, IntegrationParameters, NonlinearSORProx, SORProx};
+use solver::{
+    ConstraintGroup, ConstraintOrigin, ConstraintSet, ContactModel, ImpulseLimits,
+    IntegrationParameters, NonlinearSORProx, SORProx,
+};
 
 /// Moreau-Jean time-stepping scheme.
 pub struct MoreauJeanSolver<N: Real> {
     jacobians: Vec<N>, // FIXME: use a Vec or a DVector?
     mj_lambda_vel: DVector<N>,
     ext_vels: DVector<N>,
+    // The position corrections accumulated by position-based dynamics.
+    #[cfg(feature = "pbd")]
+    displacements: DVector<N>,
     contact_model: Box<ContactModel<N>>,
     constraints: ConstraintSet<N>,
+    constraint_groups: Vec<ConstraintGroup>,
+    bounded_joints: Vec<BoundedJoint<N>>,
+    saturated_joints: Vec<ConstraintHandle>,
+    position_impulses: HashMap<ConstraintHandle, Vec<N>>,
+    velocity_iterations: HashMap<BodyHandle, usize>,
+    #[cfg(feature = "parallel")]
+    islands: Vec<Range<usize>>,
+    nvelocity_iterations: usize,
+    nposition_iterations: usize,
+}
+
+// The velocity constraints generated by a joint with a maximum force or torque.
+struct BoundedJoint<N: Real> {
+    handle: ConstraintHandle,
+    bilateral_ground_rng: Range<usize>,
+    bilateral_rng: Range<usize>,
+    max_linear_impulse: Option<N>,
+    max_angular_impulse: Option<N>,
+}
+
+impl<N: Real> BoundedJoint<N> {
+    fn max_impulse(&self, is_angular: bool) -> Option<N> {
+        if is_angular {
+            self.max_angular_impulse
+        } else {
+            self.max_linear_impulse
+        }
+    }
+
+    fn clamp_limits(&self, limits: &mut ImpulseLimits<N>, is_angular: bool) {
+        if let Some(max_impulse) = self.max_impulse(is_angular) {
+            if let ImpulseLimits::Independent {
+                ref mut min,
+                ref mut max,
+            } = *limits
+            {
+                *min = (*min).max(-max_impulse);
+                *max = (*max).min(max_impulse);
+            }
+        }
+    }
+
+    fn is_saturated(&self, impulse: N, is_angular: bool) -> bool {
+        self.max_impulse(is_angular)
+            .map_or(false, |max_impulse| impulse.abs() >= max_impulse)
+    }
 }
 
 impl<N: Real> MoreauJeanSolver<N> {
@@ -26,8 +80,19 @@ impl<N: Real> MoreauJeanSolver<N> {
             jacobians: Vec::new(),
             mj_lambda_vel: DVector::zeros(0),
             ext_vels: DVector::zeros(0),
-            contact_model: contact_model,
-            constraints: constraints,
+            #[cfg(feature = "pbd")]
+            displacements: DVector::zeros(0),
+            contact_model,
+            constraints,
+            constraint_groups: Vec::new(),
+            bounded_joints: Vec::new(),
+            saturated_joints: Vec::new(),
+            position_impulses: HashMap::new(),
+            velocity_iterations: HashMap::new(),
+            #[cfg(feature = "parallel")]
+            islands: Vec::new(),
+            nvelocity_iterations: 0,
+            nposition_iterations: 0,
         }
     }
 
@@ -36,6 +101,59 @@ impl<N: Real> MoreauJeanSolver<N> {
         self.contact_model = model
     }
 
+    /// The contact model used by this solver.
+    pub fn contact_model(&self) -> &ContactModel<N> {
+        &*self.contact_model
+    }
+
+    /// The constraints generated and solved during the last step.
+    ///
+    /// The impulses of the velocity constraints are the ones computed by the solver. Note that
+    /// the jacobians referenced by those constraints are overwritten by the position correction.
+    pub fn constraints(&self) -> &ConstraintSet<N> {
+        &self.constraints
+    }
+
+    /// The origin of each group of velocity constraints returned by `self.constraints()`.
+    pub fn constraint_groups(&self) -> &[ConstraintGroup] {
+        &self.constraint_groups[..]
+    }
+
+    /// The joint constraints that reached their maximum force or torque during the last step.
+    pub fn saturated_constraints(&self) -> &[ConstraintHandle] {
+        &self.saturated_joints[..]
+    }
+
+    /// Sets the number of velocity iterations of the constraints involving some bodies.
+    ///
+    /// This overrides `IntegrationParameters::max_velocity_iterations` for the constraints
+    /// involving a body of the island that is a key of `iterations`. All the bodies connected by
+    /// constraints should be given the same number of iterations.
+    pub fn set_velocity_iterations(&mut self, iterations: HashMap<BodyHandle, usize>) {
+        self.velocity_iterations = iterations
+    }
+
+    /// Sets the islands of the bodies given to the next call to `self.step(...)`.
+    ///
+    /// Each range gives the indices of the bodies of one island in the body slice given to
+    /// `self.step(...)`, in increasing order. The velocity constraints of distinct islands are
+    /// then solved in parallel. Each island is still solved sequentially so the result does not
+    /// depend on the number of threads.
+    #[cfg(feature = "parallel")]
+    pub fn set_islands(&mut self, islands: Vec<Range<usize>>) {
+        self.islands = islands
+    }
+
+    /// The number of velocity iterations run during the last step.
+    pub fn velocity_iterations(&self) -> usize {
+        self.nvelocity_iterations
+    }
+
+    /// The number of position iterations run during the last step.
+    pub fn position_iterations(&self) -> usize {
+        self.nposition_iterations
+    }
+
     /// Perform one step of the time-stepping scheme.
     pub fn step(
         &mut self,
@@ -46,6 +164,17 @@ impl<N: Real> MoreauJeanSolver<N> {
         island: &[BodyHandle],
         params: &IntegrationParameters<N>,
     ) {
+        self.nvelocity_iterations = 0;
+        self.nposition_iterations = params.max_position_iterations;
+
+        #[cfg(feature = "pbd")]
+        {
+            if params.is_position_based() {
+                return self
+                    .step_position_based(counters, bodies, joints, manifolds, island, params);
+            }
+        }
+
         counters.assembly_started();
         self.assemble_system(counters, params, bodies, joints, manifolds, island);
         counters.assembly_completed();
@@ -53,7 +182,8 @@ impl<N: Real> MoreauJeanSolver<N> {
         counters.set_nconstraints(self.constraints.velocity.len());
 
         counters.velocity_resolution_started();
-        self.solve_velocity_constraints(params);
+        self.solve_velocity_constraints(params, bodies, island);
+        self.update_saturated_joints();
         self.save_cache(bodies, joints, island);
         counters.velocity_resolution_completed();
 
@@ -66,6 +196,66 @@ impl<N: Real> MoreauJeanSolver<N> {
         counters.position_resolution_completed();
     }
 
+    /// Perform one step of position-based dynamics.
+    ///
+    /// The velocities are integrated first, then the predicted positions are projected
+    /// onto the constraints. The velocities are finally corrected from the position changes.
+    #[cfg(feature = "pbd")]
+    fn step_position_based(
+        &mut self,
+        counters: &mut Counters,
+        bodies: &mut BodySet<N>,
+        joints: &mut Slab<Box<JointConstraint<N>>>,
+        manifolds: &[ColliderContactManifold<N>],
+        island: &[BodyHandle],
+        params: &IntegrationParameters<N>,
+    ) {
+        counters.assembly_started();
+        self.assemble_system(counters, params, bodies, joints, manifolds, island);
+        counters.assembly_completed();
+
+        counters.velocity_update_started();
+        for handle in island {
+            let mut body = bodies.body_mut(*handle);
+            let id = body.companion_id();
+            let ndofs = body.ndofs();
+
+            {
+                let mut vels = body.generalized_velocity_mut();
+                vels += self.ext_vels.rows(id, ndofs);
+            }
+
+            body.integrate(params);
+        }
+        counters.velocity_update_completed();
+
+        counters.position_resolution_started();
+        let solver = NonlinearSORProx::new();
+
+        solver.solve_with_displacements(
+            params,
+            bodies,
+            &mut self.constraints.position.unilateral,
+            &self.constraints.position.multibody_limits,
+            joints,
+            &mut self.position_impulses,
+            &mut self.jacobians,
+            params.max_position_iterations,
+            &mut self.displacements,
+        );
+        counters.position_resolution_completed();
+
+        let inv_dt = N::one() / params.dt;
+
+        for handle in island {
+            let mut body = bodies.body_mut(*handle);
+            let id = body.companion_id();
+            let ndofs = body.ndofs();
+            let mut vels = body.generalized_velocity_mut();
+            vels.axpy(inv_dt, &self.displacements.rows(id, ndofs), N::one());
+        }
+    }
+
     fn assemble_system(
         &mut self,
         counters: &mut Counters,
@@ -91,6 +281,9 @@ impl<N: Real> MoreauJeanSolver<N> {
 
         self.resize_buffers(system_ndofs);
         self.constraints.clear();
+        self.constraint_groups.clear();
+        self.bounded_joints.clear();
+        self.saturated_joints.clear();
 
         /*
          * Initialize M^{-1} h * dt
@@ -147,9 +340,7 @@ impl<N: Real> MoreauJeanSolver<N> {
 
         for handle in island {
             if let Some(mb) = bodies.multibody(*handle) {
-                for rb in mb.rbs().iter() {
-                    ground_jacobian_sz += rb.dof.num_velocity_constraints() * mb.ndofs() * 2;
-                }
+                ground_jacobian_sz += mb.num_velocity_constraints() * mb.ndofs() * 2;
             }
         }
 
@@ -164,8 +355,18 @@ impl<N: Real> MoreauJeanSolver<N> {
         let mut j_id = 0;
         let mut ground_j_id = jacobian_sz;
 
-        for (_, g) in joints {
+        for (handle, g) in joints {
             if g.is_active(bodies) {
+                let first_unilateral_ground = self.constraints.velocity.unilateral_ground.len();
+                let first_unilateral = self.constraints.velocity.unilateral.len();
+                let first_bilateral_ground = self.constraints.velocity.bilateral_ground.len();
+                let first_bilateral = self.constraints.velocity.bilateral.len();
+                let (b1, b2) = g.anchors();
+                let group = ConstraintGroup::begin(
+                    ConstraintOrigin::Joint(handle, b1, b2),
+                    &self.constraints.velocity,
+                );
+
                 g.velocity_constraints(
                     params,
                     bodies,
@@ -175,11 +376,66 @@ impl<N: Real> MoreauJeanSolver<N> {
                     &mut self.jacobians,
                     &mut self.constraints,
                 );
+
+                self.constraint_groups
+                    .push(group.end(&self.constraints.velocity));
+
+                if let Some(compliance) = g.compliance() {
+                    let cfm = compliance / (params.dt * params.dt);
+                    let velocity = &mut self.constraints.velocity;
+
+                    for c in &mut velocity.unilateral_ground[first_unilateral_ground..] {
+                        c.r = N::one() / (N::one() / c.r + cfm);
+                        c.cfm = cfm;
+                    }
+
+                    for c in &mut velocity.unilateral[first_unilateral..] {
+                        c.r = N::one() / (N::one() / c.r + cfm);
+                        c.cfm = cfm;
+                    }
+
+                    for c in &mut velocity.bilateral_ground[first_bilateral_ground..] {
+                        c.r = N::one() / (N::one() / c.r + cfm);
+                        c.cfm = cfm;
+                    }
+
+                    for c in &mut velocity.bilateral[first_bilateral..] {
+                        c.r = N::one() / (N::one() / c.r + cfm);
+                        c.cfm = cfm;
+                    }
+                }
+
+                if g.max_force().is_some() || g.max_torque().is_some() {
+                    let velocity = &mut self.constraints.velocity;
+                    let bounded = BoundedJoint {
+                        handle,
+                        bilateral_ground_rng: first_bilateral_ground
+                            ..velocity.bilateral_ground.len(),
+                        bilateral_rng: first_bilateral..velocity.bilateral.len(),
+                        max_linear_impulse: g.max_force().map(|f| f * params.dt),
+                        max_angular_impulse: g.max_torque().map(|t| t * params.dt),
+                    };
+
+                    for c in &mut velocity.bilateral_ground[bounded.bilateral_ground_rng.clone()] {
+                        bounded.clamp_limits(&mut c.limits, c.is_angular);
+                    }
+
+                    for c in &mut velocity.bilateral[bounded.bilateral_rng.clone()] {
+                        bounded.clamp_limits(&mut c.limits, c.is_angular);
+                    }
+
+                    self.bounded_joints.push(bounded);
+                }
             }
         }
 
         for handle in island {
             if let Some(mb) = bodies.multibody_mut(*handle) {
+                let group = ConstraintGroup::begin(
+                    ConstraintOrigin::Multibody(*handle),
+                    &self.constraints.velocity,
+                );
+
                 mb.constraints(
                     params,
                     &self.ext_vels,
@@ -187,10 +443,14 @@ impl<N: Real> MoreauJeanSolver<N> {
                     &mut self.jacobians,
                     &mut self.constraints,
                 );
+
+                self.constraint_groups
+                    .push(group.end(&self.constraints.velocity));
             }
         }
 
         counters.custom_started();
+        let group = ConstraintGroup::begin(ConstraintOrigin::Contacts, &self.constraints.velocity);
         self.contact_model.constraints(
             params,
             bodies,
@@ -201,13 +461,66 @@ impl<N: Real> MoreauJeanSolver<N> {
             &mut self.jacobians,
             &mut self.constraints,
         );
+        self.constraint_groups
+            .push(group.end(&self.constraints.velocity));
         counters.custom_completed();
     }
 
-    fn solve_velocity_constraints(&mut self, params: &IntegrationParameters<N>) {
+    fn solve_velocity_constraints(
+        &mut self,
+        params: &IntegrationParameters<N>,
+        bodies: &BodySet<N>,
+        island: &[BodyHandle],
+    ) {
         let solver = SORProx::new();
+        let mut iterations = HashMap::new();
 
-        solver.solve(
+        if !self.velocity_iterations.is_empty() {
+            for handle in island {
+                if let Some(n) = self.velocity_iterations.get(handle) {
+                    let _ = iterations.insert(bodies.body(*handle).companion_id(), *n);
+                }
+            }
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            let nbodies = self.islands.last().map(|rng| rng.end);
+
+            if self.islands.len() > 1 && nbodies == Some(island.len()) {
+                // The range of assembly ids of each island.
+                let ndofs = self.mj_lambda_vel.len();
+                let starts: Vec<usize> = self
+                    .islands
+                    .iter()
+                    .map(|rng| bodies.body(island[rng.start]).companion_id())
+                    .collect();
+                let dof_islands: Vec<_> = starts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, start)| *start..starts.get(i + 1).cloned().unwrap_or(ndofs))
+                    .collect();
+
+                let niter = solver.solve_islands(
+                    &mut self.constraints.velocity.unilateral_ground,
+                    &mut self.constraints.velocity.unilateral,
+                    &mut self.constraints.velocity.bilateral_ground,
+                    &mut self.constraints.velocity.bilateral,
+                    &mut self.mj_lambda_vel,
+                    &self.jacobians,
+                    params.max_velocity_iterations,
+                    &iterations,
+                    &dof_islands,
+                );
+
+                if let Some(niter) = niter {
+                    self.nvelocity_iterations = niter;
+                    return;
+                }
+            }
+        }
+
+        self.nvelocity_iterations = solver.solve_with_iterations(
             &mut self.constraints.velocity.unilateral_ground,
             &mut self.constraints.velocity.unilateral,
             &mut self.constraints.velocity.bilateral_ground,
@@ -215,6 +528,7 @@ impl<N: Real> MoreauJeanSolver<N> {
             &mut self.mj_lambda_vel,
             &self.jacobians,
             params.max_velocity_iterations,
+            &iterations,
         );
     }
 
@@ -232,11 +546,30 @@ impl<N: Real> MoreauJeanSolver<N> {
             &mut self.constraints.position.unilateral,
             &self.constraints.position.multibody_limits,
             joints,
+            &self.saturated_joints,
+            &mut self.position_impulses,
             &mut self.jacobians,
             params.max_position_iterations,
         );
     }
 
+    fn update_saturated_joints(&mut self) {
+        let velocity = &self.constraints.velocity;
+
+        for bounded in &self.bounded_joints {
+            let saturated = velocity.bilateral_ground[bounded.bilateral_ground_rng.clone()]
+                .iter()
+                .any(|c| bounded.is_saturated(c.impulse, c.is_angular))
+                || velocity.bilateral[bounded.bilateral_rng.clone()]
+                    .iter()
+                    .any(|c| bounded.is_saturated(c.impulse, c.is_angular));
+
+            if saturated {
+                self.saturated_joints.push(bounded.handle);
+            }
+        }
+    }
+
     fn save_cache(
         &mut self,
         bodies: &mut BodySet<N>,
@@ -262,6 +595,11 @@ impl<N: Real> MoreauJeanSolver<N> {
         // XXX: use resize functions instead of reallocating.
         self.mj_lambda_vel = DVector::zeros(ndofs);
         self.ext_vels = DVector::zeros(ndofs);
+
+        #[cfg(feature = "pbd")]
+        {
+            self.displacements = DVector::zeros(ndofs);
+        }
     }
 
     fn update_velocities_and_integrate(
@@ -1,12 +1,13 @@
-use na::{self, Dim, Dynamic, Real, U1, VectorSliceMutN};
+use na::{self, DVector, DVectorSlice, Dim, Dynamic, Real, U1, VectorSliceMutN};
 use slab::Slab;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::ops::MulAssign;
 
-use joint::JointConstraint;
-use object::BodySet;
+use joint::{ConstraintHandle, JointConstraint};
+use object::{BodyHandle, BodySet};
 use solver::helper;
-use solver::{ForceDirection, IntegrationParameters,
+use solver::{ForceDirection, GenericNonlinearConstraint, IntegrationParameters,
              MultibodyJointLimitsNonlinearConstraintGenerator, NonlinearConstraintGenerator,
              NonlinearUnilateralConstraint};
 
@@ -24,6 +25,10 @@ impl<N: Real> NonlinearSORProx<N> {
     }
 
     /// Solve a set of nonlinear position-based constraints.
+    ///
+    /// The position impulses applied to the joint constraints during the previous step are read
+    /// from `position_impulses` to warm-start the solver, and replaced by the ones of this step.
+    /// The joints listed in `yielding_joints` are not corrected so they can drift apart.
     pub fn solve(
         &self,
         params: &IntegrationParameters<N>,
@@ -31,23 +36,352 @@ impl<N: Real> NonlinearSORProx<N> {
         constraints: &mut [NonlinearUnilateralConstraint<N>],
         multibody_limits: &[MultibodyJointLimitsNonlinearConstraintGenerator],
         joints_constraints: &Slab<Box<JointConstraint<N>>>, // FIXME: ugly, use a slice of refs instead.
+        yielding_joints: &[ConstraintHandle],
+        position_impulses: &mut HashMap<ConstraintHandle, Vec<N>>,
         jacobians: &mut [N],
         max_iter: usize,
     ) {
+        self.do_solve(
+            params,
+            bodies,
+            constraints,
+            multibody_limits,
+            joints_constraints,
+            yielding_joints,
+            position_impulses,
+            jacobians,
+            max_iter,
+            &mut None,
+        )
+    }
+
+    /// Solve a set of nonlinear position-based constraints, accumulating the applied displacements.
+    ///
+    /// The generalized displacements applied to each active body are added to `displacements`,
+    /// starting at the index given by the body companion identifier.
+    pub fn solve_with_displacements(
+        &self,
+        params: &IntegrationParameters<N>,
+        bodies: &mut BodySet<N>,
+        constraints: &mut [NonlinearUnilateralConstraint<N>],
+        multibody_limits: &[MultibodyJointLimitsNonlinearConstraintGenerator],
+        joints_constraints: &Slab<Box<JointConstraint<N>>>,
+        position_impulses: &mut HashMap<ConstraintHandle, Vec<N>>,
+        jacobians: &mut [N],
+        max_iter: usize,
+        displacements: &mut DVector<N>,
+    ) {
+        self.do_solve(
+            params,
+            bodies,
+            constraints,
+            multibody_limits,
+            joints_constraints,
+            &[],
+            position_impulses,
+            jacobians,
+            max_iter,
+            &mut Some(displacements),
+        )
+    }
+
+    fn do_solve(
+        &self,
+        params: &IntegrationParameters<N>,
+        bodies: &mut BodySet<N>,
+        constraints: &mut [NonlinearUnilateralConstraint<N>],
+        multibody_limits: &[MultibodyJointLimitsNonlinearConstraintGenerator],
+        joints_constraints: &Slab<Box<JointConstraint<N>>>,
+        yielding_joints: &[ConstraintHandle],
+        position_impulses: &mut HashMap<ConstraintHandle, Vec<N>>,
+        jacobians: &mut [N],
+        max_iter: usize,
+        displacements: &mut Option<&mut DVector<N>>,
+    ) {
+        position_impulses.retain(|handle, _| {
+            joints_constraints.contains(*handle) && !yielding_joints.contains(handle)
+        });
+
+        for (handle, joint) in joints_constraints.iter() {
+            if yielding_joints.contains(&handle) {
+                continue;
+            }
+
+            let erp = joint.position_correction().unwrap_or(params.erp);
+            let compliance = joint.compliance();
+            let impulses = position_impulses.entry(handle).or_insert_with(Vec::new);
+            self.warmstart_generic(
+                params,
+                bodies,
+                &**joint,
+                erp,
+                compliance,
+                impulses,
+                jacobians,
+                displacements,
+            )
+        }
+
         for _ in 0..max_iter {
             for constraint in constraints.iter_mut() {
                 // FIXME: specialize for SPATIAL_DIM.
                 let dim1 = Dynamic::new(constraint.ndofs1);
                 let dim2 = Dynamic::new(constraint.ndofs2);
-                self.solve_unilateral(params, bodies, constraint, jacobians, dim1, dim2);
+                self.solve_unilateral(
+                    params,
+                    bodies,
+                    constraint,
+                    jacobians,
+                    dim1,
+                    dim2,
+                    displacements,
+                );
             }
 
             for generator in multibody_limits {
-                self.solve_generic(params, bodies, generator, jacobians)
+                self.solve_generic(
+                    params,
+                    bodies,
+                    generator,
+                    params.erp,
+                    None,
+                    None,
+                    jacobians,
+                    displacements,
+                )
+            }
+
+            for (handle, joint) in joints_constraints.iter() {
+                if yielding_joints.contains(&handle) {
+                    continue;
+                }
+
+                let erp = joint.position_correction().unwrap_or(params.erp);
+                let compliance = joint.compliance();
+                self.solve_generic(
+                    params,
+                    bodies,
+                    &**joint,
+                    erp,
+                    compliance,
+                    position_impulses.get_mut(&handle),
+                    jacobians,
+                    displacements,
+                )
+            }
+        }
+    }
+
+    fn apply_pair_displacement(
+        bodies: &mut BodySet<N>,
+        body1: BodyHandle,
+        body2: BodyHandle,
+        wj_id1: usize,
+        wj_id2: usize,
+        dim1: usize,
+        dim2: usize,
+        jacobians: &mut [N],
+        displacements: &mut Option<&mut DVector<N>>,
+    ) {
+        if dim1 != 0 && dim2 != 0 && bodies.body(body1).handle() == bodies.body(body2).handle() {
+            // Both body parts belong to the same multibody: merge both displacements
+            // so the kinematics of the multibody are updated only once.
+            for i in 0..dim1 {
+                jacobians[wj_id1 + i] += jacobians[wj_id2 + i];
+            }
+
+            Self::apply_displacement(
+                bodies,
+                body1,
+                &jacobians[wj_id1..wj_id1 + dim1],
+                displacements,
+            );
+        } else {
+            if dim1 != 0 {
+                Self::apply_displacement(
+                    bodies,
+                    body1,
+                    &jacobians[wj_id1..wj_id1 + dim1],
+                    displacements,
+                );
+            }
+            if dim2 != 0 {
+                Self::apply_displacement(
+                    bodies,
+                    body2,
+                    &jacobians[wj_id2..wj_id2 + dim2],
+                    displacements,
+                );
             }
+        }
+    }
+
+    fn apply_displacement(
+        bodies: &mut BodySet<N>,
+        handle: BodyHandle,
+        disp: &[N],
+        displacements: &mut Option<&mut DVector<N>>,
+    ) {
+        let mut body = bodies.body_mut(handle);
+        body.apply_displacement(disp);
 
-            for joint in &*joints_constraints {
-                self.solve_generic(params, bodies, &**joint.1, jacobians)
+        if let Some(ref mut displacements) = *displacements {
+            if body.is_active() && body.status_dependent_ndofs() == disp.len() {
+                let id = body.companion_id();
+                let mut rows = displacements.rows_mut(id, disp.len());
+                rows += DVectorSlice::from_slice(disp, disp.len());
+            }
+        }
+    }
+
+    // The target position change and the SOR-prox scaling parameter of a generic constraint.
+    fn generic_rhs_and_r(
+        params: &IntegrationParameters<N>,
+        constraint: &GenericNonlinearConstraint<N>,
+        erp: N,
+        compliance: Option<N>,
+    ) -> (N, N) {
+        let rhs = if constraint.is_angular {
+            na::sup(
+                &((constraint.rhs + params.allowed_angular_error) * erp),
+                &(-params.max_angular_correction),
+            )
+        } else {
+            na::sup(
+                &((constraint.rhs + params.allowed_linear_error) * erp),
+                &(-params.max_linear_correction),
+            )
+        };
+
+        let mut r = constraint.r;
+
+        // Avoid overshoot when the penetration vector is close to the null-space
+        // of a multibody link jacobian.
+        // FIXME: will this cause issue with very light objects?
+        // Should this be done depending on the jacobian magnitude instead
+        // (instead of JM-1J)?
+        if false {
+            // r > params.max_stabilization_multiplier {
+            r = params.max_stabilization_multiplier;
+        }
+
+        if let Some(compliance) = compliance {
+            let cfm = compliance / (params.dt * params.dt);
+            r = N::one() / (N::one() / r + cfm);
+        }
+
+        (rhs, r)
+    }
+
+    // The XPBD update of the Lagrange multiplier `lambda` accumulated during this step:
+    //
+    //     dlambda = (C - alpha * lambda) / (w + alpha)
+    //
+    // where `C` is the constraint error, `w = J M^-1 J^t`, and `alpha = compliance / dt^2`.
+    fn xpbd_impulse(
+        params: &IntegrationParameters<N>,
+        constraint: &GenericNonlinearConstraint<N>,
+        compliance: Option<N>,
+        lambda: N,
+    ) -> N {
+        let (allowed_error, max_correction) = if constraint.is_angular {
+            (params.allowed_angular_error, params.max_angular_correction)
+        } else {
+            (params.allowed_linear_error, params.max_linear_correction)
+        };
+
+        let err = na::inf(
+            &na::sup(&(-(constraint.rhs + allowed_error)), &N::zero()),
+            &max_correction,
+        );
+        let alpha = compliance.unwrap_or_else(N::zero) / (params.dt * params.dt);
+        let dlambda = (err - alpha * lambda) / (N::one() / constraint.r + alpha);
+
+        // The multiplier of a constraint pushing toward its error direction cannot be negative.
+        na::sup(&dlambda, &-lambda)
+    }
+
+    fn apply_generic_impulse(
+        bodies: &mut BodySet<N>,
+        constraint: &GenericNonlinearConstraint<N>,
+        impulse: N,
+        jacobians: &mut [N],
+        displacements: &mut Option<&mut DVector<N>>,
+    ) {
+        let dim1 = Dynamic::new(constraint.dim1);
+        let dim2 = Dynamic::new(constraint.dim2);
+
+        VectorSliceMutN::from_slice_generic(&mut jacobians[constraint.wj_id1..], dim1, U1)
+            .mul_assign(impulse);
+
+        VectorSliceMutN::from_slice_generic(&mut jacobians[constraint.wj_id2..], dim2, U1)
+            .mul_assign(impulse);
+
+        // FIXME: the body update should be performed lazily, especially because
+        // we dont actually need to update the kinematic of a multibody until
+        // we have to solve a contact involvoing one of its links.
+        Self::apply_pair_displacement(
+            bodies,
+            constraint.body1,
+            constraint.body2,
+            constraint.wj_id1,
+            constraint.wj_id2,
+            constraint.dim1,
+            constraint.dim2,
+            jacobians,
+            displacements,
+        );
+    }
+
+    // Re-applies the position impulses of the last step, scaled by the warm-starting coefficient.
+    //
+    // Each impulse is bounded by the one a single solver iteration would apply to the current
+    // error, so the warm-start never corrects more than what is actually needed.
+    fn warmstart_generic<Gen: ?Sized + NonlinearConstraintGenerator<N>>(
+        &self,
+        params: &IntegrationParameters<N>,
+        bodies: &mut BodySet<N>,
+        generator: &Gen,
+        erp: N,
+        compliance: Option<N>,
+        impulses: &mut Vec<N>,
+        jacobians: &mut [N],
+        displacements: &mut Option<&mut DVector<N>>,
+    ) {
+        let nconstraints = generator.num_position_constraints(bodies);
+        impulses.resize(nconstraints, N::zero());
+
+        if params.is_xpbd() {
+            // The XPBD multipliers are accumulated from zero at each step.
+            for impulse in impulses.iter_mut() {
+                *impulse = N::zero();
+            }
+
+            return;
+        }
+
+        for i in 0..nconstraints {
+            let cached = impulses[i] * params.warmstart_coeff;
+            impulses[i] = N::zero();
+
+            if cached <= N::zero() {
+                continue;
+            }
+
+            if let Some(constraint) = generator.position_constraint(params, i, bodies, jacobians) {
+                let (rhs, r) = Self::generic_rhs_and_r(params, &constraint, erp, compliance);
+
+                if rhs < N::zero() {
+                    let impulse = na::inf(&cached, &(-rhs * r));
+                    impulses[i] = impulse;
+                    Self::apply_generic_impulse(
+                        bodies,
+                        &constraint,
+                        impulse,
+                        jacobians,
+                        displacements,
+                    );
+                }
             }
         }
     }
@@ -57,62 +391,55 @@ impl<N: Real> NonlinearSORProx<N> {
         params: &IntegrationParameters<N>,
         bodies: &mut BodySet<N>,
         generator: &Gen,
+        erp: N,
+        compliance: Option<N>,
+        mut impulses: Option<&mut Vec<N>>,
         jacobians: &mut [N],
+        displacements: &mut Option<&mut DVector<N>>,
     ) {
         let nconstraints = generator.num_position_constraints(bodies);
 
         for i in 0..nconstraints {
-            if let Some(mut constraint) =
-                generator.position_constraint(params, i, bodies, jacobians)
-            {
-                let dim1 = Dynamic::new(constraint.dim1);
-                let dim2 = Dynamic::new(constraint.dim2);
-
-                let rhs = if constraint.is_angular {
-                    na::sup(
-                        &((constraint.rhs + params.allowed_angular_error) * params.erp),
-                        &(-params.max_angular_correction),
-                    )
-                } else {
-                    na::sup(
-                        &((constraint.rhs + params.allowed_linear_error) * params.erp),
-                        &(-params.max_linear_correction),
-                    )
-                };
+            if let Some(constraint) = generator.position_constraint(params, i, bodies, jacobians) {
+                if params.is_xpbd() {
+                    if let Some(ref mut impulses) = impulses {
+                        if i < impulses.len() {
+                            let impulse =
+                                Self::xpbd_impulse(params, &constraint, compliance, impulses[i]);
+
+                            if !impulse.is_zero() {
+                                impulses[i] += impulse;
+                                Self::apply_generic_impulse(
+                                    bodies,
+                                    &constraint,
+                                    impulse,
+                                    jacobians,
+                                    displacements,
+                                );
+                            }
 
-                // Avoid overshoot when the penetration vector is close to the null-space
-                // of a multibody link jacobian.
-                // FIXME: will this cause issue with very light objects?
-                // Should this be done depending on the jacobian magnitude instead
-                // (instead of JM-1J)?
-                if false {
-                    // constraint.r > params.max_stabilization_multiplier {
-                    constraint.r = params.max_stabilization_multiplier;
+                            continue;
+                        }
+                    }
                 }
 
+                let (rhs, r) = Self::generic_rhs_and_r(params, &constraint, erp, compliance);
+
                 if rhs < N::zero() {
-                    let impulse = -rhs * constraint.r;
-
-                    VectorSliceMutN::from_slice_generic(
-                        &mut jacobians[constraint.wj_id1..],
-                        dim1,
-                        U1,
-                    ).mul_assign(impulse);
-
-                    VectorSliceMutN::from_slice_generic(
-                        &mut jacobians[constraint.wj_id2..],
-                        dim2,
-                        U1,
-                    ).mul_assign(impulse);
-
-                    // FIXME: the body update should be performed lazily, especially because
-                    // we dont actually need to update the kinematic of a multibody until
-                    // we have to solve a contact involvoing one of its links.
-                    bodies.body_mut(constraint.body1).apply_displacement(
-                        &jacobians[constraint.wj_id1..constraint.wj_id1 + constraint.dim1],
-                    );
-                    bodies.body_mut(constraint.body2).apply_displacement(
-                        &jacobians[constraint.wj_id2..constraint.wj_id2 + constraint.dim2],
+                    let impulse = -rhs * r;
+
+                    if let Some(ref mut impulses) = impulses {
+                        if i < impulses.len() {
+                            impulses[i] += impulse;
+                        }
+                    }
+
+                    Self::apply_generic_impulse(
+                        bodies,
+                        &constraint,
+                        impulse,
+                        jacobians,
+                        displacements,
                     );
                 }
             }
@@ -127,6 +454,7 @@ impl<N: Real> NonlinearSORProx<N> {
         jacobians: &mut [N],
         dim1: D1,
         dim2: D2,
+        displacements: &mut Option<&mut DVector<N>>,
     ) {
         if self.update_contact_constraint(params, bodies, constraint, jacobians) {
             let impulse = -constraint.rhs * constraint.r;
@@ -135,16 +463,17 @@ impl<N: Real> NonlinearSORProx<N> {
             VectorSliceMutN::from_slice_generic(&mut jacobians[dim1.value()..], dim2, U1)
                 .mul_assign(impulse);
 
-            if dim1.value() != 0 {
-                bodies
-                    .body_mut(constraint.body1)
-                    .apply_displacement(&jacobians[0..dim1.value()]);
-            }
-            if dim2.value() != 0 {
-                bodies
-                    .body_mut(constraint.body2)
-                    .apply_displacement(&jacobians[dim1.value()..dim1.value() + dim2.value()]);
-            }
+            Self::apply_pair_displacement(
+                bodies,
+                constraint.body1,
+                constraint.body2,
+                0,
+                dim1.value(),
+                dim1.value(),
+                dim2.value(),
+                jacobians,
+                displacements,
+            );
         }
     }
 
@@ -226,3 +555,200 @@ impl<N: Real> NonlinearSORProx<N> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "pbd")]
+    #[test]
+    fn position_based_resting_stack_neither_sinks_nor_gains_energy() {
+        use na;
+        use ncollide::shape::{Cuboid, ShapeHandle};
+
+        use math::{Isometry, Vector};
+        use object::{BodyHandle, Material};
+        use solver::SolverMethod;
+        use volumetric::Volumetric;
+        use world::World;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.integration_parameters_mut().solver_method = SolverMethod::PositionBased;
+
+        let ground = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let ground_pos = Isometry::new(Vector::y() * -10.0, na::zero());
+        let _ = world.add_collider(
+            0.01,
+            ground,
+            BodyHandle::ground(),
+            ground_pos,
+            Material::default(),
+        );
+
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.49)));
+        let mut handles = Vec::new();
+
+        for i in 0..3 {
+            let pos = Isometry::new(Vector::y() * (i as f32 + 0.5), na::zero());
+            let body = world.add_rigid_body(pos, cube.inertia(1.0), cube.center_of_mass());
+            {
+                let rb = world.rigid_body_mut(body).unwrap();
+                rb.activation_status_mut().set_deactivation_threshold(None);
+                // This method ignores friction: lock the rotations so the boxes cannot tip and
+                // slide off the stack.
+                rb.set_rotation_locked(true);
+            }
+            let _ = world.add_collider(
+                0.01,
+                cube.clone(),
+                body,
+                Isometry::identity(),
+                Material::default(),
+            );
+            handles.push(body);
+        }
+
+        for step in 0..300 {
+            world.step();
+
+            for (i, handle) in handles.iter().enumerate() {
+                let rb = world.rigid_body(*handle).unwrap();
+                let y = rb.position().translation.vector.y;
+                let speed = rb.velocity().linear.norm();
+
+                assert!((y - (i as f32 + 0.5)).abs() < 2.0e-2, "Box {} is at {}", i, y);
+
+                // The boxes fall by the allowed penetration during the first steps.
+                if step >= 30 {
+                    assert!(speed < 5.0e-2, "Box {} moves at {}", i, speed);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "dim3")]
+    #[test]
+    fn warmstarting_reduces_the_drift_of_a_ball_constraint_chain() {
+        use na;
+        use ncollide::shape::{Cuboid, ShapeHandle};
+
+        use joint::BallConstraint;
+        use math::{Isometry, Point, Vector};
+        use object::BodyHandle;
+        use volumetric::Volumetric;
+        use world::World;
+
+        let max_drift = |warmstart_coeff: f32| {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+            world.integration_parameters_mut().warmstart_coeff = warmstart_coeff;
+            world.integration_parameters_mut().max_velocity_iterations = 1;
+            world.integration_parameters_mut().max_position_iterations = 1;
+
+            let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.25)));
+            let mut parent = BodyHandle::ground();
+            let mut anchors = Vec::new();
+
+            for i in 0..5 {
+                let body = world.add_rigid_body(
+                    Isometry::new(Vector::x() * (i as f32 + 1.0), na::zero()),
+                    cuboid.inertia(1.0),
+                    cuboid.center_of_mass(),
+                );
+                let anchor1 = if i == 0 {
+                    Point::origin()
+                } else {
+                    Point::from(Vector::x() * 0.5)
+                };
+                let anchor2 = Point::from(Vector::x() * -0.5);
+                let _ = world.add_constraint(BallConstraint::new(parent, body, anchor1, anchor2));
+                anchors.push((parent, body, anchor1, anchor2));
+                parent = body;
+            }
+
+            let mut drift = 0.0f32;
+
+            for i in 0..120 {
+                world.step();
+
+                // Both chains behave the same until the solver has impulses to warm-start from.
+                if i < 10 {
+                    continue;
+                }
+
+                for &(b1, b2, anchor1, anchor2) in &anchors {
+                    let pos1 = if b1.is_ground() {
+                        Isometry::identity()
+                    } else {
+                        world.rigid_body(b1).unwrap().position()
+                    };
+                    let pos2 = world.rigid_body(b2).unwrap().position();
+                    drift = drift.max(na::distance(&(pos1 * anchor1), &(pos2 * anchor2)));
+                }
+            }
+
+            drift
+        };
+
+        let cold = max_drift(0.0);
+        let warm = max_drift(1.0);
+        assert!(
+            warm < cold,
+            "Warm-starting should reduce the joint drift: {} >= {}.",
+            warm,
+            cold
+        );
+    }
+
+    #[cfg(all(feature = "dim3", feature = "pbd"))]
+    #[test]
+    fn xpbd_joint_stretch_is_given_by_its_compliance() {
+        use na;
+        use ncollide::shape::{Cuboid, ShapeHandle};
+
+        use joint::{BallConstraint, JointConstraint};
+        use math::{Isometry, Point, Vector};
+        use object::BodyHandle;
+        use solver::SolverMethod;
+        use volumetric::Volumetric;
+        use world::World;
+
+        let stretch = |iterations: usize| {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+            world.integration_parameters_mut().solver_method = SolverMethod::Xpbd;
+            world.integration_parameters_mut().max_position_iterations = iterations;
+
+            // The body has a unit mass.
+            let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::y() * -1.0, na::zero()),
+                cuboid.inertia(1.0),
+                cuboid.center_of_mass(),
+            );
+
+            let anchor = Point::from_coordinates(Vector::y());
+            let mut constraint =
+                BallConstraint::new(BodyHandle::ground(), body, Point::origin(), anchor);
+            constraint.set_compliance(Some(0.01));
+            let _ = world.add_constraint(constraint);
+
+            for _ in 0..600 {
+                world.step();
+            }
+
+            let pos = world.rigid_body(body).unwrap().position();
+            na::distance(&(pos * anchor), &Point::origin())
+        };
+
+        // At equilibrium, the stretch is `compliance * mass * gravity`.
+        for iterations in &[4, 16] {
+            let stretch = stretch(*iterations);
+            assert!(
+                (stretch - 0.0981).abs() < 1.0e-2,
+                "Unexpected stretch with {} iterations: {}",
+                iterations,
+                stretch
+            );
+        }
+    }
+}
@@ -1,10 +1,13 @@
 use alga::linear::FiniteDimInnerSpace;
-use na::{self, DVector, Real, Unit};
+use na::{DVector, Real, Unit};
+use ncollide::utils::GenerationalId;
 use std::ops::Range;
 
 use detection::ColliderContactManifold;
-use math::{Vector, DIM};
-use object::BodySet;
+#[cfg(feature = "dim2")]
+use math::AngularVector;
+use math::{Point, SpatialVector, Vector, ANGULAR_DIM, DIM, SPATIAL_DIM};
+use object::{BodyPart, BodySet};
 use solver::helper;
 use solver::{
     BilateralConstraint, BilateralGroundConstraint, ConstraintSet, ContactModel, ForceDirection,
@@ -14,8 +17,10 @@ use solver::{
 /// A contact model generating one non-penetration constraint and two friction constraints per contact.
 ///
 /// This contact model approximates the friction cone at a contact with pyramid.
+/// If the materials in contact have a non-zero rolling or spinning friction, additional
+/// angular constraints are generated to resist, respectively, rolling and spinning.
 pub struct SignoriniCoulombPyramidModel<N: Real> {
-    impulses: ImpulseCache<Vector<N>>,
+    impulses: ImpulseCache<SpatialVector<N>>,
     vel_ground_rng: Range<usize>,
     vel_rng: Range<usize>,
     friction_ground_rng: Range<usize>,
@@ -33,6 +38,15 @@ impl<N: Real> SignoriniCoulombPyramidModel<N> {
             friction_rng: 0..0,
         }
     }
+
+    fn rolling_and_spinning_friction(manifold: &ColliderContactManifold<N>) -> (N, N) {
+        let material1 = manifold.collider1.data().material();
+        let material2 = manifold.collider2.data().material();
+        let rolling = material1.combined_rolling_friction(material2);
+        let spinning = material1.combined_spinning_friction(material2);
+
+        (rolling, spinning)
+    }
 }
 
 impl<N: Real> Default for SignoriniCoulombPyramidModel<N> {
@@ -43,7 +57,18 @@ impl<N: Real> Default for SignoriniCoulombPyramidModel<N> {
 
 impl<N: Real> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
     fn num_velocity_constraints(&self, c: &ColliderContactManifold<N>) -> usize {
-        DIM * c.len()
+        let (rolling, spinning) = Self::rolling_and_spinning_friction(c);
+        let mut nconstraints = DIM;
+
+        if !rolling.is_zero() {
+            nconstraints += DIM - 1;
+        }
+
+        if !spinning.is_zero() {
+            nconstraints += ANGULAR_DIM + 1 - DIM;
+        }
+
+        nconstraints * c.len()
     }
 
     fn constraints(
@@ -66,16 +91,30 @@ impl<N: Real> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
             let body1 = bodies.body_part(manifold.collider1.data().body());
             let body2 = bodies.body_part(manifold.collider2.data().body());
 
-            let friction1 = manifold.collider1.data().material().friction;
-            let friction2 = manifold.collider2.data().material().friction;
-            let friction = (friction1 + friction2) * na::convert(0.5);
+            let material1 = manifold.collider1.data().material();
+            let material2 = manifold.collider2.data().material();
+            let pos1 = manifold.collider1.position();
+            let pos2 = manifold.collider2.position();
+            let principal_dir = material1
+                .friction_direction(pos1)
+                .or_else(|| material2.friction_direction(pos2));
+            let (rolling_friction, spinning_friction) =
+                Self::rolling_and_spinning_friction(manifold);
 
             for c in manifold.contacts() {
                 // if !SignoriniModel::is_constraint_active(c, manifold) {
                 //     continue;
                 // }
 
-                let impulse = self.impulses.get(c.id);
+                if !manifold.is_contact_enabled(c) {
+                    continue;
+                }
+
+                let mut impulse = self.impulses.get(c.id);
+                if !self.impulses.contains(c.id) {
+                    // Warm-start new contacts with the normal impulse of their cached point.
+                    impulse[0] = manifold.cached_impulse(c).unwrap_or_else(N::zero);
+                }
                 let impulse_id = self.impulses.entry_id(c.id);
 
                 let ground_constraint = SignoriniModel::build_velocity_constraint(
@@ -92,7 +131,9 @@ impl<N: Real> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
                     constraints,
                 );
 
-                SignoriniModel::build_position_constraint(bodies, manifold, c, constraints);
+                if params.corrects_penetrations_with_positions() {
+                    SignoriniModel::build_position_constraint(bodies, manifold, c, constraints);
+                }
 
                 let dependency;
 
@@ -104,82 +145,138 @@ impl<N: Real> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
                     dependency = constraints.len() - 1;
                 }
 
-                let assembly_id1 = body1.parent_companion_id();
-                let assembly_id2 = body2.parent_companion_id();
-
-                // Generate friction constraints.
-                let limits = ImpulseLimits::Dependent {
-                    dependency: dependency,
-                    coeff: friction,
-                };
-
                 let mut i = 1;
 
                 // FIXME: this compute the contact point locations (with margins) several times,
                 // it was already computed for the signorini law.
-                let center1 = c.contact.world1
-                    + c.contact.normal.unwrap() * manifold.collider1.data().margin();
-                let center2 = c.contact.world2
-                    - c.contact.normal.unwrap() * manifold.collider2.data().margin();
+                let normal = manifold.contact_normal(c).into_inner();
+                let center1 = c.contact.world1 + normal * manifold.collider1.data().margin();
+                let center2 = c.contact.world2 - normal * manifold.collider2.data().margin();
 
-                Vector::orthonormal_subspace_basis(&[c.contact.normal.unwrap()], |friction_dir| {
+                // Generate friction constraints. The tangent basis is aligned with the
+                // principal friction direction of anisotropic materials.
+                let mut tangents = [Vector::zeros(); DIM - 1];
+                let mut ntangents = 0;
+
+                {
+                    let mut push_tangent = |tangent: &Vector<N>| {
+                        tangents[ntangents] = *tangent;
+                        ntangents += 1;
+                        true
+                    };
+
+                    let principal = principal_dir.and_then(|dir| {
+                        Unit::try_new(dir - normal * normal.dot(&dir), N::default_epsilon())
+                    });
+
+                    if let Some(principal) = principal {
+                        let _ = push_tangent(principal.as_ref());
+                        Vector::orthonormal_subspace_basis(&[normal, *principal], push_tangent);
+                    } else {
+                        Vector::orthonormal_subspace_basis(&[normal], push_tangent);
+                    }
+                }
+
+                let friction_override = manifold.contact_data(c).and_then(|data| data.friction);
+
+                for friction_dir in &tangents[..ntangents] {
+                    let friction = friction_override.unwrap_or_else(|| {
+                        material1.combined_friction_along(pos1, material2, pos2, friction_dir)
+                    });
+                    let limits = ImpulseLimits::Dependent {
+                        dependency,
+                        coeff: friction,
+                    };
                     let dir = ForceDirection::Linear(Unit::new_unchecked(*friction_dir));
 
-                    // FIXME: will this compute the momentum twice ?
-                    let geom = helper::constraint_pair_geometry(
+                    build_friction_constraint(
+                        params,
                         &body1,
                         &body2,
                         &center1,
                         &center2,
                         &dir,
+                        limits,
+                        impulse[i],
+                        impulse_id * SPATIAL_DIM + i,
+                        ext_vels,
                         ground_j_id,
                         j_id,
                         jacobians,
+                        constraints,
                     );
 
-                    let rhs = helper::constraint_pair_velocity(
-                        &body1,
-                        &body2,
-                        assembly_id1,
-                        assembly_id2,
-                        &center1,
-                        &center2,
-                        &dir,
-                        ext_vels,
-                        jacobians,
-                        &geom,
-                    );
+                    i += 1;
+                }
 
-                    let warmstart = impulse[i] * params.warmstart_coeff;
+                // Generate rolling friction constraints.
+                if !rolling_friction.is_zero() {
+                    let limits = ImpulseLimits::Dependent {
+                        dependency,
+                        coeff: rolling_friction,
+                    };
 
-                    if geom.is_ground_constraint() {
-                        let constraint = BilateralGroundConstraint::new(
-                            geom,
-                            assembly_id1,
-                            assembly_id2,
-                            limits,
-                            rhs,
-                            warmstart,
-                            impulse_id * DIM + i,
-                        );
-                        constraints.velocity.bilateral_ground.push(constraint);
-                    } else {
-                        let constraint = BilateralConstraint::new(
-                            geom,
-                            assembly_id1,
-                            assembly_id2,
+                    #[cfg(feature = "dim3")]
+                    let rolling_axes = &tangents[..ntangents];
+                    #[cfg(feature = "dim2")]
+                    let rolling_axes = &[AngularVector::x()];
+
+                    for axis in rolling_axes {
+                        let dir = ForceDirection::Angular(Unit::new_unchecked(*axis));
+
+                        build_friction_constraint(
+                            params,
+                            &body1,
+                            &body2,
+                            &center1,
+                            &center2,
+                            &dir,
                             limits,
-                            rhs,
-                            warmstart,
-                            impulse_id * DIM + i,
+                            impulse[i],
+                            impulse_id * SPATIAL_DIM + i,
+                            ext_vels,
+                            ground_j_id,
+                            j_id,
+                            jacobians,
+                            constraints,
                         );
-                        constraints.velocity.bilateral.push(constraint);
+
+                        i += 1;
                     }
+                }
 
-                    i += 1;
+                // Generate spinning friction constraints.
+                #[cfg(feature = "dim3")]
+                {
+                    if !spinning_friction.is_zero() {
+                        let limits = ImpulseLimits::Dependent {
+                            dependency,
+                            coeff: spinning_friction,
+                        };
+                        let dir = ForceDirection::Angular(Unit::new_unchecked(normal));
 
-                    true
-                });
+                        build_friction_constraint(
+                            params,
+                            &body1,
+                            &body2,
+                            &center1,
+                            &center2,
+                            &dir,
+                            limits,
+                            impulse[SPATIAL_DIM - 1],
+                            impulse_id * SPATIAL_DIM + SPATIAL_DIM - 1,
+                            ext_vels,
+                            ground_j_id,
+                            j_id,
+                            jacobians,
+                            constraints,
+                        );
+                    }
+                }
+                #[cfg(feature = "dim2")]
+                {
+                    let _ = spinning_friction;
+                }
             }
         }
 
@@ -205,11 +302,143 @@ impl<N: Real> ContactModel<N> for SignoriniCoulombPyramidModel<N> {
         }
 
         for c in ground_friction {
-            self.impulses[c.impulse_id / DIM][c.impulse_id % DIM] = c.impulse;
+            self.impulses[c.impulse_id / SPATIAL_DIM][c.impulse_id % SPATIAL_DIM] = c.impulse;
         }
 
         for c in friction {
-            self.impulses[c.impulse_id / DIM][c.impulse_id % DIM] = c.impulse;
+            self.impulses[c.impulse_id / SPATIAL_DIM][c.impulse_id % SPATIAL_DIM] = c.impulse;
+        }
+    }
+
+    fn contact_impulse(&self, contact_id: GenerationalId) -> Option<N> {
+        if self.impulses.contains(contact_id) {
+            Some(self.impulses.get(contact_id)[0])
+        } else {
+            None
         }
     }
 }
+
+fn build_friction_constraint<N: Real>(
+    params: &IntegrationParameters<N>,
+    body1: &BodyPart<N>,
+    body2: &BodyPart<N>,
+    center1: &Point<N>,
+    center2: &Point<N>,
+    dir: &ForceDirection<N>,
+    limits: ImpulseLimits<N>,
+    impulse: N,
+    impulse_id: usize,
+    ext_vels: &DVector<N>,
+    ground_j_id: &mut usize,
+    j_id: &mut usize,
+    jacobians: &mut [N],
+    constraints: &mut ConstraintSet<N>,
+) {
+    let assembly_id1 = body1.parent_companion_id();
+    let assembly_id2 = body2.parent_companion_id();
+
+    // FIXME: will this compute the momentum twice ?
+    let geom = helper::constraint_pair_geometry(
+        body1,
+        body2,
+        center1,
+        center2,
+        dir,
+        ground_j_id,
+        j_id,
+        jacobians,
+    );
+
+    let rhs = helper::constraint_pair_velocity(
+        body1,
+        body2,
+        assembly_id1,
+        assembly_id2,
+        center1,
+        center2,
+        dir,
+        ext_vels,
+        jacobians,
+        &geom,
+    );
+
+    let warmstart = impulse * params.warmstart_coeff;
+
+    if geom.is_ground_constraint() {
+        let constraint = BilateralGroundConstraint::new(
+            geom,
+            assembly_id1,
+            assembly_id2,
+            limits,
+            rhs,
+            warmstart,
+            impulse_id,
+        );
+        constraints.velocity.bilateral_ground.push(constraint);
+    } else {
+        let constraint = BilateralConstraint::new(
+            geom,
+            assembly_id1,
+            assembly_id2,
+            limits,
+            rhs,
+            warmstart,
+            impulse_id,
+        );
+        constraints.velocity.bilateral.push(constraint);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Ball, Cuboid, ShapeHandle};
+
+    use math::{Isometry, Vector};
+    use object::{BodyHandle, Material};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn rolling_friction_stops_a_rolling_ball() {
+        let final_speed = |rolling_friction: f32| {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+
+            let mut material = Material::default();
+            material.rolling_friction = rolling_friction;
+
+            let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = world.add_collider(
+                0.01,
+                floor,
+                BodyHandle::ground(),
+                Isometry::new(Vector::y() * -10.0, na::zero()),
+                material.clone(),
+            );
+
+            let ball = ShapeHandle::new(Ball::new(0.5));
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::y() * 0.5, na::zero()),
+                ball.inertia(1.0),
+                ball.center_of_mass(),
+            );
+            let _ = world.add_collider(0.01, ball, body, Isometry::identity(), material);
+
+            world.step();
+            world.rigid_body_mut(body).unwrap().set_linear_velocity(Vector::x() * 2.0);
+
+            for _ in 0..240 {
+                world.step();
+            }
+
+            world.rigid_body(body).unwrap().velocity().linear.norm()
+        };
+
+        let rolling = final_speed(0.0);
+        let stopped = final_speed(0.1);
+        assert!(rolling > 1.0, "Speed without rolling friction: {}", rolling);
+        assert!(stopped < 5.0e-2, "Speed with rolling friction: {}", stopped);
+    }
+}
@@ -3,7 +3,7 @@ use std::ops::Range;
 
 use detection::ColliderContactManifold;
 use ncollide::query::TrackedContact;
-use ncollide::utils::IsometryOps;
+use ncollide::utils::{GenerationalId, IsometryOps};
 use object::BodySet;
 use solver::helper;
 use solver::{ConstraintSet, ContactModel, ForceDirection, ImpulseCache, IntegrationParameters,
@@ -28,6 +28,16 @@ impl<N: Real> SignoriniModel<N> {
         }
     }
 
+    // The impulse of the given contact at the last timestep, or the one of the matching point
+    // of the contact manifold cache for new contacts.
+    fn warmstart_impulse(&self, manifold: &ColliderContactManifold<N>, c: &TrackedContact<N>) -> N {
+        if self.impulses.contains(c.id) {
+            self.impulses.get(c.id)
+        } else {
+            manifold.cached_impulse(c).unwrap_or_else(N::zero)
+        }
+    }
+
     /// Build a non-penetration velocity-based constraint for the given contact.
     pub fn build_velocity_constraint(
         params: &IntegrationParameters<N>,
@@ -54,9 +64,10 @@ impl<N: Real> SignoriniModel<N> {
         let assembly_id1 = body1.parent_companion_id();
         let assembly_id2 = body2.parent_companion_id();
 
-        let center1 = c.contact.world1 + c.contact.normal.unwrap() * data1.margin();
-        let center2 = c.contact.world2 - c.contact.normal.unwrap() * data2.margin();
-        let dir = ForceDirection::Linear(-c.contact.normal);
+        let normal = manifold.contact_normal(c);
+        let center1 = c.contact.world1 + normal.into_inner() * data1.margin();
+        let center2 = c.contact.world2 - normal.into_inner() * data2.margin();
+        let dir = ForceDirection::Linear(-normal);
 
         let geom = helper::constraint_pair_geometry(
             &body1,
@@ -90,9 +101,21 @@ impl<N: Real> SignoriniModel<N> {
                 rhs += (-depth) / params.dt;
             }
         } else {
-            let rest1 = data1.material().restitution;
-            let rest2 = data2.material().restitution;
-            rhs += (rest1 + rest2) * na::convert(0.5) * rhs;
+            let restitution = manifold
+                .contact_data(c)
+                .and_then(|data| data.restitution)
+                .unwrap_or_else(|| data1.material().combined_restitution(data2.material()));
+            rhs += restitution * rhs;
+        }
+
+        if !params.corrects_penetrations_with_positions() {
+            let depth = c.contact.depth + data1.margin() + data2.margin();
+
+            // Baumgarte stabilization: push the bodies apart at the velocity level.
+            if depth > params.allowed_linear_error {
+                let correction = (depth - params.allowed_linear_error) * params.erp;
+                rhs -= na::inf(&correction, &params.max_linear_correction) / params.dt;
+            }
         }
 
         let warmstart = impulse * params.warmstart_coeff;
@@ -158,8 +181,9 @@ impl<N: Real> SignoriniModel<N> {
 
         let pos1 = body1.position();
         let pos2 = body2.position();
-        let normal1 = pos1.inverse_transform_unit_vector(&c.contact.normal);
-        let normal2 = -pos2.inverse_transform_unit_vector(&c.contact.normal);
+        let normal = manifold.contact_normal(c);
+        let normal1 = pos1.inverse_transform_unit_vector(&normal);
+        let normal2 = -pos2.inverse_transform_unit_vector(&normal);
 
         let mut kinematic = c.kinematic.clone();
         let total_margin1 = kinematic.dilation1() + data1.margin();
@@ -209,13 +233,17 @@ impl<N: Real> ContactModel<N> for SignoriniModel<N> {
                 //     continue;
                 // }
 
+                if !manifold.is_contact_enabled(c) {
+                    continue;
+                }
+
                 let _ = Self::build_velocity_constraint(
                     params,
                     bodies,
                     manifold,
                     ext_vels,
                     c,
-                    self.impulses.get(c.id),
+                    self.warmstart_impulse(manifold, c),
                     self.impulses.entry_id(c.id),
                     ground_j_id,
                     j_id,
@@ -223,7 +251,9 @@ impl<N: Real> ContactModel<N> for SignoriniModel<N> {
                     constraints,
                 );
 
-                Self::build_position_constraint(bodies, manifold, c, constraints);
+                if params.corrects_penetrations_with_positions() {
+                    Self::build_position_constraint(bodies, manifold, c, constraints);
+                }
             }
         }
 
@@ -243,4 +273,147 @@ impl<N: Real> ContactModel<N> for SignoriniModel<N> {
             self.impulses[c.impulse_id] = c.impulse;
         }
     }
+
+    fn contact_impulse(&self, contact_id: GenerationalId) -> Option<N> {
+        if self.impulses.contains(contact_id) {
+            Some(self.impulses.get(contact_id))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Ball, Cuboid, ShapeHandle};
+
+    use math::{Isometry, Vector};
+    use object::{BodyHandle, Material};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn restitution_is_ignored_below_the_velocity_threshold() {
+        let max_rebound_speed = |drop_height: f32| {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+
+            let elastic = Material::new(1.0, 0.0);
+            let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = world.add_collider(
+                0.01,
+                floor,
+                BodyHandle::ground(),
+                Isometry::new(Vector::y() * -10.0, na::zero()),
+                elastic.clone(),
+            );
+
+            let ball = ShapeHandle::new(Ball::new(0.5));
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::y() * (0.5 + drop_height), na::zero()),
+                ball.inertia(1.0),
+                ball.center_of_mass(),
+            );
+            let _ = world.add_collider(0.01, ball, body, Isometry::identity(), elastic);
+
+            let mut max_speed = 0.0f32;
+
+            for _ in 0..120 {
+                world.step();
+                max_speed = max_speed.max(world.rigid_body(body).unwrap().velocity().linear.y);
+            }
+
+            max_speed
+        };
+
+        // Impact velocities of about `0.6` and `4.4`, with a threshold of `1.0`.
+        let slow = max_rebound_speed(0.02);
+        let fast = max_rebound_speed(1.0);
+        let threshold = World::<f32>::new()
+            .integration_parameters()
+            .restitution_velocity_threshold;
+        assert!(slow < 0.1, "Slow impact rebound speed: {}", slow);
+        assert!(fast > threshold, "Fast impact rebound speed: {}", fast);
+    }
+
+    #[test]
+    fn restitutive_box_settles_without_residual_bounce() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = world.add_collider(
+            0.01,
+            floor,
+            BodyHandle::ground(),
+            Isometry::new(Vector::y() * -10.0, na::zero()),
+            Material::new(0.3, 0.5),
+        );
+
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * 1.5, na::zero()),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, cube, body, Isometry::identity(), Material::new(0.3, 0.5));
+
+        // The first impacts bounce, until the impact velocity falls below the threshold.
+        for _ in 0..120 {
+            world.step();
+        }
+
+        for _ in 0..120 {
+            world.step();
+            let vel = world.rigid_body(body).unwrap().velocity().linear.y;
+            assert!(vel.abs() < 1.0e-2, "Residual bounce: {}", vel);
+        }
+    }
+
+    #[test]
+    fn split_impulse_resolves_penetrations_without_adding_velocity() {
+        use solver::PenetrationCorrectionMethod;
+
+        let simulate = |method: PenetrationCorrectionMethod| {
+            let mut world = World::<f32>::new();
+            world.integration_parameters_mut().penetration_correction = method;
+
+            let ground = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = world.add_collider(
+                0.01,
+                ground,
+                BodyHandle::ground(),
+                Isometry::new(Vector::y() * -10.0, na::zero()),
+                Material::default(),
+            );
+
+            // The box starts penetrating the ground by 0.2.
+            let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::y() * 0.3, na::zero()),
+                cuboid.inertia(1.0),
+                cuboid.center_of_mass(),
+            );
+            let _ = world.add_collider(0.01, cuboid, body, Isometry::identity(), Material::default());
+
+            let mut max_speed = 0.0f32;
+
+            for _ in 0..60 {
+                world.step();
+                let speed = world.rigid_body(body).unwrap().velocity().linear.norm();
+                max_speed = max_speed.max(speed);
+            }
+
+            let height = world.rigid_body(body).unwrap().position().translation.vector.y;
+            (max_speed, height)
+        };
+
+        let (split_speed, split_height) = simulate(PenetrationCorrectionMethod::SplitImpulse);
+        let (baumgarte_speed, _) = simulate(PenetrationCorrectionMethod::Baumgarte);
+
+        assert!(split_height > 0.48, "The penetration should be resolved: {}", split_height);
+        assert!(split_speed < 1.0e-2, "Split impulse added velocity: {}", split_speed);
+        assert!(baumgarte_speed > 1.0, "Baumgarte should add velocity: {}", baumgarte_speed);
+    }
 }
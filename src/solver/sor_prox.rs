@@ -1,4 +1,11 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::marker::PhantomData;
+#[cfg(feature = "parallel")]
+use std::ops::Range;
 
 use na::{self, DVector, Dim, Dynamic, Real, U1, VectorSliceN};
 
@@ -32,6 +39,36 @@ impl<N: Real> SORProx<N> {
         jacobians: &[N],
         max_iter: usize,
     ) {
+        let _ = self.solve_with_iterations(
+            unilateral_ground,
+            unilateral,
+            bilateral_ground,
+            bilateral,
+            mj_lambda,
+            jacobians,
+            max_iter,
+            &HashMap::new(),
+        );
+    }
+
+    /// Solve the given set of constraints with a custom number of iterations for some of them.
+    ///
+    /// The constraints involving a body with an assembly id in `iterations` are updated during
+    /// the associated number of iterations only, and the other constraints during `max_iter`
+    /// iterations. Because constraints of distinct islands never share a body, this is equivalent
+    /// to solving each island separately with its own iteration count. Returns the number of
+    /// iterations run, i.e., the largest number of iterations of a constraint.
+    pub fn solve_with_iterations(
+        &self,
+        unilateral_ground: &mut [UnilateralGroundConstraint<N>],
+        unilateral: &mut [UnilateralConstraint<N>],
+        bilateral_ground: &mut [BilateralGroundConstraint<N>],
+        bilateral: &mut [BilateralConstraint<N>],
+        mj_lambda: &mut DVector<N>,
+        jacobians: &[N],
+        max_iter: usize,
+        iterations: &HashMap<usize, usize>,
+    ) -> usize {
         /*
          * Setup constraints.
          */
@@ -59,16 +96,134 @@ impl<N: Real> SORProx<N> {
         /*
          * Solve.
          */
-        for _ in 0..max_iter {
-            self.step(
+        if iterations.is_empty() {
+            for _ in 0..max_iter {
+                self.step(
+                    unilateral_ground,
+                    unilateral,
+                    bilateral_ground,
+                    bilateral,
+                    jacobians,
+                    mj_lambda,
+                    None,
+                )
+            }
+
+            max_iter
+        } else {
+            let limits = IterationLimits::new(
                 unilateral_ground,
                 unilateral,
                 bilateral_ground,
                 bilateral,
+                max_iter,
+                iterations,
+            );
+
+            for i in 0..limits.max {
+                self.step(
+                    unilateral_ground,
+                    unilateral,
+                    bilateral_ground,
+                    bilateral,
+                    jacobians,
+                    mj_lambda,
+                    Some((&limits, i)),
+                )
+            }
+
+            limits.max
+        }
+    }
+
+    /// Solve the given set of constraints, solving each island of bodies concurrently.
+    ///
+    /// The bodies of each island must have contiguous assembly ids and `islands` gives the range
+    /// of assembly ids of each island, in increasing order. The constraints of each island are
+    /// solved on their own by `self.solve_with_iterations`, in the same relative order as in the
+    /// given vectors, so the result is identical to a single sequential resolution. Returns the
+    /// number of iterations run, or `None` without modifying anything if a constraint involves
+    /// bodies from distinct islands.
+    #[cfg(feature = "parallel")]
+    pub fn solve_islands(
+        &self,
+        unilateral_ground: &mut Vec<UnilateralGroundConstraint<N>>,
+        unilateral: &mut Vec<UnilateralConstraint<N>>,
+        bilateral_ground: &mut Vec<BilateralGroundConstraint<N>>,
+        bilateral: &mut Vec<BilateralConstraint<N>>,
+        mj_lambda: &mut DVector<N>,
+        jacobians: &[N],
+        max_iter: usize,
+        iterations: &HashMap<usize, usize>,
+        islands: &[Range<usize>],
+    ) -> Option<usize> {
+        let unilateral_ground_ids = island_ids(unilateral_ground, islands)?;
+        let unilateral_ids = island_ids(unilateral, islands)?;
+        let bilateral_ground_ids = island_ids(bilateral_ground, islands)?;
+        let bilateral_ids = island_ids(bilateral, islands)?;
+
+        // The impulse limits of the bilateral constraints refer to unilateral constraints by
+        // index so those indices must be made relative to the constraints of the island.
+        let (ground_local, ground_global) = local_indices(&unilateral_ground_ids, islands.len());
+        let (local, global) = local_indices(&unilateral_ids, islands.len());
+
+        let mut parts: Vec<_> = split(unilateral_ground, &unilateral_ground_ids, islands, &[])
+            .into_iter()
+            .zip(split(unilateral, &unilateral_ids, islands, &[]))
+            .zip(split(bilateral_ground, &bilateral_ground_ids, islands, &ground_local))
+            .zip(split(bilateral, &bilateral_ids, islands, &local))
+            .zip(islands.iter())
+            .map(|((((ug, u), bg), b), island)| IslandSystem {
+                unilateral_ground: ug,
+                unilateral: u,
+                bilateral_ground: bg,
+                bilateral: b,
+                mj_lambda: DVector::from_fn(island.len(), |i, _| mj_lambda[island.start + i]),
+                iterations: iterations
+                    .iter()
+                    .filter(|&(id, _)| island.start <= *id && *id < island.end)
+                    .map(|(id, n)| (*id - island.start, *n))
+                    .collect(),
+                niter: 0,
+            })
+            .collect();
+
+        parts.par_iter_mut().for_each(|part| {
+            part.niter = self.solve_with_iterations(
+                &mut part.unilateral_ground,
+                &mut part.unilateral,
+                &mut part.bilateral_ground,
+                &mut part.bilateral,
+                &mut part.mj_lambda,
                 jacobians,
-                mj_lambda,
-            )
+                max_iter,
+                &part.iterations,
+            );
+        });
+
+        let mut niter = 0;
+        let mut ug_parts = Vec::with_capacity(parts.len());
+        let mut u_parts = Vec::with_capacity(parts.len());
+        let mut bg_parts = Vec::with_capacity(parts.len());
+        let mut b_parts = Vec::with_capacity(parts.len());
+
+        for (part, island) in parts.into_iter().zip(islands.iter()) {
+            mj_lambda
+                .rows_mut(island.start, island.len())
+                .copy_from(&part.mj_lambda);
+            niter = niter.max(part.niter);
+            ug_parts.push(part.unilateral_ground);
+            u_parts.push(part.unilateral);
+            bg_parts.push(part.bilateral_ground);
+            b_parts.push(part.bilateral);
         }
+
+        merge(unilateral_ground, ug_parts, &unilateral_ground_ids, islands, &[]);
+        merge(unilateral, u_parts, &unilateral_ids, islands, &[]);
+        merge(bilateral_ground, bg_parts, &bilateral_ground_ids, islands, &ground_global);
+        merge(bilateral, b_parts, &bilateral_ids, islands, &global);
+
+        Some(niter)
     }
 
     fn step(
@@ -79,8 +234,18 @@ impl<N: Real> SORProx<N> {
         bilateral: &mut [BilateralConstraint<N>],
         jacobians: &[N],
         mj_lambda: &mut DVector<N>,
+        limits: Option<(&IterationLimits, usize)>,
     ) {
-        for c in unilateral.iter_mut() {
+        // Whether a constraint already reached its number of iterations.
+        let done = |select: fn(&IterationLimits) -> &[usize], i: usize| {
+            limits.map_or(false, |(l, iter)| select(l)[i] <= iter)
+        };
+
+        for (i, c) in unilateral.iter_mut().enumerate() {
+            if done(|l| &l.unilateral[..], i) {
+                continue;
+            }
+
             if c.ndofs1 == SPATIAL_DIM && c.ndofs2 == SPATIAL_DIM {
                 // Most common case (between two free rigid bodies).
                 self.solve_unilateral(c, jacobians, mj_lambda, SpatialDim {}, SpatialDim {})
@@ -91,7 +256,11 @@ impl<N: Real> SORProx<N> {
             }
         }
 
-        for c in unilateral_ground.iter_mut() {
+        for (i, c) in unilateral_ground.iter_mut().enumerate() {
+            if done(|l| &l.unilateral_ground[..], i) {
+                continue;
+            }
+
             if c.ndofs == SPATIAL_DIM {
                 // Most common case (with one free rigid body).
                 // NOTE: it's weird that the compiler requires the { } even though SpatialDim is the
@@ -103,7 +272,11 @@ impl<N: Real> SORProx<N> {
             }
         }
 
-        for c in bilateral.iter_mut() {
+        for (i, c) in bilateral.iter_mut().enumerate() {
+            if done(|l| &l.bilateral[..], i) {
+                continue;
+            }
+
             if c.ndofs1 == SPATIAL_DIM && c.ndofs2 == SPATIAL_DIM {
                 // Most common case (between two free rigid bodies).
                 self.solve_bilateral(
@@ -121,7 +294,11 @@ impl<N: Real> SORProx<N> {
             }
         }
 
-        for c in bilateral_ground.iter_mut() {
+        for (i, c) in bilateral_ground.iter_mut().enumerate() {
+            if done(|l| &l.bilateral_ground[..], i) {
+                continue;
+            }
+
             if c.ndofs == SPATIAL_DIM {
                 // Most common case (with one free rigid body).
                 self.solve_bilateral_ground(
@@ -155,7 +332,8 @@ impl<N: Real> SORProx<N> {
         let weighted_jacobian2 = VectorSliceN::from_slice_generic(&jacobians[c.wj_id2..], dim2, U1);
 
         let dimpulse = jacobian1.dot(&mj_lambda.rows_generic(id1, dim1))
-            + jacobian2.dot(&mj_lambda.rows_generic(id2, dim2)) + c.rhs;
+            + jacobian2.dot(&mj_lambda.rows_generic(id2, dim2)) + c.rhs
+            + c.cfm * c.impulse;
 
         let new_impulse = na::sup(&N::zero(), &(c.impulse - c.r * dimpulse));
         let dlambda = new_impulse - c.impulse;
@@ -179,7 +357,8 @@ impl<N: Real> SORProx<N> {
         let jacobian = VectorSliceN::from_slice_generic(&jacobians[c.j_id..], dim, U1);
         let weighted_jacobian = VectorSliceN::from_slice_generic(&jacobians[c.wj_id..], dim, U1);
 
-        let dimpulse = jacobian.dot(&mj_lambda.rows_generic_mut(c.assembly_id, dim)) + c.rhs;
+        let dimpulse = jacobian.dot(&mj_lambda.rows_generic_mut(c.assembly_id, dim)) + c.rhs
+            + c.cfm * c.impulse;
 
         let new_impulse = na::sup(&N::zero(), &(c.impulse - c.r * dimpulse));
         let dlambda = new_impulse - c.impulse;
@@ -240,7 +419,8 @@ impl<N: Real> SORProx<N> {
         let weighted_jacobian2 = VectorSliceN::from_slice_generic(&jacobians[c.wj_id2..], dim2, U1);
 
         let dimpulse = jacobian1.dot(&mj_lambda.rows_generic(id1, dim1))
-            + jacobian2.dot(&mj_lambda.rows_generic(id2, dim2)) + c.rhs;
+            + jacobian2.dot(&mj_lambda.rows_generic(id2, dim2)) + c.rhs
+            + c.cfm * c.impulse;
 
         let new_impulse = na::clamp(c.impulse - c.r * dimpulse, min_impulse, max_impulse);
         let dlambda = new_impulse - c.impulse;
@@ -293,7 +473,8 @@ impl<N: Real> SORProx<N> {
         let jacobian = VectorSliceN::from_slice_generic(&jacobians[c.j_id..], dim, U1);
         let weighted_jacobian = VectorSliceN::from_slice_generic(&jacobians[c.wj_id..], dim, U1);
 
-        let dimpulse = jacobian.dot(&mj_lambda.rows_generic(c.assembly_id, dim)) + c.rhs;
+        let dimpulse = jacobian.dot(&mj_lambda.rows_generic(c.assembly_id, dim)) + c.rhs
+            + c.cfm * c.impulse;
 
         let new_impulse = na::clamp(c.impulse - c.r * dimpulse, min_impulse, max_impulse);
         let dlambda = new_impulse - c.impulse;
@@ -394,3 +575,333 @@ impl<N: Real> SORProx<N> {
         }
     }
 }
+
+// The number of iterations of each constraint.
+struct IterationLimits {
+    unilateral_ground: Vec<usize>,
+    unilateral: Vec<usize>,
+    bilateral_ground: Vec<usize>,
+    bilateral: Vec<usize>,
+    max: usize,
+}
+
+impl IterationLimits {
+    fn new<N: Real>(
+        unilateral_ground: &[UnilateralGroundConstraint<N>],
+        unilateral: &[UnilateralConstraint<N>],
+        bilateral_ground: &[BilateralGroundConstraint<N>],
+        bilateral: &[BilateralConstraint<N>],
+        max_iter: usize,
+        iterations: &HashMap<usize, usize>,
+    ) -> Self {
+        let get = |id1: usize, id2: usize| {
+            iterations
+                .get(&id1)
+                .or_else(|| iterations.get(&id2))
+                .cloned()
+                .unwrap_or(max_iter)
+        };
+
+        let limits = IterationLimits {
+            unilateral_ground: unilateral_ground
+                .iter()
+                .map(|c| get(c.assembly_id, c.assembly_id))
+                .collect(),
+            unilateral: unilateral
+                .iter()
+                .map(|c| get(c.assembly_id1, c.assembly_id2))
+                .collect(),
+            bilateral_ground: bilateral_ground
+                .iter()
+                .map(|c| get(c.assembly_id, c.assembly_id))
+                .collect(),
+            bilateral: bilateral
+                .iter()
+                .map(|c| get(c.assembly_id1, c.assembly_id2))
+                .collect(),
+            max: 0,
+        };
+
+        let max = limits
+            .unilateral_ground
+            .iter()
+            .chain(limits.unilateral.iter())
+            .chain(limits.bilateral_ground.iter())
+            .chain(limits.bilateral.iter())
+            .cloned()
+            .max()
+            .unwrap_or(0);
+
+        IterationLimits { max, ..limits }
+    }
+}
+
+/// The constraints of an island solved independently from the other islands.
+#[cfg(feature = "parallel")]
+struct IslandSystem<N: Real> {
+    unilateral_ground: Vec<UnilateralGroundConstraint<N>>,
+    unilateral: Vec<UnilateralConstraint<N>>,
+    bilateral_ground: Vec<BilateralGroundConstraint<N>>,
+    bilateral: Vec<BilateralConstraint<N>>,
+    mj_lambda: DVector<N>,
+    iterations: HashMap<usize, usize>,
+    niter: usize,
+}
+
+/// A velocity constraint that can be moved to the system of its island.
+#[cfg(feature = "parallel")]
+trait IslandConstraint {
+    /// The assembly ids of the bodies involved in this constraint.
+    fn assembly_ids(&self) -> (usize, usize);
+    /// Applies `f` to the assembly ids of this constraint.
+    fn map_assembly_ids(&mut self, f: &Fn(usize) -> usize);
+    /// Applies `f` to the index of the unilateral constraint the impulse limits depend on.
+    fn map_dependency(&mut self, _f: &Fn(usize) -> usize) {}
+}
+
+#[cfg(feature = "parallel")]
+impl<N: Real> IslandConstraint for UnilateralGroundConstraint<N> {
+    fn assembly_ids(&self) -> (usize, usize) {
+        (self.assembly_id, self.assembly_id)
+    }
+
+    fn map_assembly_ids(&mut self, f: &Fn(usize) -> usize) {
+        self.assembly_id = f(self.assembly_id);
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<N: Real> IslandConstraint for UnilateralConstraint<N> {
+    fn assembly_ids(&self) -> (usize, usize) {
+        (self.assembly_id1, self.assembly_id2)
+    }
+
+    fn map_assembly_ids(&mut self, f: &Fn(usize) -> usize) {
+        self.assembly_id1 = f(self.assembly_id1);
+        self.assembly_id2 = f(self.assembly_id2);
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<N: Real> IslandConstraint for BilateralGroundConstraint<N> {
+    fn assembly_ids(&self) -> (usize, usize) {
+        (self.assembly_id, self.assembly_id)
+    }
+
+    fn map_assembly_ids(&mut self, f: &Fn(usize) -> usize) {
+        self.assembly_id = f(self.assembly_id);
+    }
+
+    fn map_dependency(&mut self, f: &Fn(usize) -> usize) {
+        if let ImpulseLimits::Dependent { ref mut dependency, .. } = self.limits {
+            *dependency = f(*dependency);
+        }
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<N: Real> IslandConstraint for BilateralConstraint<N> {
+    fn assembly_ids(&self) -> (usize, usize) {
+        (self.assembly_id1, self.assembly_id2)
+    }
+
+    fn map_assembly_ids(&mut self, f: &Fn(usize) -> usize) {
+        self.assembly_id1 = f(self.assembly_id1);
+        self.assembly_id2 = f(self.assembly_id2);
+    }
+
+    fn map_dependency(&mut self, f: &Fn(usize) -> usize) {
+        if let ImpulseLimits::Dependent { ref mut dependency, .. } = self.limits {
+            *dependency = f(*dependency);
+        }
+    }
+}
+
+/// The index of the island containing the given assembly id.
+#[cfg(feature = "parallel")]
+fn find_island(id: usize, islands: &[Range<usize>]) -> Option<usize> {
+    islands
+        .binary_search_by(|island| {
+            if island.end <= id {
+                Ordering::Less
+            } else if island.start > id {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+        .ok()
+}
+
+/// The index of the island of each constraint, or `None` if one involves two islands.
+#[cfg(feature = "parallel")]
+fn island_ids<C: IslandConstraint>(
+    constraints: &[C],
+    islands: &[Range<usize>],
+) -> Option<Vec<usize>> {
+    constraints
+        .iter()
+        .map(|c| {
+            let (id1, id2) = c.assembly_ids();
+            let island = find_island(id1, islands)?;
+
+            if islands[island].start <= id2 && id2 < islands[island].end {
+                Some(island)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// The index of each constraint among the constraints of its island, and the indices of the
+/// constraints of each island.
+#[cfg(feature = "parallel")]
+fn local_indices(island_ids: &[usize], nislands: usize) -> (Vec<usize>, Vec<Vec<usize>>) {
+    let mut global: Vec<Vec<usize>> = (0..nislands).map(|_| Vec::new()).collect();
+    let local = island_ids
+        .iter()
+        .enumerate()
+        .map(|(i, island)| {
+            global[*island].push(i);
+            global[*island].len() - 1
+        })
+        .collect();
+
+    (local, global)
+}
+
+/// Moves the constraints to their island, making their assembly ids and dependencies local.
+#[cfg(feature = "parallel")]
+fn split<C: IslandConstraint>(
+    constraints: &mut Vec<C>,
+    island_ids: &[usize],
+    islands: &[Range<usize>],
+    dependencies: &[usize],
+) -> Vec<Vec<C>> {
+    let mut parts: Vec<Vec<C>> = islands.iter().map(|_| Vec::new()).collect();
+
+    for (mut c, island) in constraints.drain(..).zip(island_ids.iter()) {
+        let start = islands[*island].start;
+        c.map_assembly_ids(&|id| id - start);
+        c.map_dependency(&|dependency| dependencies[dependency]);
+        parts[*island].push(c);
+    }
+
+    parts
+}
+
+/// Moves back the constraints of the islands to their original order, ids, and dependencies.
+#[cfg(feature = "parallel")]
+fn merge<C: IslandConstraint>(
+    constraints: &mut Vec<C>,
+    parts: Vec<Vec<C>>,
+    island_ids: &[usize],
+    islands: &[Range<usize>],
+    dependencies: &[Vec<usize>],
+) {
+    let mut parts: Vec<_> = parts.into_iter().map(|part| part.into_iter()).collect();
+
+    for island in island_ids {
+        let mut c = parts[*island]
+            .next()
+            .expect("Internal error: island constraint not found.");
+        let start = islands[*island].start;
+        c.map_assembly_ids(&|id| id + start);
+        c.map_dependency(&|dependency| dependencies[*island][dependency]);
+        constraints.push(c);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use math::{Isometry, Vector};
+    use object::{BodyHandle, Material};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn island_solver_iterations_only_affect_the_tagged_island() {
+        // Two independent stacks of two boxes each, resting on the ground.
+        let build = |world: &mut World<f32>| {
+            world.set_gravity(Vector::y() * -9.81);
+            world.set_deterministic(true);
+            let ground = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = world.add_collider(
+                0.01,
+                ground,
+                BodyHandle::ground(),
+                Isometry::new(Vector::y() * -10.0, na::zero()),
+                Material::default(),
+            );
+
+            let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+            let mut stacks = Vec::new();
+
+            for x in &[-3.0f32, 3.0] {
+                let mut stack = Vec::new();
+
+                for i in 0..2 {
+                    let pos = Vector::x() * *x + Vector::y() * (0.6 + i as f32 * 1.1);
+                    let body = world.add_rigid_body(
+                        Isometry::new(pos, na::zero()),
+                        cube.inertia(1.0),
+                        cube.center_of_mass(),
+                    );
+                    world
+                        .rigid_body_mut(body)
+                        .unwrap()
+                        .activation_status_mut()
+                        .set_deactivation_threshold(None);
+                    let _ = world.add_collider(
+                        0.01,
+                        cube.clone(),
+                        body,
+                        Isometry::identity(),
+                        Material::default(),
+                    );
+                    stack.push(body);
+                }
+
+                stacks.push(stack);
+            }
+
+            stacks
+        };
+
+        let mut world1 = World::new();
+        let mut world2 = World::new();
+        let stacks1 = build(&mut world1);
+        let stacks2 = build(&mut world2);
+
+        // Tag the first stack through its top box.
+        world2.set_island_solver_iterations(stacks2[0][1], 1);
+        assert_eq!(world2.island_solver_iterations(stacks2[0][1]), Some(1));
+        assert_eq!(world2.island_solver_iterations(stacks2[0][0]), None);
+
+        for _ in 0..30 {
+            world1.step();
+            world2.step();
+        }
+
+        let position = |world: &World<f32>, body| world.rigid_body(body).unwrap().position();
+
+        assert!(
+            position(&world1, stacks1[0][0]) != position(&world2, stacks2[0][0]),
+            "The tagged island should be solved with fewer iterations."
+        );
+
+        for (h1, h2) in stacks1[1].iter().zip(stacks2[1].iter()) {
+            assert!(
+                position(&world1, *h1) == position(&world2, *h2),
+                "The untagged island should not be affected."
+            );
+        }
+
+        world2.clear_island_solver_iterations(stacks2[0][1]);
+        assert_eq!(world2.island_solver_iterations(stacks2[0][1]), None);
+    }
+}
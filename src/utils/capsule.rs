@@ -0,0 +1,80 @@
+use na::{self, Real};
+use ncollide::shape::ShapeHandle;
+
+use math::{Point, Vector};
+use utils;
+
+/// Computes a convex polygon (in 2D) or polyhedron (in 3D) approximating a capsule.
+///
+/// The capsule axis is aligned with the local `y` axis, and its cylindrical part spans
+/// `[-half_height, half_height]` along this axis. The `Capsule` shape of ncollide cannot be
+/// attached to colliders, so this shape samples the capsule boundary instead: its vertices lie on
+/// the capsule surface and it supports both collision detection and mass properties computation.
+pub fn capsule<N: Real>(half_height: N, radius: N) -> ShapeHandle<N> {
+    assert!(radius > N::zero(), "The capsule radius must be positive.");
+    assert!(
+        half_height >= N::zero(),
+        "The capsule half height must be positive or zero."
+    );
+
+    let subdivs = 8;
+    let mut points = Vec::new();
+
+    for cap in &[N::one(), -N::one()] {
+        let center = Vector::y() * (half_height * *cap);
+
+        for i in 0..subdivs {
+            // Latitude on the hemisphere, from the equator to the pole.
+            let lat: N = na::convert(i as f64 / subdivs as f64 * ::std::f64::consts::FRAC_PI_2);
+            let y = Vector::y() * (radius * lat.sin() * *cap);
+            let r = radius * lat.cos();
+
+            for dir in ring_directions::<N>(subdivs * 2) {
+                points.push(Point::from(center + y + dir * r));
+            }
+        }
+
+        points.push(Point::from(center + Vector::y() * (radius * *cap)));
+    }
+
+    utils::convex_hull(&points).expect("Internal error: degenerate capsule hull.")
+}
+
+// Unit directions orthogonal to the `y` axis, regularly spaced.
+#[cfg(feature = "dim2")]
+fn ring_directions<N: Real>(_: usize) -> Vec<Vector<N>> {
+    vec![Vector::x(), -Vector::x()]
+}
+
+// Unit directions orthogonal to the `y` axis, regularly spaced.
+#[cfg(feature = "dim3")]
+fn ring_directions<N: Real>(n: usize) -> Vec<Vector<N>> {
+    (0..n)
+        .map(|i| {
+            let angle: N = na::convert(i as f64 / n as f64 * ::std::f64::consts::PI * 2.0);
+            Vector::new(angle.cos(), N::zero(), angle.sin())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use math::{Isometry, Point, Vector};
+    use volumetric::Volumetric;
+
+    #[test]
+    fn capsule_hull_lies_on_the_capsule_surface() {
+        let shape = super::capsule(1.0f32, 0.5);
+        let aabb = shape.aabb(&Isometry::identity());
+        let mut extents = Vector::repeat(0.5);
+        extents.y = 1.5;
+
+        assert_relative_eq!(*aabb.maxs(), Point::from(extents), epsilon = 1.0e-5);
+        assert_relative_eq!(*aabb.mins(), Point::from(-extents), epsilon = 1.0e-5);
+        assert_relative_eq!(shape.center_of_mass(), Point::origin(), epsilon = 1.0e-5);
+
+        let query = shape.as_point_query().unwrap();
+        assert!(query.contains_point(&Isometry::identity(), &Point::origin()));
+        assert!(!query.contains_point(&Isometry::identity(), &Point::from(extents)));
+    }
+}
@@ -0,0 +1,66 @@
+use na::Real;
+#[cfg(feature = "dim3")]
+use ncollide::shape::ConvexHull;
+#[cfg(feature = "dim2")]
+use ncollide::shape::ConvexPolygon;
+use ncollide::shape::ShapeHandle;
+
+use math::Point;
+
+/// Computes the convex hull of a point cloud, as a shape usable by colliders.
+///
+/// The points do not have to be convex, nor ordered: the ones inside of the hull are discarded.
+/// The result is a `ConvexHull` in 3D and a `ConvexPolygon` in 2D, whose mass properties can be
+/// computed with the `Volumetric` trait. Returns `None` if the hull is degenerate, e.g., if
+/// there are not enough points or if they are all aligned.
+pub fn convex_hull<N: Real>(points: &[Point<N>]) -> Option<ShapeHandle<N>> {
+    #[cfg(feature = "dim3")]
+    {
+        ConvexHull::try_from_points(points).map(ShapeHandle::new)
+    }
+    #[cfg(feature = "dim2")]
+    {
+        ConvexPolygon::try_from_points(points).map(ShapeHandle::new)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ncollide::query::Ray;
+
+    use math::{Isometry, Point, Vector};
+    use volumetric::Volumetric;
+
+    #[test]
+    fn convex_hull_of_a_point_cloud_discards_the_inner_points() {
+        // The corners of a unit cube, and points inside of it.
+        #[cfg(feature = "dim2")]
+        let corners = [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)];
+        #[cfg(feature = "dim2")]
+        let mut points: Vec<_> = corners.iter().map(|&(x, y)| Point::new(x, y)).collect();
+        #[cfg(feature = "dim3")]
+        let mut points: Vec<_> = (0..8)
+            .map(|i| {
+                let coord = |bit| if i & bit == 0 { -0.5 } else { 0.5 };
+                Point::new(coord(1), coord(2), coord(4))
+            })
+            .collect();
+
+        for i in 0..10 {
+            points.push(Point::origin() + Vector::repeat(i as f32 * 0.09 - 0.4));
+        }
+
+        let hull = super::convex_hull(&points).unwrap();
+        assert!((hull.volume() - 1.0).abs() < 1.0e-5);
+        assert!(hull.center_of_mass().coords.norm() < 1.0e-5);
+
+        // The hull can be ray-cast like any collision shape.
+        let ray = Ray::new(Point::origin() + Vector::x() * 5.0, -Vector::x());
+        let toi = hull
+            .as_ray_cast()
+            .unwrap()
+            .toi_with_ray(&Isometry::identity(), &ray, true)
+            .unwrap();
+        assert!((toi - 4.5).abs() < 1.0e-3);
+    }
+}
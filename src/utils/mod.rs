@@ -1,10 +1,14 @@
 //! Miscellaneous utilities.
 
+pub use self::capsule::capsule;
+pub use self::convex_hull::convex_hull;
 pub use self::deterministic_state::DeterministicState;
 pub use self::generalized_cross::GeneralizedCross;
 pub use self::index_mut2::IndexMut2;
 
 pub mod union_find;
+mod capsule;
+mod convex_hull;
 mod deterministic_state;
 mod generalized_cross;
 mod index_mut2;
@@ -156,8 +156,7 @@ impl<N: Real> Volumetric<N> for ConvexPolygon<N> {
 
 #[cfg(test)]
 mod test {
-    use na::{self, Matrix1, Point2, Vector2, Vector3};
-    use ncollide::procedural;
+    use na::{Matrix1, Point2, Vector2};
     use ncollide::shape::{ConvexPolygon, Cuboid};
     use volumetric::Volumetric;
 
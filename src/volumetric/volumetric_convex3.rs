@@ -296,9 +296,9 @@ mod test {
         let mut shape = procedural::cuboid(&Vector3::new(2.0f64 - 0.08, 2.0 - 0.08, 2.0 - 0.08));
 
         for c in shape.coords.iter_mut() {
-            c.x = c.x + excentricity;
-            c.y = c.y + excentricity;
-            c.z = c.z + excentricity;
+            c.x += excentricity;
+            c.y += excentricity;
+            c.z += excentricity;
         }
 
         let indices: Vec<usize> = shape
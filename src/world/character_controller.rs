@@ -0,0 +1,353 @@
+use na::{self, Real, Unit};
+use ncollide::shape::ShapeHandle;
+use ncollide::world::CollisionGroups;
+
+use math::{Isometry, Vector};
+use object::{BodyHandle, BodyStatus, ColliderHandle, Material};
+use utils;
+use volumetric::Volumetric;
+use world::{SweepResult, World};
+
+/// A kinematic capsule moved by sweeping it against the colliders of the physics world.
+///
+/// The controller does not rely on the solver to resolve its contacts: each call to
+/// `move_and_slide` sweeps the capsule along the desired motion, stops it at the first obstacle,
+/// and slides the remaining motion along the obstacle surface. Obstacles lower than
+/// `max_step_height` are climbed automatically while the character is grounded.
+pub struct CharacterController<N: Real> {
+    body: BodyHandle,
+    collider: ColliderHandle,
+    shape: ShapeHandle<N>,
+    groups: CollisionGroups,
+    up: Unit<Vector<N>>,
+    max_step_height: N,
+    max_slope: N,
+    skin_width: N,
+    max_iterations: usize,
+    push_mass: N,
+    grounded: bool,
+}
+
+impl<N: Real> CharacterController<N> {
+    /// Adds to `world` a kinematic body with a capsule collider, and creates a controller for it.
+    ///
+    /// The capsule axis is aligned with the local `y` axis of `position`. Its shape is the convex
+    /// approximation computed by `utils::capsule`.
+    pub fn new(world: &mut World<N>, position: Isometry<N>, half_height: N, radius: N) -> Self {
+        let shape = utils::capsule(half_height, radius);
+        let body = world.add_rigid_body(position, shape.inertia(N::one()), shape.center_of_mass());
+        world
+            .rigid_body_mut(body)
+            .expect("Internal error: the character body was not found.")
+            .set_status(BodyStatus::Kinematic);
+
+        let collider = world.add_collider(
+            na::convert(0.01),
+            shape.clone(),
+            body,
+            Isometry::identity(),
+            Material::default(),
+        );
+
+        CharacterController {
+            body,
+            collider,
+            shape,
+            groups: CollisionGroups::new(),
+            up: Vector::y_axis(),
+            max_step_height: na::convert(0.25),
+            max_slope: na::convert(::std::f64::consts::PI / 4.0),
+            skin_width: na::convert(0.01),
+            max_iterations: 4,
+            push_mass: N::one(),
+            grounded: false,
+        }
+    }
+
+    /// The handle of the kinematic body moved by this controller.
+    pub fn body(&self) -> BodyHandle {
+        self.body
+    }
+
+    /// The handle of the capsule collider attached to the body of this controller.
+    pub fn collider(&self) -> ColliderHandle {
+        self.collider
+    }
+
+    /// Whether the character was standing on a walkable surface at the end of the last move.
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// The world-space direction considered as the up direction (default: `y`).
+    pub fn up(&self) -> &Unit<Vector<N>> {
+        &self.up
+    }
+
+    /// Sets the world-space direction considered as the up direction.
+    pub fn set_up(&mut self, up: Unit<Vector<N>>) {
+        self.up = up
+    }
+
+    /// The maximum height of the obstacles the character can step onto (default: `0.25`).
+    pub fn max_step_height(&self) -> N {
+        self.max_step_height
+    }
+
+    /// Sets the maximum height of the obstacles the character can step onto.
+    ///
+    /// Set this to zero to disable stepping.
+    pub fn set_max_step_height(&mut self, height: N) {
+        self.max_step_height = height
+    }
+
+    /// The maximum angle, in radians, between a walkable surface normal and the up direction (default: `π / 4`).
+    pub fn max_slope(&self) -> N {
+        self.max_slope
+    }
+
+    /// Sets the maximum angle, in radians, between a walkable surface normal and the up direction.
+    pub fn set_max_slope(&mut self, angle: N) {
+        self.max_slope = angle
+    }
+
+    /// The distance kept between the capsule and the obstacles it hits (default: `0.01`).
+    pub fn skin_width(&self) -> N {
+        self.skin_width
+    }
+
+    /// Sets the distance kept between the capsule and the obstacles it hits.
+    pub fn set_skin_width(&mut self, width: N) {
+        self.skin_width = width
+    }
+
+    /// The maximum number of sweep-and-slide iterations performed by each move (default: `4`).
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    /// Sets the maximum number of sweep-and-slide iterations performed by each move.
+    pub fn set_max_iterations(&mut self, niter: usize) {
+        self.max_iterations = niter
+    }
+
+    /// The mass used to compute the impulses applied to the dynamic bodies pushed by the character (default: `1.0`).
+    pub fn push_mass(&self) -> N {
+        self.push_mass
+    }
+
+    /// Sets the mass used to compute the impulses applied to the dynamic bodies pushed by the character.
+    ///
+    /// Set this to zero to prevent the character from pushing dynamic bodies.
+    pub fn set_push_mass(&mut self, mass: N) {
+        self.push_mass = mass
+    }
+
+    /// The collision groups used to select the colliders the character can hit.
+    pub fn collision_groups(&self) -> &CollisionGroups {
+        &self.groups
+    }
+
+    /// Sets the collision groups used to select the colliders the character can hit.
+    pub fn set_collision_groups(&mut self, groups: CollisionGroups) {
+        self.groups = groups
+    }
+
+    /// Moves the character with the given velocity during the next timestep, sliding along obstacles.
+    ///
+    /// This sets the velocity of the kinematic body so that it reaches its new position at the
+    /// end of the next call to `world.step()`. Dynamic rigid bodies blocking the character are
+    /// pushed by an impulse. Returns the velocity actually applied to the character.
+    pub fn move_and_slide(&mut self, world: &mut World<N>, velocity: Vector<N>) -> Vector<N> {
+        let dt = world.timestep();
+        let start = match world.rigid_body(self.body) {
+            Some(rb) => rb.position(),
+            None => return Vector::zeros(),
+        };
+
+        if dt.is_zero() {
+            return Vector::zeros();
+        }
+
+        let mut position = start;
+        let mut remaining = velocity * dt;
+        let mut pushes = Vec::new();
+
+        for _ in 0..self.max_iterations {
+            let (travelled, hit) = self.sweep(world, &position, remaining);
+            position.translation.vector += travelled;
+            remaining -= travelled;
+
+            let hit = match hit {
+                Some(hit) => hit,
+                None => break,
+            };
+
+            if self.grounded && !self.is_walkable(&hit.normal) {
+                if let Some(stepped) = self.step_up(world, &position, remaining) {
+                    position = stepped;
+                    break;
+                }
+            }
+
+            // Remove the part of the motion going through the obstacle.
+            let normal_motion = remaining.dot(&hit.normal);
+
+            if normal_motion < N::zero() {
+                remaining -= hit.normal * normal_motion;
+                pushes.push((hit.body, hit.normal * (normal_motion * self.push_mass / dt)));
+            }
+
+            if remaining.norm() <= N::default_epsilon() {
+                break;
+            }
+        }
+
+        self.grounded = self.detect_ground(world, &position);
+
+        let applied_velocity = (position.translation.vector - start.translation.vector) / dt;
+
+        if let Some(rb) = world.rigid_body_mut(self.body) {
+            rb.set_linear_velocity(applied_velocity);
+        }
+
+        for (body, impulse) in pushes {
+            if let Some(rb) = world.rigid_body_mut(body) {
+                if rb.is_dynamic() {
+                    rb.apply_impulse(impulse);
+                }
+            }
+        }
+
+        applied_velocity
+    }
+
+    // Sweeps the capsule and returns the displacement it can perform before hitting an obstacle.
+    fn sweep(
+        &self,
+        world: &World<N>,
+        position: &Isometry<N>,
+        motion: Vector<N>,
+    ) -> (Vector<N>, Option<SweepResult<N>>) {
+        let distance = motion.norm();
+
+        if distance <= N::default_epsilon() {
+            return (Vector::zeros(), None);
+        }
+
+        let body = self.body;
+        let hit = world.convex_sweep_with_filter(
+            self.shape.as_ref(),
+            *position,
+            motion,
+            &self.groups,
+            |collider| collider.data().body() != body,
+        );
+
+        match hit {
+            Some(hit) => {
+                let travel = (hit.toi * distance - self.skin_width).max(N::zero());
+                (motion * (travel / distance), Some(hit))
+            }
+            None => (motion, None),
+        }
+    }
+
+    // Attempts to climb the obstacle blocking the horizontal part of `motion`.
+    fn step_up(
+        &self,
+        world: &World<N>,
+        position: &Isometry<N>,
+        motion: Vector<N>,
+    ) -> Option<Isometry<N>> {
+        let up = self.up.into_inner();
+        let horizontal = motion - up * motion.dot(&up);
+
+        if self.max_step_height <= N::zero() || horizontal.norm() <= N::default_epsilon() {
+            return None;
+        }
+
+        let mut stepped = *position;
+        let (rise, _) = self.sweep(world, &stepped, up * self.max_step_height);
+        stepped.translation.vector += rise;
+
+        let (forward, hit) = self.sweep(world, &stepped, horizontal);
+
+        if hit.map_or(false, |hit| !self.is_walkable(&hit.normal)) {
+            return None;
+        }
+
+        stepped.translation.vector += forward;
+
+        // Land on top of the obstacle.
+        let (fall, hit) = self.sweep(world, &stepped, -up * (rise.norm() + self.skin_width));
+
+        match hit {
+            Some(ref hit) if self.is_walkable(&hit.normal) => {
+                stepped.translation.vector += fall;
+                Some(stepped)
+            }
+            _ => None,
+        }
+    }
+
+    fn detect_ground(&self, world: &World<N>, position: &Isometry<N>) -> bool {
+        let probe = -self.up.into_inner() * (self.skin_width * na::convert(2.0));
+        let (_, hit) = self.sweep(world, position, probe);
+        hit.map_or(false, |hit| self.is_walkable(&hit.normal))
+    }
+
+    fn is_walkable(&self, normal: &Vector<N>) -> bool {
+        normal.dot(&*self.up) >= self.max_slope.cos()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use super::CharacterController;
+    use math::{Isometry, Vector};
+    use object::{BodyHandle, Material};
+    use world::World;
+
+    #[test]
+    fn character_controller_lands_and_slides_along_walls() {
+        let mut world = World::<f32>::new();
+        let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = world.add_collider(
+            0.01,
+            floor.clone(),
+            BodyHandle::ground(),
+            Isometry::new(Vector::y() * -10.0, na::zero()),
+            Material::default(),
+        );
+        let _ = world.add_collider(
+            0.01,
+            floor,
+            BodyHandle::ground(),
+            Isometry::new(Vector::x() * 12.0, na::zero()),
+            Material::default(),
+        );
+        world.step();
+
+        let mut character = CharacterController::new(
+            &mut world,
+            Isometry::new(Vector::y() * 1.5, na::zero()),
+            0.5,
+            0.25,
+        );
+
+        for _ in 0..120 {
+            let _ = character.move_and_slide(&mut world, Vector::x() * 2.0 - Vector::y() * 2.0);
+            world.step();
+        }
+
+        let pos = world.rigid_body(character.body()).unwrap().position();
+        assert!(character.is_grounded());
+        assert!(pos.translation.vector.y > 0.7, "The character fell through the floor.");
+        assert!(pos.translation.vector.x < 1.8, "The character went through the wall.");
+        assert!(pos.translation.vector.x > 1.5, "The character should have reached the wall.");
+    }
+}
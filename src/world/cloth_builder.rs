@@ -0,0 +1,326 @@
+use na::{self, Real};
+use ncollide::shape::{Ball, ShapeHandle};
+
+use joint::{ConstraintHandle, DistanceConstraint, JointConstraint};
+use math::{Isometry, Point};
+use object::{BodyHandle, BodyStatus, Material};
+use volumetric::Volumetric;
+use world::World;
+
+/// The bodies and constraints of a cloth created by a `ClothBuilder`.
+#[derive(Clone, Debug)]
+pub struct Cloth {
+    nrows: usize,
+    ncols: usize,
+    particles: Vec<BodyHandle>,
+    constraints: Vec<ConstraintHandle>,
+}
+
+impl Cloth {
+    /// The number of rows of particles of this cloth.
+    pub fn nrows(&self) -> usize {
+        self.nrows
+    }
+
+    /// The number of columns of particles of this cloth.
+    pub fn ncols(&self) -> usize {
+        self.ncols
+    }
+
+    /// The handle of the particle at the given row and column.
+    pub fn particle(&self, row: usize, col: usize) -> BodyHandle {
+        assert!(
+            row < self.nrows && col < self.ncols,
+            "Cloth::particle: index out of bounds."
+        );
+        self.particles[row * self.ncols + col]
+    }
+
+    /// The handles of all the particles of this cloth, row by row.
+    pub fn particles(&self) -> &[BodyHandle] {
+        &self.particles
+    }
+
+    /// The handles of all the distance constraints between the particles of this cloth.
+    pub fn constraints(&self) -> &[ConstraintHandle] {
+        &self.constraints
+    }
+}
+
+/// A helper building cloths and ropes made of particles linked by distance constraints.
+///
+/// Each particle is a small rigid ball. The particles are laid out on a regular grid in the local
+/// `xy` plane in 2D, or in the local `xz` plane in 3D, with rows along the negative `y` axis in 2D
+/// and along the `z` axis in 3D. Each particle is linked to its neighbors by three kinds of
+/// distance constraints, each with its own compliance:
+///
+/// * structural constraints link a particle to the next particle of its row and of its column;
+/// * shear constraints link a particle to its diagonal neighbors;
+/// * bend constraints link a particle to the particles two rows or two columns away.
+///
+/// A single row of particles makes a rope.
+pub struct ClothBuilder<N: Real> {
+    nrows: usize,
+    ncols: usize,
+    spacing: N,
+    particle_mass: N,
+    particle_radius: N,
+    margin: N,
+    material: Material<N>,
+    structural_compliance: Option<N>,
+    shear_compliance: Option<N>,
+    bend_compliance: Option<N>,
+    shear: bool,
+    bend: bool,
+    pinned: Vec<(usize, usize)>,
+}
+
+impl<N: Real> ClothBuilder<N> {
+    /// Creates a builder of cloths with `nrows` rows and `ncols` columns of particles separated
+    /// by `spacing`.
+    ///
+    /// All the constraints are rigid by default.
+    pub fn new(nrows: usize, ncols: usize, spacing: N) -> Self {
+        assert!(
+            nrows > 0 && ncols > 0,
+            "ClothBuilder::new: a cloth needs at least one particle."
+        );
+        assert!(
+            spacing > N::zero(),
+            "ClothBuilder::new: the spacing must be positive."
+        );
+
+        ClothBuilder {
+            nrows,
+            ncols,
+            spacing,
+            particle_mass: na::convert(0.1),
+            particle_radius: spacing * na::convert(0.25),
+            margin: na::convert(0.01),
+            material: Material::default(),
+            structural_compliance: None,
+            shear_compliance: None,
+            bend_compliance: None,
+            shear: true,
+            bend: true,
+            pinned: Vec::new(),
+        }
+    }
+
+    /// Sets the mass of each particle (default: `0.1`).
+    pub fn set_particle_mass(&mut self, mass: N) {
+        assert!(mass > N::zero(), "The mass of a particle must be positive.");
+        self.particle_mass = mass
+    }
+
+    /// Sets the radius of the ball collider of each particle (default: a quarter of the spacing).
+    pub fn set_particle_radius(&mut self, radius: N) {
+        assert!(radius > N::zero(), "The radius of a particle must be positive.");
+        self.particle_radius = radius
+    }
+
+    /// Sets the margin of the colliders of the particles (default: `0.01`).
+    pub fn set_margin(&mut self, margin: N) {
+        self.margin = margin
+    }
+
+    /// Sets the material of the colliders of the particles.
+    pub fn set_material(&mut self, material: Material<N>) {
+        self.material = material
+    }
+
+    /// Sets the compliance of the structural constraints (default: `None`).
+    pub fn set_structural_compliance(&mut self, compliance: Option<N>) {
+        self.structural_compliance = compliance
+    }
+
+    /// Sets the compliance of the shear constraints (default: `None`).
+    pub fn set_shear_compliance(&mut self, compliance: Option<N>) {
+        self.shear_compliance = compliance
+    }
+
+    /// Sets the compliance of the bend constraints (default: `None`).
+    pub fn set_bend_compliance(&mut self, compliance: Option<N>) {
+        self.bend_compliance = compliance
+    }
+
+    /// Enables or disables the creation of shear constraints (default: `true`).
+    pub fn enable_shear_constraints(&mut self, enabled: bool) {
+        self.shear = enabled
+    }
+
+    /// Enables or disables the creation of bend constraints (default: `true`).
+    pub fn enable_bend_constraints(&mut self, enabled: bool) {
+        self.bend = enabled
+    }
+
+    /// Pins the particle at the given row and column so that it never moves.
+    pub fn pin(&mut self, row: usize, col: usize) {
+        assert!(
+            row < self.nrows && col < self.ncols,
+            "ClothBuilder::pin: index out of bounds."
+        );
+        self.pinned.push((row, col))
+    }
+
+    /// Adds the particles and constraints of the cloth to `world`, with its first particle at
+    /// `position`.
+    pub fn build(&self, world: &mut World<N>, position: Isometry<N>) -> Cloth {
+        let shape = ShapeHandle::new(Ball::new(self.particle_radius));
+        let inertia = shape.inertia(self.particle_mass / shape.volume());
+        let mut particles = Vec::with_capacity(self.nrows * self.ncols);
+
+        for row in 0..self.nrows {
+            for col in 0..self.ncols {
+                let center = position * self.grid_point(row, col);
+                let particle = world.add_rigid_body(
+                    Isometry::new(center.coords, na::zero()),
+                    inertia,
+                    Point::origin(),
+                );
+
+                if self.pinned.contains(&(row, col)) {
+                    world
+                        .rigid_body_mut(particle)
+                        .expect("Internal error: particle not found.")
+                        .set_status(BodyStatus::Static);
+                }
+
+                let _ = world.add_collider(
+                    self.margin,
+                    shape.clone(),
+                    particle,
+                    Isometry::identity(),
+                    self.material.clone(),
+                );
+                particles.push(particle);
+            }
+        }
+
+        let mut constraints = Vec::new();
+        {
+            let mut connect = |r1: usize, c1: usize, r2: usize, c2: usize, cfm: Option<N>| {
+                if r2 >= self.nrows || c2 >= self.ncols {
+                    return;
+                }
+
+                let distance = na::distance(&self.grid_point(r1, c1), &self.grid_point(r2, c2));
+                let mut constraint = DistanceConstraint::new(
+                    particles[r1 * self.ncols + c1],
+                    particles[r2 * self.ncols + c2],
+                    Point::origin(),
+                    Point::origin(),
+                    distance,
+                );
+                constraint.set_compliance(cfm);
+                constraints.push(world.add_constraint(constraint));
+            };
+
+            for row in 0..self.nrows {
+                for col in 0..self.ncols {
+                    connect(row, col, row, col + 1, self.structural_compliance);
+                    connect(row, col, row + 1, col, self.structural_compliance);
+
+                    if self.shear {
+                        connect(row, col, row + 1, col + 1, self.shear_compliance);
+
+                        if col > 0 {
+                            connect(row, col, row + 1, col - 1, self.shear_compliance);
+                        }
+                    }
+
+                    if self.bend {
+                        connect(row, col, row, col + 2, self.bend_compliance);
+                        connect(row, col, row + 2, col, self.bend_compliance);
+                    }
+                }
+            }
+        }
+
+        Cloth {
+            nrows: self.nrows,
+            ncols: self.ncols,
+            particles,
+            constraints,
+        }
+    }
+
+    // The position of a particle in the local frame of the cloth.
+    #[cfg(feature = "dim2")]
+    fn grid_point(&self, row: usize, col: usize) -> Point<N> {
+        let col: N = na::convert(col as f64);
+        let row: N = na::convert(row as f64);
+        Point::new(col * self.spacing, -row * self.spacing)
+    }
+
+    // The position of a particle in the local frame of the cloth.
+    #[cfg(feature = "dim3")]
+    fn grid_point(&self, row: usize, col: usize) -> Point<N> {
+        let col: N = na::convert(col as f64);
+        let row: N = na::convert(row as f64);
+        Point::new(col * self.spacing, N::zero(), row * self.spacing)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ClothBuilder;
+    use math::{Isometry, Vector};
+    use world::World;
+
+    #[test]
+    fn cloth_pinned_at_two_corners_sags_without_exploding() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let (nrows, ncols, spacing) = (5, 7, 0.25);
+        let mut builder = ClothBuilder::new(nrows, ncols, spacing);
+        builder.set_structural_compliance(Some(1.0e-3));
+        builder.set_shear_compliance(Some(1.0e-2));
+        builder.set_bend_compliance(Some(1.0e-2));
+        builder.pin(0, 0);
+        builder.pin(0, ncols - 1);
+        let cloth = builder.build(&mut world, Isometry::identity());
+
+        assert_eq!(cloth.particles().len(), nrows * ncols);
+        for handle in cloth.particles() {
+            world
+                .rigid_body_mut(*handle)
+                .unwrap()
+                .activation_status_mut()
+                .set_deactivation_threshold(None);
+        }
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let position = |row, col| {
+            world
+                .rigid_body(cloth.particle(row, col))
+                .unwrap()
+                .position()
+                .translation
+                .vector
+        };
+
+        // The pinned corners did not move.
+        assert_eq!(position(0, 0), Vector::zeros());
+        assert_eq!(position(0, ncols - 1), Vector::x() * (ncols - 1) as f32 * spacing);
+
+        // The first row sags between the pins.
+        let sag = -position(0, ncols / 2).y;
+        assert!(sag > 1.0e-2, "Sag: {}", sag);
+
+        // No particle went above the pins or far away from them.
+        let extent = (nrows + ncols) as f32 * spacing;
+        for row in 0..nrows {
+            for col in 0..ncols {
+                let pos = position(row, col);
+                assert!(pos.iter().all(|x| x.is_finite()));
+                assert!(pos.y < 1.0e-3, "Particle ({}, {}) at {:?}", row, col, pos);
+                assert!(pos.norm() < extent, "Particle ({}, {}) at {:?}", row, col, pos);
+            }
+        }
+    }
+}
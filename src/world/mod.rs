@@ -1,5 +1,18 @@
 //! The physics world.
 
-pub use self::world::{CollisionWorld, World};
+pub use self::character_controller::CharacterController;
+pub use self::cloth_builder::{Cloth, ClothBuilder};
+pub use self::ragdoll_builder::{RagdollBone, RagdollBuilder, RagdollJoint};
+pub use self::world::{
+    BoundsPolicy, CollisionWorld, ExplosionFalloff, RayCastResult, SweepResult, World,
+};
+pub use self::world_snapshot::{
+    ColliderSnapshot, ConstraintSnapshot, MultibodySnapshot, RigidBodySnapshot, ShapeDescriptor,
+    WorldSnapshot,
+};
 
+mod character_controller;
+mod cloth_builder;
+mod ragdoll_builder;
 mod world;
+mod world_snapshot;
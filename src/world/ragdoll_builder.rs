@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "dim3")]
+use na::{Unit, Vector3};
+use na::{self, Real};
+
+#[cfg(feature = "dim3")]
+use joint::BallJoint;
+use joint::{FixedJoint, FreeJoint, RevoluteJoint};
+#[cfg(feature = "dim3")]
+use math::AngularVector;
+use math::{Isometry, Vector};
+use object::{BodyHandle, Material};
+use utils;
+use volumetric::Volumetric;
+use world::World;
+
+/// The joint attaching a bone of a ragdoll to its parent bone.
+#[derive(Copy, Clone, Debug)]
+pub enum RagdollJoint<N: Real> {
+    /// A ball joint.
+    ///
+    /// The `y` axis of the bone is limited to a cone of half-angle `max_swing_angle` around the
+    /// `y` axis of its parent, if any.
+    #[cfg(feature = "dim3")]
+    Ball {
+        /// The half-angle of the cone limiting the swing of the bone.
+        max_swing_angle: Option<N>,
+    },
+    /// A revolute joint with optional angle limits.
+    Revolute {
+        /// The rotation axis, in the frame of the parent bone.
+        #[cfg(feature = "dim3")]
+        axis: Unit<AngularVector<N>>,
+        /// The lower limit of the joint angle.
+        min_angle: Option<N>,
+        /// The upper limit of the joint angle.
+        max_angle: Option<N>,
+    },
+    /// A joint preventing any relative motion of the bone wrt. its parent.
+    Fixed,
+}
+
+/// The description of a bone of a ragdoll.
+#[derive(Clone, Debug)]
+pub struct RagdollBone<N: Real> {
+    /// The unique name of this bone.
+    pub name: String,
+    /// The name of the parent bone, or `None` for the root bone.
+    pub parent: Option<String>,
+    /// The position of the joint of this bone in the frame of its parent bone.
+    ///
+    /// This is ignored for the root bone.
+    pub offset: Vector<N>,
+    /// The joint attaching this bone to its parent.
+    ///
+    /// This is ignored for the root bone.
+    pub joint: RagdollJoint<N>,
+    /// The position of the capsule of this bone, relative to the joint of this bone.
+    ///
+    /// The capsule axis is aligned with the local `y` axis of this isometry.
+    pub capsule_position: Isometry<N>,
+    /// The half height of the cylindrical part of the capsule of this bone.
+    pub half_height: N,
+    /// The radius of the capsule of this bone.
+    pub radius: N,
+}
+
+/// A helper building ragdolls made of capsules attached by joints.
+///
+/// Each bone is a link of a single multibody: the root bone is attached to the ground with a free
+/// joint, and every other bone is attached to its parent with the joint of its description. At
+/// rest, all the joints have a zero angle so all the bones have the orientation of the root bone.
+/// The orientation of each capsule wrt. its bone is given by its `capsule_position`, and its shape
+/// is the convex approximation computed by `utils::capsule`.
+pub struct RagdollBuilder<N: Real> {
+    bones: Vec<RagdollBone<N>>,
+    density: N,
+    margin: N,
+    material: Material<N>,
+}
+
+impl<N: Real> RagdollBuilder<N> {
+    /// Creates a builder of ragdolls without any bone.
+    pub fn new() -> Self {
+        RagdollBuilder {
+            bones: Vec::new(),
+            density: N::one(),
+            margin: na::convert(0.01),
+            material: Material::default(),
+        }
+    }
+
+    /// Adds a bone to the ragdoll.
+    ///
+    /// The parent of the bone, if any, must have been added before. Panics if a bone with the
+    /// same name already exists, or if this is a second root bone.
+    pub fn add_bone(&mut self, bone: RagdollBone<N>) {
+        assert!(
+            self.bones.iter().all(|b| b.name != bone.name),
+            "RagdollBuilder::add_bone: two bones cannot have the same name."
+        );
+
+        match bone.parent {
+            Some(ref parent) => assert!(
+                self.bones.iter().any(|b| b.name == *parent),
+                "RagdollBuilder::add_bone: the parent of a bone must be added before it."
+            ),
+            None => assert!(
+                self.bones.is_empty(),
+                "RagdollBuilder::add_bone: only the first bone may have no parent."
+            ),
+        }
+
+        self.bones.push(bone)
+    }
+
+    /// The bones of the ragdoll, in the order they were added.
+    pub fn bones(&self) -> &[RagdollBone<N>] {
+        &self.bones
+    }
+
+    /// Sets the density of the capsules of the bones (default: `1.0`).
+    pub fn set_density(&mut self, density: N) {
+        self.density = density
+    }
+
+    /// Sets the margin of the colliders of the bones (default: `0.01`).
+    pub fn set_margin(&mut self, margin: N) {
+        self.margin = margin
+    }
+
+    /// Sets the material of the colliders of the bones.
+    pub fn set_material(&mut self, material: Material<N>) {
+        self.material = material
+    }
+
+    /// Adds the bodies, colliders, and joints of the ragdoll to `world`, with its root bone at
+    /// `position`.
+    ///
+    /// Returns the handles of the multibody links of the bones, keyed by bone name.
+    pub fn build(
+        &self,
+        world: &mut World<N>,
+        position: Isometry<N>,
+    ) -> HashMap<String, BodyHandle> {
+        let mut handles = HashMap::new();
+
+        for bone in &self.bones {
+            let shape = utils::capsule(bone.half_height, bone.radius);
+            let inertia = shape.inertia(self.density).transformed(&bone.capsule_position);
+            let center_of_mass = bone.capsule_position * shape.center_of_mass();
+
+            let handle = match bone.parent {
+                None => world.add_multibody_link(
+                    BodyHandle::ground(),
+                    FreeJoint::new(position),
+                    na::zero(),
+                    na::zero(),
+                    inertia,
+                    center_of_mass,
+                ),
+                Some(ref parent) => {
+                    let parent = handles[parent];
+
+                    match bone.joint {
+                        #[cfg(feature = "dim3")]
+                        RagdollJoint::Ball { max_swing_angle } => {
+                            let mut joint = BallJoint::new(na::zero());
+                            joint.set_twist_axis(Vector3::y_axis());
+
+                            if let Some(limit) = max_swing_angle {
+                                joint.enable_max_swing_angle(limit);
+                            }
+
+                            world.add_multibody_link(
+                                parent,
+                                joint,
+                                bone.offset,
+                                na::zero(),
+                                inertia,
+                                center_of_mass,
+                            )
+                        }
+                        #[cfg(feature = "dim2")]
+                        RagdollJoint::Revolute {
+                            min_angle,
+                            max_angle,
+                        } => world.add_multibody_link(
+                            parent,
+                            limit_angles(RevoluteJoint::new(N::zero()), min_angle, max_angle),
+                            bone.offset,
+                            na::zero(),
+                            inertia,
+                            center_of_mass,
+                        ),
+                        #[cfg(feature = "dim3")]
+                        RagdollJoint::Revolute {
+                            axis,
+                            min_angle,
+                            max_angle,
+                        } => world.add_multibody_link(
+                            parent,
+                            limit_angles(RevoluteJoint::new(axis, N::zero()), min_angle, max_angle),
+                            bone.offset,
+                            na::zero(),
+                            inertia,
+                            center_of_mass,
+                        ),
+                        RagdollJoint::Fixed => world.add_multibody_link(
+                            parent,
+                            FixedJoint::new(Isometry::identity()),
+                            bone.offset,
+                            na::zero(),
+                            inertia,
+                            center_of_mass,
+                        ),
+                    }
+                }
+            };
+
+            let _ = world.add_collider(
+                self.margin,
+                shape,
+                handle,
+                bone.capsule_position,
+                self.material.clone(),
+            );
+            let _ = handles.insert(bone.name.clone(), handle);
+        }
+
+        handles
+    }
+}
+
+impl<N: Real> Default for RagdollBuilder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn limit_angles<N: Real>(
+    mut joint: RevoluteJoint<N>,
+    min_angle: Option<N>,
+    max_angle: Option<N>,
+) -> RevoluteJoint<N> {
+    if let Some(limit) = min_angle {
+        joint.enable_min_angle(limit);
+    }
+
+    if let Some(limit) = max_angle {
+        joint.enable_max_angle(limit);
+    }
+
+    joint
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+
+    use super::{RagdollBone, RagdollBuilder, RagdollJoint};
+    use math::{Isometry, Vector};
+    use world::World;
+
+    #[test]
+    fn ragdoll_builder_connects_a_chain_of_three_bones() {
+        let bone = |name: &str, parent: Option<&str>, offset: f32, joint| RagdollBone {
+            name: name.to_string(),
+            parent: parent.map(|p| p.to_string()),
+            offset: Vector::y() * offset,
+            joint,
+            capsule_position: Isometry::new(Vector::y() * 0.25, na::zero()),
+            half_height: 0.15,
+            radius: 0.1,
+        };
+
+        #[cfg(feature = "dim2")]
+        let spine_joint = RagdollJoint::Revolute {
+            min_angle: Some(-0.5),
+            max_angle: Some(0.5),
+        };
+        #[cfg(feature = "dim3")]
+        let spine_joint = RagdollJoint::Ball {
+            max_swing_angle: Some(0.5),
+        };
+        #[cfg(feature = "dim2")]
+        let neck_joint = RagdollJoint::Revolute {
+            min_angle: None,
+            max_angle: None,
+        };
+        #[cfg(feature = "dim3")]
+        let neck_joint = RagdollJoint::Revolute {
+            axis: Vector::x_axis(),
+            min_angle: None,
+            max_angle: None,
+        };
+
+        let mut builder = RagdollBuilder::new();
+        builder.add_bone(bone("pelvis", None, 0.0, RagdollJoint::Fixed));
+        builder.add_bone(bone("spine", Some("pelvis"), 0.5, spine_joint));
+        builder.add_bone(bone("head", Some("spine"), 0.6, neck_joint));
+        assert_eq!(builder.bones().len(), 3);
+
+        let mut world = World::<f32>::new();
+        let handles = builder.build(&mut world, Isometry::new(Vector::y() * 2.0, na::zero()));
+        assert_eq!(handles.len(), 3);
+
+        let pelvis = world.multibody_link(handles["pelvis"]).unwrap();
+        let spine = world.multibody_link(handles["spine"]).unwrap();
+        let head = world.multibody_link(handles["head"]).unwrap();
+        assert!(pelvis.id().is_root());
+        assert_eq!(spine.parent_id(), pelvis.id());
+        assert_eq!(head.parent_id(), spine.id());
+        #[cfg(feature = "dim3")]
+        assert_eq!(spine.joint().ndofs(), 3);
+        assert_eq!(head.joint().ndofs(), 1);
+
+        // The joints lie at the offsets of the bones, and the capsules above them.
+        assert!((spine.position().translation.vector - Vector::y() * 2.5).norm() < 1.0e-5);
+        assert!((head.position().translation.vector - Vector::y() * 3.1).norm() < 1.0e-5);
+        assert!((head.center_of_mass().coords - Vector::y() * 3.35).norm() < 1.0e-5);
+
+        for handle in handles.values() {
+            assert_eq!(world.body_part_colliders(*handle).count(), 1);
+        }
+    }
+}
@@ -1,28 +1,115 @@
 use slab::Slab;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::f64;
+#[cfg(feature = "parallel")]
+use std::ops::Range;
+use std::vec::Drain;
 
-use na::{self, Real};
+use na::{self, Real, Unit};
 use ncollide;
 use ncollide::broad_phase::BroadPhasePairFilter;
 use ncollide::events::{ContactEvents, ProximityEvents};
-use ncollide::shape::ShapeHandle;
+use ncollide::bounding_volume::{BoundingVolume, AABB};
+use ncollide::query::{self, ContactManifold, Proximity, Ray, TrackedContact};
+use ncollide::shape::{Compound, Shape, ShapeHandle};
 use ncollide::world::{CollisionGroups, CollisionObjectHandle, GeometricQueryType};
 
-use counters::Counters;
-use detection::{ActivationManager, ColliderContactManifold};
+use counters::{Counters, StepStats};
+use detection::{
+    gjk_epa_contact, ActivationManager, CachedContact, ColliderContactManifold,
+    ContactConstraintData, ContactEvent, ContactEventHandler, ContactManifoldCache,
+    ContactModificationHandler, NarrowPhaseAlgorithm,
+};
 use force_generator::{ForceGenerator, ForceGeneratorHandle};
-use joint::{ConstraintHandle, Joint, JointConstraint};
-use math::{Inertia, Isometry, Point, Vector};
+use joint::{ConstraintHandle, Joint, JointConstraint, LimitState};
+use math::{AngularVector, Force, Inertia, Isometry, Point, Vector, Velocity};
 use object::{
     Body, BodyHandle, BodyMut, BodyPart, BodyPartMut, BodySet, BodyStatus, Collider, ColliderData,
-    ColliderHandle, Colliders, Material, Multibody, MultibodyLinkMut, MultibodyLinkRef,
-    MultibodyWorkspace, RigidBody, SensorHandle,
+    ColliderHandle, Colliders, HeightField, Material, Multibody, MultibodyLinkMut,
+    MultibodyLinkRef, MultibodyWorkspace, RigidBody, SensorHandle,
+};
+#[cfg(feature = "dim3")]
+use object::TriMeshBody;
+use solver::{
+    ConstraintGroup, ConstraintOrigin, ConstraintSet, ContactModel, IntegrationParameters,
+    MoreauJeanSolver, SignoriniCoulombPyramidModel,
+};
+use utils::union_find::{self, UnionFindSet};
+use utils::GeneralizedCross;
+use world::{
+    ColliderSnapshot, ConstraintSnapshot, MultibodySnapshot, RigidBodySnapshot, ShapeDescriptor,
+    WorldSnapshot,
 };
-use solver::{ContactModel, IntegrationParameters, MoreauJeanSolver, SignoriniCoulombPyramidModel};
 
 /// Type of the collision world used by nphysics.
 pub type CollisionWorld<N> = ncollide::world::CollisionWorld<N, ColliderData<N>>;
 
+/// The attenuation of an explosion impulse wrt. the distance to the explosion center.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExplosionFalloff {
+    /// The impulse decreases linearly from its peak value at the center to zero at the explosion radius.
+    Linear,
+    /// The impulse is the peak value divided by `1 + d²` where `d` is the distance to the center.
+    InverseSquare,
+    /// The impulse is the peak value everywhere inside of the explosion radius.
+    Constant,
+}
+
+impl ExplosionFalloff {
+    fn attenuate<N: Real>(&self, peak: N, distance: N, radius: N) -> N {
+        match *self {
+            ExplosionFalloff::Linear => peak * (N::one() - distance / radius),
+            ExplosionFalloff::InverseSquare => peak / (N::one() + distance * distance),
+            ExplosionFalloff::Constant => peak,
+        }
+    }
+}
+
+/// What happens to a body whose center of mass leaves the bounds of the world.
+///
+/// See `World::set_bounds`.
+pub enum BoundsPolicy {
+    /// The body is put to sleep.
+    Deactivate,
+    /// The body is removed from the world, together with its colliders and constraints.
+    Delete,
+    /// The given closure is called with the handle of the body.
+    Callback(Box<FnMut(BodyHandle) + Send + Sync>),
+}
+
+/// The result of a ray cast against the colliders of the physics world.
+#[derive(Copy, Clone, Debug)]
+pub struct RayCastResult<N: Real> {
+    /// The body part the intersected collider is attached to.
+    pub body: BodyHandle,
+    /// The intersected collider.
+    pub collider: ColliderHandle,
+    /// The time of impact of the ray with the collider.
+    ///
+    /// The intersection point is `ray.origin + ray.dir * toi`.
+    pub toi: N,
+    /// The world-space normal of the collider surface at the intersection point.
+    pub normal: Vector<N>,
+}
+
+/// The result of a convex shape sweep against the colliders of the physics world.
+#[derive(Copy, Clone, Debug)]
+pub struct SweepResult<N: Real> {
+    /// The body part the hit collider is attached to.
+    pub body: BodyHandle,
+    /// The hit collider.
+    pub collider: ColliderHandle,
+    /// The fraction of the motion at which the swept shape first touches the collider.
+    ///
+    /// The position of the swept shape at the time of impact is `start` translated by `motion * toi`.
+    pub toi: N,
+    /// The world-space normal of the collider surface at the contact point.
+    ///
+    /// It points toward the swept shape.
+    pub normal: Vector<N>,
+}
+
 /// The physics world.
 pub struct World<N: Real> {
     counters: Counters,
@@ -39,7 +126,27 @@ pub struct World<N: Real> {
     constraints: Slab<Box<JointConstraint<N>>>,
     forces: Slab<Box<ForceGenerator<N>>>,
     params: IntegrationParameters<N>,
+    last_dt: Option<N>,
+    time: N,
+    time_accumulator: N,
+    max_substeps: Option<usize>,
     workspace: MultibodyWorkspace<N>,
+    deterministic: bool,
+    narrow_phase_algorithm: NarrowPhaseAlgorithm<N>,
+    // NOTE: the hash maps of the world are only used for lookups, see `set_deterministic`.
+    island_solver_iterations: HashMap<BodyHandle, usize>,
+    bounds: Option<(AABB<N>, BoundsPolicy)>,
+    escaped_bodies: HashSet<BodyHandle>,
+    body_contact_events: Vec<ContactEvent>,
+    contact_event_handler: Option<Box<ContactEventHandler>>,
+    contact_modification_handler: Option<Box<ContactModificationHandler<N>>>,
+    contact_material_resolver: Option<Box<Fn(BodyHandle, BodyHandle) -> (N, N) + Send + Sync>>,
+    contact_manifold_cache: ContactManifoldCache<N>,
+    limit_states: HashMap<ConstraintHandle, LimitState>,
+    limit_transitions: Vec<(ConstraintHandle, LimitState)>,
+    last_step_stats: StepStats,
+    #[cfg(feature = "dim3")]
+    tri_mesh_bodies: HashMap<ColliderHandle, TriMeshBody<N>>,
 }
 
 impl<N: Real> World<N> {
@@ -82,7 +189,26 @@ impl<N: Real> World<N> {
             constraints,
             forces,
             params,
+            last_dt: None,
+            time: N::zero(),
+            time_accumulator: N::zero(),
+            max_substeps: None,
             workspace,
+            deterministic: false,
+            narrow_phase_algorithm: NarrowPhaseAlgorithm::Default,
+            island_solver_iterations: HashMap::new(),
+            bounds: None,
+            escaped_bodies: HashSet::new(),
+            body_contact_events: Vec::new(),
+            contact_event_handler: None,
+            contact_modification_handler: None,
+            contact_material_resolver: None,
+            contact_manifold_cache: ContactManifoldCache::new(na::convert(0.02f64)),
+            limit_states: HashMap::new(),
+            limit_transitions: Vec::new(),
+            last_step_stats: StepStats::default(),
+            #[cfg(feature = "dim3")]
+            tri_mesh_bodies: HashMap::new(),
         }
     }
 
@@ -91,6 +217,95 @@ impl<N: Real> World<N> {
         self.prediction
     }
 
+    /// Sets the prediction distance used for collision detection (default: `0.002`).
+    ///
+    /// Contacts are generated as soon as two colliders are closer than this distance, and the
+    /// solver only prevents them from closing their gap by more than their approach velocity
+    /// allows during the timestep. Increasing this distance thus avoids penetrations and jitter
+    /// of resting contacts at large timesteps, at the cost of more contacts to solve. This only
+    /// affects the colliders and sensors added after this call.
+    pub fn set_prediction(&mut self, prediction: N) {
+        assert!(
+            prediction >= N::zero(),
+            "The prediction distance must be positive or zero."
+        );
+        self.prediction = prediction
+    }
+
+    /// Enables or disables deterministic stepping.
+    ///
+    /// If enabled, the bodies and contact manifolds are sorted before being handed to the
+    /// solver, and the contact events are sorted before being reported, so that identical worlds
+    /// always produce bit-identical results. This has a small cost so it is disabled by default.
+    ///
+    /// The results never depend on the iteration order of hash maps, which changes from one map
+    /// to another and from one process to another: the contact manifold cache is ordered, and
+    /// the other hash maps of the world (the custom solver iterations of the islands, the escaped
+    /// bodies, the limit states of the joints, and the triangle mesh bodies) are only used for
+    /// lookups, or their iteration results are sorted or combined in an order-independent way.
+    ///
+    /// See `self.step_deterministic()` to enable this for a single step.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic
+    }
+
+    /// Whether deterministic stepping is enabled.
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// The algorithm used to compute the contact normals of pairs of convex shapes.
+    pub fn narrow_phase_algorithm(&self) -> &NarrowPhaseAlgorithm<N> {
+        &self.narrow_phase_algorithm
+    }
+
+    /// Sets the algorithm used to compute the contact normals of pairs of convex shapes
+    /// (default: `NarrowPhaseAlgorithm::Default`).
+    pub fn set_narrow_phase_algorithm(&mut self, algorithm: NarrowPhaseAlgorithm<N>) {
+        if let NarrowPhaseAlgorithm::GjkEpa {
+            max_iterations,
+            tolerance,
+        } = algorithm
+        {
+            assert!(
+                max_iterations > 0 && tolerance > N::zero(),
+                "GJK and EPA need at least one iteration and a positive tolerance."
+            );
+        }
+
+        self.narrow_phase_algorithm = algorithm
+    }
+
+    /// Sets the number of velocity iterations of the constraint solver for the island containing
+    /// `body`.
+    ///
+    /// This overrides `IntegrationParameters::max_velocity_iterations` for all the bodies
+    /// connected to `body`, directly or not, by contacts and active joints. The iteration count is
+    /// stored on `body` and the island containing it is recomputed at each timestep. If an island
+    /// contains several bodies with a custom iteration count, the largest one is used.
+    pub fn set_island_solver_iterations(&mut self, body: BodyHandle, n: usize) {
+        assert!(
+            !body.is_ground() && self.bodies.contains(body),
+            "The island solver iterations can only be set on a body of this world."
+        );
+        assert!(n > 0, "The constraint solver needs at least one iteration.");
+
+        let _ = self.island_solver_iterations.insert(body, n);
+    }
+
+    /// The number of velocity iterations set with `set_island_solver_iterations` on `body`, if any.
+    pub fn island_solver_iterations(&self, body: BodyHandle) -> Option<usize> {
+        self.island_solver_iterations.get(&body).cloned()
+    }
+
+    /// Removes the number of velocity iterations set on `body`.
+    ///
+    /// Its island then uses the global number of iterations unless another of its bodies has a
+    /// custom one.
+    pub fn clear_island_solver_iterations(&mut self, body: BodyHandle) {
+        let _ = self.island_solver_iterations.remove(&body);
+    }
+
     /// Disable the perfomance counters that measure various times and statistics during a timestep.
     pub fn disable_performance_counters(&mut self) {
         self.counters.disable();
@@ -111,6 +326,46 @@ impl<N: Real> World<N> {
         self.solver.set_contact_model(Box::new(model))
     }
 
+    /// The cache of the contact points between pairs of body parts, kept across timesteps.
+    ///
+    /// The points of this cache are used to warm-start the contacts newly created by the
+    /// narrow phase, when they match a point of the previous timestep.
+    pub fn contact_manifold_cache(&self) -> &ContactManifoldCache<N> {
+        &self.contact_manifold_cache
+    }
+
+    /// A mutable reference to the cache of the contact points between pairs of body parts.
+    ///
+    /// This can be used to change the distance cached points may slide before being dropped
+    /// (default: `0.02`).
+    pub fn contact_manifold_cache_mut(&mut self) -> &mut ContactManifoldCache<N> {
+        &mut self.contact_manifold_cache
+    }
+
+    /// The total kinetic energy of the dynamic rigid bodies of this world.
+    pub fn kinetic_energy(&self) -> N {
+        self.dynamic_rigid_bodies()
+            .fold(N::zero(), |acc, rb| acc + rb.kinetic_energy())
+    }
+
+    /// The total linear momentum of the dynamic rigid bodies of this world.
+    pub fn linear_momentum(&self) -> Vector<N> {
+        self.dynamic_rigid_bodies()
+            .fold(Vector::zeros(), |acc, rb| acc + rb.linear_momentum())
+    }
+
+    /// The total angular momentum of the dynamic rigid bodies of this world, wrt. the world origin.
+    pub fn angular_momentum(&self) -> AngularVector<N> {
+        self.dynamic_rigid_bodies().fold(AngularVector::zeros(), |acc, rb| {
+            let orbital = rb.center_of_mass().coords.gcross(&rb.linear_momentum());
+            acc + orbital + rb.angular_momentum()
+        })
+    }
+
+    fn dynamic_rigid_bodies(&self) -> impl Iterator<Item = &RigidBody<N>> {
+        self.bodies.rigid_bodies().filter(|rb| rb.is_dynamic())
+    }
+
     /// Retrieve a reference to the parameters for the integration.
     pub fn integration_parameters(&self) -> &IntegrationParameters<N> {
         &self.params
@@ -127,10 +382,27 @@ impl<N: Real> World<N> {
     }
 
     /// Sets the timestep used for the integration.
+    ///
+    /// The impulses cached for warm-starting the solver are automatically rescaled at the next
+    /// step to account for the timestep change.
     pub fn set_timestep(&mut self, dt: N) {
         self.params.dt = dt;
     }
 
+    /// Sets the maximum norm of the linear velocity of the dynamic rigid bodies.
+    ///
+    /// Set this to `None` (the default) to disable the clamping.
+    pub fn set_max_linear_velocity(&mut self, max: Option<N>) {
+        self.params.max_linear_velocity = max;
+    }
+
+    /// Sets the maximum norm of the angular velocity of the dynamic rigid bodies.
+    ///
+    /// Set this to `None` (the default) to disable the clamping.
+    pub fn set_max_angular_velocity(&mut self, max: Option<N>) {
+        self.params.max_angular_velocity = max;
+    }
+
     /// Activate the given body.
     pub fn activate_body(&mut self, handle: BodyHandle) {
         Self::activate_body_at(&mut self.bodies, handle)
@@ -168,6 +440,7 @@ impl<N: Real> World<N> {
     /// Remove the specified constraint from the world.
     pub fn remove_constraint(&mut self, handle: ConstraintHandle) -> Box<JointConstraint<N>> {
         let constraint = self.constraints.remove(handle);
+        let _ = self.limit_states.remove(&handle);
         let (anchor1, anchor2) = constraint.anchors();
         self.activate_body(anchor1);
         self.activate_body(anchor2);
@@ -180,6 +453,10 @@ impl<N: Real> World<N> {
         self.cworld.remove(handles);
         self.colliders_w_parent
             .retain(|handle| !handles.contains(handle));
+
+        #[cfg(feature = "dim3")]
+        self.tri_mesh_bodies
+            .retain(|handle, _| !handles.contains(handle));
     }
 
     /// Add a force generator to the world.
@@ -218,9 +495,108 @@ impl<N: Real> World<N> {
         &self.gravity
     }
 
+    /// Applies a radial impulse to all the dynamic body parts with a collider intersecting the given ball.
+    ///
+    /// The impulse is applied at the center of mass of each body part, along the direction from
+    /// `center` to this center of mass. Its magnitude is `peak_impulse` attenuated according to
+    /// `falloff` wrt. the distance between `center` and the center of mass. Body parts with a
+    /// center of mass farther than `radius` from `center` are not affected. The impulse is
+    /// actually applied during the next execution of `self.step()`.
+    pub fn apply_explosion_impulse(
+        &mut self,
+        center: Point<N>,
+        radius: N,
+        peak_impulse: N,
+        falloff: ExplosionFalloff,
+    ) {
+        self.do_apply_explosion_impulse(center, radius, peak_impulse, falloff, false)
+    }
+
+    /// Applies a radial impulse to all the dynamic body parts visible from the explosion center.
+    ///
+    /// This is the same as `self.apply_explosion_impulse(...)` except that a body part is
+    /// not affected if the segment between `center` and its center of mass intersects a collider
+    /// attached to another body part. This is more expensive since one ray is cast per body part.
+    pub fn apply_occluded_explosion_impulse(
+        &mut self,
+        center: Point<N>,
+        radius: N,
+        peak_impulse: N,
+        falloff: ExplosionFalloff,
+    ) {
+        self.do_apply_explosion_impulse(center, radius, peak_impulse, falloff, true)
+    }
+
+    fn do_apply_explosion_impulse(
+        &mut self,
+        center: Point<N>,
+        radius: N,
+        peak_impulse: N,
+        falloff: ExplosionFalloff,
+        occlusion: bool,
+    ) {
+        let extents = Vector::repeat(radius);
+        let aabb = AABB::new(center - extents, center + extents);
+        let groups = CollisionGroups::new();
+        let mut impulses = Vec::new();
+        let mut visited = HashSet::new();
+
+        for collider in self.cworld.interferences_with_aabb(&aabb, &groups) {
+            let handle = collider.data().body();
+
+            if collider.data().is_sensor() || !visited.insert(handle) {
+                continue;
+            }
+
+            let body = self.bodies.body_part(handle);
+
+            if body.status_dependent_parent_ndofs() == 0 {
+                continue;
+            }
+
+            let dir = body.center_of_mass() - center;
+            let distance = dir.norm();
+
+            if distance > radius || distance.is_zero() {
+                continue;
+            }
+
+            if occlusion {
+                let ray = Ray::new(center, dir);
+                let occluded = self
+                    .cworld
+                    .interferences_with_ray(&ray, &groups)
+                    .any(|(other, inter)| {
+                        inter.toi < N::one() && other.data().body() != handle
+                            && !other.data().is_sensor()
+                    });
+
+                if occluded {
+                    continue;
+                }
+            }
+
+            let magnitude = falloff.attenuate(peak_impulse, distance, radius);
+            impulses.push((handle, dir * (magnitude / distance)));
+        }
+
+        let inv_dt = N::one() / self.params.dt;
+        for (handle, impulse) in impulses {
+            Self::activate_body_at(&mut self.bodies, handle);
+            self.bodies
+                .body_part_mut(handle)
+                .apply_force(&Force::linear(impulse * inv_dt));
+        }
+    }
+
     /// Execute one time step of the physics simulation.
     pub fn step(&mut self) {
         self.counters.step_started();
+
+        for rb in self.bodies.rigid_bodies_mut() {
+            rb.save_previous_position();
+        }
+
         self.counters.update_started();
         // FIXME: objects involeved in a non-linear position stabilization elready
         // updated their kinematics.
@@ -269,6 +645,8 @@ impl<N: Real> World<N> {
         self.counters.narrow_phase_completed();
         self.counters.collision_detection_completed();
 
+        self.update_body_contact_events();
+
         if self.counters.enabled() {
             let npairs = self.cworld.contact_pairs().count();
             self.counters.set_ncontact_pairs(npairs);
@@ -285,30 +663,231 @@ impl<N: Real> World<N> {
         );
         self.counters.island_construction_completed();
 
-        let mut contact_manifolds = Vec::new(); // FIXME: avoid allocations.
-        for (coll1, coll2, c) in self.cworld.contact_manifolds() {
-            // assert!(coll1.data().body() != coll2.data().body());
+        // NOTE: the contact manifolds borrow the collision world until the end of this block.
+        let (free_bodies, cached_points) = {
+            let mut contact_manifolds = Vec::new(); // FIXME: avoid allocations.
+            for (coll1, coll2, c) in self.cworld.contact_manifolds() {
+                // assert!(coll1.data().body() != coll2.data().body());
+
+                if coll1.data().is_sensor() || coll2.data().is_sensor() {
+                    continue;
+                }
+
+                let b1 = self.bodies.body(coll1.data().body());
+                let b2 = self.bodies.body(coll2.data().body());
+
+                if b1.status() != BodyStatus::Disabled && b2.status() != BodyStatus::Disabled
+                    && ((b1.status_dependent_ndofs() != 0 && b1.is_active())
+                        || (b2.status_dependent_ndofs() != 0 && b2.is_active()))
+                {
+                    let mut manifold = ColliderContactManifold::new(coll1, coll2, c);
+                    let smoothed = self.has_smoothed_normals(coll1, coll2);
+                    let refined_normal = self.refined_contact_normal(coll1, coll2);
+
+                    if self.contact_material_resolver.is_some()
+                        || self.contact_modification_handler.is_some()
+                        || smoothed
+                        || refined_normal.is_some()
+                    {
+                        let (b1, b2) = (manifold.body1(), manifold.body2());
+                        let materials = self
+                            .contact_material_resolver
+                            .as_ref()
+                            .map(|resolve| resolve(b1, b2));
+
+                        for contact in c.contacts() {
+                            let mut data = ContactConstraintData::new(contact);
+                            data.normal = refined_normal;
+
+                            if smoothed {
+                                data.normal = self.smoothed_contact_normal(&manifold, contact);
+                            }
+
+                            if let Some((friction, restitution)) = materials {
+                                data.friction = Some(friction);
+                                data.restitution = Some(restitution);
+                            }
+
+                            if let Some(ref mut handler) = self.contact_modification_handler {
+                                handler.modify_contact(b1, b2, &mut data);
+                            }
+
+                            manifold.modified_contacts.push(data);
+                        }
+                    }
+
+                    contact_manifolds.push(manifold);
+                }
+            }
+
+            if self.deterministic {
+                // The narrow-phase does not guarantee any particular iteration order
+                // so we have to sort the manifolds to obtain reproducible results.
+                self.active_bodies.sort();
+                contact_manifolds.sort_by_key(|m| {
+                    (
+                        m.collider1.handle().uid(),
+                        m.collider2.handle().uid(),
+                        m.manifold.subshape_id1(),
+                        m.manifold.subshape_id2(),
+                    )
+                });
+            }
+
+            // Warm-start the new contacts with the impulses of the matching cached points.
+            let mut cached_points = Vec::new(); // FIXME: avoid allocations.
+            for manifold in &mut contact_manifolds {
+                let (b1, b2) = (manifold.body1(), manifold.body2());
+                let inv_pos1 = self.bodies.body_part(b1).position().inverse();
+                let inv_pos2 = self.bodies.body_part(b2).position().inverse();
+                let contacts = manifold.manifold.contacts();
+
+                for c in contacts {
+                    if !manifold.is_contact_enabled(c) {
+                        continue;
+                    }
+
+                    let point = CachedContact {
+                        local1: inv_pos1 * c.contact.world1,
+                        local2: inv_pos2 * c.contact.world2,
+                        depth: c.contact.depth,
+                        impulse: N::zero(),
+                    };
+
+                    if let Some(cached) = self.contact_manifold_cache.find(b1, b2, &point) {
+                        manifold.cached_impulses.push((c.id, cached.impulse));
+                    }
+
+                    cached_points.push((b1, b2, c.id, point));
+                }
+            }
+
+            // Rigid bodies not involved in any contact or joint do not need the full
+            // constraint solver: they are simply integrated with a semi-implicit Euler step.
+            let mut constrained_bodies = HashSet::new(); // FIXME: avoid allocations.
+            for m in &contact_manifolds {
+                let _ = constrained_bodies.insert(m.body1());
+                let _ = constrained_bodies.insert(m.body2());
+            }
+
+            for (_, c) in self.constraints.iter() {
+                if c.is_active(&self.bodies) {
+                    let (b1, b2) = c.anchors();
+                    let _ = constrained_bodies.insert(b1);
+                    let _ = constrained_bodies.insert(b2);
+                }
+            }
+
+            let nbodies = self.active_bodies.len();
+            let mut free_bodies = Vec::new(); // FIXME: avoid allocations.
+            {
+                let bodies = &self.bodies;
+                self.active_bodies.retain(|handle| {
+                    if bodies.rigid_body(*handle).is_some() && !constrained_bodies.contains(handle) {
+                        free_bodies.push(*handle);
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
 
-            let b1 = self.bodies.body(coll1.data().body());
-            let b2 = self.bodies.body(coll2.data().body());
+            let island_roots = self.island_roots(&contact_manifolds);
+            let velocity_iterations = self.island_velocity_iterations(&island_roots);
 
-            if b1.status() != BodyStatus::Disabled && b2.status() != BodyStatus::Disabled
-                && ((b1.status_dependent_ndofs() != 0 && b1.is_active())
-                    || (b2.status_dependent_ndofs() != 0 && b2.is_active()))
+            #[cfg(feature = "parallel")]
             {
-                contact_manifolds.push(ColliderContactManifold::new(coll1, coll2, c));
+                let islands = self.sort_active_bodies_by_island(&island_roots);
+                self.solver.set_islands(islands);
+            }
+
+            // Each free body is an island on its own.
+            let nislands = free_bodies.len() + {
+                let mut roots = island_roots;
+                roots.sort();
+                roots.dedup();
+                roots.len()
+            };
+
+            // The cached impulses are proportional to the timestep they were computed with, so
+            // they are rescaled for warm-starting if the timestep changed since the last step.
+            let rescaled_params;
+            let solver_params = match self.last_dt {
+                Some(last_dt) if last_dt != self.params.dt && !last_dt.is_zero() => {
+                    let mut params = self.params.clone();
+                    params.warmstart_coeff *= self.params.dt / last_dt;
+                    rescaled_params = params;
+                    &rescaled_params
+                }
+                _ => &self.params,
+            };
+
+            self.solver.set_velocity_iterations(velocity_iterations);
+
+            self.counters.solver_started();
+            self.solver.step(
+                &mut self.counters,
+                &mut self.bodies,
+                &mut self.constraints,
+                &contact_manifolds[..],
+                &self.active_bodies[..],
+                solver_params,
+            );
+            self.last_dt = Some(self.params.dt);
+
+            let mut ncontact_constraints = 0;
+            let mut njoint_constraints = 0;
+            for group in self.solver.constraint_groups() {
+                match group.origin {
+                    ConstraintOrigin::Contacts => ncontact_constraints += group.len(),
+                    _ => njoint_constraints += group.len(),
+                }
             }
+
+            self.last_step_stats = StepStats {
+                nislands,
+                nbodies,
+                nfree_bodies: free_bodies.len(),
+                ncontact_constraints,
+                njoint_constraints,
+                nvelocity_iterations: self.solver.velocity_iterations(),
+                nposition_iterations: self.solver.position_iterations(),
+                step_time: None,
+            };
+
+            (free_bodies, cached_points)
+        };
+
+        // Refresh the contact manifold cache with the impulses computed by the solver.
+        // NOTE: ordered so the cache is updated in the same order whatever the hashing.
+        let mut cached_pairs = BTreeMap::new(); // FIXME: avoid allocations.
+        for (b1, b2, id, mut point) in cached_points {
+            point.impulse = self
+                .solver
+                .contact_model()
+                .contact_impulse(id)
+                .unwrap_or_else(N::zero);
+            cached_pairs
+                .entry((b1, b2))
+                .or_insert_with(Vec::new)
+                .push(point);
         }
 
-        self.counters.solver_started();
-        self.solver.step(
-            &mut self.counters,
-            &mut self.bodies,
-            &mut self.constraints,
-            &contact_manifolds[..],
-            &self.active_bodies[..],
-            &self.params,
-        );
+        self.contact_manifold_cache
+            .retain(|b1, b2| cached_pairs.contains_key(&(b1, b2)));
+        for (pair, points) in &cached_pairs {
+            self.contact_manifold_cache.update(pair.0, pair.1, points);
+        }
+
+        for handle in &free_bodies {
+            if let Some(rb) = self.bodies.rigid_body_mut(*handle) {
+                rb.integrate_unconstrained(&self.params)
+            }
+        }
+
+        self.update_limit_transitions();
+
+        self.active_bodies.extend(free_bodies);
 
         // FIXME: not sure what is the most pretty/efficient way of doing this.
         for rb in self.bodies.rigid_bodies_mut() {
@@ -323,104 +902,696 @@ impl<N: Real> World<N> {
         }
 
         self.bodies.clear_forces();
+        self.time += self.params.dt;
         self.counters.solver_completed();
+        self.apply_bounds();
         self.counters.step_completed();
-    }
 
-    /// Remove the specified bodies.
-    pub fn remove_bodies(&mut self, bodies: &[BodyHandle]) {
-        for body in bodies {
-            self.bodies.remove_body(*body);
+        if self.counters.enabled() {
+            self.last_step_stats.step_time = Some(self.counters.step_time());
         }
+    }
 
-        self.cleanup_after_body_removal();
+    /// Statistics about the last execution of `self.step()`.
+    ///
+    /// The time spent for the step is only measured if the performance counters are enabled.
+    pub fn last_step_stats(&self) -> &StepStats {
+        &self.last_step_stats
     }
 
-    /// Remove several links of a single multibody.
+    /// Sets the bounds of this world and what happens to the bodies leaving them.
     ///
-    /// Panics if not all links belong to the same multibody.
-    pub fn remove_multibody_links(&mut self, links: &[BodyHandle]) {
-        self.bodies.remove_multibody_links(links);
-        self.cleanup_after_body_removal();
+    /// After each step, `policy` is applied once to each dynamic or kinematic body whose center of
+    /// mass just left `bounds`. The center of mass of a multibody is the one of all its links, and
+    /// the policy is applied to the handle of the multibody. A body is considered again only after
+    /// it has come back inside of the bounds.
+    pub fn set_bounds(&mut self, bounds: AABB<N>, policy: BoundsPolicy) {
+        self.bounds = Some((bounds, policy));
+        self.escaped_bodies.clear();
     }
 
-    fn cleanup_after_body_removal(&mut self) {
-        self.activate_bodies_touching_deleted_bodies();
-        self.cleanup_colliders_with_deleted_parents();
-        self.cleanup_constraints_with_deleted_anchors();
+    /// The bounds of this world, if any.
+    pub fn bounds(&self) -> Option<&AABB<N>> {
+        self.bounds.as_ref().map(|b| &b.0)
     }
 
-    fn activate_bodies_touching_deleted_bodies(&mut self) {
-        let bodies = &mut self.bodies;
+    /// Removes the bounds of this world and their policy.
+    pub fn remove_bounds(&mut self) -> Option<(AABB<N>, BoundsPolicy)> {
+        self.escaped_bodies.clear();
+        self.bounds.take()
+    }
 
-        for (co1, co2, detector) in self.cworld.contact_pairs() {
-            if detector.num_contacts() != 0 {
-                let b1_exists = bodies.contains(co1.data().body());
-                let b2_exists = bodies.contains(co2.data().body());
+    // Applies the bounds policy to the bodies that just left the bounds.
+    fn apply_bounds(&mut self) {
+        let escaped: Vec<BodyHandle> = {
+            let bounds = match self.bounds {
+                Some((ref bounds, _)) => bounds,
+                None => return,
+            };
+            let inside = |pt: Point<N>| {
+                pt.coords
+                    .iter()
+                    .zip(bounds.mins().coords.iter().zip(bounds.maxs().coords.iter()))
+                    .all(|(x, (min, max))| *x >= *min && *x <= *max)
+            };
+            let moving = |status: BodyStatus| {
+                status == BodyStatus::Dynamic || status == BodyStatus::Kinematic
+            };
+
+            let rbs = self
+                .bodies
+                .rigid_bodies()
+                .filter(|rb| moving(rb.status()))
+                .map(|rb| (rb.handle(), rb.center_of_mass()));
+            let mbs = self
+                .bodies
+                .multibodies()
+                .filter(|mb| moving(mb.status()))
+                .map(|mb| {
+                    let mut mass = N::zero();
+                    let mut com = Vector::zeros();
+
+                    for link in mb.links() {
+                        let link_mass = link.inertia().mass();
+                        mass += link_mass;
+                        com += link.center_of_mass().coords * link_mass;
+                    }
 
-                if !b1_exists {
-                    if b2_exists {
-                        Self::activate_body_at(bodies, co2.data().body());
+                    if mass > N::zero() {
+                        com /= mass;
                     }
-                } else if !b2_exists {
-                    Self::activate_body_at(bodies, co1.data().body());
+
+                    (mb.handle(), Point::from(com))
+                });
+
+            let outside: HashSet<BodyHandle> = rbs
+                .chain(mbs)
+                .filter(|body| !inside(body.1))
+                .map(|body| body.0)
+                .collect();
+            let mut escaped: Vec<_> = outside
+                .iter()
+                .filter(|handle| !self.escaped_bodies.contains(*handle))
+                .cloned()
+                .collect();
+            escaped.sort();
+            self.escaped_bodies = outside;
+            escaped
+        };
+
+        if escaped.is_empty() {
+            return;
+        }
+
+        match self.bounds {
+            Some((_, BoundsPolicy::Deactivate)) => {
+                for handle in &escaped {
+                    self.bodies.body_mut(*handle).deactivate();
+                }
+            }
+            Some((_, BoundsPolicy::Delete)) => self.remove_bodies(&escaped),
+            Some((_, BoundsPolicy::Callback(ref mut callback))) => {
+                for handle in &escaped {
+                    callback(*handle);
                 }
             }
+            None => {}
         }
     }
 
-    fn cleanup_colliders_with_deleted_parents(&mut self) {
-        let mut i = 0;
+    /// Execute one time step of the physics simulation with deterministic stepping enabled.
+    ///
+    /// Given the same initial state and the same sequence of external inputs (forces, impulses,
+    /// and modifications of the bodies between steps), worlds stepped with this method produce
+    /// bit-identical results at every step. The setting of `self.set_deterministic(...)` is left
+    /// unchanged.
+    pub fn step_deterministic(&mut self) {
+        let deterministic = self.deterministic;
+        self.deterministic = true;
+        self.step();
+        self.deterministic = deterministic;
+    }
 
-        while i < self.colliders_w_parent.len() {
-            let cid = self.colliders_w_parent[i];
-            let parent = self
-                .collider(cid)
-                .expect("Internal error: collider not present")
-                .data()
-                .body();
+    /// Advances the simulation by `frame_dt` using as many steps of duration `fixed_dt` as fit.
+    ///
+    /// The remaining time is accumulated and simulated by subsequent calls. At most
+    /// `self.max_substeps()` steps are executed by a single call: the time that would require
+    /// more steps is dropped so the simulation falls behind instead of taking longer and longer
+    /// to catch up. The timestep of `self.integration_parameters()` is left unchanged. Returns
+    /// the ratio between the remaining time and `fixed_dt`, in `[0, 1[`, that can be used to
+    /// interpolate the rendered positions between the last two steps.
+    pub fn step_fixed(&mut self, frame_dt: N, fixed_dt: N) -> N {
+        assert!(
+            fixed_dt > N::zero(),
+            "The fixed timestep must be strictly positive."
+        );
 
-            if !self.bodies.contains(parent) {
-                self.cworld.remove(&[cid]);
-                let _ = self.colliders_w_parent.swap_remove(i);
-            } else {
-                i += 1;
+        let dt = self.params.dt;
+        self.params.dt = fixed_dt;
+        self.time_accumulator += frame_dt;
+        let mut nsubsteps = 0;
+
+        while self.time_accumulator >= fixed_dt {
+            if self.max_substeps.map_or(false, |max| nsubsteps >= max) {
+                // Drop the time that cannot be simulated, keeping the interpolation ratio.
+                self.time_accumulator = self.time_accumulator % fixed_dt;
+                break;
             }
+
+            self.step();
+            self.time_accumulator -= fixed_dt;
+            nsubsteps += 1;
         }
+
+        self.params.dt = dt;
+        self.time_accumulator / fixed_dt
     }
 
-    fn cleanup_constraints_with_deleted_anchors(&mut self) {
-        let bodies = &mut self.bodies;
+    /// The maximum number of steps executed by a single call to `self.step_fixed(...)`, if any.
+    pub fn max_substeps(&self) -> Option<usize> {
+        self.max_substeps
+    }
 
-        self.constraints.retain(|_, constraint| {
-            let (b1, b2) = constraint.anchors();
-            let b1_exists = bodies.contains(b1);
-            let b2_exists = bodies.contains(b2);
+    /// Sets the maximum number of steps executed by a single call to `self.step_fixed(...)`.
+    ///
+    /// Set it to `None` to always simulate all the accumulated time (the default).
+    pub fn set_max_substeps(&mut self, max_substeps: Option<usize>) {
+        self.max_substeps = max_substeps
+    }
 
-            if !b1_exists {
-                if b2_exists {
-                    Self::activate_body_at(bodies, b2);
-                }
-            } else if !b2_exists {
-                Self::activate_body_at(bodies, b1);
+    /// The total simulated time, i.e., the sum of the timesteps of all the steps executed so far.
+    pub fn time(&self) -> N {
+        self.time
+    }
+
+    /// Captures the complete simulation state of this world.
+    ///
+    /// See `WorldSnapshot` for what the snapshot contains.
+    pub fn save_snapshot(&self) -> WorldSnapshot<N> {
+        let mut rigid_bodies: Vec<_> = self
+            .bodies
+            .rigid_bodies()
+            .map(|rb| RigidBodySnapshot {
+                handle: rb.handle(),
+                position: rb.position(),
+                velocity: rb.generalized_velocity().iter().cloned().collect(),
+            })
+            .collect();
+        rigid_bodies.sort_by_key(|rb| rb.handle);
+
+        let mut multibodies: Vec<_> = self
+            .bodies
+            .multibodies()
+            .map(|mb| MultibodySnapshot {
+                handle: mb.handle(),
+                coordinates: mb.generalized_position().iter().cloned().collect(),
+                velocity: mb.generalized_velocity_slice().to_vec(),
+            })
+            .collect();
+        multibodies.sort_by_key(|mb| mb.handle);
+
+        let constraints = self
+            .constraints
+            .iter()
+            .map(|(handle, constraint)| ConstraintSnapshot {
+                handle,
+                position_correction: constraint.position_correction(),
+                compliance: constraint.compliance(),
+                max_force: constraint.max_force(),
+                max_torque: constraint.max_torque(),
+                impulses: constraint.cached_impulses(),
+            })
+            .collect();
+
+        let mut colliders: Vec<_> = self
+            .cworld
+            .collision_objects()
+            .map(|collider| ColliderSnapshot {
+                uid: collider.handle().uid(),
+                body: collider.data().body(),
+                position_wrt_body: *collider.data().position_wrt_body(),
+                shape: ShapeDescriptor::from_shape(collider.shape().as_ref()),
+            })
+            .collect();
+        colliders.sort_by_key(|co| co.uid);
+
+        // NOTE: the pairs are already sorted by the cache.
+        let cached_contacts: Vec<_> = self
+            .contact_manifold_cache
+            .pairs()
+            .map(|(b1, b2, contacts)| (b1, b2, contacts.to_vec()))
+            .collect();
+
+        WorldSnapshot {
+            time: self.time,
+            last_dt: self.last_dt,
+            rigid_bodies,
+            multibodies,
+            constraints,
+            colliders,
+            cached_contacts,
+        }
+    }
+
+    /// Restores the simulation state captured by `self.save_snapshot()`.
+    ///
+    /// The snapshot must have been saved from this world, or from a world built identically, i.e.,
+    /// with the same bodies, joint constraints, and colliders added in the same order. Panics if
+    /// any of them is missing, or if a collider is attached to another body part or has another
+    /// shape. Bodies, constraints, and colliders added after the snapshot was saved are left
+    /// unchanged.
+    pub fn load_snapshot(&mut self, snapshot: WorldSnapshot<N>) {
+        for co in &snapshot.colliders {
+            let collider = self
+                .cworld
+                .collision_objects()
+                .find(|collider| collider.handle().uid() == co.uid)
+                .expect("World::load_snapshot: collider not found.");
+
+            assert!(
+                collider.data().body() == co.body
+                    && ShapeDescriptor::from_shape(collider.shape().as_ref()) == co.shape,
+                "World::load_snapshot: the colliders do not match the snapshot."
+            );
+        }
+
+        let mut handles = Vec::new();
+
+        for rb in snapshot.rigid_bodies {
+            let body = self
+                .bodies
+                .rigid_body_mut(rb.handle)
+                .expect("World::load_snapshot: rigid body not found.");
+            body.set_position(rb.position);
+
+            for (v, snap) in body.generalized_velocity_mut().iter_mut().zip(rb.velocity) {
+                *v = snap
             }
 
-            b1_exists && b2_exists
-        })
+            handles.push(rb.handle);
+        }
+
+        for mb in snapshot.multibodies {
+            let multibody = self
+                .bodies
+                .multibody_mut(mb.handle)
+                .expect("World::load_snapshot: multibody not found.");
+            assert_eq!(
+                mb.velocity.len(),
+                multibody.ndofs(),
+                "World::load_snapshot: the multibodies do not match the snapshot."
+            );
+            multibody.set_generalized_position(&mb.coordinates);
+            multibody.set_generalized_velocity(&mb.velocity);
+            handles.extend(multibody.links().map(|link| link.handle()));
+        }
+
+        for snap in snapshot.constraints {
+            let constraint = self
+                .constraints
+                .get_mut(snap.handle)
+                .expect("World::load_snapshot: constraint not found.");
+            constraint.set_position_correction(snap.position_correction);
+            constraint.set_compliance(snap.compliance);
+            constraint.set_max_force(snap.max_force);
+            constraint.set_max_torque(snap.max_torque);
+            constraint.set_cached_impulses(&snap.impulses);
+        }
+
+        self.contact_manifold_cache.clear();
+        for (b1, b2, contacts) in snapshot.cached_contacts {
+            self.contact_manifold_cache.update(b1, b2, &contacts);
+        }
+
+        for handle in &handles {
+            Self::activate_body_at(&mut self.bodies, *handle);
+        }
+
+        self.update_collider_positions(&handles);
+        self.time = snapshot.time;
+        self.last_dt = snapshot.last_dt;
     }
 
-    /// Add a rigid body to the world and retrieve its handle.
-    pub fn add_rigid_body(
-        &mut self,
-        position: Isometry<N>,
-        local_inertia: Inertia<N>,
-        local_center_of_mass: Point<N>,
-    ) -> BodyHandle {
-        self.bodies
-            .add_rigid_body(position, local_inertia, local_center_of_mass)
+    /// The constraints generated and solved during the last execution of `self.step()`.
+    ///
+    /// Use `self.last_constraint_groups()` to retrieve what generated each velocity constraint.
+    pub fn last_constraints(&self) -> &ConstraintSet<N> {
+        self.solver.constraints()
     }
 
-    /// Add a multibody link to the world and retrieve its handle.
+    /// The origin of each group of velocity constraints returned by `self.last_constraints()`.
+    pub fn last_constraint_groups(&self) -> &[ConstraintGroup] {
+        self.solver.constraint_groups()
+    }
+
+    /// The joint constraints that reached their maximum force or torque during the last execution of `self.step()`.
+    ///
+    /// See `JointConstraint::set_max_force` and `JointConstraint::set_max_torque`.
+    pub fn saturated_constraints(&self) -> &[ConstraintHandle] {
+        self.solver.saturated_constraints()
+    }
+
+    /// The joint constraints which limit state changed during the last execution of `self.step()`.
+    ///
+    /// Each entry is the handle of the joint constraint and its new limit state.
+    pub fn limit_state_transitions(&self) -> &[(ConstraintHandle, LimitState)] {
+        &self.limit_transitions[..]
+    }
+
+    fn update_limit_transitions(&mut self) {
+        self.limit_transitions.clear();
+
+        for (handle, constraint) in self.constraints.iter() {
+            let state = constraint.limit_state();
+            let previous = self
+                .limit_states
+                .get(&handle)
+                .cloned()
+                .unwrap_or(LimitState::Inactive);
+
+            if state != previous {
+                self.limit_transitions.push((handle, state));
+
+                if state == LimitState::Inactive {
+                    let _ = self.limit_states.remove(&handle);
+                } else {
+                    let _ = self.limit_states.insert(handle, state);
+                }
+            }
+        }
+    }
+
+    /// Sets the handler called at each timestep for each contact event between body parts.
+    pub fn set_contact_event_handler<H: ContactEventHandler + 'static>(&mut self, handler: H) {
+        self.contact_event_handler = Some(Box::new(handler))
+    }
+
+    /// Removes the contact event handler of this world, if any.
+    pub fn remove_contact_event_handler(&mut self) -> Option<Box<ContactEventHandler>> {
+        self.contact_event_handler.take()
+    }
+
+    /// Sets the handler called at each timestep to modify the contacts before they are solved.
+    pub fn set_contact_modification_handler<H: ContactModificationHandler<N> + 'static>(
+        &mut self,
+        handler: H,
+    ) {
+        self.contact_modification_handler = Some(Box::new(handler))
+    }
+
+    /// Removes the contact modification handler of this world, if any.
+    pub fn remove_contact_modification_handler(
+        &mut self,
+    ) -> Option<Box<ContactModificationHandler<N>>> {
+        self.contact_modification_handler.take()
+    }
+
+    /// Sets the function returning the friction and restitution coefficients of the contacts
+    /// between two body parts, instead of combining the materials of their colliders.
+    ///
+    /// The function is called once per contact manifold at each timestep, before the contact
+    /// modification handler, if any.
+    pub fn set_contact_material_resolver<F>(&mut self, resolver: F)
+    where
+        F: Fn(BodyHandle, BodyHandle) -> (N, N) + Send + Sync + 'static,
+    {
+        self.contact_material_resolver = Some(Box::new(resolver))
+    }
+
+    /// Removes the contact material resolver of this world, if any.
+    ///
+    /// The contact coefficients are then combined from the collider materials again.
+    pub fn remove_contact_material_resolver(&mut self) {
+        self.contact_material_resolver = None
+    }
+
+    // Converts the contact events between colliders into contact events between body parts.
+    fn update_body_contact_events(&mut self) {
+        self.body_contact_events.clear();
+
+        for event in self.cworld.contact_events().iter() {
+            let (coll1, coll2, started) = match *event {
+                ncollide::events::ContactEvent::Started(coll1, coll2) => (coll1, coll2, true),
+                ncollide::events::ContactEvent::Stopped(coll1, coll2) => (coll1, coll2, false),
+            };
+
+            let b1 = self.cworld.collision_object(coll1).map(|co| co.data().body());
+            let b2 = self.cworld.collision_object(coll2).map(|co| co.data().body());
+
+            if let (Some(b1), Some(b2)) = (b1, b2) {
+                if b1 != b2 {
+                    if started {
+                        self.body_contact_events.push(ContactEvent::Started(b1, b2));
+                    } else {
+                        self.body_contact_events.push(ContactEvent::Stopped(b1, b2));
+                    }
+                }
+            }
+        }
+
+        if self.deterministic {
+            // The collision world reports the contact events in an arbitrary order.
+            self.body_contact_events.sort_by_key(|event| match *event {
+                ContactEvent::Started(b1, b2) => (0, b1, b2),
+                ContactEvent::Stopped(b1, b2) => (1, b1, b2),
+            });
+        }
+
+        if let Some(ref mut handler) = self.contact_event_handler {
+            for event in &self.body_contact_events {
+                match *event {
+                    ContactEvent::Started(b1, b2) => handler.handle_contact_started(b1, b2),
+                    ContactEvent::Stopped(b1, b2) => handler.handle_contact_stopped(b1, b2),
+                }
+            }
+        }
+    }
+
+    /// Remove the specified bodies.
+    pub fn remove_bodies(&mut self, bodies: &[BodyHandle]) {
+        for body in bodies {
+            self.bodies.remove_body(*body);
+        }
+
+        self.cleanup_after_body_removal();
+    }
+
+    /// Remove several links of a single multibody.
+    ///
+    /// Panics if not all links belong to the same multibody.
+    pub fn remove_multibody_links(&mut self, links: &[BodyHandle]) {
+        self.bodies.remove_multibody_links(links);
+        self.cleanup_after_body_removal();
+    }
+
+    /// Remove a multibody link together with all its descendants.
+    ///
+    /// The colliders attached to the removed links are removed too, as well as the constraints
+    /// attached to them. The handles of the remaining links stay valid.
+    pub fn remove_multibody_link(&mut self, link: BodyHandle) {
+        self.bodies.remove_multibody_subtree(link);
+        self.cleanup_after_body_removal();
+    }
+
+    /// Detach a multibody link together with all its descendants from their multibody.
+    ///
+    /// The detached link becomes the root of a new independent multibody: its joint is replaced
+    /// by a free joint attached to the ground that keeps its current position and velocity. The
+    /// handles of all the links stay valid, and the colliders and constraints attached to the
+    /// detached links remain attached to them. Returns the handle of the new multibody, i.e.,
+    /// `link`. Panics if `link` is the root link of its multibody.
+    pub fn detach_multibody_link(&mut self, link: BodyHandle) -> BodyHandle {
+        let root = self
+            .multibody(link)
+            .expect("World::detach_multibody_link: the body must identify a multibody link.")
+            .handle();
+        self.bodies.detach_multibody_link(link);
+        self.activate_body(root);
+        self.activate_body(link);
+        link
+    }
+
+    fn cleanup_after_body_removal(&mut self) {
+        self.activate_bodies_touching_deleted_bodies();
+        self.cleanup_colliders_with_deleted_parents();
+        self.cleanup_constraints_with_deleted_anchors();
+
+        let bodies = &self.bodies;
+        self.island_solver_iterations
+            .retain(|body, _| bodies.contains(*body));
+        self.escaped_bodies.retain(|body| bodies.contains(*body));
+    }
+
+    // The root of the island of each active body, in the order of `self.active_bodies`.
+    fn island_roots(&self, manifolds: &[ColliderContactManifold<N>]) -> Vec<usize> {
+        let bodies = &self.bodies;
+        let ids: HashMap<BodyHandle, usize> = self
+            .active_bodies
+            .iter()
+            .enumerate()
+            .map(|(i, handle)| (*handle, i))
+            .collect();
+        let id = |part: BodyHandle| ids.get(&bodies.body(part).handle()).cloned();
+        let mut sets: Vec<_> = (0..self.active_bodies.len())
+            .map(UnionFindSet::new)
+            .collect();
+
+        {
+            let mut connect = |b1, b2| {
+                if let (Some(i1), Some(i2)) = (id(b1), id(b2)) {
+                    union_find::union(i1, i2, &mut sets)
+                }
+            };
+
+            for m in manifolds {
+                connect(m.body1(), m.body2());
+            }
+
+            for (_, c) in self.constraints.iter() {
+                if c.is_active(bodies) {
+                    let (b1, b2) = c.anchors();
+                    connect(b1, b2);
+                }
+            }
+        }
+
+        (0..self.active_bodies.len())
+            .map(|i| union_find::find(i, &mut sets))
+            .collect()
+    }
+
+    // Sorts the active bodies by island, keeping their relative order within each island, given
+    // the island roots computed by `self.island_roots(...)`. Returns the range of indices of
+    // the bodies of each island in `self.active_bodies`.
+    #[cfg(feature = "parallel")]
+    fn sort_active_bodies_by_island(&mut self, roots: &[usize]) -> Vec<Range<usize>> {
+        let mut sorted: Vec<_> = roots
+            .iter()
+            .cloned()
+            .zip(self.active_bodies.iter().cloned())
+            .collect();
+        // NOTE: the sort is stable so the result is deterministic.
+        sorted.sort_by_key(|&(root, _)| root);
+
+        let mut islands: Vec<Range<usize>> = Vec::new();
+        for (i, &(root, handle)) in sorted.iter().enumerate() {
+            self.active_bodies[i] = handle;
+
+            if i == 0 || sorted[i - 1].0 != root {
+                islands.push(i..i + 1);
+            } else if let Some(island) = islands.last_mut() {
+                island.end = i + 1;
+            }
+        }
+
+        islands
+    }
+
+    // The number of velocity iterations of each active body belonging to an island with a
+    // custom iteration count, given the island roots computed by `self.island_roots(...)`.
+    fn island_velocity_iterations(&self, roots: &[usize]) -> HashMap<BodyHandle, usize> {
+        let mut result = HashMap::new();
+
+        if self.island_solver_iterations.is_empty() {
+            return result;
+        }
+
+        let mut body_iterations = HashMap::new();
+        for (part, n) in &self.island_solver_iterations {
+            let iterations = body_iterations
+                .entry(self.bodies.body(*part).handle())
+                .or_insert(*n);
+            *iterations = (*iterations).max(*n);
+        }
+
+        let mut island_iterations = HashMap::new();
+        for (handle, root) in self.active_bodies.iter().zip(roots.iter()) {
+            if let Some(n) = body_iterations.get(handle) {
+                let iterations = island_iterations.entry(*root).or_insert(*n);
+                *iterations = (*iterations).max(*n);
+            }
+        }
+
+        for (handle, root) in self.active_bodies.iter().zip(roots.iter()) {
+            if let Some(n) = island_iterations.get(root) {
+                let _ = result.insert(*handle, *n);
+            }
+        }
+
+        result
+    }
+
+    fn activate_bodies_touching_deleted_bodies(&mut self) {
+        let bodies = &mut self.bodies;
+
+        for (co1, co2, detector) in self.cworld.contact_pairs() {
+            if detector.num_contacts() != 0 {
+                let b1_exists = bodies.contains(co1.data().body());
+                let b2_exists = bodies.contains(co2.data().body());
+
+                if !b1_exists {
+                    if b2_exists {
+                        Self::activate_body_at(bodies, co2.data().body());
+                    }
+                } else if !b2_exists {
+                    Self::activate_body_at(bodies, co1.data().body());
+                }
+            }
+        }
+    }
+
+    fn cleanup_colliders_with_deleted_parents(&mut self) {
+        let mut i = 0;
+
+        while i < self.colliders_w_parent.len() {
+            let cid = self.colliders_w_parent[i];
+            let parent = self
+                .collider(cid)
+                .expect("Internal error: collider not present")
+                .data()
+                .body();
+
+            if !self.bodies.contains(parent) {
+                self.cworld.remove(&[cid]);
+                let _ = self.colliders_w_parent.swap_remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn cleanup_constraints_with_deleted_anchors(&mut self) {
+        let bodies = &mut self.bodies;
+
+        self.constraints.retain(|_, constraint| {
+            let (b1, b2) = constraint.anchors();
+            let b1_exists = bodies.contains(b1);
+            let b2_exists = bodies.contains(b2);
+
+            if !b1_exists {
+                if b2_exists {
+                    Self::activate_body_at(bodies, b2);
+                }
+            } else if !b2_exists {
+                Self::activate_body_at(bodies, b1);
+            }
+
+            b1_exists && b2_exists
+        })
+    }
+
+    /// Add a rigid body to the world and retrieve its handle.
+    pub fn add_rigid_body(
+        &mut self,
+        position: Isometry<N>,
+        local_inertia: Inertia<N>,
+        local_center_of_mass: Point<N>,
+    ) -> BodyHandle {
+        self.bodies
+            .add_rigid_body(position, local_inertia, local_center_of_mass)
+    }
+
+    /// Add a multibody link to the world and retrieve its handle.
     pub fn add_multibody_link<J: Joint<N>>(
         &mut self,
         parent: BodyHandle,
@@ -456,6 +1627,148 @@ impl<N: Real> World<N> {
         self.add_collision_object(query, margin, shape, parent, to_parent, material)
     }
 
+    /// Add a collider made of several shapes to the world and retrieve its handle.
+    ///
+    /// Each shape is given with its position relative to the collider. A contact manifold is
+    /// generated for each shape in contact but all the contacts are applied to the same parent body.
+    pub fn add_compound_collider(
+        &mut self,
+        margin: N,
+        shapes: Vec<(Isometry<N>, ShapeHandle<N>)>,
+        parent: BodyHandle,
+        to_parent: Isometry<N>,
+        material: Material<N>,
+    ) -> ColliderHandle {
+        let shape = ShapeHandle::new(Compound::new(shapes));
+        self.add_collider(margin, shape, parent, to_parent, material)
+    }
+
+    /// Add a static terrain collider described by a height field to the world and retrieve its
+    /// handle.
+    ///
+    /// The collider is attached to the ground at the `position` given in world-space.
+    pub fn add_height_field(
+        &mut self,
+        margin: N,
+        height_field: &HeightField<N>,
+        position: Isometry<N>,
+        material: Material<N>,
+    ) -> ColliderHandle {
+        let shape = height_field.to_shape();
+        self.add_collider(margin, shape, BodyHandle::ground(), position, material)
+    }
+
+    /// Add a static collider described by a triangle mesh to the world and retrieve its handle.
+    ///
+    /// The collider is attached to the ground at the `position` given in world-space. The normals
+    /// of its contacts are smoothed as described by `TriMeshBody`.
+    #[cfg(feature = "dim3")]
+    pub fn add_tri_mesh_body(
+        &mut self,
+        margin: N,
+        tri_mesh: TriMeshBody<N>,
+        position: Isometry<N>,
+        material: Material<N>,
+    ) -> ColliderHandle {
+        let shape = tri_mesh.to_shape();
+        let handle = self.add_collider(margin, shape, BodyHandle::ground(), position, material);
+        let _ = self.tri_mesh_bodies.insert(handle, tri_mesh);
+
+        handle
+    }
+
+    /// The triangle mesh of the given collider, if it was added with `add_tri_mesh_body`.
+    #[cfg(feature = "dim3")]
+    pub fn tri_mesh_body(&self, handle: ColliderHandle) -> Option<&TriMeshBody<N>> {
+        self.tri_mesh_bodies.get(&handle)
+    }
+
+    // The normal computed by the narrow phase algorithm of the world for both colliders, if it
+    // does not rely on the contact manifold generators of ncollide.
+    fn refined_contact_normal(
+        &self,
+        coll1: &Collider<N>,
+        coll2: &Collider<N>,
+    ) -> Option<Unit<Vector<N>>> {
+        match self.narrow_phase_algorithm {
+            NarrowPhaseAlgorithm::Default => None,
+            NarrowPhaseAlgorithm::GjkEpa {
+                max_iterations,
+                tolerance,
+            } => {
+                let g1 = coll1.shape().as_support_map()?;
+                let g2 = coll2.shape().as_support_map()?;
+                let contact = gjk_epa_contact(
+                    coll1.position(),
+                    g1,
+                    coll2.position(),
+                    g2,
+                    N::max_value(),
+                    max_iterations,
+                    tolerance,
+                )?;
+
+                Some(contact.normal)
+            }
+        }
+    }
+
+    // Whether one of the colliders is a triangle mesh with smoothed contact normals.
+    #[cfg(feature = "dim3")]
+    fn has_smoothed_normals(&self, coll1: &Collider<N>, coll2: &Collider<N>) -> bool {
+        self.tri_mesh_bodies.contains_key(&coll1.handle())
+            || self.tri_mesh_bodies.contains_key(&coll2.handle())
+    }
+
+    #[cfg(feature = "dim2")]
+    fn has_smoothed_normals(&self, _: &Collider<N>, _: &Collider<N>) -> bool {
+        false
+    }
+
+    // The smoothed normal of `contact` on the triangle mesh involved in `manifold`, oriented
+    // from the first collider toward the second one.
+    #[cfg(feature = "dim3")]
+    fn smoothed_contact_normal(
+        &self,
+        manifold: &ColliderContactManifold<N>,
+        contact: &TrackedContact<N>,
+    ) -> Option<Unit<Vector<N>>> {
+        let smooth = |collider: &Collider<N>, point: &Point<N>, face: usize| {
+            let tri_mesh = self.tri_mesh_bodies.get(&collider.handle())?;
+            let local_point = collider.position().inverse() * point;
+            let local_normal = tri_mesh.smoothed_normal(face, &local_point);
+
+            Some(Unit::new_unchecked(collider.position() * local_normal.into_inner()))
+        };
+
+        let normal = smooth(
+            manifold.collider1,
+            &contact.contact.world1,
+            manifold.manifold.subshape_id1(),
+        ).or_else(|| {
+            smooth(
+                manifold.collider2,
+                &contact.contact.world2,
+                manifold.manifold.subshape_id2(),
+            )
+        })?;
+
+        if normal.dot(&contact.contact.normal) < N::zero() {
+            Some(-normal)
+        } else {
+            Some(normal)
+        }
+    }
+
+    #[cfg(feature = "dim2")]
+    fn smoothed_contact_normal(
+        &self,
+        _: &ColliderContactManifold<N>,
+        _: &TrackedContact<N>,
+    ) -> Option<Unit<Vector<N>>> {
+        None
+    }
+
     /// Add a sensor to the world and retrieve its handle.
     pub fn add_sensor(
         &mut self,
@@ -464,14 +1777,22 @@ impl<N: Real> World<N> {
         to_parent: Isometry<N>,
     ) -> SensorHandle {
         let query = GeometricQueryType::Proximity(self.prediction * na::convert(0.5f64));
-        self.add_collision_object(
+        let handle = self.add_collision_object(
             query,
             N::zero(),
             shape,
             parent,
             to_parent,
             Material::default(),
-        )
+        );
+
+        self.cworld
+            .collision_object_mut(handle)
+            .expect("Internal error: sensor not found.")
+            .data_mut()
+            .set_sensor(true);
+
+        handle
     }
 
     fn add_collision_object(
@@ -556,6 +1877,55 @@ impl<N: Real> World<N> {
         self.bodies.multibody_link_mut(handle)
     }
 
+    /// Sets the position of the root of the specified multibody, which must be attached by a
+    /// `FreeJoint`, and the velocity of its root if `velocity` is not `None`.
+    ///
+    /// The multibody is activated, and the positions of its links and of their colliders are
+    /// updated immediately, without waiting for the next step. Panics if `handle` is not a link
+    /// of a multibody of this world, or if the multibody root is not attached by a free joint.
+    pub fn set_multibody_root_position(
+        &mut self,
+        handle: BodyHandle,
+        position: Isometry<N>,
+        velocity: Option<Velocity<N>>,
+    ) {
+        let links: Vec<BodyHandle> = {
+            let mb = self
+                .bodies
+                .multibody_mut(handle)
+                .expect("The handle does not correspond to a multibody of this world.");
+            mb.set_root_position(position);
+            if let Some(velocity) = velocity {
+                mb.set_root_velocity(velocity);
+            }
+            mb.activate();
+            mb.links().map(|link| link.handle()).collect()
+        };
+
+        self.update_collider_positions(&links);
+    }
+
+    // Moves the colliders attached to the given body parts to the current body part positions.
+    fn update_collider_positions(&mut self, handles: &[BodyHandle]) {
+        for collider_id in &self.colliders_w_parent {
+            let new_pos = {
+                let collider = self
+                    .cworld
+                    .collision_object(*collider_id)
+                    .expect("Internal error: collider not found.");
+
+                if !handles.contains(&collider.data().body()) {
+                    continue;
+                }
+
+                let body = self.bodies.body_part(collider.data().body());
+                body.position() * collider.data().position_wrt_body()
+            };
+
+            self.cworld.set_position(*collider_id, new_pos);
+        }
+    }
+
     /// Get a reference to the specified rigid body.
     ///
     /// Returns `None` if the handle does not correspond to a rigid body in this world.
@@ -579,30 +1949,435 @@ impl<N: Real> World<N> {
         &mut self.cworld
     }
 
-    /// Get a mutable reference to the specified collider.
+    /// Get a reference to the specified collider.
     ///
     /// Returns `None` if the handle does not correspond to a collider in this world.
     pub fn collider(&self, handle: ColliderHandle) -> Option<&Collider<N>> {
         self.cworld.collision_object(handle)
     }
 
-    /// Gets the handle of the parent body the specified collider is attached to.
-    pub fn collider_body_handle(&self, handle: ColliderHandle) -> Option<BodyHandle> {
+    /// An iterator through the handles of all the colliders attached to the given body part.
+    ///
+    /// This includes its sensors.
+    pub fn body_part_colliders<'a>(
+        &'a self,
+        handle: BodyHandle,
+    ) -> impl Iterator<Item = ColliderHandle> + 'a {
         self.cworld
-            .collision_object(handle)
-            .map(|co| co.data().body())
+            .collision_objects()
+            .filter(move |co| co.data().body() == handle)
+            .map(|co| co.handle())
     }
 
-    /// An iterator through all the colliders on this collision world.
-    pub fn colliders(&self) -> Colliders<N> {
-        self.cworld.collision_objects()
+    /// Sets the material of the specified collider.
+    ///
+    /// Returns `false` if the handle does not correspond to a collider in this world.
+    pub fn set_collider_material(&mut self, handle: ColliderHandle, material: Material<N>) -> bool {
+        match self.cworld.collision_object_mut(handle) {
+            Some(collider) => {
+                collider.data_mut().set_material(material);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets the collision groups of the specified collider only.
+    ///
+    /// Use `self.set_collision_groups` to modify all the colliders of a body part at once.
+    pub fn set_collider_collision_groups(
+        &mut self,
+        handle: ColliderHandle,
+        groups: CollisionGroups,
+    ) {
+        self.cworld.set_collision_groups(handle, groups);
+    }
+
+    /// Gets the handle of the parent body the specified collider is attached to.
+    pub fn collider_body_handle(&self, handle: ColliderHandle) -> Option<BodyHandle> {
+        self.cworld
+            .collision_object(handle)
+            .map(|co| co.data().body())
+    }
+
+    /// Sets the collision groups of all the colliders attached to the given body part.
+    pub fn set_collision_groups(&mut self, handle: BodyHandle, groups: CollisionGroups) {
+        for collider in self.body_part_collider_handles(handle) {
+            self.cworld.set_collision_groups(collider, groups);
+        }
     }
 
-    /// An iterator through all the contact events generated during the last execution of `self.step()`.
-    pub fn contact_events(&self) -> &ContactEvents {
+    /// Sets the groups all the colliders attached to the given body part are member of.
+    pub fn set_collision_membership(&mut self, handle: BodyHandle, groups: &[usize]) {
+        self.modify_collision_groups(handle, |g| g.set_membership(groups))
+    }
+
+    /// Sets the groups all the colliders attached to the given body part can interact with.
+    pub fn set_collision_whitelist(&mut self, handle: BodyHandle, groups: &[usize]) {
+        self.modify_collision_groups(handle, |g| g.set_whitelist(groups))
+    }
+
+    /// Sets the groups all the colliders attached to the given body part cannot interact with.
+    pub fn set_collision_blacklist(&mut self, handle: BodyHandle, groups: &[usize]) {
+        self.modify_collision_groups(handle, |g| g.set_blacklist(groups))
+    }
+
+    fn modify_collision_groups<F: Fn(&mut CollisionGroups)>(&mut self, handle: BodyHandle, f: F) {
+        for collider in self.body_part_collider_handles(handle) {
+            let mut groups = *self
+                .cworld
+                .collision_object(collider)
+                .expect("Internal error: collider not found.")
+                .collision_groups();
+            f(&mut groups);
+            self.cworld.set_collision_groups(collider, groups);
+        }
+    }
+
+    fn body_part_collider_handles(&self, handle: BodyHandle) -> Vec<ColliderHandle> {
+        self.body_part_colliders(handle).collect()
+    }
+
+    /// Sets a user-defined predicate that prevents two body parts from interacting when it returns `false`.
+    ///
+    /// This predicate is evaluated by the broad phase so body parts rejected by it never generate any
+    /// contact nor proximity event. It replaces the predicate previously set, if any.
+    pub fn set_body_pair_filter<F>(&mut self, filter: F)
+    where
+        F: Fn(BodyHandle, BodyHandle) -> bool + Send + Sync + 'static,
+    {
+        self.remove_body_pair_filter();
+        self.cworld.register_broad_phase_pair_filter(
+            BODY_PAIR_FILTER_NAME,
+            BodyPairFilter {
+                filter: Box::new(filter),
+            },
+        );
+    }
+
+    /// Removes the user-defined predicate set by `self.set_body_pair_filter(...)`, if any.
+    pub fn remove_body_pair_filter(&mut self) {
+        self.cworld
+            .unregister_broad_phase_pair_filter(BODY_PAIR_FILTER_NAME);
+    }
+
+    /// An iterator through all the colliders on this collision world.
+    pub fn colliders(&self) -> Colliders<N> {
+        self.cworld.collision_objects()
+    }
+
+    /// The contact events between body parts generated during the last execution of `self.step()`.
+    pub fn contact_events(&self) -> &[ContactEvent] {
+        &self.body_contact_events[..]
+    }
+
+    /// Removes and returns the contact events between body parts generated during the last
+    /// execution of `self.step()`.
+    pub fn drain_contact_events(&mut self) -> Drain<ContactEvent> {
+        self.body_contact_events.drain(..)
+    }
+
+    /// The contact events between colliders generated during the last execution of `self.step()`.
+    pub fn collider_contact_events(&self) -> &ContactEvents {
         self.cworld.contact_events()
     }
 
+    /// An iterator through all the contact manifolds involving the specified body part.
+    ///
+    /// Each item is the handle of the other body part in contact and the contact manifold
+    /// between their colliders. The contact normals of the manifold may point toward or away
+    /// from the specified body part depending on the order the colliders were paired. Use
+    /// `self.contact_impulse(contact)` to retrieve the normal impulse applied at each contact
+    /// during the last execution of `self.step()`.
+    pub fn contacts_with<'a>(
+        &'a self,
+        handle: BodyHandle,
+    ) -> impl Iterator<Item = (BodyHandle, &'a ContactManifold<N>)> + 'a {
+        self.cworld
+            .contact_manifolds()
+            .filter_map(move |(coll1, coll2, manifold)| {
+                if coll1.data().body() == handle {
+                    Some((coll2.data().body(), manifold))
+                } else if coll2.data().body() == handle {
+                    Some((coll1.data().body(), manifold))
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// A contact manifold with at least one contact between the colliders of the body parts `a`
+    /// and `b`, as computed by the narrow phase during the last execution of `self.step()`.
+    ///
+    /// If several colliders of those body parts are in contact, any of their manifolds may be
+    /// returned. The contact normals may point toward or away from `a` depending on the order
+    /// the colliders were paired.
+    pub fn contact_manifold(&self, a: BodyHandle, b: BodyHandle) -> Option<&ContactManifold<N>> {
+        self.contacts_with(a)
+            .find(|&(other, manifold)| other == b && manifold.len() != 0)
+            .map(|(_, manifold)| manifold)
+    }
+
+    /// Whether the colliders of the body parts `a` and `b` were found in contact by the narrow
+    /// phase during the last execution of `self.step()`.
+    pub fn in_contact(&self, a: BodyHandle, b: BodyHandle) -> bool {
+        self.contact_manifold(a, b).is_some()
+    }
+
+    /// The normal impulse applied at the given contact during the last execution of `self.step()`.
+    ///
+    /// Divide this impulse by the timestep to obtain the contact force magnitude.
+    /// Returns `None` if the contact was not handled by the solver, or if the contact
+    /// model does not keep track of impulses.
+    pub fn contact_impulse(&self, contact: &TrackedContact<N>) -> Option<N> {
+        self.solver.contact_model().contact_impulse(contact.id)
+    }
+
+    /// The first collider intersected by the given ray with a time of impact smaller than `max_toi`.
+    ///
+    /// Sensors are ignored.
+    pub fn ray_cast(
+        &self,
+        ray: &Ray<N>,
+        max_toi: N,
+        groups: &CollisionGroups,
+    ) -> Option<RayCastResult<N>> {
+        let mut result: Option<RayCastResult<N>> = None;
+
+        for hit in self.ray_cast_unsorted(ray, max_toi, groups) {
+            if result.map_or(true, |best| hit.toi < best.toi) {
+                result = Some(hit)
+            }
+        }
+
+        result
+    }
+
+    /// All the colliders intersected by the given ray with a time of impact smaller than `max_toi`.
+    ///
+    /// The results are sorted by increasing time of impact. Sensors are ignored.
+    pub fn ray_cast_all(
+        &self,
+        ray: &Ray<N>,
+        max_toi: N,
+        groups: &CollisionGroups,
+    ) -> Vec<RayCastResult<N>> {
+        let mut result: Vec<_> = self.ray_cast_unsorted(ray, max_toi, groups).collect();
+        // NOTE: degenerate shapes may yield a NaN time of impact, so do not assume a total order.
+        result.sort_by(|a, b| a.toi.partial_cmp(&b.toi).unwrap_or(Ordering::Equal));
+        result
+    }
+
+    fn ray_cast_unsorted<'a>(
+        &'a self,
+        ray: &'a Ray<N>,
+        max_toi: N,
+        groups: &'a CollisionGroups,
+    ) -> impl Iterator<Item = RayCastResult<N>> + 'a {
+        self.cworld
+            .interferences_with_ray(ray, groups)
+            .filter_map(move |(collider, inter)| {
+                if collider.data().is_sensor() || inter.toi > max_toi {
+                    None
+                } else {
+                    Some(RayCastResult {
+                        body: collider.data().body(),
+                        collider: collider.handle(),
+                        toi: inter.toi,
+                        normal: inter.normal,
+                    })
+                }
+            })
+    }
+
+    /// The body parts with at least one collider containing the given world-space point.
+    ///
+    /// Each body part appears only once, even if several of its colliders contain the point.
+    /// Sensors are ignored.
+    pub fn bodies_containing_point(
+        &self,
+        point: &Point<N>,
+        groups: &CollisionGroups,
+    ) -> Vec<BodyHandle> {
+        let mut result = Vec::new();
+
+        for collider in self.colliders_containing_point(point, groups) {
+            let body = collider.data().body();
+
+            if !result.contains(&body) {
+                result.push(body);
+            }
+        }
+
+        result
+    }
+
+    /// The body part of the first collider found to contain the given world-space point.
+    ///
+    /// Sensors are ignored.
+    pub fn first_body_containing_point(
+        &self,
+        point: &Point<N>,
+        groups: &CollisionGroups,
+    ) -> Option<BodyHandle> {
+        self.colliders_containing_point(point, groups)
+            .next()
+            .map(|collider| collider.data().body())
+    }
+
+    /// The first collider hit by `shape` when translated by `motion` from the position `start`.
+    ///
+    /// Colliders already intersecting the shape at `start` are reported with a time of impact
+    /// equal to zero. Sensors are ignored.
+    pub fn convex_sweep(
+        &self,
+        shape: &Shape<N>,
+        start: Isometry<N>,
+        motion: Vector<N>,
+        groups: &CollisionGroups,
+    ) -> Option<SweepResult<N>> {
+        self.convex_sweep_with_filter(shape, start, motion, groups, |_| true)
+    }
+
+    // Same as `convex_sweep` but ignores the colliders for which `filter` returns `false`.
+    pub(crate) fn convex_sweep_with_filter<F: Fn(&Collider<N>) -> bool>(
+        &self,
+        shape: &Shape<N>,
+        start: Isometry<N>,
+        motion: Vector<N>,
+        groups: &CollisionGroups,
+        filter: F,
+    ) -> Option<SweepResult<N>> {
+        let mut end = start;
+        end.translation.vector += motion;
+        let swept_aabb = shape.aabb(&start).merged(&shape.aabb(&end));
+        let mut result: Option<(&Collider<N>, N)> = None;
+
+        for collider in self.cworld.interferences_with_aabb(&swept_aabb, groups) {
+            if collider.data().is_sensor() || !filter(collider) {
+                continue;
+            }
+
+            let mut toi = query::time_of_impact(
+                &start,
+                &motion,
+                shape,
+                collider.position(),
+                &Vector::zeros(),
+                collider.shape().as_ref(),
+            );
+
+            // NOTE: ncollide may report a zero time of impact for a motion oblique to a
+            // nearby obstacle, so those are checked by conservative advancement.
+            if toi == Some(N::zero()) {
+                toi = conservative_time_of_impact(
+                    &start,
+                    &motion,
+                    shape,
+                    collider.position(),
+                    collider.shape().as_ref(),
+                );
+            }
+
+            if let Some(toi) = toi {
+                if toi <= N::one() && result.map_or(true, |best| toi < best.1) {
+                    result = Some((collider, toi))
+                }
+            }
+        }
+
+        result.map(|(collider, toi)| {
+            let mut hit = start;
+            hit.translation.vector += motion * toi;
+
+            // The normal is retrieved from a contact query at the time of impact.
+            let normal = query::contact(
+                &hit,
+                shape,
+                collider.position(),
+                collider.shape().as_ref(),
+                self.prediction,
+            ).map_or_else(
+                || {
+                    motion
+                        .try_normalize(N::default_epsilon())
+                        .map_or(Vector::zeros(), |dir| -dir)
+                },
+                |contact| -contact.normal.into_inner(),
+            );
+
+            SweepResult {
+                body: collider.data().body(),
+                collider: collider.handle(),
+                toi,
+                normal,
+            }
+        })
+    }
+
+    /// The body parts with at least one collider with an AABB overlapping the given world-space AABB.
+    ///
+    /// This relies only on the broad phase so it does not check whether the collider shapes
+    /// themselves actually intersect `aabb`. Each body part appears only once. Sensors are ignored.
+    pub fn bodies_overlapping_aabb(&self, aabb: &AABB<N>) -> Vec<BodyHandle> {
+        let mut result = Vec::new();
+        let groups = CollisionGroups::new();
+
+        for collider in self.cworld.interferences_with_aabb(aabb, &groups) {
+            let body = collider.data().body();
+
+            if !collider.data().is_sensor() && !result.contains(&body) {
+                result.push(body);
+            }
+        }
+
+        result
+    }
+
+    fn colliders_containing_point<'a>(
+        &'a self,
+        point: &'a Point<N>,
+        groups: &'a CollisionGroups,
+    ) -> impl Iterator<Item = &'a Collider<N>> + 'a {
+        self.cworld
+            .interferences_with_point(point, groups)
+            .filter(|collider| !collider.data().is_sensor())
+    }
+
+    /// The body parts with at least one collider intersecting the given sensor.
+    ///
+    /// This is computed from the results of the narrow phase of the last execution of `self.step()`.
+    /// Each body part appears only once, even if several of its colliders intersect the sensor.
+    pub fn sensor_intersections(&self, sensor: SensorHandle) -> Vec<BodyHandle> {
+        let mut result = Vec::new();
+        let sensor_body = match self.cworld.collision_object(sensor) {
+            Some(sensor) => sensor.data().body(),
+            None => return result,
+        };
+
+        for (co1, co2, detector) in self.cworld.proximity_pairs() {
+            if detector.proximity() != Proximity::Intersecting {
+                continue;
+            }
+
+            let other = if co1.handle() == sensor {
+                co2
+            } else if co2.handle() == sensor {
+                co1
+            } else {
+                continue;
+            };
+
+            let body = other.data().body();
+
+            if body != sensor_body && !result.contains(&body) {
+                result.push(body);
+            }
+        }
+
+        result
+    }
+
     /// An iterator through all the proximity events generated during the last execution of `self.step()`.
     pub fn proximity_events(&self) -> &ProximityEvents {
         self.cworld.proximity_events()
@@ -623,12 +2398,839 @@ impl<N: Real> BroadPhasePairFilter<N, ColliderData<N>> for BodyStatusCollisionFi
     }
 }
 
+const BODY_PAIR_FILTER_NAME: &str = "__nphysics_internal_body_pair_filter";
+
+struct BodyPairFilter {
+    filter: Box<Fn(BodyHandle, BodyHandle) -> bool + Send + Sync>,
+}
+
+impl<N: Real> BroadPhasePairFilter<N, ColliderData<N>> for BodyPairFilter {
+    fn is_pair_valid(&self, b1: &Collider<N>, b2: &Collider<N>) -> bool {
+        (self.filter)(b1.data().body(), b2.data().body())
+    }
+}
+
+// The time of impact of `shape1` translated by `motion` with the static `shape2`.
+//
+// The shape is advanced by its distance to the obstacle until they touch, which never overshoots
+// the actual time of impact.
+fn conservative_time_of_impact<N: Real>(
+    m1: &Isometry<N>,
+    motion: &Vector<N>,
+    shape1: &Shape<N>,
+    m2: &Isometry<N>,
+    shape2: &Shape<N>,
+) -> Option<N> {
+    let max_iter = 64;
+    let tolerance = N::default_epsilon().sqrt();
+    let speed = motion.norm();
+    let mut toi = N::zero();
+
+    if speed.is_zero() {
+        return None;
+    }
+
+    for _ in 0..max_iter {
+        let mut m = *m1;
+        m.translation.vector += motion * toi;
+        let distance = query::distance(&m, shape1, m2, shape2);
+
+        if distance <= tolerance {
+            return Some(toi);
+        }
+
+        toi += distance / speed;
+
+        if toi > N::one() {
+            return None;
+        }
+    }
+
+    Some(toi)
+}
+
 #[cfg(test)]
 mod test {
+    use na;
+    use ncollide::shape::{Ball, Cuboid, ShapeHandle};
+    use ncollide::world::CollisionGroups;
+
+    use math::{Isometry, Point, Vector};
+    use object::{BodyHandle, Material};
+    use volumetric::Volumetric;
     use world::World;
 
     #[test]
     fn world_is_send_sync() {
         let _ = Box::new(World::<f32>::new()) as Box<Send + Sync>;
     }
+
+    #[test]
+    fn dynamic_body_passes_through_sensor() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let sensor_shape = ShapeHandle::new(Cuboid::new(Vector::repeat(1.0)));
+        let _ = world.add_sensor(sensor_shape, BodyHandle::ground(), Isometry::identity());
+
+        let ball = ShapeHandle::new(Ball::new(0.1));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * 2.0, na::zero()),
+            ball.inertia(1.0),
+            ball.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, ball, body, Isometry::identity(), Material::default());
+
+        let mut nproximity_events = 0;
+
+        for _ in 0..120 {
+            world.step();
+
+            for _ in world.proximity_events() {
+                nproximity_events += 1;
+            }
+        }
+
+        let y = world.rigid_body(body).unwrap().position().translation.vector.y;
+        assert!(y < -1.0, "The body should have fallen through the sensor.");
+        assert!(nproximity_events >= 2, "The sensor should have detected the body.");
+    }
+
+    #[test]
+    fn excluded_collision_groups_do_not_generate_contacts() {
+        let mut world = World::<f32>::new();
+        let ball = ShapeHandle::new(Ball::new(0.5));
+        let mut bodies = Vec::new();
+
+        for _ in 0..2 {
+            let body = world.add_rigid_body(
+                Isometry::identity(),
+                ball.inertia(1.0),
+                ball.center_of_mass(),
+            );
+            let _ = world.add_collider(
+                0.01,
+                ball.clone(),
+                body,
+                Isometry::identity(),
+                Material::default(),
+            );
+            bodies.push(body);
+        }
+
+        let mut groups1 = CollisionGroups::new();
+        groups1.set_membership(&[1]);
+        groups1.set_blacklist(&[2]);
+        let mut groups2 = CollisionGroups::new();
+        groups2.set_membership(&[2]);
+
+        world.set_collision_groups(bodies[0], groups1);
+        world.set_collision_groups(bodies[1], groups2);
+
+        for _ in 0..10 {
+            world.step();
+            assert_eq!(world.contacts_with(bodies[0]).count(), 0);
+            assert!(world.contact_events().is_empty());
+        }
+    }
+
+    #[test]
+    fn point_containment_finds_the_enclosing_body() {
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::x() * 2.0, na::zero()),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, cube, body, Isometry::identity(), Material::default());
+        world.step();
+
+        let groups = CollisionGroups::new();
+        let inside = Point::origin() + Vector::x() * 2.2;
+        let outside = Point::origin();
+
+        assert_eq!(world.bodies_containing_point(&inside, &groups), vec![body]);
+        assert_eq!(world.first_body_containing_point(&inside, &groups), Some(body));
+        assert!(world.bodies_containing_point(&outside, &groups).is_empty());
+    }
+
+    #[test]
+    fn convex_sweep_hits_the_obstacle() {
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::x() * 3.0, na::zero()),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_collider(0.0, cube, body, Isometry::identity(), Material::default());
+        world.step();
+
+        let ball = Ball::new(0.5f32);
+        let groups = CollisionGroups::new();
+        let hit = world
+            .convex_sweep(&ball, Isometry::identity(), Vector::x() * 4.0, &groups)
+            .expect("The swept ball should hit the cube.");
+
+        assert_eq!(hit.body, body);
+        assert!((hit.toi - 0.5).abs() < 1.0e-3, "TOI: {}", hit.toi);
+        assert!((hit.normal + Vector::x()).norm() < 1.0e-3);
+        assert!(world
+            .convex_sweep(&ball, Isometry::identity(), -Vector::x() * 4.0, &groups)
+            .is_none());
+    }
+
+    #[test]
+    fn body_part_colliders_enumerate_all_the_colliders_of_a_body() {
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = world.add_rigid_body(
+            Isometry::identity(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let other = world.add_rigid_body(
+            Isometry::new(Vector::x() * 5.0, na::zero()),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+
+        let collider1 = world.add_collider(
+            0.01,
+            cube.clone(),
+            body,
+            Isometry::new(Vector::x() * -0.5, na::zero()),
+            Material::default(),
+        );
+        let collider2 = world.add_collider(
+            0.01,
+            cube.clone(),
+            body,
+            Isometry::new(Vector::x() * 0.5, na::zero()),
+            Material::default(),
+        );
+        let other_collider =
+            world.add_collider(0.01, cube, other, Isometry::identity(), Material::default());
+
+        let colliders: Vec<_> = world.body_part_colliders(body).collect();
+        assert_eq!(colliders.len(), 2);
+        assert!(colliders.contains(&collider1) && colliders.contains(&collider2));
+        let other_colliders: Vec<_> = world.body_part_colliders(other).collect();
+        assert!(other_colliders == [other_collider]);
+
+        // Each collider can then be modified independently.
+        assert!(world.set_collider_material(collider2, Material::new(0.0, 0.9)));
+        let friction = |world: &World<f32>, handle| {
+            world.collider(handle).unwrap().data().material().friction
+        };
+        assert_relative_eq!(friction(&world, collider2), 0.9);
+        assert_relative_eq!(friction(&world, collider1), Material::<f32>::default().friction);
+
+        let mut groups = CollisionGroups::new();
+        groups.set_membership(&[3]);
+        world.set_collider_collision_groups(collider1, groups);
+        assert!(!world.collider(collider1).unwrap().collision_groups().is_member_of(0));
+        assert!(world.collider(collider2).unwrap().collision_groups().is_member_of(0));
+    }
+
+    #[test]
+    fn in_contact_flips_when_two_boxes_collide() {
+        let mut world = World::<f32>::new();
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+
+        let add_box = |world: &mut World<f32>, x: f32| {
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::x() * x, na::zero()),
+                cube.inertia(1.0),
+                cube.center_of_mass(),
+            );
+            let _ = world.add_collider(
+                0.01,
+                cube.clone(),
+                body,
+                Isometry::identity(),
+                Material::default(),
+            );
+            body
+        };
+        let left = add_box(&mut world, -2.0);
+        let right = add_box(&mut world, 2.0);
+        let far = add_box(&mut world, 10.0);
+        world
+            .rigid_body_mut(right)
+            .unwrap()
+            .set_linear_velocity(Vector::x() * -3.0);
+
+        // The gap of 3 between both boxes is closed after one second.
+        for _ in 0..50 {
+            world.step();
+            assert!(!world.in_contact(left, right));
+            assert!(world.contact_manifold(left, right).is_none());
+        }
+
+        for _ in 0..20 {
+            world.step();
+        }
+
+        assert!(world.in_contact(left, right));
+        assert!(world.in_contact(right, left));
+        assert!(world.contact_manifold(left, right).unwrap().len() != 0);
+        assert!(!world.in_contact(left, far));
+        assert!(!world.in_contact(right, far));
+    }
+
+    #[test]
+    fn contact_material_resolver_overrides_the_collider_materials() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.set_contact_material_resolver(|_, _| (0.0, 0.0));
+
+        let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = world.add_collider(
+            0.01,
+            floor,
+            BodyHandle::ground(),
+            Isometry::new(Vector::y() * -10.0, na::zero()),
+            Material::new(0.0, 1.0),
+        );
+
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * 0.5, na::zero()),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, cube, body, Isometry::identity(), Material::new(0.0, 1.0));
+        world
+            .rigid_body_mut(body)
+            .unwrap()
+            .set_linear_velocity(Vector::x() * 3.0);
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let vel = world.rigid_body(body).unwrap().velocity().linear;
+        assert!(vel.x > 2.9, "Velocity: {}", vel);
+    }
+
+    #[test]
+    fn timestep_change_does_not_jolt_resting_bodies() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = world.add_collider(
+            0.01,
+            floor,
+            BodyHandle::ground(),
+            Isometry::new(Vector::y() * -10.0, na::zero()),
+            Material::default(),
+        );
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * 0.5, na::zero()),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, cuboid, body, Isometry::identity(), Material::default());
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        for i in 0..120 {
+            if i % 10 == 0 {
+                let dt = if (i / 10) % 2 == 0 { 1.0 / 240.0 } else { 1.0 / 30.0 };
+                world.set_timestep(dt);
+            }
+
+            world.step();
+            let vel = world.rigid_body(body).unwrap().velocity().linear;
+            assert!(vel.norm() < 1.0e-2, "Velocity spike at step {}: {:?}", i, vel);
+        }
+    }
+
+    #[test]
+    fn step_fixed_simulates_the_elapsed_time_with_fixed_substeps() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ball = ShapeHandle::new(Ball::new(0.5));
+        let body =
+            world.add_rigid_body(Isometry::identity(), ball.inertia(1.0), ball.center_of_mass());
+        let _ = world.add_collider(0.01, ball, body, Isometry::identity(), Material::default());
+
+        let fixed_dt = 1.0 / 120.0;
+        let frame_dts = [0.016, 0.033, 0.001, 0.05, 0.0, 0.009, 0.1, 0.0125];
+        let mut elapsed = 0.0;
+
+        for _ in 0..10 {
+            for frame_dt in frame_dts.iter() {
+                elapsed += *frame_dt;
+                let alpha = world.step_fixed(*frame_dt, fixed_dt);
+                assert!(alpha >= 0.0 && alpha < 1.0, "Invalid interpolation factor: {}", alpha);
+                assert!(elapsed - world.time() >= -1.0e-4);
+                assert!(elapsed - world.time() < fixed_dt + 1.0e-4);
+            }
+        }
+
+        // The timestep used by `step` is left unchanged.
+        assert_relative_eq!(world.integration_parameters().dt, 1.0 / 60.0);
+    }
+
+    #[test]
+    fn step_fixed_drops_the_time_exceeding_the_maximum_number_of_substeps() {
+        let mut world = World::<f32>::new();
+        world.set_max_substeps(Some(4));
+        assert_eq!(world.max_substeps(), Some(4));
+
+        // A long frame would need 12 substeps.
+        let fixed_dt = 1.0 / 120.0;
+        let alpha = world.step_fixed(0.1 + fixed_dt * 0.5, fixed_dt);
+        assert!((world.time() - 4.0 * fixed_dt).abs() < 1.0e-5);
+        assert!((alpha - 0.5).abs() < 1.0e-2, "Invalid interpolation factor: {}", alpha);
+
+        // The dropped time is not simulated by the next calls.
+        let _ = world.step_fixed(fixed_dt * 0.75, fixed_dt);
+        assert!((world.time() - 5.0 * fixed_dt).abs() < 1.0e-5);
+
+        world.set_max_substeps(None);
+        let _ = world.step_fixed(0.1, fixed_dt);
+        assert!((world.time() - 17.0 * fixed_dt).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn contact_manifold_cache_keeps_the_points_of_a_resting_box() {
+        use detection::MAX_CACHED_CONTACTS;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = world.add_collider(
+            0.01,
+            floor,
+            BodyHandle::ground(),
+            Isometry::new(Vector::y() * -10.0, na::zero()),
+            Material::default(),
+        );
+
+        let cuboid = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * 0.5, na::zero()),
+            cuboid.inertia(1.0),
+            cuboid.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, cuboid, body, Isometry::identity(), Material::default());
+        world
+            .rigid_body_mut(body)
+            .unwrap()
+            .activation_status_mut()
+            .set_deactivation_threshold(None);
+
+        for _ in 0..60 {
+            world.step();
+        }
+
+        let cache = world.contact_manifold_cache();
+        let points = cache
+            .contacts(BodyHandle::ground(), body)
+            .or_else(|| cache.contacts(body, BodyHandle::ground()))
+            .unwrap();
+        assert!(!points.is_empty() && points.len() <= MAX_CACHED_CONTACTS);
+
+        // The cached impulses support the weight of the box.
+        let total_impulse = points.iter().fold(0.0, |acc, p| acc + p.impulse);
+        let weight_impulse = 9.81 * world.timestep();
+        assert!((total_impulse - weight_impulse).abs() < 5.0e-2 * weight_impulse);
+    }
+
+    #[test]
+    fn speculative_contacts_prevent_penetration_at_large_timesteps() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        world.set_timestep(0.1);
+        world.set_prediction(0.5);
+
+        let floor = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = world.add_collider(
+            0.01,
+            floor,
+            BodyHandle::ground(),
+            Isometry::new(Vector::y() * -10.0, na::zero()),
+            Material::new(0.0, 0.5),
+        );
+
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * 0.8, na::zero()),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_collider(0.01, cube, body, Isometry::identity(), Material::new(0.0, 0.5));
+
+        let mut lowest = ::std::f32::MAX;
+
+        for _ in 0..50 {
+            world.step();
+            let height = world.rigid_body(body).unwrap().position().translation.vector.y;
+            lowest = lowest.min(height);
+        }
+
+        // A speed of about `2.4` is reached before the impact, i.e., a motion of `0.24` per step.
+        let rb = world.rigid_body(body).unwrap();
+        assert!(lowest > 0.5 - 1.0e-2, "Penetration depth: {}", 0.5 - lowest);
+        assert!(rb.velocity().linear.norm() < 1.0e-2, "Residual jitter: {}", rb.velocity().linear);
+    }
+
+    fn build_pyramid(world: &mut World<f32>) -> Vec<BodyHandle> {
+        world.set_gravity(Vector::y() * -9.81);
+        world.set_deterministic(true);
+
+        let ground = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let ground_pos = Isometry::new(Vector::y() * -10.0, na::zero());
+        let _ = world.add_collider(
+            0.01,
+            ground,
+            BodyHandle::ground(),
+            ground_pos,
+            Material::default(),
+        );
+
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.49)));
+        let mut handles = Vec::new();
+
+        for i in 0usize..5 {
+            for j in i..5 {
+                let x = j as f32 - i as f32 * 0.5;
+                let y = i as f32 + 0.5;
+                let pos = Isometry::new(Vector::x() * x + Vector::y() * y, na::zero());
+                let body = world.add_rigid_body(pos, cube.inertia(1.0), cube.center_of_mass());
+                let _ = world.add_collider(
+                    0.01,
+                    cube.clone(),
+                    body,
+                    Isometry::identity(),
+                    Material::default(),
+                );
+                handles.push(body);
+            }
+        }
+
+        handles
+    }
+
+    #[test]
+    fn deterministic_steps_are_reproducible() {
+        let mut world1 = World::<f32>::new();
+        let mut world2 = World::<f32>::new();
+        let handles1 = build_pyramid(&mut world1);
+        let handles2 = build_pyramid(&mut world2);
+
+        for _ in 0..300 {
+            world1.step();
+            world2.step();
+        }
+
+        for (h1, h2) in handles1.iter().zip(handles2.iter()) {
+            let pos1 = world1.rigid_body(*h1).unwrap().position();
+            let pos2 = world2.rigid_body(*h2).unwrap().position();
+            assert!(pos1 == pos2, "Both simulations should be bit-identical.");
+        }
+    }
+
+    #[test]
+    fn bounds_policy_fires_once_for_a_body_falling_through_the_floor() {
+        use ncollide::bounding_volume::AABB;
+        use std::sync::{Arc, Mutex};
+        use super::BoundsPolicy;
+
+        let falling_world = || {
+            let mut world = World::<f32>::new();
+            world.set_gravity(Vector::y() * -9.81);
+            let ground = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+            let _ = world.add_collider(
+                0.01,
+                ground,
+                BodyHandle::ground(),
+                Isometry::new(Vector::y() * -10.0, na::zero()),
+                Material::default(),
+            );
+
+            // The body has no collider so it falls through the floor.
+            let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::y() * 2.0, na::zero()),
+                cube.inertia(1.0),
+                cube.center_of_mass(),
+            );
+            world
+                .rigid_body_mut(body)
+                .unwrap()
+                .activation_status_mut()
+                .set_deactivation_threshold(None);
+
+            let half_extents = Vector::repeat(5.0);
+            let bounds = AABB::new(
+                Point::from(-half_extents),
+                Point::from(half_extents),
+            );
+            (world, body, bounds)
+        };
+
+        let (mut world, body, bounds) = falling_world();
+        let escaped = Arc::new(Mutex::new(Vec::new()));
+        let recorded = escaped.clone();
+        world.set_bounds(
+            bounds,
+            BoundsPolicy::Callback(Box::new(move |handle| recorded.lock().unwrap().push(handle))),
+        );
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        assert!(world.rigid_body(body).unwrap().center_of_mass().y < -5.0);
+        assert_eq!(*escaped.lock().unwrap(), vec![body]);
+
+        let (mut world, body, bounds) = falling_world();
+        world.set_bounds(bounds, BoundsPolicy::Delete);
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        assert!(world.rigid_body(body).is_none());
+    }
+
+    #[test]
+    fn deterministic_replay_gives_identical_positions_at_every_step() {
+        let mut world1 = World::<f32>::new();
+        let mut world2 = World::<f32>::new();
+        let handles1 = build_pyramid(&mut world1);
+        let handles2 = build_pyramid(&mut world2);
+        world1.set_deterministic(false);
+        world2.set_deterministic(false);
+
+        for i in 0..200 {
+            // The same sequence of external inputs is applied to both worlds.
+            for (world, handles) in &mut [(&mut world1, &handles1), (&mut world2, &handles2)] {
+                let top = world.rigid_body_mut(handles[handles.len() - 1]).unwrap();
+                let center = top.center_of_mass();
+                top.apply_force_at_point(Vector::x() * 5.0, &center);
+
+                if i % 50 == 10 {
+                    top.apply_impulse(Vector::y() * 3.0 - Vector::x());
+                }
+            }
+
+            world1.step_deterministic();
+            world2.step_deterministic();
+            assert_eq!(world1.contact_events(), world2.contact_events());
+
+            for (h1, h2) in handles1.iter().zip(handles2.iter()) {
+                let pos1 = world1.rigid_body(*h1).unwrap().position();
+                let pos2 = world2.rigid_body(*h2).unwrap().position();
+                assert!(pos1 == pos2, "Both simulations should be bit-identical at step {}.", i);
+            }
+        }
+
+        assert!(!world1.is_deterministic());
+    }
+
+    #[test]
+    fn last_step_stats_count_the_islands_and_constraints_of_the_scene() {
+        #[cfg(feature = "dim2")]
+        use joint::RevoluteConstraint as BallConstraint;
+        #[cfg(feature = "dim3")]
+        use joint::BallConstraint;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let ground = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = world.add_collider(
+            0.01,
+            ground,
+            BodyHandle::ground(),
+            Isometry::new(Vector::y() * -10.0, na::zero()),
+            Material::default(),
+        );
+
+        // A box resting on the ground, a pendulum, and a body falling freely.
+        let mut handles = Vec::new();
+        for x in &[0.0f32, 7.0, -5.0] {
+            let y = if *x == 0.0 { 0.5 } else { 5.0 };
+            let body = world.add_rigid_body(
+                Isometry::new(Vector::x() * *x + Vector::y() * y, na::zero()),
+                cube.inertia(1.0),
+                cube.center_of_mass(),
+            );
+            world
+                .rigid_body_mut(body)
+                .unwrap()
+                .activation_status_mut()
+                .set_deactivation_threshold(None);
+            handles.push(body);
+        }
+
+        let _ = world.add_collider(
+            0.01,
+            cube.clone(),
+            handles[0],
+            Isometry::identity(),
+            Material::default(),
+        );
+        let _ = world.add_constraint(BallConstraint::new(
+            BodyHandle::ground(),
+            handles[1],
+            Point::from(Vector::x() * 5.0 + Vector::y() * 5.0),
+            Point::from(Vector::x() * -2.0),
+        ));
+
+        for _ in 0..5 {
+            world.step();
+        }
+
+        let params = world.integration_parameters().clone();
+        let stats = *world.last_step_stats();
+        assert_eq!(stats.nislands, 3);
+        assert_eq!(stats.nbodies, 3);
+        assert_eq!(stats.nfree_bodies, 1);
+        assert!(stats.ncontact_constraints > 0);
+        #[cfg(feature = "dim2")]
+        assert_eq!(stats.njoint_constraints, 2);
+        #[cfg(feature = "dim3")]
+        assert_eq!(stats.njoint_constraints, 3);
+        assert_eq!(stats.nvelocity_iterations, params.max_velocity_iterations);
+        assert_eq!(stats.nposition_iterations, params.max_position_iterations);
+        assert_eq!(stats.step_time, None);
+
+        world.set_island_solver_iterations(handles[1], params.max_velocity_iterations + 5);
+        world.enable_performance_counters();
+        world.step();
+
+        let stats = *world.last_step_stats();
+        assert_eq!(stats.nvelocity_iterations, params.max_velocity_iterations + 5);
+        assert!(stats.step_time.is_some());
+    }
+
+    #[test]
+    fn resting_box_reports_its_contacts_and_their_impulses() {
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+
+        let ground = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let ground_pos = Isometry::new(Vector::y() * -10.0, na::zero());
+        let _ = world.add_collider(
+            0.01,
+            ground,
+            BodyHandle::ground(),
+            ground_pos,
+            Material::default(),
+        );
+
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.49)));
+        let body = world.add_rigid_body(
+            Isometry::new(Vector::y() * 0.5, na::zero()),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        world
+            .rigid_body_mut(body)
+            .unwrap()
+            .activation_status_mut()
+            .set_deactivation_threshold(None);
+        let _ = world.add_collider(
+            0.01,
+            cube.clone(),
+            body,
+            Isometry::identity(),
+            Material::default(),
+        );
+
+        for _ in 0..120 {
+            world.step();
+        }
+
+        let manifolds: Vec<_> = world
+            .contacts_with(body)
+            .filter(|&(_, manifold)| manifold.len() != 0)
+            .collect();
+        assert_eq!(manifolds.len(), 1);
+
+        let (other, manifold) = manifolds[0];
+        assert_eq!(other, BodyHandle::ground());
+        assert!(world.in_contact(body, BodyHandle::ground()));
+
+        // One contact per vertex of the face of the box lying on the ground.
+        #[cfg(feature = "dim2")]
+        assert_eq!(manifold.len(), 2);
+        #[cfg(feature = "dim3")]
+        assert_eq!(manifold.len(), 4);
+
+        // The contact normals point from the first collider of the pair to the second one.
+        let box_is_first = world
+            .collision_world()
+            .contact_manifolds()
+            .any(|(coll1, _, _)| coll1.data().body() == body);
+        let up = if box_is_first { -1.0 } else { 1.0 };
+
+        let mut total_impulse = 0.0;
+        for contact in manifold.contacts() {
+            assert!((contact.contact.normal.y * up - 1.0).abs() < 1.0e-3);
+
+            let impulse = world.contact_impulse(contact).unwrap();
+            assert!(impulse > 0.0, "Non-positive contact impulse: {}", impulse);
+            total_impulse += impulse;
+        }
+
+        // The contacts hold the weight of the box.
+        let weight_impulse = cube.mass(1.0) * 9.81 * world.timestep();
+        assert!(
+            (total_impulse - weight_impulse).abs() < weight_impulse * 0.05,
+            "Unexpected total impulse: {} instead of {}",
+            total_impulse,
+            weight_impulse
+        );
+    }
+
+    #[test]
+    fn deterministic_snapshots_do_not_depend_on_hash_map_ordering() {
+        use world::WorldSnapshot;
+
+        // NOTE: each hash map is created with its own random keys, even in a single process, so
+        // any hash map iterated by a step would make these worlds diverge.
+        let mut world1 = World::<f32>::new();
+        let mut world2 = World::<f32>::new();
+        let _ = build_pyramid(&mut world1);
+        let _ = build_pyramid(&mut world2);
+
+        for _ in 0..100 {
+            world1.step();
+            world2.step();
+        }
+
+        let snapshot1 = world1.save_snapshot();
+        let snapshot2 = world2.save_snapshot();
+        assert!(!snapshot1.cached_contacts.is_empty());
+
+        let pairs = |snapshot: &WorldSnapshot<f32>| -> Vec<_> {
+            snapshot
+                .cached_contacts
+                .iter()
+                .map(|&(b1, b2, ref contacts)| {
+                    let impulses: Vec<_> = contacts.iter().map(|c| c.impulse).collect();
+                    (b1, b2, impulses)
+                })
+                .collect()
+        };
+
+        let pairs1 = pairs(&snapshot1);
+        assert_eq!(pairs1, pairs(&snapshot2));
+        assert!(pairs1.windows(2).all(|w| (w[0].0, w[0].1) < (w[1].0, w[1].1)));
+    }
 }
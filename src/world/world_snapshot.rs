@@ -0,0 +1,286 @@
+use na::Real;
+use ncollide::shape::{Ball, Cuboid, Plane, Shape};
+#[cfg(feature = "dim3")]
+use ncollide::shape::ConvexHull;
+#[cfg(feature = "dim2")]
+use ncollide::shape::ConvexPolygon;
+
+use detection::CachedContact;
+use joint::ConstraintHandle;
+use math::{Isometry, Point, Vector};
+use object::BodyHandle;
+
+/// A description of the shape of a collider.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum ShapeDescriptor<N: Real> {
+    /// A ball.
+    Ball {
+        /// The radius of the ball.
+        radius: N,
+    },
+    /// A cuboid.
+    Cuboid {
+        /// The half extents of the cuboid.
+        half_extents: Vector<N>,
+    },
+    /// A plane.
+    Plane {
+        /// The outward normal of the plane.
+        normal: Vector<N>,
+    },
+    /// A convex polygon in 2D or a convex polyhedron in 3D.
+    Convex {
+        /// The vertices of the convex shape.
+        points: Vec<Point<N>>,
+    },
+    /// Any other shape.
+    Other,
+}
+
+impl<N: Real> ShapeDescriptor<N> {
+    /// The description of the given shape.
+    pub fn from_shape(shape: &Shape<N>) -> Self {
+        if let Some(s) = shape.as_shape::<Ball<N>>() {
+            return ShapeDescriptor::Ball { radius: s.radius() };
+        }
+
+        if let Some(s) = shape.as_shape::<Cuboid<N>>() {
+            return ShapeDescriptor::Cuboid {
+                half_extents: *s.half_extents(),
+            };
+        }
+
+        if let Some(s) = shape.as_shape::<Plane<N>>() {
+            return ShapeDescriptor::Plane {
+                normal: s.normal().into_inner(),
+            };
+        }
+
+        #[cfg(feature = "dim3")]
+        {
+            if let Some(s) = shape.as_shape::<ConvexHull<N>>() {
+                return ShapeDescriptor::Convex {
+                    points: s.points().to_vec(),
+                };
+            }
+        }
+
+        #[cfg(feature = "dim2")]
+        {
+            if let Some(s) = shape.as_shape::<ConvexPolygon<N>>() {
+                return ShapeDescriptor::Convex {
+                    points: s.points().to_vec(),
+                };
+            }
+        }
+
+        ShapeDescriptor::Other
+    }
+}
+
+/// The state of a rigid body stored in a `WorldSnapshot`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct RigidBodySnapshot<N: Real> {
+    /// The handle of the rigid body.
+    pub handle: BodyHandle,
+    /// The position of the rigid body.
+    pub position: Isometry<N>,
+    /// The generalized velocity of the rigid body.
+    pub velocity: Vec<N>,
+}
+
+/// The state of a multibody stored in a `WorldSnapshot`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct MultibodySnapshot<N: Real> {
+    /// The handle of the multibody, i.e., of its root link.
+    pub handle: BodyHandle,
+    /// The generalized coordinates of the multibody.
+    pub coordinates: Vec<N>,
+    /// The generalized velocity of the multibody.
+    pub velocity: Vec<N>,
+}
+
+/// The parameters and cached impulses of a joint constraint stored in a `WorldSnapshot`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct ConstraintSnapshot<N: Real> {
+    /// The handle of the joint constraint.
+    pub handle: ConstraintHandle,
+    /// The error reduction parameter of the joint constraint, if any.
+    pub position_correction: Option<N>,
+    /// The compliance of the joint constraint, if any.
+    pub compliance: Option<N>,
+    /// The maximum force of the joint constraint, if any.
+    pub max_force: Option<N>,
+    /// The maximum torque of the joint constraint, if any.
+    pub max_torque: Option<N>,
+    /// The impulses cached by the joint constraint.
+    pub impulses: Vec<N>,
+}
+
+/// The description of a collider stored in a `WorldSnapshot`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct ColliderSnapshot<N: Real> {
+    /// The unique identifier of the handle of the collider.
+    pub uid: usize,
+    /// The body part the collider is attached to.
+    pub body: BodyHandle,
+    /// The position of the collider relative to its body part.
+    pub position_wrt_body: Isometry<N>,
+    /// The shape of the collider.
+    pub shape: ShapeDescriptor<N>,
+}
+
+/// The complete simulation state of a world.
+///
+/// A snapshot is created by `World::save_snapshot` and restored by `World::load_snapshot`. It
+/// does not contain the bodies, joints, and colliders themselves but only their state, so it can
+/// only be restored on the world it was saved from or on a world built identically. The shapes of
+/// the colliders are recorded to check that the worlds match.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct WorldSnapshot<N: Real> {
+    /// The simulation time.
+    pub time: N,
+    /// The timestep of the last step, if any.
+    pub last_dt: Option<N>,
+    /// The state of the rigid bodies.
+    pub rigid_bodies: Vec<RigidBodySnapshot<N>>,
+    /// The state of the multibodies.
+    pub multibodies: Vec<MultibodySnapshot<N>>,
+    /// The parameters and cached impulses of the joint constraints.
+    pub constraints: Vec<ConstraintSnapshot<N>>,
+    /// The description of the colliders.
+    pub colliders: Vec<ColliderSnapshot<N>>,
+    /// The contact points cached for each pair of body parts.
+    pub cached_contacts: Vec<(BodyHandle, BodyHandle, Vec<CachedContact<N>>)>,
+}
+
+#[cfg(test)]
+mod test {
+    use na;
+    use ncollide::shape::{Cuboid, ShapeHandle};
+
+    use math::{Isometry, Point, Vector};
+    use object::{BodyHandle, Material};
+    use volumetric::Volumetric;
+    use world::World;
+
+    #[test]
+    fn loading_a_snapshot_restores_the_state_of_the_world() {
+        #[cfg(feature = "dim2")]
+        use joint::RevoluteConstraint as BallConstraint;
+        #[cfg(feature = "dim3")]
+        use joint::BallConstraint;
+        use joint::RevoluteJoint;
+
+        let mut world = World::<f32>::new();
+        world.set_gravity(Vector::y() * -9.81);
+        let cube = ShapeHandle::new(Cuboid::new(Vector::repeat(0.5)));
+        let ground = ShapeHandle::new(Cuboid::new(Vector::repeat(10.0)));
+        let _ = world.add_collider(
+            0.01,
+            ground,
+            BodyHandle::ground(),
+            Isometry::new(Vector::y() * -10.0, na::zero()),
+            Material::default(),
+        );
+
+        // A box falling on the ground.
+        let falling = world.add_rigid_body(
+            Isometry::new(Vector::y() * 1.0, na::zero()),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_collider(
+            0.01,
+            cube.clone(),
+            falling,
+            Isometry::identity(),
+            Material::default(),
+        );
+
+        // A rigid pendulum attached to the ground.
+        let pendulum = world.add_rigid_body(
+            Isometry::new(Vector::x() * 2.0 + Vector::y() * 5.0, na::zero()),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+        let _ = world.add_constraint(BallConstraint::new(
+            BodyHandle::ground(),
+            pendulum,
+            Point::from(Vector::y() * 5.0),
+            Point::from(Vector::x() * -2.0),
+        ));
+
+        // A multibody chain swinging under gravity.
+        #[cfg(feature = "dim2")]
+        let joint = RevoluteJoint::new(0.0);
+        #[cfg(feature = "dim3")]
+        let joint = RevoluteJoint::new(Vector::z_axis(), 0.0);
+        let link = world.add_multibody_link(
+            BodyHandle::ground(),
+            joint,
+            Vector::x() * -6.0 + Vector::y() * 5.0,
+            -Vector::x(),
+            cube.inertia(1.0),
+            cube.center_of_mass(),
+        );
+
+        for handle in &[falling, pendulum] {
+            world
+                .rigid_body_mut(*handle)
+                .unwrap()
+                .activation_status_mut()
+                .set_deactivation_threshold(None);
+        }
+        world
+            .multibody_mut(link)
+            .unwrap()
+            .activation_status_mut()
+            .set_deactivation_threshold(None);
+
+        let state = |world: &World<f32>| {
+            let mut state = Vec::new();
+            for handle in &[falling, pendulum] {
+                let rb = world.rigid_body(*handle).unwrap();
+                state.extend(rb.position().translation.vector.iter().cloned());
+                state.extend(rb.generalized_velocity().iter().cloned());
+            }
+            let mb = world.multibody(link).unwrap();
+            state.extend(mb.generalized_position().iter().cloned());
+            state.extend(mb.generalized_velocity().iter().cloned());
+            state
+        };
+
+        for _ in 0..40 {
+            world.step();
+        }
+
+        let snapshot = world.save_snapshot();
+        let saved = state(&world);
+        let time = world.time();
+        assert!(!snapshot.cached_contacts.is_empty());
+
+        let mut expected = Vec::new();
+        for _ in 0..40 {
+            world.step();
+            expected.push(state(&world));
+        }
+
+        world.load_snapshot(snapshot);
+        assert_eq!(state(&world), saved);
+        assert_relative_eq!(world.time(), time);
+
+        for expected in &expected {
+            world.step();
+            for (a, b) in state(&world).iter().zip(expected.iter()) {
+                assert!((a - b).abs() < 1.0e-3, "{} != {}", a, b);
+            }
+        }
+    }
+}